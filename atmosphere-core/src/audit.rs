@@ -0,0 +1,134 @@
+//! Audit logging for tables.
+//!
+//! Tables opting in using `#[audit]` record every row they create, update, upsert or delete into a
+//! companion table named `<table>_audit` in the same schema. Each entry holds the operation, the
+//! acting user (see [`actor`]), a timestamp and the row before and after the change as `JSONB`.
+//!
+//! The entry is written by the same statement that changes the row (through a data modifying
+//! `WITH` clause), so it is always part of the same transaction: rolled back changes leave no audit
+//! entries behind. The companion table has to exist, its definition is returned by [`table_sql`].
+//! Sensitive columns (see [`crate::redact`]) are recorded as `«redacted»`.
+//!
+//! ```ignore
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "user")]
+//! #[audit]
+//! struct User {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! sqlx::query(&atmosphere::audit::table_sql::<User>()).execute(&pool).await?;
+//!
+//! atmosphere::audit::actor("admin", async {
+//!     user.update(&pool).await
+//! })
+//! .await?;
+//! ```
+
+use std::{cell::RefCell, future::Future, sync::Arc};
+
+use sqlx::QueryBuilder;
+
+use crate::{
+    hooks::{Hook, HookStage},
+    query::{Operation, Query},
+    redact,
+    runtime::{scoped::Scoped, sql},
+    Bind, Result, SchemaContext, Table,
+};
+
+/// The hook recording changes to a table into its audit table, registered by `#[audit]`.
+///
+/// Being a [`HookStage::PreBind`] hook that rewrites the whole query, it has to run after all other
+/// hooks modifying the query. `#[audit]` therefore registers it after all `#[hooks(..)]`.
+pub struct Audit;
+
+impl<T: Table + Bind + Sync + 'static> Hook<T> for Audit {
+    fn stage(&self) -> HookStage {
+        HookStage::PreBind
+    }
+
+    fn modify(&self, query: &mut Query<T>) -> Result<()> {
+        let op = match query.op {
+            Operation::Insert => "insert",
+            Operation::Update => "update",
+            Operation::Upsert => "upsert",
+            Operation::Delete => "delete",
+            Operation::Select | Operation::Other => return Ok(()),
+        };
+
+        let mut changed = QueryBuilder::new(query.sql().to_owned());
+
+        if !query.sql().contains("\nRETURNING\n") {
+            sql::returning::<T>(&mut changed);
+        }
+
+        // hard deletes leave no row behind, soft deletes are updates of the deleted column
+        let after = if query.sql().starts_with("DELETE") {
+            "NULL".to_owned()
+        } else {
+            format!("to_jsonb(__changed){}", redacted::<T>())
+        };
+
+        query
+            .values
+            .push(Arc::new(ACTOR.with(|a| a.borrow().clone())));
+
+        let actor = query.bindings().columns().len() + query.values.len();
+
+        let audited = format!(
+            "WITH __changed AS (\n{}\n), __audit AS (\n  INSERT INTO {} (operation, actor, at, before, after)\n  SELECT '{op}', ${actor}, CURRENT_TIMESTAMP, to_jsonb(__before){redacted}, {after}\n  FROM __changed\n  LEFT JOIN {} AS __before ON __before.{pk} = __changed.{pk}\n)\nSELECT * FROM __changed",
+            changed.sql(),
+            table::<T>(),
+            sql::table::<T>(),
+            pk = T::PRIMARY_KEY.sql,
+            redacted = redacted::<T>(),
+        );
+
+        query.embed(audited, "WITH __changed AS (\n".len());
+
+        Ok(())
+    }
+}
+
+/// Overwrites the sensitive columns of a row converted to `JSONB` (see [`crate::redact`])
+fn redacted<T: Table>() -> String {
+    let columns: Vec<String> = redact::sensitive::<T>()
+        .map(|column| format!("'{column}', '{}'", redact::REDACTED))
+        .collect();
+
+    match columns.is_empty() {
+        true => String::new(),
+        false => format!(" || jsonb_build_object({})", columns.join(", ")),
+    }
+}
+
+/// The audit table of `T`
+fn table<T: Table>() -> String {
+    format!(
+        "\"{}\".\"{}_audit\"",
+        SchemaContext::schema::<T>(),
+        T::name()
+    )
+}
+
+/// Returns the `CREATE TABLE` statement of the audit table of `T`
+pub fn table_sql<T: Table>() -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} (\n  id BIGSERIAL PRIMARY KEY,\n  operation TEXT NOT NULL,\n  actor TEXT,\n  at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,\n  before JSONB,\n  after JSONB\n)",
+        table::<T>()
+    )
+}
+
+thread_local! {
+    static ACTOR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Attributes all changes to audited tables made by `f` to `actor`.
+///
+/// Changes made outside of this scope are recorded without an actor.
+pub fn actor<F: Future>(actor: impl Into<String>, f: F) -> impl Future<Output = F::Output> {
+    Scoped::new(&ACTOR, actor.into(), f)
+}
@@ -0,0 +1,181 @@
+//! Circuit breaking during database outages.
+//!
+//! While the database is unreachable, every query waits for the connection attempt or the pool to
+//! time out. A [`CircuitBreaker`] trips after a number of consecutive communication failures
+//! ([`QueryError::Io`]) and fails all queries right away with [`Error::Unavailable`] instead. Once
+//! its cooldown passed, it lets a single query through to probe whether the database recovered:
+//! the breaker closes if it succeeds and trips again if it fails.
+//!
+//! The breaker is an [interceptor](crate::intercept), so it guards the statements of all tables:
+//!
+//! ```ignore
+//! let breaker = CircuitBreaker::new(5, Duration::from_secs(10));
+//!
+//! atmosphere::intercept::install(breaker.clone());
+//!
+//! match User::read(&pool, &0).await {
+//!     Err(Error::Unavailable) => { .. } // the database is down, `breaker.state()` is open
+//!     ..
+//! }
+//! ```
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    intercept::{Interceptor, Outcome, Statement},
+    query::QueryError,
+    Error, Result,
+};
+
+/// The state of a [`CircuitBreaker`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Queries are executed
+    Closed,
+    /// Queries fail with [`Error::Unavailable`] until the cooldown passed
+    Open,
+    /// A single query probes whether the database recovered, all others fail
+    HalfOpen,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probing: Instant },
+}
+
+/// Fails queries fast while the database is unavailable, see [`crate::breaker`]
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl CircuitBreaker {
+    /// Trips after `threshold` consecutive communication failures, probing the database again
+    /// every `cooldown`
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            state: Arc::new(Mutex::new(State::Closed { failures: 0 })),
+        }
+    }
+
+    /// The current state of the breaker
+    pub fn state(&self) -> BreakerState {
+        match *self.state.lock().expect("breaker state poisoned") {
+            State::Closed { .. } => BreakerState::Closed,
+            State::Open { .. } => BreakerState::Open,
+            State::HalfOpen { .. } => BreakerState::HalfOpen,
+        }
+    }
+
+    /// Admits a query, unless the breaker is open or another query probes the database
+    fn admit(&self, now: Instant) -> Result<()> {
+        let mut state = self.state.lock().expect("breaker state poisoned");
+
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now >= until => {
+                *state = State::HalfOpen { probing: now };
+                Ok(())
+            }
+            // a probe that never finished (e.g. because a hook failed) is replaced
+            State::HalfOpen { probing } if now >= probing + self.cooldown => {
+                *state = State::HalfOpen { probing: now };
+                Ok(())
+            }
+            State::Open { .. } | State::HalfOpen { .. } => Err(Error::Unavailable),
+        }
+    }
+
+    /// Records the outcome of a query, queries failing otherwise than on communication tell that
+    /// the database is reachable
+    fn record(&self, failed: bool, now: Instant) {
+        let mut state = self.state.lock().expect("breaker state poisoned");
+
+        *state = match (*state, failed) {
+            (_, false) => State::Closed { failures: 0 },
+            (State::Closed { failures }, true) if failures + 1 < self.threshold => State::Closed {
+                failures: failures + 1,
+            },
+            (State::Open { until }, true) => State::Open { until },
+            (_, true) => State::Open {
+                until: now + self.cooldown,
+            },
+        };
+    }
+}
+
+#[async_trait]
+impl Interceptor for CircuitBreaker {
+    async fn before(&self, _: &Statement<'_>) -> Result<()> {
+        self.admit(Instant::now())
+    }
+
+    async fn after(&self, _: &Statement<'_>, outcome: &Outcome<'_>) -> Result<()> {
+        self.record(
+            matches!(outcome.error, Some(QueryError::Io(_))),
+            Instant::now(),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{BreakerState, CircuitBreaker};
+    use crate::Error;
+
+    #[test]
+    fn trips_and_recovers() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+
+        breaker.record(true, now);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        // successes reset the consecutive failures
+        breaker.record(false, now);
+        breaker.record(true, now);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        breaker.record(true, now);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(matches!(breaker.admit(now), Err(Error::Unavailable)));
+
+        // a single probe is admitted after the cooldown
+        let later = now + Duration::from_secs(10);
+
+        assert!(breaker.admit(later).is_ok());
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.admit(later).is_err());
+
+        // a failing probe trips the breaker again
+        breaker.record(true, later);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(breaker.admit(later + Duration::from_secs(5)).is_err());
+
+        // a probe that never finishes is replaced after the cooldown
+        let probe = later + Duration::from_secs(10);
+
+        assert!(breaker.admit(probe).is_ok());
+        assert!(breaker.admit(probe + Duration::from_secs(10)).is_ok());
+
+        // a succeeding probe closes it
+        breaker.record(false, probe);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.admit(probe).is_ok());
+    }
+}
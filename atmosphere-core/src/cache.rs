@@ -0,0 +1,538 @@
+//! In-process and shared caches of rows by primary key.
+//!
+//! Tables using `#[table(.., cache(ttl = "30s"))]` keep the rows read by [`crate::Read::read`] and
+//! [`crate::Read::find`] in memory and serve further reads of the same primary key from there,
+//! without querying the database, until they expire after `ttl`. At most `capacity` rows (1024
+//! unless configured, e.g. `cache(ttl = "5m", capacity = 10000)`) are kept per table, the least
+//! recently used rows are evicted first. Rows are cached by schema, so tables read within
+//! [`SchemaContext::scope`] do not share their rows with other schemas.
+//!
+//! Every write to the table through atmosphere evicts the row it changes, writes of many rows at
+//! once (e.g. `delete_many`) and writes executed without hooks (e.g. `delete_by_<column>` of
+//! unique columns or deleting the rows referring to another row) clear the cache of the table. Rows changed inside of
+//! [`crate::transaction`] are evicted again once the transaction has been committed, and reads
+//! inside of it bypass the cache, so uncommitted rows are never cached.
+//!
+//! The in-process cache is local to the process: rows changed by other processes, or by sql not
+//! executed through atmosphere, are served until they expire. Reads served from the cache do not
+//! run the hooks of the table.
+//!
+//! # Shared caches
+//!
+//! Horizontally scaled services share the cached rows of tables using `cache(.., shared)` through
+//! the [`CacheBackend`] installed using [`set_backend`], e.g. [`redis::Redis`] (feature `redis`).
+//! Rows are stored as JSON, so the tables have to implement `serde::Serialize` and
+//! `serde::Deserialize`. As writes of any instance evict the changed rows from the backend, shared
+//! rows are not cached in process.
+//!
+//! ```ignore
+//! atmosphere::cache::set_backend(Redis::connect("redis://cache:6379").await?);
+//!
+//! #[derive(Schema, Serialize, Deserialize)]
+//! #[table(schema = "public", name = "user", cache(ttl = "5m", shared))]
+//! struct User {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     name: String,
+//! }
+//! ```
+//!
+//! Failures of the backend fail the queries using it.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use miette::Diagnostic;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::{
+    hooks::{Hook, HookInput, HookStage},
+    query::{Operation, Query},
+    runtime::transaction,
+    Bind, Result, SchemaContext, Table,
+};
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+/// The number of rows cached per table unless configured using `cache(.., capacity = ..)`
+pub const CAPACITY: usize = 1024;
+
+/// Errors of shared caches
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum CacheError {
+    /// No backend has been installed using [`set_backend`]
+    #[error("no cache backend has been installed")]
+    #[diagnostic(code(atmosphere::cache::backend))]
+    NoBackend,
+
+    /// The backend failed to store, retrieve or remove a row
+    #[error("cache backend failed")]
+    #[diagnostic(code(atmosphere::cache::failed))]
+    Backend(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Stores the rows of shared caches, e.g. in a Redis instance shared by all instances of a service
+#[async_trait]
+pub trait CacheBackend: Send + Sync + 'static {
+    /// Returns the value stored at `key`, if any
+    async fn get(&self, key: &str) -> std::result::Result<Option<Vec<u8>>, CacheError>;
+
+    /// Stores `value` at `key`, expiring after `ttl`
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> std::result::Result<(), CacheError>;
+
+    /// Removes the value stored at `key`
+    async fn remove(&self, key: &str) -> std::result::Result<(), CacheError>;
+
+    /// Removes the values of all keys starting with `prefix`
+    async fn clear(&self, prefix: &str) -> std::result::Result<(), CacheError>;
+}
+
+static BACKEND: RwLock<Option<Arc<dyn CacheBackend>>> = RwLock::new(None);
+
+/// Installs the backend used by all shared caches, replacing the previous one
+pub fn set_backend(backend: impl CacheBackend) {
+    *BACKEND.write().expect("cache backend poisoned") = Some(Arc::new(backend));
+}
+
+fn backend() -> std::result::Result<Arc<dyn CacheBackend>, CacheError> {
+    BACKEND
+        .read()
+        .expect("cache backend poisoned")
+        .clone()
+        .ok_or(CacheError::NoBackend)
+}
+
+/// The operations on the cache of a table, see [`Table::cache`]
+#[async_trait]
+pub trait RowCache<T: Table>: Send + Sync {
+    /// Returns the cached row with the primary key `pk`, unless it expired
+    async fn get(&self, pk: &T::PrimaryKey) -> Result<Option<T>>;
+
+    /// The current generation of the cache, taken before reading a row to [`RowCache::insert`]
+    fn generation(&self) -> u64;
+
+    /// Caches `row`, unless rows have been evicted since `generation`, as it might be outdated
+    async fn insert(&self, row: &T, generation: u64) -> Result<()>;
+
+    /// Evicts the row with the primary key `pk` (or all rows if `None`) right away, and again
+    /// once the surrounding transaction has been committed
+    async fn invalidate(&'static self, pk: Option<&T::PrimaryKey>) -> Result<()>;
+
+    /// The number of rows cached in process, including expired rows not evicted yet
+    fn len(&self) -> usize;
+
+    /// Whether no rows are cached in process
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The rows of `T` cached in process, generated for tables using `#[table(.., cache(..))]`
+pub struct Cache<T: Table> {
+    ttl: Duration,
+    capacity: usize,
+    state: OnceLock<Mutex<State<T>>>,
+}
+
+struct State<T: Table> {
+    rows: Lru<Key<T>, T>,
+    /// Incremented whenever rows are evicted, rows read before are not cached anymore
+    generation: u64,
+}
+
+/// The schema of a cached row (see [`SchemaContext::schema`]) and its primary key
+type Key<T> = (String, <T as Table>::PrimaryKey);
+
+impl<T: Table> Cache<T> {
+    /// A cache of up to `capacity` rows, each expiring `ttl` after it has been read
+    pub const fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            state: OnceLock::new(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State<T>> {
+        self.state
+            .get_or_init(|| {
+                Mutex::new(State {
+                    rows: Lru::new(self.capacity),
+                    generation: 0,
+                })
+            })
+            .lock()
+            .expect("cache poisoned")
+    }
+
+    /// The key of the row with the primary key `pk` in the schema in scope
+    fn key(pk: &T::PrimaryKey) -> Key<T>
+    where
+        T::PrimaryKey: Clone,
+    {
+        (SchemaContext::schema::<T>(), pk.clone())
+    }
+}
+
+#[async_trait]
+impl<T> RowCache<T> for Cache<T>
+where
+    T: Table + Clone + Sync,
+    T::PrimaryKey: Hash + Eq + Clone,
+{
+    async fn get(&self, pk: &T::PrimaryKey) -> Result<Option<T>> {
+        Ok(self.state().rows.get(&Self::key(pk)).cloned())
+    }
+
+    fn generation(&self) -> u64 {
+        self.state().generation
+    }
+
+    async fn insert(&self, row: &T, generation: u64) -> Result<()> {
+        let mut state = self.state();
+
+        if state.generation == generation {
+            state
+                .rows
+                .insert(Self::key(row.pk()), row.clone(), Instant::now() + self.ttl);
+        }
+
+        Ok(())
+    }
+
+    async fn invalidate(&'static self, pk: Option<&T::PrimaryKey>) -> Result<()> {
+        let evict = move |key: Option<&Key<T>>| {
+            let mut state = self.state();
+
+            state.generation += 1;
+
+            match key {
+                Some(key) => state.rows.remove(key),
+                None => state.rows.clear(),
+            }
+        };
+
+        let key = pk.map(Self::key);
+
+        evict(key.as_ref());
+
+        if transaction::scoped() {
+            transaction::on_commit(move || evict(key.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.state().rows.len()
+    }
+}
+
+/// The rows of `T` cached in the installed [`CacheBackend`], generated for tables using
+/// `#[table(.., cache(.., shared))]`
+pub struct Shared<T> {
+    ttl: Duration,
+    /// Incremented whenever rows are evicted by this process, rows read before are not cached
+    generation: AtomicU64,
+    rows: PhantomData<fn() -> T>,
+}
+
+impl<T: Table> Shared<T> {
+    /// A cache of rows expiring `ttl` after they have been read
+    pub const fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            generation: AtomicU64::new(0),
+            rows: PhantomData,
+        }
+    }
+
+    /// The prefix of the keys of all rows of `T`
+    fn prefix() -> String {
+        format!("atmosphere:{}.{}:", SchemaContext::schema::<T>(), T::name())
+    }
+}
+
+impl<T: Table> Shared<T>
+where
+    T::PrimaryKey: Serialize,
+{
+    /// The key of the row with the primary key `pk`
+    fn key(pk: &T::PrimaryKey) -> Result<String> {
+        let pk = serde_json::to_string(pk).map_err(|err| CacheError::Backend(err.into()))?;
+
+        Ok(format!("{}{pk}", Self::prefix()))
+    }
+
+    async fn evict(key: Option<&str>) -> std::result::Result<(), CacheError> {
+        match key {
+            Some(key) => backend()?.remove(key).await,
+            None => backend()?.clear(&Self::prefix()).await,
+        }
+    }
+}
+
+#[async_trait]
+impl<T> RowCache<T> for Shared<T>
+where
+    T: Table + Serialize + DeserializeOwned + Sync,
+    T::PrimaryKey: Serialize,
+{
+    async fn get(&self, pk: &T::PrimaryKey) -> Result<Option<T>> {
+        let Some(value) = backend()?.get(&Self::key(pk)?).await? else {
+            return Ok(None);
+        };
+
+        // rows cached by other versions of the service might not match `T`
+        Ok(serde_json::from_slice(&value).ok())
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    async fn insert(&self, row: &T, generation: u64) -> Result<()> {
+        if self.generation() != generation {
+            return Ok(());
+        }
+
+        let value = serde_json::to_vec(row).map_err(|err| CacheError::Backend(err.into()))?;
+
+        Ok(backend()?
+            .set(&Self::key(row.pk())?, value, self.ttl)
+            .await?)
+    }
+
+    async fn invalidate(&'static self, pk: Option<&T::PrimaryKey>) -> Result<()> {
+        let key = pk.map(Self::key).transpose()?;
+
+        self.generation.fetch_add(1, Ordering::AcqRel);
+
+        Self::evict(key.as_deref()).await?;
+
+        if transaction::scoped() {
+            let key = Arc::new(key);
+
+            transaction::defer(Arc::new(move |stage| {
+                let key = key.clone();
+
+                Box::pin(async move {
+                    if stage == HookStage::PostCommit {
+                        self.generation.fetch_add(1, Ordering::AcqRel);
+                        Self::evict(key.as_deref()).await?;
+                    }
+
+                    Ok(())
+                })
+            }));
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        0
+    }
+}
+
+/// The cache reads of `T` are served from, `None` inside of transactions
+pub(crate) fn of<T: Table>() -> Option<&'static dyn RowCache<T>> {
+    T::cache().filter(|_| !transaction::scoped())
+}
+
+/// Clears the cache of `T` before `query` is executed without hooks, unless it only reads rows
+pub(crate) async fn evict<T: Table + Bind>(query: &Query<T>) -> Result<()> {
+    if matches!(query.op, Operation::Select | Operation::Insert) {
+        return Ok(());
+    }
+
+    match T::cache() {
+        Some(cache) => cache.invalidate(None).await,
+        None => Ok(()),
+    }
+}
+
+/// The hook evicting changed rows from the cache of a table, registered by
+/// `#[table(.., cache(..))]`
+pub struct Invalidate;
+
+#[async_trait]
+impl<T: Table + Bind + Sync + 'static> Hook<T> for Invalidate {
+    fn stage(&self) -> HookStage {
+        HookStage::PreBind
+    }
+
+    async fn apply(&self, ctx: &Query<T>, input: &mut HookInput<'_, T>) -> Result<()> {
+        // rows are cached once read, created rows have not been read yet
+        if matches!(ctx.op, Operation::Select | Operation::Insert) {
+            return Ok(());
+        }
+
+        let Some(cache) = T::cache() else {
+            return Ok(());
+        };
+
+        match input {
+            HookInput::Row(row) => cache.invalidate(Some(row.pk())).await,
+            HookInput::PrimaryKey(pk) => cache.invalidate(Some(pk)).await,
+            _ => cache.invalidate(None).await,
+        }
+    }
+}
+
+/// A map of up to `capacity` expiring values, evicting the least recently used value first
+struct Lru<K, V> {
+    capacity: usize,
+    entries: HashMap<K, Entry<V>>,
+    /// The keys of all entries by when they were used last
+    recent: BTreeMap<u64, K>,
+    /// Incremented whenever an entry is used
+    clock: u64,
+}
+
+struct Entry<V> {
+    value: V,
+    expires: Instant,
+    used: u64,
+}
+
+impl<K, V> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recent: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Lru<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        if self.entries.get(key)?.expires <= Instant::now() {
+            self.remove(key);
+            return None;
+        }
+
+        let entry = self.entries.get_mut(key)?;
+
+        self.clock += 1;
+
+        let key = self.recent.remove(&entry.used)?;
+        self.recent.insert(self.clock, key);
+
+        entry.used = self.clock;
+
+        Some(&entry.value)
+    }
+
+    fn insert(&mut self, key: K, value: V, expires: Instant) {
+        self.remove(&key);
+
+        self.clock += 1;
+        self.recent.insert(self.clock, key.clone());
+
+        let used = self.clock;
+
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                expires,
+                used,
+            },
+        );
+
+        while self.entries.len() > self.capacity {
+            let Some((_, key)) = self.recent.pop_first() else {
+                break;
+            };
+
+            self.entries.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recent.remove(&entry.used);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recent.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::Lru;
+
+    #[test]
+    fn least_recently_used() {
+        let expires = Instant::now() + Duration::from_secs(60);
+        let mut lru = Lru::new(2);
+
+        lru.insert(1, "one", expires);
+        lru.insert(2, "two", expires);
+
+        assert_eq!(lru.get(&1), Some(&"one"));
+
+        lru.insert(3, "three", expires);
+
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.get(&2), None);
+        assert_eq!(lru.get(&1), Some(&"one"));
+        assert_eq!(lru.get(&3), Some(&"three"));
+
+        lru.insert(1, "uno", expires);
+        lru.insert(4, "four", expires);
+
+        assert_eq!(lru.get(&3), None);
+        assert_eq!(lru.get(&1), Some(&"uno"));
+
+        lru.remove(&1);
+        lru.remove(&2);
+
+        assert_eq!(lru.len(), 1);
+
+        lru.clear();
+
+        assert_eq!(lru.get(&4), None);
+    }
+
+    #[test]
+    fn expiry() {
+        let mut lru = Lru::new(2);
+
+        lru.insert(1, "one", Instant::now());
+        lru.insert(2, "two", Instant::now() + Duration::from_secs(60));
+
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(lru.get(&2), Some(&"two"));
+        assert_eq!(lru.len(), 1);
+    }
+}
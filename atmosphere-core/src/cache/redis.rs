@@ -0,0 +1,107 @@
+//! A [`CacheBackend`] storing the rows of shared caches in Redis.
+//!
+//! ```ignore
+//! atmosphere::cache::set_backend(Redis::connect("redis://cache:6379").await?);
+//! ```
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::{aio::ConnectionManager, AsyncCommands, Client, RedisError};
+
+use super::{CacheBackend, CacheError};
+
+/// The number of keys removed by a single `DEL` while clearing a table
+const BATCH: usize = 512;
+
+impl From<RedisError> for CacheError {
+    fn from(err: RedisError) -> Self {
+        Self::Backend(Box::new(err))
+    }
+}
+
+/// A Redis backend, reconnecting whenever the connection is lost
+#[derive(Clone)]
+pub struct Redis {
+    conn: ConnectionManager,
+}
+
+impl Redis {
+    /// Connects to the Redis instance at `url`, e.g. `redis://cache:6379/0`
+    pub async fn connect(url: &str) -> Result<Self, CacheError> {
+        let client = Client::open(url)?;
+
+        Ok(Self::new(ConnectionManager::new(client).await?))
+    }
+
+    /// Uses the existing connection `conn`
+    pub const fn new(conn: ConnectionManager) -> Self {
+        Self { conn }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for Redis {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        Ok(self.conn.clone().get(key).await?)
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<(), CacheError> {
+        let millis = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+
+        Ok(self.conn.clone().pset_ex(key, value, millis).await?)
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), CacheError> {
+        Ok(self.conn.clone().del(key).await?)
+    }
+
+    async fn clear(&self, prefix: &str) -> Result<(), CacheError> {
+        let mut conn = self.conn.clone();
+
+        let pattern = format!("{}*", escape(prefix));
+
+        let keys: Vec<String> = {
+            let mut iter = conn.scan_match::<_, String>(pattern).await?;
+            let mut keys = vec![];
+
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+
+            keys
+        };
+
+        for batch in keys.chunks(BATCH) {
+            conn.del::<_, ()>(batch).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Escapes the glob characters of `prefix` for `SCAN .. MATCH`
+fn escape(prefix: &str) -> String {
+    let mut escaped = String::with_capacity(prefix.len());
+
+    for c in prefix.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape;
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape("atmosphere:public.user:"), "atmosphere:public.user:");
+        assert_eq!(escape("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\");
+    }
+}
@@ -0,0 +1,108 @@
+//! Tagging of generated sql with comments
+//!
+//! Tags set for everything executed within a future using [`tagged`] are appended to all generated
+//! statements as a comment in the format of [sqlcommenter](https://google.github.io/sqlcommenter/)
+//! (keys sorted, keys and values url encoded, values quoted). This allows attributing the load
+//! recorded by the database (e.g. in `pg_stat_statements`) or found in its logs to the code path
+//! issuing the statements. Scopes can be nested, inner tags are added to (or override) outer ones.
+//!
+//! ```ignore
+//! atmosphere::comment::tagged([("app", "checkout"), ("route", "POST /orders")], async {
+//!     order.create(&pool).await
+//! })
+//! .await?;
+//!
+//! // INSERT INTO "public"."order" (..) VALUES (..) /*app='checkout',route='POST%20%2Forders'*/
+//! ```
+
+use std::{cell::RefCell, collections::BTreeMap, fmt::Write, future::Future};
+
+use crate::runtime::scoped::Scoped;
+
+type Tags = BTreeMap<String, String>;
+
+thread_local! {
+    static TAGS: RefCell<Option<Tags>> = const { RefCell::new(None) };
+}
+
+/// Appends `tags` to all statements generated by `f`, in addition to the tags in scope.
+pub fn tagged<F, K, V>(
+    tags: impl IntoIterator<Item = (K, V)>,
+    f: F,
+) -> impl Future<Output = F::Output>
+where
+    F: Future,
+    K: Into<String>,
+    V: Into<String>,
+{
+    let mut scoped = TAGS.with(|t| t.borrow().clone()).unwrap_or_default();
+
+    scoped.extend(tags.into_iter().map(|(k, v)| (k.into(), v.into())));
+
+    Scoped::new(&TAGS, scoped, f)
+}
+
+/// The comment appended to statements generated in the current scope, if any tags are set
+pub(crate) fn current() -> Option<String> {
+    TAGS.with(|tags| tags.borrow().as_ref().and_then(render))
+}
+
+fn render(tags: &Tags) -> Option<String> {
+    if tags.is_empty() {
+        return None;
+    }
+
+    let tags = tags
+        .iter()
+        .map(|(k, v)| format!("{}='{}'", encode(k), encode(v)))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    Some(format!(" /*{tags}*/"))
+}
+
+/// Percent encodes everything but unreserved characters, which also keeps the comment from being
+/// terminated early
+fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            _ => {
+                let _ = write!(encoded, "%{b:02X}");
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Tags};
+
+    #[test]
+    fn sqlcommenter() {
+        let tags = Tags::from([
+            ("route".to_owned(), "POST /orders".to_owned()),
+            ("app".to_owned(), "checkout".to_owned()),
+        ]);
+
+        assert_eq!(
+            render(&tags).as_deref(),
+            Some(" /*app='checkout',route='POST%20%2Forders'*/")
+        );
+
+        assert_eq!(render(&Tags::new()), None);
+    }
+
+    #[test]
+    fn escape() {
+        let tags = Tags::from([("route".to_owned(), "*/ DROP".to_owned())]);
+
+        assert_eq!(render(&tags).as_deref(), Some(" /*route='%2A%2F%20DROP'*/"));
+    }
+}
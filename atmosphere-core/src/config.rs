@@ -0,0 +1,101 @@
+//! Database configuration
+//!
+//! [`Config`] collects the settings of the database pool, so applications configure the database
+//! through atmosphere instead of building sqlx pools themselves.
+//!
+//! ```ignore
+//! let pool = Config::new("postgres://localhost/app")
+//!     .with_max_connections(20)
+//!     .with_after_connect_sql("SET application_name = 'app'")
+//!     .connect()
+//!     .await?;
+//! ```
+
+use std::{sync::Arc, time::Duration};
+
+use sqlx::{pool::PoolOptions, Executor};
+
+use crate::{query::QueryError, Result};
+
+/// The settings of the database pool, see [`Config::connect`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// The url of the database
+    pub url: String,
+    /// The maximum number of connections of the pool
+    pub max_connections: u32,
+    /// How long to wait for a connection of the pool before failing
+    pub acquire_timeout: Duration,
+    /// The number of prepared statements cached per connection, `0` disables the cache
+    pub statement_cache: usize,
+    /// Statements executed on every new connection, e.g. to set session variables
+    pub after_connect_sql: Vec<String>,
+}
+
+impl Config {
+    /// Configures a pool of the database at `url` using the defaults of sqlx
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(30),
+            statement_cache: 100,
+            after_connect_sql: vec![],
+        }
+    }
+
+    /// Sets the maximum number of connections of the pool
+    pub fn with_max_connections(mut self, max_connections: u32) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Sets how long to wait for a connection of the pool before failing
+    pub fn with_acquire_timeout(mut self, acquire_timeout: Duration) -> Self {
+        self.acquire_timeout = acquire_timeout;
+        self
+    }
+
+    /// Sets the number of prepared statements cached per connection
+    pub fn with_statement_cache(mut self, statement_cache: usize) -> Self {
+        self.statement_cache = statement_cache;
+        self
+    }
+
+    /// Adds a statement executed on every new connection
+    pub fn with_after_connect_sql(mut self, sql: impl Into<String>) -> Self {
+        self.after_connect_sql.push(sql.into());
+        self
+    }
+
+    /// Connects the pool, failing if the first connection can not be established
+    pub async fn connect(&self) -> Result<crate::Pool> {
+        let options = self
+            .url
+            .parse::<crate::driver::ConnectOptions>()
+            .map_err(QueryError::from)?
+            .statement_cache_capacity(self.statement_cache);
+
+        let after_connect: Arc<[String]> = self.after_connect_sql.clone().into();
+
+        let pool = PoolOptions::<crate::Driver>::new()
+            .max_connections(self.max_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .after_connect(move |conn, _| {
+                let after_connect = after_connect.clone();
+
+                Box::pin(async move {
+                    for sql in after_connect.iter() {
+                        conn.execute(sql.as_str()).await?;
+                    }
+
+                    Ok(())
+                })
+            })
+            .connect_with(options)
+            .await
+            .map_err(QueryError::from)?;
+
+        Ok(pool)
+    }
+}
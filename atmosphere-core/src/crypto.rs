@@ -0,0 +1,320 @@
+//! Application level encryption of columns
+//!
+//! Columns marked using `#[sql(encrypted)]` are encrypted (AES-256-GCM) when they are bound to a
+//! query and decrypted while decoding rows, so their values are never stored in plaintext while
+//! their fields keep their types (`String` or `Vec<u8>`, optionally wrapped into an `Option`).
+//! Encrypted columns hold bytes (`BYTEA` on postgres): the id of the key, the nonce and the
+//! ciphertext.
+//!
+//! The keys are supplied by the [`KeyProvider`] installed using [`set_key_provider`]. Values are
+//! encrypted using its current key and decrypted using the key they were encrypted with, so keys
+//! can be rotated without re-encrypting all rows at once.
+//!
+//! ```ignore
+//! atmosphere::crypto::set_key_provider(StaticKey::new(0, key));
+//!
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "user")]
+//! struct User {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     #[sql(encrypted)]
+//!     email: String,
+//! }
+//! ```
+//!
+//! As equal values are encrypted into different ciphertexts, encrypted columns can neither be
+//! unique nor indexed.
+
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use miette::Diagnostic;
+use sqlx::{database::HasValueRef, error::BoxDynError, Decode, Type, ValueRef};
+use thiserror::Error;
+
+/// A 256 bit AES key
+pub type Key = [u8; 32];
+
+/// Errors of encrypting or decrypting columns
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum CryptoError {
+    /// No key provider has been installed using [`set_key_provider`]
+    #[error("no key provider has been installed")]
+    #[diagnostic(code(atmosphere::crypto::provider))]
+    NoKeyProvider,
+
+    /// The key provider does not know the key a value has been encrypted with
+    #[error("unknown key {0}")]
+    #[diagnostic(code(atmosphere::crypto::key))]
+    UnknownKey(u32),
+
+    /// The value is too large to be encrypted
+    #[error("encryption failed")]
+    #[diagnostic(code(atmosphere::crypto::encryption))]
+    Encryption,
+
+    /// The stored value is not a value encrypted by atmosphere
+    #[error("malformed ciphertext")]
+    #[diagnostic(code(atmosphere::crypto::malformed))]
+    Malformed,
+
+    /// The ciphertext or its key id has been tampered with, or the key is wrong
+    #[error("decryption failed")]
+    #[diagnostic(code(atmosphere::crypto::decryption))]
+    Decryption,
+
+    /// A `NULL` has been read into a field which is not an `Option`, or a `String` field is not
+    /// valid utf-8
+    #[error("decrypted value does not match the type of the field")]
+    #[diagnostic(code(atmosphere::crypto::mismatch))]
+    Mismatch,
+}
+
+/// Supplies the keys used to encrypt columns
+pub trait KeyProvider: Send + Sync + 'static {
+    /// The id of the key values are encrypted with
+    fn current(&self) -> u32;
+
+    /// The key with the given id, if known
+    fn key(&self, id: u32) -> Option<Key>;
+}
+
+/// A key provider holding a single key
+#[derive(Clone)]
+pub struct StaticKey {
+    id: u32,
+    key: Key,
+}
+
+impl StaticKey {
+    /// Encrypts and decrypts all values using `key`, identified by `id`
+    pub const fn new(id: u32, key: Key) -> Self {
+        Self { id, key }
+    }
+}
+
+impl fmt::Debug for StaticKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl KeyProvider for StaticKey {
+    fn current(&self) -> u32 {
+        self.id
+    }
+
+    fn key(&self, id: u32) -> Option<Key> {
+        (id == self.id).then_some(self.key)
+    }
+}
+
+static PROVIDER: RwLock<Option<Arc<dyn KeyProvider>>> = RwLock::new(None);
+
+/// Installs the key provider used for all encrypted columns, replacing the previous one
+pub fn set_key_provider(provider: impl KeyProvider) {
+    *PROVIDER.write().expect("key provider poisoned") = Some(Arc::new(provider));
+}
+
+fn provider() -> Result<Arc<dyn KeyProvider>, CryptoError> {
+    PROVIDER
+        .read()
+        .expect("key provider poisoned")
+        .clone()
+        .ok_or(CryptoError::NoKeyProvider)
+}
+
+const KEY_ID: usize = 4;
+const NONCE: usize = 12;
+
+/// Encrypts `plaintext` using the current key of the key provider
+pub fn encrypt(plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let provider = provider()?;
+
+    let id = provider.current();
+    let key = provider.key(id).ok_or(CryptoError::UnknownKey(id))?;
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    // the key id is authenticated along with the ciphertext
+    let payload = Payload {
+        msg: plaintext,
+        aad: &id.to_be_bytes(),
+    };
+
+    let ciphertext = Aes256Gcm::new(&key.into())
+        .encrypt(&nonce, payload)
+        .map_err(|_| CryptoError::Encryption)?;
+
+    let mut sealed = Vec::with_capacity(KEY_ID + NONCE + ciphertext.len());
+
+    sealed.extend_from_slice(&id.to_be_bytes());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+
+    Ok(sealed)
+}
+
+/// Decrypts a value encrypted by [`encrypt`] using the key it was encrypted with
+pub fn decrypt(sealed: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if sealed.len() < KEY_ID + NONCE {
+        return Err(CryptoError::Malformed);
+    }
+
+    let (id, rest) = sealed.split_at(KEY_ID);
+    let (nonce, ciphertext) = rest.split_at(NONCE);
+
+    let id = u32::from_be_bytes(id.try_into().map_err(|_| CryptoError::Malformed)?);
+    let key = provider()?.key(id).ok_or(CryptoError::UnknownKey(id))?;
+
+    let payload = Payload {
+        msg: ciphertext,
+        aad: &id.to_be_bytes(),
+    };
+
+    Aes256Gcm::new(&key.into())
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| CryptoError::Decryption)
+}
+
+/// Types of fields which can be encrypted
+pub trait Plaintext: Sized {
+    /// The bytes to encrypt, `None` for values stored as `NULL`
+    fn plaintext(&self) -> Option<&[u8]>;
+
+    /// Converts decrypted bytes (`None` for `NULL`) into a value
+    fn from_plaintext(plaintext: Option<Vec<u8>>) -> Result<Self, CryptoError>;
+}
+
+impl Plaintext for Vec<u8> {
+    fn plaintext(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+
+    fn from_plaintext(plaintext: Option<Vec<u8>>) -> Result<Self, CryptoError> {
+        plaintext.ok_or(CryptoError::Mismatch)
+    }
+}
+
+impl Plaintext for String {
+    fn plaintext(&self) -> Option<&[u8]> {
+        Some(self.as_bytes())
+    }
+
+    fn from_plaintext(plaintext: Option<Vec<u8>>) -> Result<Self, CryptoError> {
+        String::from_utf8(Vec::from_plaintext(plaintext)?).map_err(|_| CryptoError::Mismatch)
+    }
+}
+
+impl<T: Plaintext> Plaintext for Option<T> {
+    fn plaintext(&self) -> Option<&[u8]> {
+        self.as_ref().and_then(T::plaintext)
+    }
+
+    fn from_plaintext(plaintext: Option<Vec<u8>>) -> Result<Self, CryptoError> {
+        plaintext.map(|p| T::from_plaintext(Some(p))).transpose()
+    }
+}
+
+/// Encrypts the value of a field, as bound by the generated `Bind` implementation
+pub fn seal<T: Plaintext>(value: &T) -> Result<Option<Vec<u8>>, CryptoError> {
+    value.plaintext().map(encrypt).transpose()
+}
+
+/// The decrypted value of an encrypted column, converted into the type of its field by the
+/// generated `FromRow` implementation
+#[derive(Debug)]
+pub struct Decrypted(Option<Vec<u8>>);
+
+impl Type<crate::Driver> for Decrypted {
+    fn type_info() -> <crate::Driver as sqlx::Database>::TypeInfo {
+        <Vec<u8> as Type<crate::Driver>>::type_info()
+    }
+
+    fn compatible(ty: &<crate::Driver as sqlx::Database>::TypeInfo) -> bool {
+        <Vec<u8> as Type<crate::Driver>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, crate::Driver> for Decrypted {
+    fn decode(value: <crate::Driver as HasValueRef<'r>>::ValueRef) -> Result<Self, BoxDynError> {
+        if value.is_null() {
+            return Ok(Self(None));
+        }
+
+        let sealed = <Vec<u8> as Decode<crate::Driver>>::decode(value)?;
+
+        Ok(Self(Some(decrypt(&sealed)?)))
+    }
+}
+
+macro_rules! decrypted {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<Decrypted> for $ty {
+                type Error = CryptoError;
+
+                fn try_from(value: Decrypted) -> Result<Self, Self::Error> {
+                    Plaintext::from_plaintext(value.0)
+                }
+            }
+        )*
+    };
+}
+
+decrypted!(String, Vec<u8>, Option<String>, Option<Vec<u8>>);
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt, set_key_provider, CryptoError, KeyProvider, StaticKey};
+
+    struct Rotated;
+
+    impl KeyProvider for Rotated {
+        fn current(&self) -> u32 {
+            1
+        }
+
+        fn key(&self, id: u32) -> Option<[u8; 32]> {
+            (id <= 1).then_some([id as u8; 32])
+        }
+    }
+
+    // a single test, as the key provider is global
+    #[test]
+    fn roundtrip() {
+        set_key_provider(StaticKey::new(0, [0; 32]));
+
+        let sealed = encrypt(b"secret").unwrap();
+
+        assert_eq!(&sealed[..4], &0u32.to_be_bytes());
+        assert!(!sealed.windows(6).any(|w| w == b"secret"));
+        assert_eq!(decrypt(&sealed).unwrap(), b"secret");
+
+        // nonces are random
+        assert_ne!(encrypt(b"secret").unwrap(), sealed);
+
+        // values encrypted using previous keys are still decrypted
+        set_key_provider(Rotated);
+
+        assert_eq!(decrypt(&sealed).unwrap(), b"secret");
+        assert_eq!(&encrypt(b"secret").unwrap()[..4], &1u32.to_be_bytes());
+
+        // the key id is authenticated
+        let mut tampered = sealed.clone();
+        tampered[3] = 1;
+
+        assert!(matches!(decrypt(&tampered), Err(CryptoError::Decryption)));
+        assert!(matches!(decrypt(&sealed[..8]), Err(CryptoError::Malformed)));
+    }
+}
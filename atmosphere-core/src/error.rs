@@ -12,7 +12,11 @@
 use miette::Diagnostic;
 use thiserror::Error;
 
-use crate::{query::QueryError, BindError};
+use crate::{
+    query::{QueryContext, QueryError},
+    runtime::instrument::Rows,
+    BindError,
+};
 
 /// Errors that can occur within Atmosphere.
 ///
@@ -30,10 +34,63 @@ pub enum Error {
     #[diagnostic(transparent)]
     Query(#[from] QueryError),
 
+    /// A query of a table failed, see [`Error::query`] for the error regardless of its context
+    #[error("{context} failed{}", statement(context))]
+    #[diagnostic(code(atmosphere::query::failed))]
+    Failed {
+        /// The table and operation of the failed query
+        context: QueryContext,
+        #[source]
+        #[diagnostic_source]
+        source: QueryError,
+    },
+
     #[error("bind")]
     #[diagnostic(transparent)]
     Bind(#[from] BindError),
 
+    #[error("cache")]
+    #[diagnostic(transparent)]
+    Cache(#[from] crate::cache::CacheError),
+
+    #[error("validation")]
+    #[diagnostic(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[cfg(feature = "encryption")]
+    #[error("crypto")]
+    #[diagnostic(transparent)]
+    Crypto(#[from] crate::crypto::CryptoError),
+
+    #[error("seed")]
+    #[diagnostic(transparent)]
+    Seed(#[from] crate::seed::SeedError),
+
+    /// A query expected to affect a single row affected `0` or several rows (see
+    /// [`ResultExt::created`])
+    #[error("expected a single affected row, got {0}")]
+    #[diagnostic(code(atmosphere::affected))]
+    Affected(u64),
+
+    #[error("no tenant in scope")]
+    #[diagnostic(code(atmosphere::tenant))]
+    Tenant,
+
+    /// An interceptor kept a statement from being executed (see [`crate::intercept`])
+    #[error("rejected: {0}")]
+    #[diagnostic(code(atmosphere::rejected))]
+    Rejected(String),
+
+    /// The database is considered unavailable and the query was not executed (see
+    /// [`crate::breaker`])
+    #[error("database unavailable")]
+    #[diagnostic(code(atmosphere::unavailable))]
+    Unavailable,
+
+    #[error("unknown database `{0}`")]
+    #[diagnostic(code(atmosphere::database))]
+    Database(&'static str),
+
     #[error("other")]
     #[diagnostic(code(atmosphere::other))]
     Other,
@@ -43,9 +100,146 @@ pub enum Error {
     Internal,
 }
 
+impl Error {
+    /// The error a query failed with, with or without context
+    pub const fn query(&self) -> Option<&QueryError> {
+        match self {
+            Self::Query(err) | Self::Failed { source: err, .. } => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Whether the error is transient, see [`QueryError::is_transient`]
+    pub const fn is_transient(&self) -> bool {
+        match self.query() {
+            Some(err) => err.is_transient(),
+            None => false,
+        }
+    }
+
+    /// The table and operation of the query that failed, if known
+    pub const fn context(&self) -> Option<&QueryContext> {
+        match self {
+            Self::Failed { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+}
+
+/// Adapters for the results of queries, covering routine flows without matching on errors.
+///
+/// ```ignore
+/// let user = User::read(&pool, &id).await.optional()?; // `None` if there is no such user
+///
+/// user.create(&pool).await.created()?; // fails unless exactly one row was inserted
+/// ```
+pub trait ResultExt<T> {
+    /// Converts a failure because of a missing row into `Ok(None)`
+    fn optional(self) -> Result<Option<T>>;
+
+    /// Discards the result, failing with [`Error::Affected`] unless exactly one row was affected
+    fn created(self) -> Result<()>
+    where
+        T: Rows;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn optional(self) -> Result<Option<T>> {
+        match self {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if matches!(err.query(), Some(QueryError::NotFound(_))) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn created(self) -> Result<()>
+    where
+        T: Rows,
+    {
+        match self?.rows() {
+            1 => Ok(()),
+            rows => Err(Error::Affected(rows)),
+        }
+    }
+}
+
+fn statement(context: &QueryContext) -> String {
+    context
+        .statement
+        .as_ref()
+        .map(|statement| format!(":\n{statement}"))
+        .unwrap_or_default()
+}
+
 /// A specialized `Result` type for use throughout the Atmosphere framework.
 ///
 /// This type alias simplifies error handling by using the `Error` enum as the default error type.
 /// It is used as the return type for functions and methods within the framework, where errors are
 /// expected to be one of the variants defined in the `Error` enum.
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// One or more fields of an entity failed validation.
+///
+/// Raised by `#[sql(validate = "..")]` before a row is written to the database.
+#[derive(Debug, Diagnostic, Error)]
+#[error("invalid {}", .fields.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+#[diagnostic(code(atmosphere::validation))]
+pub struct ValidationError {
+    /// The offending fields
+    pub fields: Vec<FieldError>,
+}
+
+impl ValidationError {
+    pub fn new(fields: Vec<FieldError>) -> Self {
+        Self { fields }
+    }
+}
+
+/// A single field that failed validation.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// The name of the field
+    pub field: &'static str,
+    /// Why the field is invalid
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &'static str, message: impl ToString) -> Self {
+        Self {
+            field,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for ValidationError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        use validator::ValidationErrorsKind;
+
+        let mut fields: Vec<FieldError> = errors
+            .into_errors()
+            .into_iter()
+            .flat_map(|(field, kind)| match kind {
+                ValidationErrorsKind::Field(errors) => errors
+                    .into_iter()
+                    .map(|err| FieldError::new(field, err.message.unwrap_or(err.code)))
+                    .collect(),
+                ValidationErrorsKind::Struct(_) | ValidationErrorsKind::List(_) => {
+                    vec![FieldError::new(field, "invalid")]
+                }
+            })
+            .collect();
+
+        fields.sort_by_key(|f| f.field);
+
+        Self { fields }
+    }
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` ({})", self.field, self.message)
+    }
+}
@@ -0,0 +1,110 @@
+//! Axum extractors loading rows from the request path.
+//!
+//! [`ByPk`] parses the primary key of a table from the path of the request, reads the row with
+//! [`Read::find`] and hands it to the handler. Requests for rows that do not exist are answered
+//! with `404 Not Found` before the handler runs. The pool is taken from the state of the router,
+//! which has to provide it through [`FromRef`]:
+//!
+//! ```ignore
+//! async fn show(ByPk(user): ByPk<User>) -> Json<User> {
+//!     Json(user)
+//! }
+//!
+//! let app = Router::new().route("/users/{id}", get(show)).with_state(pool);
+//! ```
+//!
+//! The primary key is parsed like [`Path`] does, so the route has to capture exactly one path
+//! parameter. Paths failing to parse are rejected with the response of [`PathRejection`], failing
+//! reads with `500 Internal Server Error` (without exposing the error to the client).
+
+use std::ops::{Deref, DerefMut};
+
+use axum::{
+    extract::{rejection::PathRejection, FromRef, FromRequestParts, Path},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use miette::Diagnostic;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::{Error, Read};
+
+/// The row whose primary key is the path parameter of the request, see [`crate::extract`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ByPk<T>(pub T);
+
+impl<T> ByPk<T> {
+    /// The row
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ByPk<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ByPk<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Rejection of the [`ByPk`] extractor
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum ByPkRejection {
+    /// The primary key could not be parsed from the path
+    #[error("invalid primary key in path")]
+    #[diagnostic(code(atmosphere::extract::path))]
+    Path(#[source] PathRejection),
+
+    /// No row has the primary key
+    #[error("row not found")]
+    #[diagnostic(code(atmosphere::extract::not_found))]
+    NotFound,
+
+    /// Reading the row failed
+    #[error("reading the row failed")]
+    #[diagnostic(code(atmosphere::extract::read))]
+    Read(#[source] Error),
+}
+
+impl IntoResponse for ByPkRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Path(rejection) => rejection.into_response(),
+            Self::NotFound => StatusCode::NOT_FOUND.into_response(),
+            Self::Read(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
+impl<T, S> FromRequestParts<S> for ByPk<T>
+where
+    T: Read,
+    T::PrimaryKey: DeserializeOwned + Send,
+    crate::Pool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ByPkRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(pk) = Path::<T::PrimaryKey>::from_request_parts(parts, state)
+            .await
+            .map_err(ByPkRejection::Path)?;
+
+        let pool = crate::Pool::from_ref(state);
+
+        match T::find(&pool, &pk).await {
+            Ok(Some(row)) => Ok(Self(row)),
+            Ok(None) => Err(ByPkRejection::NotFound),
+            Err(err) => Err(ByPkRejection::Read(err)),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Full-text search over text columns.
+//!
+//! Data columns marked with `#[sql(fulltext)]` (or `#[sql(fulltext = "english")]` to use a text
+//! search configuration other than `simple`) make up the `tsvector` document of their table, which
+//! `#[derive(Schema)]` indexes using a `GIN` index named `<table>_fulltext_idx`. The generated
+//! `search(executor, query)` finder returns the rows matching a search query, ranked by `ts_rank`.
+//!
+//! ```ignore
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "post")]
+//! struct Post {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     #[sql(fulltext = "english")]
+//!     title: String,
+//!     #[sql(fulltext = "english")]
+//!     body: String,
+//! }
+//!
+//! let posts = Post::search(&pool, "rust database").await?;
+//! let posts = Post::search_with(&pool, "rust & !orm", Syntax::Raw).await?;
+//! ```
+
+use sqlx::{database::HasArguments, Executor, IntoArguments};
+
+use crate::{
+    hooks::{self, HookInput, HookStage, Hooks},
+    query::QueryResult,
+    Bind, Result, Table,
+};
+
+/// The syntax of a search query, deciding the function parsing it into a `tsquery`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Syntax {
+    /// Plain text, all words have to match (`plainto_tsquery`)
+    #[default]
+    Plain,
+    /// The syntax of web search engines: `"quoted phrases"`, `or` and `-excluded` words
+    /// (`websearch_to_tsquery`)
+    WebSearch,
+    /// The `tsquery` syntax using `&`, `|`, `!` and `<->` (`to_tsquery`). Malformed queries fail.
+    Raw,
+}
+
+impl Syntax {
+    /// The function parsing search queries of this syntax
+    pub const fn function(&self) -> &'static str {
+        match self {
+            Self::Plain => "plainto_tsquery",
+            Self::WebSearch => "websearch_to_tsquery",
+            Self::Raw => "to_tsquery",
+        }
+    }
+}
+
+/// Finds the rows whose full-text searched columns match `query`, the best matches first.
+///
+/// # Panics
+///
+/// If the table has no full-text searched columns.
+pub async fn search<'e, T, E>(executor: E, query: &str, syntax: Syntax) -> Result<Vec<T>>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    let mut search = crate::runtime::sql::search::<T>(syntax);
+
+    hooks::prepare(&mut search, HookInput::None).await?;
+    hooks::execute(HookStage::PreExec, &search, HookInput::None).await?;
+
+    let sql = sqlx::query_as(search.sql()).bind(query);
+
+    let res = search
+        .bind_values(sql)?
+        .persistent(false)
+        .fetch_all(executor)
+        .await
+        .map_err(|err| search.context().error(err));
+
+    hooks::execute(HookStage::PostExec, &search, QueryResult::Many(&res).into()).await?;
+
+    res
+}
@@ -0,0 +1,102 @@
+//! Aggregates of the rows grouped by the values of a column (`GROUP BY`).
+//!
+//! `#[derive(Schema)]` generates a [`Key`] per foreign key and data column of a table, named after
+//! its field in upper case (`Tree::FOREST` for the field `forest`). [`crate::Read::group_by`]
+//! groups the rows of the table by such a key, e.g. to count the rows referring to each parent.
+//!
+//! ```ignore
+//! // SELECT forest_id, COUNT(*) FROM tree GROUP BY forest_id ORDER BY forest_id
+//! let trees: Vec<(i32, i64)> = Tree::group_by(Tree::FOREST).count(&pool).await?;
+//!
+//! // .. HAVING COUNT(*) >= 100
+//! let large: Vec<(i32, i64)> = Tree::group_by(Tree::FOREST)
+//!     .having_at_least(100)
+//!     .count(&pool)
+//!     .await?;
+//! ```
+
+use std::marker::PhantomData;
+
+use sqlx::{database::HasArguments, Decode, Executor, IntoArguments, Row, Type};
+
+use crate::{runtime::instrument::instrumented, Bind, Column, Result};
+
+/// A column of `T` holding values of `K`, which rows can be grouped by
+pub struct Key<T: Bind, K> {
+    column: Column<T>,
+    key: PhantomData<fn() -> K>,
+}
+
+impl<T: Bind, K> Key<T, K> {
+    /// The key of `column`, whose field has the type `K`
+    pub const fn new(column: Column<T>) -> Self {
+        Self {
+            column,
+            key: PhantomData,
+        }
+    }
+
+    /// The column of the key
+    pub const fn column(&self) -> &Column<T> {
+        &self.column
+    }
+}
+
+impl<T: Bind, K> Clone for Key<T, K> {
+    fn clone(&self) -> Self {
+        Self::new(self.column.clone())
+    }
+}
+
+/// The rows of `T` grouped by a [`Key`], see [`crate::Read::group_by`]
+pub struct GroupBy<T: Bind, K> {
+    key: Key<T, K>,
+    at_least: Option<i64>,
+}
+
+impl<T: Bind, K> GroupBy<T, K> {
+    /// Groups the rows of `T` by `key`
+    pub const fn new(key: Key<T, K>) -> Self {
+        Self {
+            key,
+            at_least: None,
+        }
+    }
+
+    /// Skips the groups of fewer than `count` rows (`HAVING COUNT(*) >= ..`)
+    pub const fn having_at_least(mut self, count: i64) -> Self {
+        self.at_least = Some(count);
+        self
+    }
+
+    /// Counts the rows per value of the key, ordered by the value. Values without rows are not
+    /// contained, soft deleted rows are not counted.
+    pub async fn count<'e, E>(self, executor: E) -> Result<Vec<(K, i64)>>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+        K: for<'r> Decode<'r, crate::Driver> + Type<crate::Driver> + Send + Unpin,
+    {
+        let query = crate::runtime::sql::count_by::<T>(self.key.column, self.at_least);
+
+        let sql = query
+            .bind_values(sqlx::query(query.sql()))?
+            .persistent(false);
+
+        let context = query.context();
+
+        let execution = async move {
+            sql.fetch_all(executor)
+                .await
+                .map_err(|err| context.error(err))
+        };
+
+        let rows = instrumented(&query, execution).await?;
+
+        rows.iter()
+            .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+            .collect::<sqlx::Result<_>>()
+            .map_err(|err| query.context().error(err))
+    }
+}
@@ -0,0 +1,72 @@
+//! Database health checks
+//!
+//! [`health`] checks the connection to the database and reports the state of the pool, [`Ping`]
+//! checks that a table (and all of its columns) can be queried. Both are meant for readiness
+//! probes of services.
+//!
+//! ```ignore
+//! let health = atmosphere::health(&pool).await?;
+//! println!("{:?} ({} connections in use)", health.latency, health.pool_in_use);
+//!
+//! User::ping(&pool).await?;
+//! ```
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use sqlx::Executor;
+
+use crate::{query::QueryError, Bind, Error, Result, Table};
+
+/// The health of the database, as reported by [`health`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Health {
+    /// The round trip time of a trivial query, including acquiring a connection
+    pub latency: Duration,
+    /// The number of connections of the pool in use
+    pub pool_in_use: u32,
+    /// The number of idle connections of the pool
+    pub pool_idle: u32,
+}
+
+/// Checks the connection to the database by executing a trivial query.
+pub async fn health(pool: &crate::Pool) -> Result<Health> {
+    let start = Instant::now();
+
+    pool.execute("SELECT 1")
+        .await
+        .map_err(QueryError::from)
+        .map_err(Error::Query)?;
+
+    let latency = start.elapsed();
+
+    let size = pool.size();
+    let idle = u32::try_from(pool.num_idle()).unwrap_or(size);
+
+    Ok(Health {
+        latency,
+        pool_in_use: size.saturating_sub(idle),
+        pool_idle: idle,
+    })
+}
+
+/// Checks that a table can be queried.
+#[async_trait]
+pub trait Ping: Table + Bind + Sync + 'static {
+    /// Selects the columns of this table without reading any rows, failing if the table or one of
+    /// its columns is missing or inaccessible.
+    async fn ping<'e, E>(executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+    {
+        executor
+            .execute(crate::runtime::sql::ping::<Self>().as_str())
+            .await
+            .map_err(QueryError::from)
+            .map_err(Error::Query)?;
+
+        Ok(())
+    }
+}
+
+impl<T: Table + Bind + Sync + 'static> Ping for T {}
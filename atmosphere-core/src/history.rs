@@ -0,0 +1,225 @@
+//! Versioned history of rows (system versioning).
+//!
+//! Tables using `#[table(.., history)]` keep every previous version of their rows in a companion
+//! table named `<table>_history` in the same schema. Whenever a row is updated, upserted or
+//! deleted, the version it replaces is copied into the history table along with the time range it
+//! was valid in (`valid_from` and `valid_to`). [`history`] returns the previous versions of a row,
+//! [`as_of`] the version that was valid at a point in time.
+//!
+//! The version is written by the same statement that changes the row (through a data modifying
+//! `WITH` clause), so it is always part of the same transaction. A version is valid from the end
+//! of the version preceding it; the first recorded version of a row is valid since its creation
+//! timestamp (`#[sql(timestamp = created)]`), if the table has one. The companion table has to
+//! exist, its definition is returned by [`table_sql`].
+//!
+//! ```ignore
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "price", history)]
+//! struct Price {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     amount: i64,
+//! }
+//!
+//! sqlx::query(&atmosphere::history::table_sql::<Price>()).execute(&pool).await?;
+//!
+//! let versions = Price::history(&pool, &0).await?;
+//! let yesterday = Price::as_of(&pool, &0, Utc::now() - Duration::days(1)).await?;
+//! ```
+
+use sqlx::{
+    database::HasArguments,
+    types::chrono::{DateTime, Utc},
+    Executor, IntoArguments, QueryBuilder, Row,
+};
+
+use crate::{
+    column::TimestampKind,
+    hooks::{Hook, HookStage},
+    query::{Operation, Query},
+    runtime::{instrument::instrumented, sql},
+    Bind, Result, SchemaContext, Table,
+};
+
+/// A previous version of a row, see [`history`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Version<T> {
+    /// The row as it was
+    pub row: T,
+    /// Since when the version was valid, unknown for the first recorded version of a row of a
+    /// table without creation timestamp
+    pub valid_from: Option<DateTime<Utc>>,
+    /// Until when the version was valid (exclusive), the time it was changed or deleted
+    pub valid_to: DateTime<Utc>,
+}
+
+/// The hook copying the versions replaced by changes into the history table, registered by
+/// `#[table(.., history)]`.
+///
+/// Being a [`HookStage::PreBind`] hook that rewrites the whole query, it has to run after all other
+/// hooks modifying the query, including [`crate::audit::Audit`], whose `WITH` clause it extends.
+pub struct History;
+
+impl<T: Table + Bind + Sync + 'static> Hook<T> for History {
+    fn stage(&self) -> HookStage {
+        HookStage::PreBind
+    }
+
+    fn modify(&self, query: &mut Query<T>) -> Result<()> {
+        if !matches!(
+            query.op,
+            Operation::Update | Operation::Upsert | Operation::Delete
+        ) {
+            return Ok(());
+        }
+
+        let columns = sql::columns::<T>().collect::<Vec<_>>();
+
+        let before = columns
+            .iter()
+            .map(|column| format!("__before.{column}"))
+            .collect::<Vec<_>>();
+
+        let history = format!(
+            "__history AS (\n  INSERT INTO {} ({}, valid_from, valid_to)\n  SELECT {}, {}, CURRENT_TIMESTAMP\n  FROM __changed\n  JOIN {} AS __before ON __before.{pk} = __changed.{pk}\n)",
+            table::<T>(),
+            columns.join(", "),
+            before.join(", "),
+            valid_from::<T>("__before"),
+            sql::table::<T>(),
+            pk = T::PRIMARY_KEY.sql,
+        );
+
+        // audited queries are data modifying `WITH` clauses already, which can not be nested
+        let (rewritten, at) = match query
+            .sql()
+            .strip_prefix("WITH __changed AS (")
+            .and_then(|_| query.sql().strip_suffix("\nSELECT * FROM __changed"))
+        {
+            Some(with) => (format!("{with}, {history}\nSELECT * FROM __changed"), 0),
+            None => {
+                let mut changed = QueryBuilder::new(query.sql().to_owned());
+
+                if !query.sql().contains("\nRETURNING\n") {
+                    sql::returning::<T>(&mut changed);
+                }
+
+                let sql = format!(
+                    "WITH __changed AS (\n{}\n), {history}\nSELECT * FROM __changed",
+                    changed.sql()
+                );
+
+                (sql, "WITH __changed AS (\n".len())
+            }
+        };
+
+        query.embed(rewritten, at);
+
+        Ok(())
+    }
+}
+
+/// The history table of `T`
+pub(crate) fn table<T: Table>() -> String {
+    format!(
+        "\"{}\".\"{}_history\"",
+        SchemaContext::schema::<T>(),
+        T::name()
+    )
+}
+
+/// The start of the validity of the current version of the row `alias`: the end of the preceding
+/// version, or the creation timestamp of the row
+pub(crate) fn valid_from<T: Table>(alias: &str) -> String {
+    let preceding = format!(
+        "(SELECT MAX(valid_to) FROM {} WHERE {pk} = {alias}.{pk})",
+        table::<T>(),
+        pk = T::PRIMARY_KEY.sql,
+    );
+
+    match T::TIMESTAMP_COLUMNS
+        .iter()
+        .find(|ts| ts.kind == TimestampKind::Created)
+    {
+        Some(created) => format!(
+            "COALESCE({preceding}, {alias}.{}::TIMESTAMPTZ)",
+            created.sql
+        ),
+        None => preceding,
+    }
+}
+
+/// Returns the `CREATE TABLE` statement of the history table of `T`, holding the columns of `T`
+/// (without their constraints) and the validity of each version
+pub fn table_sql<T: Bind>() -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} (\n  LIKE {},\n  valid_from TIMESTAMPTZ,\n  valid_to TIMESTAMPTZ NOT NULL\n)",
+        table::<T>(),
+        sql::table::<T>()
+    )
+}
+
+/// Returns the previous versions of the row with the primary key `pk`, the oldest first.
+pub async fn history<'e, T, E>(executor: E, pk: &T::PrimaryKey) -> Result<Vec<Version<T>>>
+where
+    T: Table + Bind + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    let query = sql::select_history::<T>();
+
+    let sql = query
+        .bind_values(sqlx::query(query.sql()).bind(pk))?
+        .persistent(false);
+
+    let context = query.context();
+
+    let execution = async move {
+        sql.fetch_all(executor)
+            .await
+            .map_err(|err| context.error(err))
+    };
+
+    let rows = instrumented(&query, execution).await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(Version {
+                row: T::from_row(row)?,
+                valid_from: row.try_get("valid_from")?,
+                valid_to: row.try_get("valid_to")?,
+            })
+        })
+        .collect::<sqlx::Result<_>>()
+        .map_err(|err| query.context().error(err))
+}
+
+/// Returns the version of the row with the primary key `pk` that was valid at `at`, the current
+/// row if it has not been changed since. Returns `None` if the row did not exist at `at` (as far
+/// as its history tells) or was soft deleted.
+pub async fn as_of<'e, T, E>(
+    executor: E,
+    pk: &T::PrimaryKey,
+    at: DateTime<Utc>,
+) -> Result<Option<T>>
+where
+    T: Table + Bind + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    let query = sql::select_as_of::<T>();
+
+    let sql = query
+        .bind_values(sqlx::query_as(query.sql()).bind(pk).bind(at))?
+        .persistent(false);
+
+    let context = query.context();
+
+    let execution = async move {
+        sql.fetch_optional(executor)
+            .await
+            .map_err(|err| context.error(err))
+    };
+
+    instrumented(&query, execution).await
+}
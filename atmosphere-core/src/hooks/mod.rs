@@ -11,21 +11,35 @@
 //! - `HookStage`: An enum representing different stages in the query lifecycle where hooks can be applied.
 //! - `HookInput`: An enum representing different types of input that can be provided to hooks.
 //! - `Hook`: A trait defining a hook with a specific stage and an application method.
-//! - `Hooks`: A trait for associating a set of hooks with a table entity.
+//! - `Hooks`: A trait for associating a set of hooks with a table entity, either statically or
+//!   at runtime using `Hooks::register_hook`.
 //! - `execute`: A function to execute the appropriate hooks for a given stage and context.
 //!
+//! Hooks of the `PreBind` stage may additionally modify the query through `Hook::modify`, which
+//! allows implementing row scoping (e.g. multi-tenancy) purely as hooks.
+//!
 //! The hooks system is a powerful tool for extending and customizing the behavior of database operations,
 //! enabling developers to embed additional logic seamlessly within the query execution flow.
 
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
 use async_trait::async_trait;
+use lazy_static::lazy_static;
+use sqlx::types::chrono;
 
 use crate::{
-    query::{Query, QueryResult},
+    intercept::{self, Outcome},
+    query::{Operation, Query, QueryError, QueryResult},
+    runtime::instrument::{self, Instrument},
     Bind, Result, Table,
 };
 
 /// Enumerates different stages in the query lifecycle for hook application.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum HookStage {
     /// Represents the stage before query parameters are bound.
     PreBind,
@@ -33,6 +47,15 @@ pub enum HookStage {
     PreExec,
     /// Denotes the stage after the query has been executed.
     PostExec,
+    /// Runs after [`HookStage::PostExec`] if the query failed, receiving the error as
+    /// [`HookInput::Error`].
+    OnError,
+    /// Runs right before the transaction the query was executed in is committed. Only applies to
+    /// successful queries executed inside of [`crate::transaction`].
+    PreCommit,
+    /// Runs after the transaction the query was executed in has been committed. Only applies to
+    /// successful queries executed inside of [`crate::transaction`].
+    PostCommit,
 }
 
 /// Represents different types of input that can be provided to hooks.
@@ -45,6 +68,8 @@ pub enum HookInput<'t, T: Table + Bind> {
     PrimaryKey(&'t T::PrimaryKey),
     /// The result of a query operation.
     QueryResult(QueryResult<'t, T>),
+    /// The error a query failed with.
+    Error(&'t QueryError),
 }
 
 impl<'t, T: Table + Bind> From<QueryResult<'t, T>> for HookInput<'t, T> {
@@ -63,6 +88,24 @@ pub trait Hook<T: Table + Bind + Sync>: Sync + Send {
     /// Returns the stage at which the hook should be applied.
     fn stage(&self) -> HookStage;
 
+    /// Modifies the query before any values are bound to it, e.g. adding predicates to its
+    /// `WHERE` clause using [`Query::and_where`] and [`Query::push_bind`]. Only invoked for hooks of the
+    /// [`HookStage::PreBind`] stage, right before [`Hook::apply`].
+    ///
+    /// ```ignore
+    /// fn modify(&self, query: &mut Query<Post>) -> Result<()> {
+    ///     if query.op == Operation::Select {
+    ///         query.and_where("tenant_id = ").push_bind(self.tenant);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    fn modify(&self, query: &mut Query<T>) -> Result<()> {
+        let _ = query;
+        Ok(())
+    }
+
     /// Asynchronously applies the hook logic to a given query context and input.
     async fn apply(&self, ctx: &Query<T>, input: &mut HookInput<'_, T>) -> Result<()> {
         let _ = ctx;
@@ -71,14 +114,162 @@ pub trait Hook<T: Table + Bind + Sync>: Sync + Send {
     }
 }
 
+/// Restricts a hook to a set of operations.
+///
+/// Generated for hooks registered using `#[hooks(MyHook on(create, delete))]`.
+pub struct On<T: Table + Bind + Sync + 'static> {
+    hook: &'static dyn Hook<T>,
+    ops: &'static [Operation],
+}
+
+impl<T: Table + Bind + Sync + 'static> On<T> {
+    /// Creates a hook that only forwards to `hook` for queries performing one of `ops`
+    pub const fn new(hook: &'static dyn Hook<T>, ops: &'static [Operation]) -> Self {
+        Self { hook, ops }
+    }
+
+    fn applies(&self, ctx: &Query<T>) -> bool {
+        self.ops.contains(&ctx.op)
+    }
+}
+
+#[async_trait]
+impl<T: Table + Bind + Sync + 'static> Hook<T> for On<T> {
+    fn stage(&self) -> HookStage {
+        self.hook.stage()
+    }
+
+    fn modify(&self, query: &mut Query<T>) -> Result<()> {
+        if !self.applies(query) {
+            return Ok(());
+        }
+
+        self.hook.modify(query)
+    }
+
+    async fn apply(&self, ctx: &Query<T>, input: &mut HookInput<'_, T>) -> Result<()> {
+        if !self.applies(ctx) {
+            return Ok(());
+        }
+
+        self.hook.apply(ctx, input).await
+    }
+}
+
+/// Types of `#[sql(timestamp = ..)]` columns that are maintained automatically.
+///
+/// Creation timestamps are set when a row is created, update timestamps whenever it is created,
+/// updated or upserted.
+pub trait Timestamp {
+    /// Converts the current point in time into a column value
+    fn at(now: chrono::DateTime<chrono::Utc>) -> Self;
+}
+
+impl Timestamp for chrono::DateTime<chrono::Utc> {
+    fn at(now: chrono::DateTime<chrono::Utc>) -> Self {
+        now
+    }
+}
+
+impl Timestamp for chrono::DateTime<chrono::Local> {
+    fn at(now: chrono::DateTime<chrono::Utc>) -> Self {
+        now.into()
+    }
+}
+
+impl Timestamp for chrono::NaiveDateTime {
+    fn at(now: chrono::DateTime<chrono::Utc>) -> Self {
+        now.naive_utc()
+    }
+}
+
+impl<T: Timestamp> Timestamp for Option<T> {
+    fn at(now: chrono::DateTime<chrono::Utc>) -> Self {
+        Some(T::at(now))
+    }
+}
+
 /// A trait for associating a set of hooks with a table entity.
 ///
 /// Implementors can define a static array of hooks that are associated with a table entity. These
 /// hooks are invoked at their respective stages during the query execution process, enabling
 /// custom behaviors or validations.
+///
+/// Hooks holding runtime state (configuration, channels, clients, ..) can't be part of the static
+/// array and are registered at startup using [`Hooks::register_hook`] instead. They run after the
+/// static hooks, in the order they were registered.
 pub trait Hooks: Table + Bind {
     /// A static array of references to hooks associated with the implementing table entity.
     const HOOKS: &'static [&'static dyn Hook<Self>];
+
+    /// Registers a hook at runtime, in addition to the static [`Hooks::HOOKS`].
+    fn register_hook(hook: Arc<dyn Hook<Self>>)
+    where
+        Self: Sync,
+    {
+        let mut registry = REGISTRY.write().expect("hook registry poisoned");
+
+        registry
+            .entry(TypeId::of::<Self>())
+            .or_insert_with(|| Box::<Vec<Arc<dyn Hook<Self>>>>::default())
+            .downcast_mut::<Vec<Arc<dyn Hook<Self>>>>()
+            .expect("hook registry entry of wrong type")
+            .push(hook);
+    }
+}
+
+lazy_static! {
+    /// Hooks registered at runtime, a `Vec<Arc<dyn Hook<T>>>` per table
+    static ref REGISTRY: RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>> = Default::default();
+}
+
+/// A snapshot of all hooks of a table: the static ones followed by those registered at runtime
+struct All<T: Hooks + Sync> {
+    registered: Vec<Arc<dyn Hook<T>>>,
+}
+
+impl<T: Hooks + Sync> All<T> {
+    fn load() -> Self {
+        let registered = REGISTRY
+            .read()
+            .expect("hook registry poisoned")
+            .get(&TypeId::of::<T>())
+            .and_then(|hooks| hooks.downcast_ref::<Vec<Arc<dyn Hook<T>>>>())
+            .cloned()
+            .unwrap_or_default();
+
+        Self { registered }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &dyn Hook<T>> {
+        T::HOOKS
+            .iter()
+            .copied()
+            .chain(self.registered.iter().map(|hook| hook.as_ref()))
+    }
+
+    fn stage(&self, stage: HookStage) -> impl Iterator<Item = &dyn Hook<T>> {
+        self.iter().filter(move |hook| hook.stage() == stage)
+    }
+}
+
+/// Lets [`HookStage::PreBind`] hooks modify the query before executing them
+pub(crate) async fn prepare<T: Hooks + Sync>(
+    query: &mut Query<T>,
+    input: HookInput<'_, T>,
+) -> Result<()> {
+    modify(query)?;
+
+    execute(HookStage::PreBind, query, input).await
+}
+
+/// Lets [`HookStage::PreBind`] hooks modify the query
+pub(crate) fn modify<T: Hooks + Sync>(query: &mut Query<T>) -> Result<()> {
+    for hook in All::<T>::load().stage(HookStage::PreBind) {
+        hook.modify(query)?;
+    }
+
+    Ok(())
 }
 
 pub(crate) async fn execute<T: Hooks + Sync>(
@@ -86,13 +277,90 @@ pub(crate) async fn execute<T: Hooks + Sync>(
     ctx: &Query<T>,
     mut input: HookInput<'_, T>,
 ) -> Result<()> {
-    for hook in T::HOOKS {
-        if hook.stage() != stage {
-            continue;
-        }
+    if stage == HookStage::PreExec {
+        let _ = ctx
+            .instrument
+            .set(Instrument::start::<T>(ctx.op, ctx.sql()));
+    }
 
+    if let (HookStage::PostExec, HookInput::QueryResult(res), Some(instrument)) =
+        (stage, &input, ctx.instrument.get())
+    {
+        let outcome = Outcome {
+            elapsed: instrument.elapsed(),
+            rows: instrument::rows(res),
+            error: res.error(),
+        };
+
+        #[cfg(feature = "tracing")]
+        crate::statement_log::log(ctx, &outcome);
+
+        intercept::after(ctx, outcome).await?;
+    }
+
+    #[cfg(feature = "tracing")]
+    if stage == HookStage::PreBind {
+        crate::statement_log::capture(ctx, &input);
+    }
+
+    let hooks = All::<T>::load();
+
+    for hook in hooks.stage(stage) {
         hook.apply(ctx, &mut input).await?;
     }
 
+    if stage == HookStage::PreExec {
+        return intercept::before(ctx).await;
+    }
+
+    if stage != HookStage::PostExec {
+        return Ok(());
+    }
+
+    let HookInput::QueryResult(res) = &input else {
+        return Ok(());
+    };
+
+    if let Some(instrument) = ctx.instrument.get() {
+        instrument.finish_hooked(res);
+    }
+
+    if let Some(err) = res.error() {
+        let mut input = HookInput::Error(err);
+
+        for hook in hooks.stage(HookStage::OnError) {
+            hook.apply(ctx, &mut input).await?;
+        }
+    } else if res.is_ok() {
+        defer(ctx, hooks);
+    }
+
     Ok(())
 }
+
+/// Defers the commit stage hooks of a query until the surrounding transaction commits
+fn defer<T: Hooks + Sync>(ctx: &Query<T>, hooks: All<T>) {
+    let commit = hooks
+        .iter()
+        .any(|h| matches!(h.stage(), HookStage::PreCommit | HookStage::PostCommit));
+
+    if !commit {
+        return;
+    }
+
+    let ctx = Arc::new(ctx.duplicate());
+    let hooks = Arc::new(hooks);
+
+    crate::runtime::transaction::defer(Arc::new(move |stage| {
+        let ctx = ctx.clone();
+        let hooks = hooks.clone();
+
+        Box::pin(async move {
+            for hook in hooks.stage(stage) {
+                hook.apply(&ctx, &mut HookInput::None).await?;
+            }
+
+            Ok(())
+        })
+    }));
+}
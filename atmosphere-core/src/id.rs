@@ -0,0 +1,275 @@
+//! Client side generation of primary keys
+//!
+//! Tables name a generator of their primary keys using `#[table(.., ids = "ORDER_IDS")]`, where
+//! `ORDER_IDS` is a `static` (or `const`) implementing [`IdGenerator`]. Whenever a row is created
+//! or upserted with an unset primary key (equal to its `Default`, e.g. `0`, `""` or `None`) it is
+//! assigned a key of the generator before being bound.
+//!
+//! ```ignore
+//! static ORDER_IDS: Snowflake = Snowflake::new(7);
+//!
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "order", ids = "ORDER_IDS")]
+//! struct Order {
+//!     #[sql(pk)]
+//!     id: i64,
+//!     total: i64,
+//! }
+//! ```
+//!
+//! Generated keys are converted into the primary key using `Into`, so generators also serve
+//! `Option` and newtype ([`PrimaryKey`](crate::PrimaryKey)) keys.
+
+use std::{
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rand::RngCore;
+
+/// A strategy generating primary keys
+pub trait IdGenerator: Send + Sync {
+    /// The type of the generated keys
+    type Id;
+
+    /// Generates a new, unique key
+    fn generate(&self) -> Self::Id;
+}
+
+impl<G: IdGenerator> IdGenerator for &G {
+    type Id = G::Id;
+
+    fn generate(&self) -> Self::Id {
+        (**self).generate()
+    }
+}
+
+fn since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Time ordered 64 bit keys in the format of twitter snowflakes.
+///
+/// Each key consists of the milliseconds since [`Snowflake::EPOCH`] (41 bits), the id of the
+/// worker generating it (10 bits) and a sequence number distinguishing the keys generated within
+/// the same millisecond (12 bits). Keys are unique as long as every process uses a different
+/// worker id.
+#[derive(Debug)]
+pub struct Snowflake {
+    worker: i64,
+    /// The millisecond of the last key and its sequence number
+    state: Mutex<(i64, i64)>,
+}
+
+impl Snowflake {
+    /// The epoch of the timestamps (`2020-01-01T00:00:00Z`) in milliseconds since the unix epoch
+    pub const EPOCH: i64 = 1_577_836_800_000;
+
+    const WORKER_BITS: u32 = 10;
+    const SEQUENCE_BITS: u32 = 12;
+
+    /// Generates keys as `worker`, which must be less than `1024`
+    pub const fn new(worker: u16) -> Self {
+        assert!(
+            worker < 1 << Self::WORKER_BITS,
+            "snowflake workers are limited to 10 bits"
+        );
+
+        Self {
+            worker: worker as i64,
+            state: Mutex::new((0, 0)),
+        }
+    }
+
+    fn now() -> i64 {
+        since_epoch().as_millis() as i64 - Self::EPOCH
+    }
+}
+
+impl IdGenerator for Snowflake {
+    type Id = i64;
+
+    fn generate(&self) -> i64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let (last, sequence) = *state;
+
+        let mut now = Self::now().max(last);
+
+        let sequence = match now == last {
+            true => (sequence + 1) & ((1 << Self::SEQUENCE_BITS) - 1),
+            false => 0,
+        };
+
+        // the sequence of this millisecond is exhausted, wait for the next one
+        if now == last && sequence == 0 {
+            while now <= last {
+                std::hint::spin_loop();
+                now = Self::now();
+            }
+        }
+
+        *state = (now, sequence);
+
+        now << (Self::WORKER_BITS + Self::SEQUENCE_BITS)
+            | self.worker << Self::SEQUENCE_BITS
+            | sequence
+    }
+}
+
+/// Lexicographically sortable keys in the [ULID](https://github.com/ulid/spec) format.
+///
+/// Keys hold a millisecond timestamp (48 bits) and 80 random bits, encoded as 26 characters of
+/// Crockford's base32.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ulid;
+
+impl IdGenerator for Ulid {
+    type Id = String;
+
+    fn generate(&self) -> String {
+        const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+        let mut random = [0; 16];
+        rand::thread_rng().fill_bytes(&mut random[6..]);
+
+        let millis = since_epoch().as_millis() as u64 & ((1 << 48) - 1);
+        random[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+
+        let value = u128::from_be_bytes(random);
+
+        (0..26)
+            .rev()
+            .map(|i| ALPHABET[(value >> (i * 5)) as usize & 31] as char)
+            .collect()
+    }
+}
+
+/// K-sortable keys in the [KSUID](https://github.com/segmentio/ksuid) format.
+///
+/// Keys hold the seconds since the KSUID epoch (32 bits) and 128 random bits, encoded as 27
+/// characters of base62.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ksuid;
+
+impl Ksuid {
+    /// The epoch of the timestamps (`2014-05-13T16:53:20Z`) in seconds since the unix epoch
+    pub const EPOCH: u64 = 1_400_000_000;
+}
+
+impl IdGenerator for Ksuid {
+    type Id = String;
+
+    fn generate(&self) -> String {
+        const ALPHABET: &[u8; 62] =
+            b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+        let mut bytes = [0u8; 20];
+
+        let seconds = since_epoch().as_secs().saturating_sub(Self::EPOCH) as u32;
+        bytes[..4].copy_from_slice(&seconds.to_be_bytes());
+        rand::thread_rng().fill_bytes(&mut bytes[4..]);
+
+        // long division of the 160 bit number by 62, one digit at a time
+        let mut digits = [b'0'; 27];
+
+        for digit in digits.iter_mut().rev() {
+            let mut remainder = 0u32;
+
+            for byte in bytes.iter_mut() {
+                let value = remainder << 8 | *byte as u32;
+                *byte = (value / 62) as u8;
+                remainder = value % 62;
+            }
+
+            *digit = ALPHABET[remainder as usize];
+        }
+
+        digits.into_iter().map(char::from).collect()
+    }
+}
+
+/// Consecutive keys counted in process.
+///
+/// Suited for processes which are the only writer of a table. Tables which already hold rows start
+/// the sequence after their largest key, e.g. as read by `SELECT max(id)` on startup.
+#[derive(Debug)]
+pub struct Sequence {
+    next: AtomicI64,
+}
+
+impl Sequence {
+    /// Generates the keys `start`, `start + 1`, ..
+    pub const fn starting_at(start: i64) -> Self {
+        Self {
+            next: AtomicI64::new(start),
+        }
+    }
+
+    /// Continues the sequence after `last`, e.g. if it is seeded at runtime
+    pub fn resume_after(&self, last: i64) {
+        self.next.fetch_max(last + 1, Ordering::Relaxed);
+    }
+}
+
+impl IdGenerator for Sequence {
+    type Id = i64;
+
+    fn generate(&self) -> i64 {
+        self.next.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{IdGenerator, Ksuid, Sequence, Snowflake, Ulid};
+
+    #[test]
+    fn snowflake() {
+        let generator = Snowflake::new(3);
+
+        let ids: Vec<i64> = (0..10_000).map(|_| generator.generate()).collect();
+
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(ids.iter().all(|id| (id >> 12) & 1023 == 3));
+    }
+
+    #[test]
+    fn ulid() {
+        let ids: HashSet<String> = (0..100).map(|_| Ulid.generate()).collect();
+
+        assert_eq!(ids.len(), 100);
+        assert!(ids.iter().all(|id| id.len() == 26));
+        assert!(ids.iter().all(|id| id.starts_with('0')));
+    }
+
+    #[test]
+    fn ksuid() {
+        let ids: HashSet<String> = (0..100).map(|_| Ksuid.generate()).collect();
+
+        assert_eq!(ids.len(), 100);
+        assert!(ids.iter().all(|id| id.len() == 27));
+        assert!(ids
+            .iter()
+            .all(|id| id.bytes().all(|b| b.is_ascii_alphanumeric())));
+    }
+
+    #[test]
+    fn sequence() {
+        let sequence = Sequence::starting_at(1);
+
+        assert_eq!(sequence.generate(), 1);
+        assert_eq!(sequence.generate(), 2);
+
+        sequence.resume_after(41);
+
+        assert_eq!(sequence.generate(), 42);
+    }
+}
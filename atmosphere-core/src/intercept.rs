@@ -0,0 +1,185 @@
+//! Interceptors around the execution of all generated statements.
+//!
+//! Hooks belong to a single table. An [`Interceptor`] is installed once using [`install`] and
+//! sees the statements of all tables, including the ones executed without hooks (relationships,
+//! finders, ..), which makes it the place for cross-cutting concerns:
+//!
+//! - [`Interceptor::rewrite`] rewrites a generated statement before any hook modifies it, e.g. to
+//!   prepend optimizer hints
+//! - [`Interceptor::before`] runs right before a statement is executed and can keep it from being
+//!   executed by failing, e.g. while a circuit breaker is open
+//! - [`Interceptor::after`] runs after a statement has been executed and receives its
+//!   [`Outcome`], e.g. to record timings or to count failures
+//!
+//! Interceptors form a chain: `rewrite` and `before` run in the order the interceptors were
+//! installed, `after` in the reverse order. Interceptors can not execute a statement again, as its
+//! executor is consumed by the execution, see [`crate::retry`] for retries.
+//!
+//! ```ignore
+//! struct Breaker { open: AtomicBool }
+//!
+//! #[async_trait]
+//! impl Interceptor for Breaker {
+//!     async fn before(&self, _: &Statement<'_>) -> Result<()> {
+//!         match self.open.load(Ordering::Relaxed) {
+//!             true => Err(Error::Rejected("circuit open".to_owned())),
+//!             false => Ok(()),
+//!         }
+//!     }
+//! }
+//!
+//! atmosphere::intercept::install(Breaker { open: AtomicBool::new(false) });
+//! ```
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    query::{Operation, Query, QueryError},
+    Bind, Result, SchemaContext, Table,
+};
+
+/// A statement generated for a table
+#[derive(Clone, Copy, Debug)]
+pub struct Statement<'a> {
+    /// The schema of the table
+    pub schema: &'a str,
+    /// The name of the table
+    pub table: &'a str,
+    /// The operation the statement performs
+    pub op: Operation,
+    /// The sql of the statement
+    pub sql: &'a str,
+}
+
+/// The outcome of executing a [`Statement`]
+#[derive(Clone, Copy, Debug)]
+pub struct Outcome<'a> {
+    /// The time the execution took, including the hooks of its table
+    pub elapsed: Duration,
+    /// The number of rows affected or returned, if the statement succeeded
+    pub rows: Option<u64>,
+    /// The error the statement failed with, if it failed in the database
+    pub error: Option<&'a QueryError>,
+}
+
+/// Intercepts the execution of the statements of all tables, see [`crate::intercept`]
+#[async_trait]
+pub trait Interceptor: Send + Sync + 'static {
+    /// Returns the sql to execute instead of the generated `statement`, if any. The rewritten sql
+    /// has to keep the placeholders of the statement.
+    fn rewrite(&self, statement: &Statement<'_>) -> Option<String> {
+        let _ = statement;
+        None
+    }
+
+    /// Runs right before `statement` is executed, failing fails the query without executing it
+    async fn before(&self, statement: &Statement<'_>) -> Result<()> {
+        let _ = statement;
+        Ok(())
+    }
+
+    /// Runs after `statement` has been executed, failing fails the query
+    async fn after(&self, statement: &Statement<'_>, outcome: &Outcome<'_>) -> Result<()> {
+        let _ = (statement, outcome);
+        Ok(())
+    }
+}
+
+static INTERCEPTORS: RwLock<Vec<Arc<dyn Interceptor>>> = RwLock::new(Vec::new());
+
+/// Installs `interceptor` for the statements of all tables, after the interceptors installed
+/// before
+pub fn install(interceptor: impl Interceptor) {
+    INTERCEPTORS
+        .write()
+        .expect("interceptor registry poisoned")
+        .push(Arc::new(interceptor));
+}
+
+fn installed() -> Vec<Arc<dyn Interceptor>> {
+    INTERCEPTORS
+        .read()
+        .expect("interceptor registry poisoned")
+        .clone()
+}
+
+/// Lets the installed interceptors rewrite the statement `sql` of `T`, `None` if none did
+pub(crate) fn rewrite<T: Table>(op: Operation, sql: &str) -> Option<String> {
+    let interceptors = installed();
+
+    if interceptors.is_empty() {
+        return None;
+    }
+
+    let schema = SchemaContext::schema::<T>();
+    let table = T::name();
+
+    let mut rewritten: Option<String> = None;
+
+    for interceptor in &interceptors {
+        let statement = Statement {
+            schema: &schema,
+            table: &table,
+            op,
+            sql: rewritten.as_deref().unwrap_or(sql),
+        };
+
+        if let Some(sql) = interceptor.rewrite(&statement) {
+            rewritten = Some(sql);
+        }
+    }
+
+    rewritten
+}
+
+/// Runs the installed interceptors before `query` is executed
+pub(crate) async fn before<T: Bind>(query: &Query<T>) -> Result<()> {
+    let interceptors = installed();
+
+    if interceptors.is_empty() {
+        return Ok(());
+    }
+
+    let schema = SchemaContext::schema::<T>();
+    let table = T::name();
+    let statement = statement(query, &schema, &table);
+
+    for interceptor in &interceptors {
+        interceptor.before(&statement).await?;
+    }
+
+    Ok(())
+}
+
+/// Runs the installed interceptors after `query` has been executed
+pub(crate) async fn after<T: Bind>(query: &Query<T>, outcome: Outcome<'_>) -> Result<()> {
+    let interceptors = installed();
+
+    if interceptors.is_empty() {
+        return Ok(());
+    }
+
+    let schema = SchemaContext::schema::<T>();
+    let table = T::name();
+    let statement = statement(query, &schema, &table);
+
+    for interceptor in interceptors.iter().rev() {
+        interceptor.after(&statement, &outcome).await?;
+    }
+
+    Ok(())
+}
+
+fn statement<'a, T: Bind>(query: &'a Query<T>, schema: &'a str, table: &'a str) -> Statement<'a> {
+    Statement {
+        schema,
+        table,
+        op: query.op,
+        sql: query.sql(),
+    }
+}
@@ -0,0 +1,189 @@
+//! `INTERVAL` columns holding durations.
+//!
+//! `sqlx` encodes `std::time::Duration` as `INTERVAL`, but does not decode it, as an interval of
+//! months has no fixed duration. [`Interval`] wraps a `Duration` and reads intervals made up of
+//! days and time, treating a day as 24 hours. Intervals are ordered by their duration, so they can
+//! be compared both in rust and in sql (`WHERE retention > $1`).
+//!
+//! ```ignore
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "job")]
+//! struct Job {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     timeout: Interval,
+//!     retention: Option<Interval>,
+//! }
+//!
+//! let job = Job { id: 0, timeout: Interval::from_secs(30), retention: None };
+//! ```
+
+use std::{ops::Deref, time::Duration};
+
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{types::PgInterval, PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef},
+    Decode, Encode, Postgres, Type,
+};
+
+const MICROS_PER_DAY: i64 = 24 * 60 * 60 * 1_000_000;
+
+/// A duration stored as `INTERVAL`, with the precision of postgres (microseconds)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval(pub Duration);
+
+impl Interval {
+    /// An interval of `secs` seconds
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+
+    /// An interval of `millis` milliseconds
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(Duration::from_millis(millis))
+    }
+
+    /// The duration of the interval
+    pub const fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl Deref for Interval {
+    type Target = Duration;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Duration> for Interval {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<Interval> for Duration {
+    fn from(interval: Interval) -> Self {
+        interval.0
+    }
+}
+
+impl TryFrom<PgInterval> for Interval {
+    type Error = BoxDynError;
+
+    fn try_from(interval: PgInterval) -> Result<Self, Self::Error> {
+        if interval.months != 0 {
+            return Err("an `INTERVAL` of months has no fixed duration".into());
+        }
+
+        let micros = i64::from(interval.days)
+            .checked_mul(MICROS_PER_DAY)
+            .and_then(|days| days.checked_add(interval.microseconds))
+            .ok_or("the `INTERVAL` overflows a duration")?;
+
+        let micros = u64::try_from(micros).map_err(|_| "a negative `INTERVAL` is no duration")?;
+
+        Ok(Self(Duration::from_micros(micros)))
+    }
+}
+
+impl From<Interval> for PgInterval {
+    /// Converts the duration, truncating it to microseconds and saturating at the largest
+    /// `INTERVAL` of microseconds
+    fn from(interval: Interval) -> Self {
+        Self {
+            months: 0,
+            days: 0,
+            microseconds: i64::try_from(interval.0.as_micros()).unwrap_or(i64::MAX),
+        }
+    }
+}
+
+/// Described as a `duration` string, as the representation of intervals is left to the api
+#[cfg(feature = "utoipa")]
+impl utoipa::PartialSchema for Interval {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        use utoipa::openapi::schema::{KnownFormat, ObjectBuilder, SchemaFormat, Type};
+
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .format(Some(SchemaFormat::KnownFormat(KnownFormat::Duration)))
+            .into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl utoipa::ToSchema for Interval {}
+
+impl Type<Postgres> for Interval {
+    fn type_info() -> PgTypeInfo {
+        <PgInterval as Type<Postgres>>::type_info()
+    }
+}
+
+impl PgHasArrayType for Interval {
+    fn array_type_info() -> PgTypeInfo {
+        <PgInterval as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl Encode<'_, Postgres> for Interval {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        PgInterval::from(*self).encode_by_ref(buf)
+    }
+
+    fn size_hint(&self) -> usize {
+        PgInterval::from(*self).size_hint()
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Interval {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Self::try_from(<PgInterval as Decode<Postgres>>::decode(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sqlx::postgres::types::PgInterval;
+
+    use super::Interval;
+
+    #[test]
+    fn conversion() {
+        let interval = PgInterval {
+            months: 0,
+            days: 1,
+            microseconds: 1_500_000,
+        };
+
+        assert_eq!(
+            Interval::try_from(interval).unwrap(),
+            Interval(Duration::from_secs(86_401) + Duration::from_millis(500))
+        );
+
+        let month = PgInterval {
+            months: 1,
+            days: 0,
+            microseconds: 0,
+        };
+
+        assert!(Interval::try_from(month).is_err());
+
+        let negative = PgInterval {
+            months: 0,
+            days: 0,
+            microseconds: -1,
+        };
+
+        assert!(Interval::try_from(negative).is_err());
+
+        let truncated = PgInterval::from(Interval(Duration::from_nanos(1_999)));
+
+        assert_eq!(truncated.microseconds, 1);
+    }
+}
@@ -0,0 +1,152 @@
+//! `JSONB` operations on JSON columns.
+//!
+//! Data columns marked with `#[sql(json)]` hold `JSONB` documents (e.g. `sqlx::types::JsonValue` or
+//! `sqlx::types::Json<T>`). `#[derive(Schema)]` generates helpers for the common manipulations of
+//! such a column `data`, which are implemented by the functions of this module:
+//!
+//! - `merge_data(executor, pk, partial)` merges an object into the document ([`merge`])
+//! - `set_data_path(executor, pk, path, value)` sets the value at a path ([`set_path`])
+//! - `find_by_data_contains(executor, value)` finds the rows containing a value ([`find_contains`])
+//!
+//! The documents are changed by the database, without reading the row first.
+//!
+//! ```ignore
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "user")]
+//! struct User {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     #[sql(json)]
+//!     settings: JsonValue,
+//! }
+//!
+//! User::merge_settings(&pool, &id, &json!({ "theme": "dark" })).await?;
+//! User::set_settings_path(&pool, &id, &["notifications", "email"], &json!(false)).await?;
+//!
+//! let dark = User::find_by_settings_contains(&pool, &json!({ "theme": "dark" })).await?;
+//! ```
+
+use sqlx::{database::HasArguments, types::JsonValue, Executor, IntoArguments};
+
+use crate::{
+    hooks::{self, HookInput, HookStage, Hooks},
+    query::{Query, QueryResult, WriteOutcome},
+    runtime::changes,
+    schema::Updatable,
+    Bind, DataColumn, Result, Table,
+};
+
+/// Merges the object `partial` into the document held by `column` of the row with the primary key
+/// `pk` (`jsonb || partial`). Keys present in both are overwritten, others are kept.
+pub async fn merge<'e, T, E>(
+    executor: E,
+    column: &'static DataColumn<T>,
+    pk: &T::PrimaryKey,
+    partial: &JsonValue,
+) -> Result<WriteOutcome>
+where
+    T: Table + Updatable + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    let mut query = crate::runtime::sql::json_merge::<T>(column);
+
+    hooks::prepare(&mut query, HookInput::PrimaryKey(pk)).await?;
+
+    let sql = sqlx::query(query.sql()).bind(pk).bind(partial);
+
+    execute(executor, &query, pk, sql).await
+}
+
+/// Sets the value at `path` (a key or array index per level) within the document held by `column`
+/// of the row with the primary key `pk` to `value` (`jsonb_set`). Missing keys of the last level
+/// are created, missing levels above are not.
+pub async fn set_path<'e, T, E>(
+    executor: E,
+    column: &'static DataColumn<T>,
+    pk: &T::PrimaryKey,
+    path: &[&str],
+    value: &JsonValue,
+) -> Result<WriteOutcome>
+where
+    T: Table + Updatable + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    let mut query = crate::runtime::sql::json_set::<T>(column);
+
+    hooks::prepare(&mut query, HookInput::PrimaryKey(pk)).await?;
+
+    let sql = sqlx::query(query.sql()).bind(pk).bind(path).bind(value);
+
+    execute(executor, &query, pk, sql).await
+}
+
+/// Finds all rows whose document held by `column` contains `value` (`jsonb @> value`).
+pub async fn find_contains<'e, T, E>(
+    executor: E,
+    column: &'static DataColumn<T>,
+    value: &JsonValue,
+) -> Result<Vec<T>>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    let mut query = crate::runtime::sql::select_json_contains::<T>(column);
+
+    hooks::prepare(&mut query, HookInput::None).await?;
+    hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+
+    let sql = sqlx::query_as(query.sql()).bind(value);
+
+    let res = query
+        .bind_values(sql)?
+        .persistent(false)
+        .fetch_all(executor)
+        .await
+        .map_err(|err| query.context().error(err));
+
+    hooks::execute(HookStage::PostExec, &query, QueryResult::Many(&res).into()).await?;
+
+    res
+}
+
+type Arguments<'q> = <crate::Driver as HasArguments<'q>>::Arguments;
+
+/// Executes an update of the row with the primary key `pk`, whose columns are bound to `sql`
+async fn execute<'e, 'q, T, E>(
+    executor: E,
+    query: &'q Query<T>,
+    pk: &T::PrimaryKey,
+    sql: sqlx::query::Query<'q, crate::Driver, Arguments<'q>>,
+) -> Result<WriteOutcome>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'a> <crate::Driver as HasArguments<'a>>::Arguments: IntoArguments<'a, crate::Driver> + Send,
+{
+    let sql = query.bind_values(sql)?;
+
+    hooks::execute(HookStage::PreExec, query, HookInput::None).await?;
+
+    let res = sql
+        .persistent(false)
+        .execute(executor)
+        .await
+        .map(WriteOutcome::from)
+        .map_err(|err| query.context().error(err));
+
+    hooks::execute(
+        HookStage::PostExec,
+        query,
+        QueryResult::Execution(&res).into(),
+    )
+    .await?;
+
+    if matches!(&res, Ok(done) if done.rows_affected > 0) {
+        changes::publish::<T>(query.op, pk, None);
+    }
+
+    res
+}
@@ -23,30 +23,109 @@
 
 #![cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 
+/// Records the changes of tables in audit tables written by the same statement.
+#[cfg(feature = "postgres")]
+pub mod audit;
 /// Facilitates binding entities to queries, ensuring type safety and ease of use in query construction.
 pub mod bind;
+/// Fails queries fast while the database is unavailable.
+pub mod breaker;
+/// Caches rows read by primary key in memory.
+pub mod cache;
+/// Tags generated sql with comments, attributing statements to code paths.
+pub mod comment;
+/// Configures and connects the database pool.
+pub mod config;
+/// Encrypts columns on the client, keeping them from being stored in plaintext.
+#[cfg(feature = "encryption")]
+pub mod crypto;
 /// Defines high-level database error types, offering a structured approach to error handling.
 pub mod error;
+/// Loads rows from the path of requests in axum handlers.
+#[cfg(feature = "axum")]
+pub mod extract;
+/// Searches text columns using the full-text search of postgres.
+#[cfg(feature = "postgres")]
+pub mod fulltext;
+/// Aggregates rows grouped by the values of a column.
+pub mod group;
+/// Checks the health of the database for readiness probes.
+pub mod health;
+/// Keeps the previous versions of rows in history tables.
+#[cfg(feature = "postgres")]
+pub mod history;
 /// Implements a hook system, allowing custom logic to be executed at different stages of database
 /// interactions.
 pub mod hooks;
+/// Generates primary keys on the client, e.g. snowflakes or ULIDs.
+pub mod id;
+/// Intercepts the execution of the statements of all tables, e.g. for timing or circuit breaking.
+pub mod intercept;
+/// Stores durations in `INTERVAL` columns.
+#[cfg(feature = "postgres")]
+pub mod interval;
+/// Manipulates `JSONB` columns without falling back to raw sql.
+#[cfg(feature = "postgres")]
+pub mod json;
+/// Searches text columns for substrings using `LIKE`.
+pub mod like;
+/// Generates migrations from the differences between table definitions and a live database.
+#[cfg(feature = "postgres")]
+pub mod migrate;
+/// Describes tables and their endpoints in OpenAPI documents.
+#[cfg(feature = "utoipa")]
+pub mod openapi;
 /// Offers an abstraction layer for building and executing SQL queries, simplifying complex query
 /// logic.
 pub mod query;
+/// Treats tables as work queues that can be safely consumed by concurrent workers.
+#[cfg(feature = "postgres")]
+pub mod queue;
+/// Keeps the values of sensitive columns out of logs and diagnostics.
+pub mod redact;
 /// Models SQL relationships, providing tools to define and manipulate relationships between
 /// database entities.
 pub mod rel;
+/// Retries queries failing with transient errors, using exponential backoff.
+pub mod retry;
 /// Manages the runtime environment for database operations, encompassing execution contexts and
 /// configurations.
 pub mod runtime;
 /// Contains compile-time generated SQL schema traits, enabling a declarative approach to schema
 /// definition.
 pub mod schema;
+/// Loads seed data from JSON in the order of the foreign keys of the tables.
+pub mod seed;
+/// Remembers the rows read within a unit of work, so each row is read at most once.
+pub mod session;
+/// Partitions tables across multiple databases by primary key.
+pub mod shard;
+/// Logs queries exceeding a duration threshold.
+#[cfg(feature = "tracing")]
+pub mod slow;
+/// Logs all generated statements with their bound columns, but without their values.
+#[cfg(feature = "tracing")]
+pub mod statement_log;
+/// Restricts the rows of tables to the tenant in scope.
+pub mod tenant;
 /// Provides utilities for automated testing of SQL interactions, ensuring reliability and
 /// correctness of database operations.
 pub mod testing;
+/// Finds rows by time windows over their timestamp columns.
+pub mod window;
 
+pub use config::Config;
 pub use driver::{Driver, Pool};
+pub use health::{health, Health, Ping};
+#[cfg(feature = "postgres")]
+pub use migrate::validate_schema;
+pub use runtime::changes::{changes, ChangeEvent};
+pub use runtime::databases::Databases;
+pub use runtime::pools::Pools;
+pub use runtime::transaction::{transaction, transaction_with, IsolationLevel};
+pub use shard::{ShardRouter, Sharded};
+#[cfg(feature = "utoipa")]
+pub use utoipa;
 
 /// Driver System
 ///
@@ -72,6 +151,10 @@ pub mod driver {
     /// Atmosphere Database Pool
     pub type Pool = sqlx::PgPool;
 
+    #[cfg(all(feature = "postgres", not(any(feature = "mysql", feature = "sqlite"))))]
+    /// Atmosphere Database Connection Options
+    pub type ConnectOptions = sqlx::postgres::PgConnectOptions;
+
     #[cfg(all(feature = "mysql", not(any(feature = "postgres", feature = "sqlite"))))]
     /// Atmosphere Database Driver
     pub type Driver = sqlx::MySql;
@@ -80,6 +163,10 @@ pub mod driver {
     /// Atmosphere Database Pool
     pub type Pool = sqlx::MySqlPool;
 
+    #[cfg(all(feature = "mysql", not(any(feature = "postgres", feature = "sqlite"))))]
+    /// Atmosphere Database Connection Options
+    pub type ConnectOptions = sqlx::mysql::MySqlConnectOptions;
+
     #[cfg(all(feature = "sqlite", not(any(feature = "postgres", feature = "mysql"))))]
     /// Atmosphere Database Driver
     pub type Driver = sqlx::Sqlite;
@@ -87,11 +174,25 @@ pub mod driver {
     #[cfg(all(feature = "sqlite", not(any(feature = "postgres", feature = "mysql"))))]
     /// Atmosphere Database Pool
     pub type Pool = sqlx::SqlitePool;
+
+    #[cfg(all(feature = "sqlite", not(any(feature = "postgres", feature = "mysql"))))]
+    /// Atmosphere Database Connection Options
+    pub type ConnectOptions = sqlx::sqlite::SqliteConnectOptions;
 }
 
 pub use bind::*;
 pub use error::*;
 pub use schema::*;
 
+#[doc(hidden)]
+pub use inventory;
 #[doc(hidden)]
 pub use sqlx;
+
+#[cfg(feature = "validator")]
+#[doc(hidden)]
+pub use validator;
+
+#[cfg(feature = "uuid")]
+#[doc(hidden)]
+pub use uuid;
@@ -0,0 +1,81 @@
+//! Substring search over text columns using `LIKE`.
+//!
+//! `#[derive(Schema)]` generates `search_by_<col>(executor, pattern)` for every `String` (or
+//! `Option<String>`) data column, which finds the rows whose column contains `pattern` regardless
+//! of case (`ILIKE` on postgres). The wildcards `%` and `_` within `pattern` are escaped and match
+//! literally, so user input can be passed as is.
+//!
+//! ```ignore
+//! // SELECT .. FROM forest WHERE name ILIKE '%100!%%' ESCAPE '!'
+//! let forests = Forest::search_by_name(&pool, "100%").await?;
+//! ```
+
+use sqlx::{database::HasArguments, Executor, IntoArguments};
+
+use crate::{
+    hooks::{self, HookInput, HookStage, Hooks},
+    query::QueryResult,
+    Bind, DataColumn, Result, Table,
+};
+
+/// The character escaping wildcards in the patterns of generated queries (`ESCAPE '!'`)
+pub const ESCAPE: char = '!';
+
+/// Escapes the wildcards `%` and `_` (and the escape character itself) within `input`, so it
+/// matches literally in a pattern escaped by [`ESCAPE`]
+pub fn escape(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | ESCAPE) {
+            escaped.push(ESCAPE);
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Finds all rows whose text held by `column` contains `pattern` regardless of case, matching
+/// `pattern` literally.
+pub async fn search_by<'e, T, E>(
+    executor: E,
+    column: &'static DataColumn<T>,
+    pattern: &str,
+) -> Result<Vec<T>>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    let mut query = crate::runtime::sql::select_like::<T>(column);
+
+    hooks::prepare(&mut query, HookInput::None).await?;
+    hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+
+    let sql = sqlx::query_as(query.sql()).bind(format!("%{}%", escape(pattern)));
+
+    let res = query
+        .bind_values(sql)?
+        .persistent(false)
+        .fetch_all(executor)
+        .await
+        .map_err(|err| query.context().error(err));
+
+    hooks::execute(HookStage::PostExec, &query, QueryResult::Many(&res).into()).await?;
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape;
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape("grunewald"), "grunewald");
+        assert_eq!(escape("100%"), "100!%");
+        assert_eq!(escape("a_b!"), "a!_b!!");
+    }
+}
@@ -0,0 +1,551 @@
+//! Migration generation
+//!
+//! A [`Migrator`] compares the definitions of a set of tables against the tables of a live
+//! database (as reported by its `information_schema`) and generates the statements migrating the
+//! database to the definitions: missing tables are created, missing columns and indexes added,
+//! removed columns dropped, and changed types, nullability and new foreign keys altered.
+//!
+//! ```ignore
+//! let migration = Migrator::new()
+//!     .table::<User>()
+//!     .table::<Post>()
+//!     .diff(&pool)
+//!     .await?;
+//!
+//! if !migration.is_empty() {
+//!     migration.write("migrations", "sync schema")?;
+//! }
+//! ```
+//!
+//! The generated statements are a starting point and should be reviewed before being applied: a
+//! renamed field shows up as a dropped and an added column, and `NOT NULL` columns can only be
+//! added to tables that are empty (or after a default has been added by hand).
+//!
+//! Applications that don't manage their schema through atmosphere can check it on startup using
+//! [`validate_schema`] instead, turning mismatches into a readable report rather than query
+//! errors at runtime:
+//!
+//! ```ignore
+//! let report = atmosphere::validate_schema::<(Forest, Tree)>(&pool).await?;
+//!
+//! assert!(report.is_valid(), "{report}");
+//! ```
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use sqlx::{types::chrono::Utc, Executor, FromRow};
+
+use crate::{
+    column::ColumnType, query::QueryError, runtime::sql, Bind, Error, Result, SchemaContext,
+    TableVisitor, Tables,
+};
+
+/// The tables migrations are generated for.
+#[derive(Default)]
+pub struct Migrator {
+    tables: Vec<fn() -> Definition>,
+}
+
+impl Migrator {
+    /// Creates a migrator without any tables
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the table `T`. Missing tables are created in the order they were added, so tables
+    /// should be added after the tables they refer to.
+    pub fn table<T: Bind>(mut self) -> Self {
+        self.tables.push(definition::<T>);
+        self
+    }
+
+    /// Adds all tables of a tuple of tables, see [`Migrator::table`]
+    pub fn tables<T: Tables>(mut self) -> Self {
+        T::visit(&mut self);
+        self
+    }
+
+    /// Compares the tables against the database and returns the statements migrating it.
+    ///
+    /// # Panics
+    ///
+    /// If the sql type of a column is unknown. `#[derive(Schema)]` sets the type of all columns.
+    pub async fn diff<'e, E>(&self, executor: E) -> Result<Migration>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+    {
+        let definitions = self.definitions();
+        let existing = existing(&definitions, executor).await?;
+
+        let mut statements = vec![];
+        // foreign keys are added once all tables and columns exist
+        let mut constraints = vec![];
+
+        for definition in &definitions {
+            let existing: Vec<&ExistingColumn> = existing
+                .iter()
+                .filter(|c| c.table_schema == definition.schema && c.table_name == definition.name)
+                .collect();
+
+            let table = &definition.table;
+
+            if existing.is_empty() {
+                statements.push(definition.create.clone());
+                continue;
+            }
+
+            for column in &definition.columns {
+                let name = column.name;
+
+                let Some(current) = existing.iter().find(|c| c.column_name == name) else {
+                    statements.push(format!(
+                        "ALTER TABLE {table} ADD COLUMN {}",
+                        column.definition
+                    ));
+
+                    if let Some(comment) = &column.comment {
+                        statements.push(comment.clone());
+                    }
+
+                    if let Some(fk) = &column.foreign_key {
+                        constraints.push(format!("ALTER TABLE {table} ADD {fk}"));
+                    }
+
+                    continue;
+                };
+
+                if current.udt_name != udt_name(&column.ty) {
+                    let ty = sql::type_name(&column.ty);
+
+                    statements.push(format!(
+                        "ALTER TABLE {table} ALTER COLUMN {name} TYPE {ty} USING {name}::{ty}"
+                    ));
+                }
+
+                match (current.nullable, column.ty.nullable) {
+                    (true, false) => statements.push(format!(
+                        "ALTER TABLE {table} ALTER COLUMN {name} SET NOT NULL"
+                    )),
+                    (false, true) => statements.push(format!(
+                        "ALTER TABLE {table} ALTER COLUMN {name} DROP NOT NULL"
+                    )),
+                    _ => {}
+                }
+
+                if let (false, Some(fk)) = (current.foreign_key, &column.foreign_key) {
+                    constraints.push(format!("ALTER TABLE {table} ADD {fk}"));
+                }
+            }
+
+            for index in &definition.indexes {
+                if !existing[0].indexes.iter().any(|i| i == index.name) {
+                    statements.push(index.definition.clone());
+                }
+            }
+
+            for current in existing {
+                if !definition
+                    .columns
+                    .iter()
+                    .any(|c| c.name == current.column_name)
+                {
+                    statements.push(format!(
+                        "ALTER TABLE {table} DROP COLUMN {}",
+                        current.column_name
+                    ));
+                }
+            }
+        }
+
+        statements.extend(constraints);
+
+        Ok(Migration { statements })
+    }
+
+    /// Checks that all tables, columns, nullability, foreign keys and indexes of the tables exist
+    /// in the database, see [`validate_schema`].
+    ///
+    /// # Panics
+    ///
+    /// If the sql type of a column is unknown. `#[derive(Schema)]` sets the type of all columns.
+    pub async fn validate<'e, E>(&self, executor: E) -> Result<SchemaReport>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+    {
+        let definitions = self.definitions();
+        let existing = existing(&definitions, executor).await?;
+
+        let mut mismatches = vec![];
+
+        for definition in &definitions {
+            let table = format!("{}.{}", definition.schema, definition.name);
+
+            let existing: Vec<&ExistingColumn> = existing
+                .iter()
+                .filter(|c| c.table_schema == definition.schema && c.table_name == definition.name)
+                .collect();
+
+            if existing.is_empty() {
+                mismatches.push(Mismatch::MissingTable { table });
+                continue;
+            }
+
+            for column in &definition.columns {
+                let Some(current) = existing.iter().find(|c| c.column_name == column.name) else {
+                    mismatches.push(Mismatch::MissingColumn {
+                        table: table.clone(),
+                        column: column.name,
+                    });
+                    continue;
+                };
+
+                if current.udt_name != udt_name(&column.ty) {
+                    mismatches.push(Mismatch::Type {
+                        table: table.clone(),
+                        column: column.name,
+                        declared: udt_name(&column.ty),
+                        actual: current.udt_name.clone(),
+                    });
+                }
+
+                if current.nullable != column.ty.nullable {
+                    mismatches.push(Mismatch::Nullability {
+                        table: table.clone(),
+                        column: column.name,
+                        nullable: column.ty.nullable,
+                    });
+                }
+
+                if column.foreign_key.is_some() && !current.foreign_key {
+                    mismatches.push(Mismatch::MissingForeignKey {
+                        table: table.clone(),
+                        column: column.name,
+                    });
+                }
+            }
+
+            for index in &definition.indexes {
+                if !existing[0].indexes.iter().any(|i| i == index.name) {
+                    mismatches.push(Mismatch::MissingIndex {
+                        table: table.clone(),
+                        index: index.name,
+                    });
+                }
+            }
+        }
+
+        Ok(SchemaReport { mismatches })
+    }
+
+    fn definitions(&self) -> Vec<Definition> {
+        self.tables.iter().map(|table| table()).collect()
+    }
+}
+
+/// Checks that the tables `T` (a tuple of tables) match the database.
+///
+/// All tables, columns, foreign keys and indexes declared have to exist, with the declared type and
+/// nullability. Columns that exist in the database only are ignored.
+///
+/// ```ignore
+/// let report = atmosphere::validate_schema::<(Forest, Tree)>(&pool).await?;
+///
+/// assert!(report.is_valid(), "{report}");
+/// ```
+pub async fn validate_schema<'e, T: Tables>(
+    executor: impl Executor<'e, Database = crate::Driver>,
+) -> Result<SchemaReport> {
+    Migrator::new().tables::<T>().validate(executor).await
+}
+
+/// The result of validating tables against a database, see [`validate_schema`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchemaReport {
+    /// All differences found between the declared tables and the database
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl SchemaReport {
+    /// Whether the database matches the declared tables
+    pub fn is_valid(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "the schema matches the database");
+        }
+
+        write!(f, "the schema does not match the database:")?;
+
+        for mismatch in &self.mismatches {
+            write!(f, "\n  - {mismatch}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A difference between a declared table and the database. Tables are named `schema.table`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The table does not exist
+    MissingTable { table: String },
+    /// The column does not exist
+    MissingColumn { table: String, column: &'static str },
+    /// The column has a different type than declared (as `information_schema` `udt_name`)
+    Type {
+        table: String,
+        column: &'static str,
+        declared: String,
+        actual: String,
+    },
+    /// The column is declared as `nullable` but the database says otherwise
+    Nullability {
+        table: String,
+        column: &'static str,
+        nullable: bool,
+    },
+    /// The column is declared as foreign key but has no foreign key constraint
+    MissingForeignKey { table: String, column: &'static str },
+    /// The index does not exist
+    MissingIndex { table: String, index: &'static str },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTable { table } => write!(f, "table {table} does not exist"),
+            Self::MissingColumn { table, column } => {
+                write!(f, "column {table}.{column} does not exist")
+            }
+            Self::Type {
+                table,
+                column,
+                declared,
+                actual,
+            } => write!(
+                f,
+                "column {table}.{column} is declared as {declared} but is {actual}"
+            ),
+            Self::Nullability {
+                table,
+                column,
+                nullable: true,
+            } => write!(
+                f,
+                "column {table}.{column} is declared nullable but is NOT NULL"
+            ),
+            Self::Nullability { table, column, .. } => {
+                write!(
+                    f,
+                    "column {table}.{column} is declared NOT NULL but is nullable"
+                )
+            }
+            Self::MissingForeignKey { table, column } => {
+                write!(f, "column {table}.{column} has no foreign key constraint")
+            }
+            Self::MissingIndex { table, index } => {
+                write!(f, "index {index} of table {table} does not exist")
+            }
+        }
+    }
+}
+
+impl TableVisitor for Migrator {
+    fn visit<T: Bind>(&mut self) {
+        self.tables.push(definition::<T>);
+    }
+}
+
+/// The statements migrating a database to the definitions of its tables.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Migration {
+    /// The statements in the order they have to be executed in
+    pub statements: Vec<String>,
+}
+
+impl Migration {
+    /// Whether the database already matches the table definitions
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// The migration as a sql script
+    pub fn sql(&self) -> String {
+        self.statements.iter().map(|s| format!("{s};\n")).collect()
+    }
+
+    /// Writes the migration into `dir` as `<timestamp>_<description>.sql`, the naming scheme of
+    /// sqlx migrations. Returns the path of the written file.
+    pub fn write(&self, dir: impl AsRef<Path>, description: &str) -> io::Result<PathBuf> {
+        let path = dir.as_ref().join(format!(
+            "{}_{}.sql",
+            Utc::now().format("%Y%m%d%H%M%S"),
+            description.replace(' ', "_")
+        ));
+
+        fs::write(&path, self.sql())?;
+
+        Ok(path)
+    }
+}
+
+/// The columns of all tables within the given schemas, along with the indexes of their table
+const COLUMNS: &str = "SELECT
+  c.table_schema::text,
+  c.table_name::text,
+  c.column_name::text,
+  c.udt_name::text,
+  c.is_nullable = 'YES' AS nullable,
+  EXISTS (
+    SELECT 1
+    FROM information_schema.key_column_usage k
+    JOIN information_schema.table_constraints t
+      ON t.constraint_schema = k.constraint_schema AND t.constraint_name = k.constraint_name
+    WHERE t.constraint_type = 'FOREIGN KEY'
+      AND k.table_schema = c.table_schema
+      AND k.table_name = c.table_name
+      AND k.column_name = c.column_name
+  ) AS foreign_key,
+  ARRAY(
+    SELECT i.indexname::text
+    FROM pg_indexes i
+    WHERE i.schemaname = c.table_schema AND i.tablename = c.table_name
+  ) AS indexes
+FROM information_schema.columns c
+WHERE c.table_schema = ANY($1)";
+
+/// Fetches the columns of all tables in the schemas of `definitions`
+async fn existing<'e, E>(definitions: &[Definition], executor: E) -> Result<Vec<ExistingColumn>>
+where
+    E: Executor<'e, Database = crate::Driver>,
+{
+    let mut schemas: Vec<&str> = definitions.iter().map(|d| d.schema.as_str()).collect();
+    schemas.sort();
+    schemas.dedup();
+
+    sqlx::query_as(COLUMNS)
+        .bind(schemas)
+        .fetch_all(executor)
+        .await
+        .map_err(QueryError::from)
+        .map_err(Error::Query)
+}
+
+#[derive(FromRow)]
+struct ExistingColumn {
+    table_schema: String,
+    table_name: String,
+    column_name: String,
+    udt_name: String,
+    nullable: bool,
+    foreign_key: bool,
+    indexes: Vec<String>,
+}
+
+/// The definition of a table, captured when the migration is generated
+struct Definition {
+    schema: String,
+    name: String,
+    /// The qualified name of the table
+    table: String,
+    create: String,
+    columns: Vec<ColumnDefinition>,
+    indexes: Vec<IndexDefinition>,
+}
+
+struct ColumnDefinition {
+    name: &'static str,
+    ty: ColumnType,
+    definition: String,
+    foreign_key: Option<String>,
+    /// The statement describing the column, if it has a comment
+    comment: Option<String>,
+}
+
+struct IndexDefinition {
+    name: &'static str,
+    definition: String,
+}
+
+fn definition<T: Bind>() -> Definition {
+    let typed = |sql: &str, ty: Option<ColumnType>| {
+        ty.unwrap_or_else(|| panic!("the type of {}.{sql} is unknown", T::TABLE))
+    };
+
+    let pk = ColumnType::of::<T::PrimaryKey>();
+
+    let mut columns = vec![ColumnDefinition {
+        name: T::PRIMARY_KEY.sql,
+        ty: pk,
+        definition: sql::column_definition::<T>(T::PRIMARY_KEY.sql, Some(pk)),
+        foreign_key: None,
+        comment: comment::<T>(T::PRIMARY_KEY.sql, T::PRIMARY_KEY.comment),
+    }];
+
+    for fk in T::FOREIGN_KEYS {
+        columns.push(ColumnDefinition {
+            name: fk.sql,
+            ty: typed(fk.sql, fk.ty),
+            definition: sql::column_definition::<T>(fk.sql, fk.ty),
+            foreign_key: sql::foreign_key(fk),
+            comment: comment::<T>(fk.sql, fk.comment),
+        });
+    }
+
+    for data in T::DATA_COLUMNS {
+        columns.push(ColumnDefinition {
+            name: data.sql,
+            ty: typed(data.sql, data.ty),
+            definition: sql::column_definition::<T>(data.sql, data.ty),
+            foreign_key: None,
+            comment: comment::<T>(data.sql, data.comment),
+        });
+    }
+
+    for ts in T::TIMESTAMP_COLUMNS {
+        columns.push(ColumnDefinition {
+            name: ts.sql,
+            ty: typed(ts.sql, ts.ty),
+            definition: sql::column_definition::<T>(ts.sql, ts.ty),
+            foreign_key: None,
+            comment: comment::<T>(ts.sql, ts.comment),
+        });
+    }
+
+    Definition {
+        schema: SchemaContext::schema::<T>(),
+        name: T::name().into_owned(),
+        table: sql::table::<T>(),
+        create: sql::create_table::<T>(),
+        columns,
+        indexes: T::INDEXES
+            .iter()
+            .map(|index| IndexDefinition {
+                name: index.name,
+                definition: sql::create_index::<T>(index),
+            })
+            .collect(),
+    }
+}
+
+/// The statement describing `column` with `comment`
+fn comment<T: Bind>(column: &str, comment: Option<&str>) -> Option<String> {
+    comment.map(|comment| sql::comment_on_column::<T>(column, comment))
+}
+
+/// The name of a type in `information_schema.columns.udt_name`
+fn udt_name(ty: &ColumnType) -> String {
+    let name = ty.name().to_lowercase();
+
+    match name.strip_suffix("[]") {
+        Some(element) => format!("_{element}"),
+        None => name,
+    }
+}
@@ -0,0 +1,194 @@
+//! OpenAPI documentation of tables using `utoipa`.
+//!
+//! With the `utoipa` feature, `#[table]` derives `utoipa::ToSchema` for tables (and for their
+//! `<Table>Patch` types), so the schemas of an API are the ones of the database models.
+//! `#[derive(Schema)]` additionally implements [`Documented`], which describes the CRUD endpoints
+//! of a table as OpenAPI path items:
+//!
+//! ```ignore
+//! #[derive(OpenApi)]
+//! struct Api;
+//!
+//! let mut api = Api::openapi();
+//!
+//! api.paths.merge(User::paths("/users"));
+//! User::components(api.components.get_or_insert_with(Default::default));
+//! ```
+//!
+//! The paths describe the endpoints a table supports: `GET` and `POST` on `/users`, `GET`, `PUT`,
+//! `PATCH` and `DELETE` on `/users/{id}` (the primary key). Endpoints writing rows are left out
+//! for views and tables opting out of updates or deletes, `PATCH` is only described for tables
+//! generating a patch type using `#[table(patch)]`.
+//!
+//! The columns of tables have to implement `utoipa::ToSchema` (or be described using
+//! `#[schema(value_type = ..)]`). Newtype primary keys deriving `PrimaryKey` and
+//! [`Interval`](crate::interval::Interval) implement it, `#[sql(json)]` columns are described as
+//! objects, while enums mapped using `SqlEnum` have to derive it. The derive macros of `utoipa`
+//! refer to the crate by name, so it has to be a dependency of the crate defining the tables.
+
+use utoipa::{
+    openapi::{
+        path::{HttpMethod, Operation, OperationBuilder, ParameterBuilder, ParameterIn},
+        request_body::RequestBodyBuilder,
+        schema::{Array, Components},
+        ContentBuilder, Paths, PathsBuilder, Ref, RefOr, Required, ResponseBuilder, Schema,
+    },
+    ToSchema,
+};
+
+use crate::Table;
+
+const JSON: &str = "application/json";
+
+/// Tables described in OpenAPI documents, implemented by `#[derive(Schema)]` with the `utoipa`
+/// feature, see [`crate::openapi`]
+pub trait Documented: Table + ToSchema {
+    /// Whether rows are created through the api
+    const CREATE: bool;
+    /// Whether rows are updated through the api
+    const UPDATE: bool;
+    /// Whether rows are deleted through the api
+    const DELETE: bool;
+
+    /// The schema of the primary key, the path parameter of the endpoints of single rows
+    fn pk_schema() -> RefOr<Schema>;
+
+    /// The name and schema of the `<Table>Patch` type, if generated using `#[table(patch)]`
+    fn patch_schema() -> Option<(String, RefOr<Schema>)> {
+        None
+    }
+
+    /// The path items of the endpoints of the table below `base`, e.g. `/users`
+    fn paths(base: &str) -> Paths {
+        let base = base.trim_end_matches('/');
+        let single = format!("{base}/{{{}}}", Self::PRIMARY_KEY.field);
+
+        let row = || RefOr::from(Ref::from_schema_name(<Self as ToSchema>::name()));
+
+        let mut paths = PathsBuilder::new().build();
+
+        paths.add_path_operation(
+            base,
+            vec![HttpMethod::Get],
+            operation::<Self>("list", None, "200", Some(Array::new(row()).into())),
+        );
+
+        if Self::CREATE {
+            paths.add_path_operation(
+                base,
+                vec![HttpMethod::Post],
+                operation::<Self>("create", Some(row()), "201", Some(row())),
+            );
+        }
+
+        paths.add_path_operation(
+            &single,
+            vec![HttpMethod::Get],
+            single_operation::<Self>("read", None, "200", Some(row())),
+        );
+
+        if Self::UPDATE {
+            paths.add_path_operation(
+                &single,
+                vec![HttpMethod::Put],
+                single_operation::<Self>("update", Some(row()), "200", Some(row())),
+            );
+
+            if let Some((patch, _)) = Self::patch_schema() {
+                let patch = RefOr::from(Ref::from_schema_name(patch));
+
+                paths.add_path_operation(
+                    &single,
+                    vec![HttpMethod::Patch],
+                    single_operation::<Self>("patch", Some(patch), "200", Some(row())),
+                );
+            }
+        }
+
+        if Self::DELETE {
+            paths.add_path_operation(
+                &single,
+                vec![HttpMethod::Delete],
+                single_operation::<Self>("delete", None, "204", None),
+            );
+        }
+
+        paths
+    }
+
+    /// Adds the schemas of the table, its patch type and the types they refer to to `components`
+    fn components(components: &mut Components) {
+        let mut schemas = vec![(<Self as ToSchema>::name().into_owned(), Self::schema())];
+
+        <Self as ToSchema>::schemas(&mut schemas);
+        schemas.extend(Self::patch_schema());
+
+        components.schemas.extend(schemas);
+    }
+}
+
+/// An operation of the endpoints of `T`, taking `body` and responding with `response` and `status`
+fn operation<T: Documented>(
+    verb: &str,
+    body: Option<RefOr<Schema>>,
+    status: &str,
+    response: Option<RefOr<Schema>>,
+) -> Operation {
+    let body = body.map(|schema| {
+        RequestBodyBuilder::new()
+            .content(JSON, ContentBuilder::new().schema(Some(schema)).build())
+            .required(Some(Required::True))
+            .build()
+    });
+
+    let description = match verb {
+        "list" => "The rows",
+        "create" => "The created row",
+        "update" => "The updated row",
+        "patch" => "The patched row",
+        "delete" => "The row has been deleted",
+        _ => "The row",
+    };
+
+    let response = match response {
+        Some(schema) => ResponseBuilder::new()
+            .description(description)
+            .content(JSON, ContentBuilder::new().schema(Some(schema)).build()),
+        None => ResponseBuilder::new().description(description),
+    };
+
+    OperationBuilder::new()
+        .tag(T::TABLE)
+        .operation_id(Some(format!("{verb}_{}", T::TABLE)))
+        .request_body(body)
+        .response(status, response.build())
+        .build()
+}
+
+/// An operation of the endpoint of a single row of `T`, see [`operation`]
+fn single_operation<T: Documented>(
+    verb: &str,
+    body: Option<RefOr<Schema>>,
+    status: &str,
+    response: Option<RefOr<Schema>>,
+) -> Operation {
+    let pk = ParameterBuilder::new()
+        .name(T::PRIMARY_KEY.field)
+        .parameter_in(ParameterIn::Path)
+        .required(Required::True)
+        .schema(Some(T::pk_schema()))
+        .build();
+
+    let mut operation = operation::<T>(verb, body, status, response);
+
+    operation.parameters.get_or_insert_with(Vec::new).push(pk);
+    operation.responses.responses.insert(
+        "404".to_owned(),
+        ResponseBuilder::new()
+            .description("No row has the primary key")
+            .build()
+            .into(),
+    );
+
+    operation
+}
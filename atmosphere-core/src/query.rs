@@ -4,11 +4,34 @@
 //! This module includes custom error types for different database-related errors, enums for query
 //! operations and cardinality, and a struct for building and managing queries for database tables.
 
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
+
 use miette::Diagnostic;
-use sqlx::QueryBuilder;
+use sqlx::{database::HasArguments, query::QueryAs, Encode, QueryBuilder, Type};
 use thiserror::Error;
 
-use crate::{runtime::sql::Bindings, Bind, Result, Table};
+use crate::{
+    describe::{self, ColumnDescription, ColumnKind},
+    runtime::{
+        instrument::Instrument,
+        scoped::Scoped,
+        sql::{
+            dialect::{Current, Dialect},
+            Bindings,
+        },
+    },
+    tenant::Tenant,
+    Bind, Error, Result, SqlPreview, Table,
+};
 
 /// Errors that can occur while executing a database query.
 ///
@@ -54,23 +77,97 @@ pub enum QueryError {
 /// Includes uniqueness violations, foreign key violations, and integrity check errors,
 /// encapsulating different types of constraint-related issues that can occur during database
 /// operations.
+///
+/// Violations of queries generated for a table (see [`Error::Failed`]) name the violated column if
+/// it can be told from the constraint reported by the database. This is the case for the
+/// constraints created by [`Ddl`](crate::Ddl) and for constraints named like the ones postgres
+/// generates (`<table>_<column>_key`, `<table>_<column>_fkey`).
 #[derive(Debug, Diagnostic, Error)]
 #[non_exhaustive]
 pub enum ViolationError {
     /// Row uniqueness violated
-    #[error("uniqueness violation")]
+    #[error("uniqueness violation{}", of(column))]
     #[diagnostic(code(atmosphere::violation::uniqueness))]
-    Unique(#[source] sqlx::Error),
+    Unique {
+        /// The sql name of the column holding a duplicate value, if known
+        column: Option<&'static str>,
+        #[source]
+        source: sqlx::Error,
+    },
 
     /// Foreign key violation
-    #[error("foreign key violation")]
+    #[error("foreign key violation{}", of(column))]
     #[diagnostic(code(atmosphere::violation::foreign_key))]
-    ForeignKey(#[source] sqlx::Error),
+    ForeignKey {
+        /// The sql name of the foreign key column referencing a missing row, if known
+        column: Option<&'static str>,
+        #[source]
+        source: sqlx::Error,
+    },
 
     /// Integritry check failed
-    #[error("integrity check")]
+    #[error("integrity check{}", of(column))]
     #[diagnostic(code(atmosphere::violation::integrity))]
-    Check(#[source] sqlx::Error),
+    Check {
+        /// The sql name of the column whose check failed, if known
+        column: Option<&'static str>,
+        #[source]
+        source: sqlx::Error,
+    },
+}
+
+impl ViolationError {
+    /// The sql name of the violated column, if known
+    pub const fn column(&self) -> Option<&'static str> {
+        match self {
+            Self::Unique { column, .. }
+            | Self::ForeignKey { column, .. }
+            | Self::Check { column, .. } => *column,
+        }
+    }
+
+    /// Maps the constraint reported by the database back to the violated column of `table`
+    fn resolve(&mut self, table: &str) {
+        let (column, source, suffix) = match self {
+            Self::Unique { column, source } => (column, &*source, "key"),
+            Self::ForeignKey { column, source } => (column, &*source, "fkey"),
+            Self::Check { column, source } => (column, &*source, "check"),
+        };
+
+        let sqlx::Error::Database(err) = source else {
+            return;
+        };
+
+        let Some(description) = describe::tables().into_iter().find(|t| t.table == table) else {
+            return;
+        };
+
+        let violated = |c: &ColumnDescription| match err.constraint() {
+            Some(constraint) => {
+                constraint == format!("{table}_{}_{suffix}", c.name)
+                    || (c.kind == ColumnKind::PrimaryKey && constraint == format!("{table}_pkey"))
+                    || constraint == c.name
+            }
+            // sqlite and mysql only name the constraint in their message, e.g.
+            // `UNIQUE constraint failed: user.email` or `Duplicate entry .. for key 'user.email'`
+            None => {
+                let message = err.message().trim_end_matches('\'');
+                message.ends_with(&format!(".{}", c.name))
+                    || message.contains(&format!("{table}_{}_{suffix}", c.name))
+                    || (c.kind == ColumnKind::PrimaryKey && message.ends_with(".PRIMARY"))
+            }
+        };
+
+        *column = description
+            .columns
+            .iter()
+            .find(|c| violated(c))
+            .map(|c| c.name);
+    }
+}
+
+fn of(column: &Option<&'static str>) -> String {
+    column.map(|c| format!(" of `{c}`")).unwrap_or_default()
 }
 
 /// Encapsulates errors derived from SQLSTATE codes.
@@ -95,12 +192,58 @@ pub enum SqlError {
     #[diagnostic(code(atmosphere::sqlstate::syntax))]
     Syntax(#[source] sqlx::Error),
 
+    /// SQLSTATE 40001 (serialization failure) and 40P01 (deadlock), the transaction may succeed
+    /// if it is run again
+    #[error("serialization failure")]
+    #[diagnostic(code(atmosphere::sqlstate::serialization))]
+    Serialization(#[source] sqlx::Error),
+
     /// All other classes
     #[error("other")]
     #[diagnostic(code(atmosphere::sqlstate::other))]
     Other(#[source] sqlx::Error),
 }
 
+impl QueryError {
+    /// Whether the error is transient, so that retrying the query may succeed: communication
+    /// errors (e.g. connection resets) and timeouts acquiring a connection, see
+    /// [`crate::retry`]. Misconfigurations and closed pools are not transient.
+    pub const fn is_transient(&self) -> bool {
+        match self {
+            Self::Io(err) => transient(err),
+            _ => false,
+        }
+    }
+
+    /// Whether the transaction the query ran in failed because of a serialization failure or a
+    /// deadlock, see [`crate::transaction_with`]
+    pub const fn is_serialization_failure(&self) -> bool {
+        matches!(self, Self::Sql(SqlError::Serialization(_)))
+    }
+
+    /// The class of the error, as reported by metrics and traces
+    pub const fn class(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io",
+            Self::NotFound(_) => "not_found",
+            Self::Sql(_) => "sql",
+            Self::Violation(_) => "violation",
+            Self::Other(_) => "other",
+            Self::InternalError(_) => "internal",
+        }
+    }
+}
+
+/// Whether `err` is transient, see [`QueryError::is_transient`]
+pub(crate) const fn transient(err: &sqlx::Error) -> bool {
+    use sqlx::Error as E;
+
+    matches!(
+        err,
+        E::Io(_) | E::Protocol(_) | E::Tls(_) | E::PoolTimedOut | E::WorkerCrashed
+    )
+}
+
 impl From<sqlx::Error> for QueryError {
     fn from(err: sqlx::Error) -> Self {
         use sqlx::Error as E;
@@ -116,15 +259,24 @@ impl From<sqlx::Error> for QueryError {
             | E::WorkerCrashed => Self::Io(err),
             E::Database(ref e) => {
                 if e.is_unique_violation() {
-                    return Self::Violation(ViolationError::Unique(err));
+                    return Self::Violation(ViolationError::Unique {
+                        column: None,
+                        source: err,
+                    });
                 }
 
                 if e.is_foreign_key_violation() {
-                    return Self::Violation(ViolationError::ForeignKey(err));
+                    return Self::Violation(ViolationError::ForeignKey {
+                        column: None,
+                        source: err,
+                    });
                 }
 
                 if e.is_check_violation() {
-                    return Self::Violation(ViolationError::Check(err));
+                    return Self::Violation(ViolationError::Check {
+                        column: None,
+                        source: err,
+                    });
                 }
 
                 // SQLSTATE code handling
@@ -135,6 +287,11 @@ impl From<sqlx::Error> for QueryError {
                         return Self::InternalError(err);
                     }
 
+                    if matches!(c.as_ref(), "40001" | "40P01") {
+                        crate::runtime::transaction::conflict();
+                        return Self::Sql(SqlError::Serialization(err));
+                    }
+
                     return match &c.as_ref()[0..1] {
                         "22" => Self::Sql(SqlError::DataException(err)),
                         "23" => Self::Sql(SqlError::IntegrityConstraint(err)),
@@ -158,6 +315,121 @@ pub enum Cardinality {
     Many,
 }
 
+impl Cardinality {
+    /// The name of the cardinality, as used in error messages
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::One => "one",
+            Self::Many => "many",
+        }
+    }
+}
+
+static STATEMENTS: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static SCOPED_STATEMENTS: RefCell<Option<bool>> = const { RefCell::new(None) };
+}
+
+/// Includes the statement and the names of its bound columns in the context of failed queries
+/// (see [`QueryContext::statement`]). Off by default, as errors tend to end up in logs and
+/// responses which should not reveal the structure of the database.
+pub fn include_statements(enabled: bool) {
+    STATEMENTS.store(enabled, Ordering::Relaxed);
+}
+
+/// Includes statements in the context of queries failing within `f` if `enabled`, overriding
+/// [`include_statements`] for `f` only
+pub fn with_statements<F: Future>(enabled: bool, f: F) -> impl Future<Output = F::Output> {
+    Scoped::new(&SCOPED_STATEMENTS, enabled, f)
+}
+
+/// Whether statements are included in the context of failed queries in the current scope
+fn statements() -> bool {
+    SCOPED_STATEMENTS
+        .with(|scoped| *scoped.borrow())
+        .unwrap_or_else(|| STATEMENTS.load(Ordering::Relaxed))
+}
+
+static BATCH_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SCOPED_BATCH_SIZE: RefCell<Option<usize>> = const { RefCell::new(None) };
+}
+
+/// Limits the number of rows a batch operation (e.g. [`crate::Read::find_many`]) handles per
+/// statement, splitting larger batches into several statements. By default (or after setting
+/// `0`) batches are only split where they would exceed the bind parameter limit of the driver
+/// ([`Dialect::MAX_BINDS`]), which caps larger sizes as well.
+pub fn batch_size(size: usize) {
+    BATCH_SIZE.store(size, Ordering::Relaxed);
+}
+
+/// Limits the number of rows per statement of batch operations executed within `f` to `size`,
+/// overriding [`batch_size`] for `f` only
+pub fn with_batch_size<F: Future>(size: usize, f: F) -> impl Future<Output = F::Output> {
+    Scoped::new(&SCOPED_BATCH_SIZE, size, f)
+}
+
+/// The number of rows per statement of a batch operation binding `binds` parameters per row,
+/// with `reserved` parameters bound once per statement (see [`batch_size`])
+pub(crate) fn chunk_size(binds: usize, reserved: usize) -> usize {
+    let max = (Current::MAX_BINDS - reserved) / binds.max(1);
+
+    let size = SCOPED_BATCH_SIZE
+        .with(|scoped| *scoped.borrow())
+        .unwrap_or_else(|| BATCH_SIZE.load(Ordering::Relaxed));
+
+    match size {
+        0 => max,
+        size => size.min(max),
+    }
+    .max(1)
+}
+
+/// The table and operation of a query, attached to the errors it fails with (see
+/// [`Error::Failed`]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryContext {
+    /// The name of the table
+    pub table: &'static str,
+    /// The operation the query performs
+    pub op: Operation,
+    /// The rows the query affects
+    pub cardinality: Cardinality,
+    /// The statement of the query, if enabled using [`include_statements`]
+    pub statement: Option<Arc<SqlPreview>>,
+}
+
+impl QueryContext {
+    /// Classifies `err` and attaches the context to it, including the column of violations
+    pub fn error(&self, err: impl Into<QueryError>) -> Error {
+        let mut source = err.into();
+
+        if let QueryError::Violation(violation) = &mut source {
+            violation.resolve(self.table);
+        }
+
+        Error::Failed {
+            context: self.clone(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for QueryContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) on `{}`",
+            self.op.name(),
+            self.cardinality.name(),
+            self.table
+        )
+    }
+}
+
 /// Describes the types of operations that a query performs.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Operation {
@@ -169,26 +441,152 @@ pub enum Operation {
     Other,
 }
 
+impl Operation {
+    /// The name of the operation, as used in metrics and traces
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Select => "select",
+            Self::Insert => "insert",
+            Self::Update => "update",
+            Self::Upsert => "upsert",
+            Self::Delete => "delete",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Describes how a row locked with `SELECT .. FOR UPDATE` behaves if it is already locked by
+/// another transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lock {
+    /// Wait until the lock is released
+    #[default]
+    Wait,
+    /// Fail immediately (`NOWAIT`)
+    NoWait,
+    /// Ignore rows that are already locked (`SKIP LOCKED`)
+    SkipLocked,
+}
+
+impl Lock {
+    /// The sql clause locking rows in this mode
+    pub const fn sql(&self) -> &'static str {
+        match self {
+            Self::Wait => "FOR UPDATE",
+            Self::NoWait => "FOR UPDATE NOWAIT",
+            Self::SkipLocked => "FOR UPDATE SKIP LOCKED",
+        }
+    }
+}
+
 /// Represents a atmosphere query over a database table.
 pub struct Query<T: Bind> {
     pub op: Operation,
     pub cardinality: Cardinality,
     pub(crate) builder: QueryBuilder<'static, crate::Driver>,
     pub(crate) bindings: Bindings<T>,
+    pub(crate) values: Vec<Arc<dyn Value<T>>>,
+    /// Whether the query filters by tenant while no tenant is in scope
+    pub(crate) unscoped: bool,
+    /// The instrumentation of the query while it is executed
+    pub(crate) instrument: OnceLock<Instrument>,
+    /// The fingerprints of the values bound to the columns, see [`crate::statement_log`]
+    #[cfg(feature = "tracing")]
+    pub(crate) fingerprints: OnceLock<Vec<Option<u64>>>,
+    /// Where [`Query::push`] and [`Query::and_where`] add sql to the query
+    filter: Filter,
+}
+
+/// The end of the `WHERE` clause of a query, before trailing clauses such as `ORDER BY` or
+/// `FOR UPDATE` and before its comment
+#[derive(Clone, Copy, Debug)]
+struct Filter {
+    /// The byte offset sql is inserted at
+    at: usize,
+    /// Whether the query has a `WHERE` clause
+    exists: bool,
+    /// Whether the sql was rewritten by an interceptor, leaving the offsets of the generator
+    /// meaningless
+    rewritten: bool,
 }
 
 impl<T: Bind> Query<T> {
     pub(crate) fn new(
         op: Operation,
         cardinality: Cardinality,
-        builder: QueryBuilder<'static, crate::Driver>,
+        mut builder: QueryBuilder<'static, crate::Driver>,
         bindings: Bindings<T>,
     ) -> Self {
+        let rewritten = match crate::intercept::rewrite::<T>(op, builder.sql()) {
+            Some(sql) => {
+                builder = QueryBuilder::new(sql);
+                true
+            }
+            None => false,
+        };
+
+        let filter = Filter {
+            at: builder.sql().len(),
+            exists: true,
+            rewritten,
+        };
+
+        if let Some(comment) = crate::comment::current() {
+            builder.push(comment);
+        }
+
         Self {
             op,
             cardinality,
             builder,
             bindings,
+            values: vec![],
+            unscoped: false,
+            instrument: OnceLock::new(),
+            #[cfg(feature = "tracing")]
+            fingerprints: OnceLock::new(),
+            filter,
+        }
+    }
+
+    /// Marks the end of the `WHERE` clause at byte offset `at` of the generated sql, `exists`
+    /// telling whether the query has one. Defaults to the end of the generated sql, with a
+    /// `WHERE` clause.
+    pub(crate) fn filtered(mut self, at: usize, exists: bool) -> Self {
+        if !self.filter.rewritten {
+            self.filter.at = at;
+            self.filter.exists = exists;
+        }
+
+        self
+    }
+
+    /// Binds the tenant in scope to the tenant conditions of `tables` tables. The conditions use
+    /// the placeholders following the column bindings.
+    pub(crate) fn scoped(mut self, tables: usize) -> Self {
+        if tables == 0 {
+            return self;
+        }
+
+        let Some(tenant) = Tenant::current() else {
+            self.unscoped = true;
+            return self;
+        };
+
+        for _ in 0..tables {
+            self.values.push(Arc::new(tenant.clone()));
+        }
+
+        self
+    }
+
+    /// The table and operation of the query
+    pub fn context(&self) -> QueryContext {
+        QueryContext {
+            table: T::TABLE,
+            op: self.op,
+            cardinality: self.cardinality,
+            statement: statements().then(|| Arc::new(SqlPreview::query(self))),
         }
     }
 
@@ -201,12 +599,235 @@ impl<T: Bind> Query<T> {
     pub const fn bindings(&self) -> &Bindings<T> {
         &self.bindings
     }
+
+    /// Adds `sql` as a condition to the `WHERE` clause of the query, creating the clause if the
+    /// query has none. The condition can be continued using [`Query::push`] and
+    /// [`Query::push_bind`].
+    ///
+    /// Meant to be used by [`HookStage::PreBind`](crate::hooks::HookStage::PreBind) hooks (see
+    /// [`Hook::modify`](crate::hooks::Hook::modify)), e.g. to narrow down the rows a query affects.
+    pub fn and_where(&mut self, sql: impl fmt::Display) -> &mut Self {
+        let keyword = if self.filter.exists {
+            " AND "
+        } else {
+            "\nWHERE "
+        };
+
+        self.filter.exists = true;
+        self.insert(format!("{keyword}{sql}"));
+        self
+    }
+
+    /// Inserts raw sql at the end of the `WHERE` clause of the query, before trailing clauses such
+    /// as `ORDER BY`, `FOR UPDATE` or `RETURNING`.
+    pub fn push(&mut self, sql: impl fmt::Display) -> &mut Self {
+        self.insert(sql);
+        self
+    }
+
+    /// Inserts a placeholder at the end of the `WHERE` clause of the query and binds `value` to
+    /// it, after all column bindings.
+    pub fn push_bind<V>(&mut self, value: V) -> &mut Self
+    where
+        V: for<'q> Encode<'q, crate::Driver> + Type<crate::Driver> + Send + Sync + 'static,
+    {
+        self.values.push(Arc::new(value));
+        self.insert(format!(
+            "${}",
+            self.bindings.columns().len() + self.values.len()
+        ));
+        self
+    }
+
+    /// Replaces the sql of the query with `sql`, which embeds the former sql at byte offset `at`
+    #[cfg(feature = "postgres")]
+    pub(crate) fn embed(&mut self, sql: String, at: usize) {
+        self.builder = QueryBuilder::new(sql);
+        self.filter.at += at;
+    }
+
+    /// Inserts `sql` at the end of the `WHERE` clause, keeping everything following it in place
+    fn insert(&mut self, sql: impl fmt::Display) {
+        let sql = sql.to_string();
+        let (head, tail) = self.builder.sql().split_at(self.filter.at);
+
+        self.builder = QueryBuilder::new(format!("{head}{sql}{tail}"));
+        self.filter.at += sql.len();
+    }
+
+    /// Binds the values pushed using [`Query::push_bind`] and the tenant in scope to an sqlx query
+    #[doc(hidden)]
+    pub fn bind_values<'q, Q: BindValue<'q, T>>(&'q self, mut query: Q) -> Result<Q> {
+        if self.unscoped {
+            return Err(Error::Tenant);
+        }
+
+        for value in &self.values {
+            query = query.bind_value(value.as_ref());
+        }
+
+        Ok(query)
+    }
+
+    /// The time elapsed since the execution of the query started, right before its
+    /// [`PreExec`](crate::hooks::HookStage::PreExec) hooks. `None` if it has not been executed.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.instrument.get().map(Instrument::elapsed)
+    }
+
+    /// Creates an unbound copy of this query
+    pub(crate) fn duplicate(&self) -> Self {
+        Self {
+            op: self.op,
+            cardinality: self.cardinality,
+            builder: QueryBuilder::new(self.sql().to_owned()),
+            bindings: self.bindings.clone(),
+            values: self.values.clone(),
+            unscoped: self.unscoped,
+            instrument: OnceLock::new(),
+            #[cfg(feature = "tracing")]
+            fingerprints: OnceLock::new(),
+            filter: self.filter,
+        }
+    }
+}
+
+type Arguments<'q> = <crate::Driver as HasArguments<'q>>::Arguments;
+
+/// A value bound to a query in addition to its column bindings
+#[doc(hidden)]
+pub trait Value<T>: Send + Sync {
+    fn bind<'q>(
+        &'q self,
+        query: sqlx::query::Query<'q, crate::Driver, Arguments<'q>>,
+    ) -> sqlx::query::Query<'q, crate::Driver, Arguments<'q>>;
+
+    fn bind_as<'q>(
+        &'q self,
+        query: QueryAs<'q, crate::Driver, T, Arguments<'q>>,
+    ) -> QueryAs<'q, crate::Driver, T, Arguments<'q>>;
+
+    /// The fingerprint of the value, see [`crate::statement_log`]
+    #[cfg(feature = "tracing")]
+    fn fingerprint(&self) -> u64;
+}
+
+impl<T, V> Value<T> for V
+where
+    V: for<'q> Encode<'q, crate::Driver> + Type<crate::Driver> + Send + Sync,
+{
+    fn bind<'q>(
+        &'q self,
+        query: sqlx::query::Query<'q, crate::Driver, Arguments<'q>>,
+    ) -> sqlx::query::Query<'q, crate::Driver, Arguments<'q>> {
+        query.bind(self)
+    }
+
+    fn bind_as<'q>(
+        &'q self,
+        query: QueryAs<'q, crate::Driver, T, Arguments<'q>>,
+    ) -> QueryAs<'q, crate::Driver, T, Arguments<'q>> {
+        query.bind(self)
+    }
+
+    #[cfg(feature = "tracing")]
+    fn fingerprint(&self) -> u64 {
+        crate::statement_log::fingerprint(self)
+    }
+}
+
+/// sqlx queries that values pushed onto a [`Query`] can be bound to
+#[doc(hidden)]
+pub trait BindValue<'q, T> {
+    fn bind_value(self, value: &'q dyn Value<T>) -> Self;
+}
+
+impl<'q, T> BindValue<'q, T> for sqlx::query::Query<'q, crate::Driver, Arguments<'q>> {
+    fn bind_value(self, value: &'q dyn Value<T>) -> Self {
+        value.bind(self)
+    }
+}
+
+impl<'q, T> BindValue<'q, T> for QueryAs<'q, crate::Driver, T, Arguments<'q>> {
+    fn bind_value(self, value: &'q dyn Value<T>) -> Self {
+        value.bind_as(self)
+    }
+}
+
+/// The outcome of a write (insert, update or delete), uniform across drivers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct WriteOutcome {
+    /// The number of rows inserted, updated or deleted
+    pub rows_affected: u64,
+    /// The id generated for an `auto` primary key by an insert.
+    ///
+    /// Only reported by MySQL (`LAST_INSERT_ID()`) and SQLite (the rowid). PostgreSQL does not
+    /// report generated ids, the row created is updated with its key using `RETURNING` instead.
+    pub last_insert_id: Option<u64>,
+}
+
+// named concretely, as coherence can not tell the projection of the driver apart from `Self`
+#[cfg(feature = "postgres")]
+type DriverResult = sqlx::postgres::PgQueryResult;
+#[cfg(feature = "mysql")]
+type DriverResult = sqlx::mysql::MySqlQueryResult;
+#[cfg(feature = "sqlite")]
+type DriverResult = sqlx::sqlite::SqliteQueryResult;
+
+impl WriteOutcome {
+    /// The outcome of an insert into a table with an `auto` primary key
+    pub(crate) fn inserted(res: DriverResult) -> Self {
+        #[cfg(feature = "mysql")]
+        let id = Some(res.last_insert_id());
+        #[cfg(feature = "sqlite")]
+        let id = u64::try_from(res.last_insert_rowid()).ok();
+        #[cfg(feature = "postgres")]
+        let id = None;
+
+        Self {
+            rows_affected: res.rows_affected(),
+            last_insert_id: id.filter(|id| *id != 0),
+        }
+    }
+}
+
+impl From<DriverResult> for WriteOutcome {
+    fn from(res: DriverResult) -> Self {
+        Self {
+            rows_affected: res.rows_affected(),
+            last_insert_id: None,
+        }
+    }
 }
 
 /// Describes possible results of executing a query.
 pub enum QueryResult<'t, T: Table + Bind> {
-    Execution(&'t Result<<crate::Driver as sqlx::Database>::QueryResult>),
+    Execution(&'t Result<WriteOutcome>),
     Optional(&'t Result<Option<T>>),
     One(&'t Result<T>),
     Many(&'t Result<Vec<T>>),
 }
+
+impl<'t, T: Table + Bind> QueryResult<'t, T> {
+    /// Whether the query was executed successfully
+    pub const fn is_ok(&self) -> bool {
+        match self {
+            Self::Execution(res) => res.is_ok(),
+            Self::Optional(res) => res.is_ok(),
+            Self::One(res) => res.is_ok(),
+            Self::Many(res) => res.is_ok(),
+        }
+    }
+
+    /// The error the query failed with, if it failed during execution
+    pub fn error(&self) -> Option<&'t QueryError> {
+        let err = match *self {
+            Self::Execution(res) => res.as_ref().err(),
+            Self::Optional(res) => res.as_ref().err(),
+            Self::One(res) => res.as_ref().err(),
+            Self::Many(res) => res.as_ref().err(),
+        };
+
+        err.and_then(Error::query)
+    }
+}
@@ -0,0 +1,122 @@
+//! Work queues on top of regular tables.
+//!
+//! A table implementing [`Queue`] can be used as a job queue shared by any number of concurrent
+//! workers: [`Queue::claim_next`] atomically picks the oldest pending row, moves it into the
+//! in-progress state and returns it. Rows that are concurrently being claimed by other workers are
+//! skipped (`FOR UPDATE SKIP LOCKED`), so no row is ever handed out twice and workers never block
+//! each other.
+//!
+//! ```ignore
+//! #[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+//! #[sqlx(type_name = "job_state", rename_all = "lowercase")]
+//! enum JobState {
+//!     Pending,
+//!     Running,
+//!     Done,
+//! }
+//!
+//! #[table(schema = "public", name = "job")]
+//! struct Job {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     state: JobState,
+//!     payload: String,
+//! }
+//!
+//! impl Queue for Job {
+//!     type State = JobState;
+//!
+//!     const STATE: &'static str = "state";
+//!
+//!     fn pending() -> JobState {
+//!         JobState::Pending
+//!     }
+//!
+//!     fn in_progress() -> JobState {
+//!         JobState::Running
+//!     }
+//! }
+//!
+//! while let Some(mut job) = Job::claim_next(&pool).await? {
+//!     // ..
+//!     job.state = JobState::Done;
+//!     job.update(&pool).await?;
+//! }
+//! ```
+
+use crate::{
+    hooks::{self, HookInput, HookStage, Hooks},
+    query::QueryResult,
+    runtime::changes,
+    Bind, DataColumn, Result, Table,
+};
+
+use async_trait::async_trait;
+use sqlx::{database::HasArguments, Encode, Executor, IntoArguments, Type};
+
+/// A table whose rows are jobs that are claimed by workers.
+#[async_trait]
+pub trait Queue: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
+    /// The type of the state column
+    type State: for<'q> Encode<'q, crate::Driver> + Type<crate::Driver> + Send + Sync;
+
+    /// The field name of the data column holding the state of each job
+    const STATE: &'static str;
+
+    /// The state of jobs waiting to be claimed
+    fn pending() -> Self::State;
+
+    /// The state claimed jobs are moved into
+    fn in_progress() -> Self::State;
+
+    /// Claims the oldest pending job (by primary key), marks it as in progress and returns it.
+    /// Returns `None` if no job is pending or all pending jobs are currently being claimed by
+    /// other workers.
+    async fn claim_next<'e, E>(executor: E) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let mut query = crate::runtime::sql::claim_next::<Self>(state::<Self>());
+
+        hooks::prepare(&mut query, HookInput::None).await?;
+        hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+
+        let sql = sqlx::query_as(query.sql())
+            .bind(Self::in_progress())
+            .bind(Self::pending());
+
+        let res = query
+            .bind_values(sql)?
+            .persistent(false)
+            .fetch_optional(executor)
+            .await
+            .map_err(|err| query.context().error(err));
+
+        hooks::execute(
+            HookStage::PostExec,
+            &query,
+            QueryResult::Optional(&res).into(),
+        )
+        .await?;
+
+        if let Ok(Some(job)) = &res {
+            changes::publish(query.op, job.pk(), Some(job));
+        }
+
+        res
+    }
+}
+
+/// Looks up the state column of a queue.
+///
+/// # Panics
+///
+/// If [`Queue::STATE`] does not name a data column of the table.
+fn state<T: Queue>() -> &'static DataColumn<T> {
+    T::DATA_COLUMNS
+        .iter()
+        .find(|c| c.field == T::STATE)
+        .unwrap_or_else(|| panic!("{} is not a data column of {}", T::STATE, T::TABLE))
+}
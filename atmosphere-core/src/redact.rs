@@ -0,0 +1,59 @@
+//! Redaction of sensitive columns
+//!
+//! The values of columns marked using `#[sql(sensitive)]` (e.g. personal data) never appear in
+//! logs, error messages or audit records. Wherever atmosphere would print them, they are replaced
+//! by [`REDACTED`]:
+//!
+//! - `#[derive(Schema)]` implements `Debug` for tables with sensitive columns, printing
+//!   `«redacted»` instead of their values. Such tables must not derive `Debug` themselves.
+//! - Validation errors (`#[sql(validate = "..")]`) of sensitive fields replace the message, which
+//!   might quote the value.
+//! - Audit records (see [`crate::audit`]) hold `«redacted»` instead of the values.
+//!
+//! Statements and their previews (see [`crate::Preview`]) only ever hold placeholders and are
+//! unaffected.
+//!
+//! ```ignore
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "user")]
+//! struct User {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     #[sql(sensitive)]
+//!     email: String,
+//! }
+//!
+//! // User { id: 1, email: «redacted» }
+//! println!("{user:?}");
+//! ```
+
+use std::fmt;
+
+use crate::Table;
+
+/// Printed in place of the values of sensitive columns
+pub const REDACTED: &str = "«redacted»";
+
+/// Formats as [`REDACTED`], standing in for the value of a sensitive column
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// The sql names of the sensitive columns of `T`
+pub fn sensitive<T: Table>() -> impl Iterator<Item = &'static str> {
+    T::DATA_COLUMNS
+        .iter()
+        .filter(|c| c.sensitive)
+        .map(|c| c.sql)
+}
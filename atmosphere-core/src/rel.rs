@@ -4,15 +4,18 @@
 //! 'RefersTo' and 'ReferredBy'. These traits facilitate operations like resolving and deleting
 //! relationships in a database using SQLx.
 
+use std::collections::HashMap;
+use std::hash::Hash;
+
 use async_trait::async_trait;
 use sqlx::database::HasArguments;
-use sqlx::{Executor, IntoArguments};
+use sqlx::{Acquire, Decode, Executor, IntoArguments, Row};
 
 use crate::bind::Bind;
-use crate::query::{Query, QueryError};
-use crate::runtime::sql;
-use crate::schema::Table;
-use crate::{Error, ForeignKey, Result};
+use crate::query::{self, QueryError, WriteOutcome};
+use crate::runtime::{instrument::instrumented, sql};
+use crate::schema::{Deletable, Table};
+use crate::{ForeignKey, Result};
 
 /// Defines a relationship where `Self` refers to `Other`.
 ///
@@ -34,19 +37,52 @@ where
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let Query { builder, .. } = sql::select::<Other>();
+        let query = sql::select::<Other>();
 
-        let mut query = sqlx::query_as(builder.sql());
+        let mut sql = sqlx::query_as(query.sql());
 
         let fk = Self::FOREIGN_KEY.as_col();
-        query = self.bind(&fk, query).unwrap();
-
-        query
-            .persistent(false)
-            .fetch_one(executor)
-            .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query)
+        sql = self.bind(&fk, sql)?;
+
+        let sql = query.bind_values(sql)?.persistent(false);
+
+        let context = query.context();
+
+        let execution = async move {
+            sql.fetch_one(executor)
+                .await
+                .map_err(|err| context.error(err))
+        };
+
+        instrumented(&query, execution).await
+    }
+
+    /// Asynchronously resolves the `Other` entity that `Self` refers to, returning `None` if the
+    /// foreign key is `NULL` or does not point to an existing row.
+    async fn resolve_optional<'e, E>(&self, executor: E) -> Result<Option<Other>>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let query = sql::select::<Other>();
+
+        let mut sql = sqlx::query_as(query.sql());
+
+        let fk = Self::FOREIGN_KEY.as_col();
+        sql = self.bind(&fk, sql)?;
+
+        let sql = query.bind_values(sql)?.persistent(false);
+
+        let context = query.context();
+
+        let execution = async move {
+            sql.fetch_optional(executor)
+                .await
+                .map_err(|err| context.error(err))
+        };
+
+        instrumented(&query, execution).await
     }
 }
 
@@ -68,19 +104,86 @@ where
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let Query { builder, .. } = sql::select_by::<Other>(Other::FOREIGN_KEY.as_col());
+        let query = sql::select_by::<Other>(Other::FOREIGN_KEY.as_col());
 
-        let mut query = sqlx::query_as(builder.sql());
+        let mut sql = sqlx::query_as(query.sql());
 
         let pk = Self::PRIMARY_KEY.as_col();
-        query = self.bind(&pk, query).unwrap();
-
-        query
-            .persistent(false)
-            .fetch_all(executor)
-            .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query)
+        sql = self.bind(&pk, sql)?;
+
+        let sql = query.bind_values(sql)?.persistent(false);
+
+        let context = query.context();
+
+        let execution = async move {
+            sql.fetch_all(executor)
+                .await
+                .map_err(|err| context.error(err))
+        };
+
+        instrumented(&query, execution).await
+    }
+
+    /// Fetches all `Other` entities referring to any of the given `parents` using a single query
+    /// and groups them by the primary key of their parent.
+    ///
+    /// Every parent is contained in the returned map, parents without referring entities are
+    /// mapped to an empty `Vec`. Large batches of parents are split into several queries within
+    /// the bind parameter limit of the driver (see [`crate::query::batch_size`]), executed on a
+    /// single connection of `acquire`.
+    async fn resolve_for<'a, A>(
+        parents: &[Self],
+        acquire: A,
+    ) -> Result<HashMap<Self::PrimaryKey, Vec<Other>>>
+    where
+        A: Acquire<'a, Database = crate::Driver> + Send,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+        Self::PrimaryKey: Clone + Eq + Hash + for<'r> Decode<'r, crate::Driver>,
+    {
+        let mut resolved: HashMap<Self::PrimaryKey, Vec<Other>> = parents
+            .iter()
+            .map(|parent| (parent.pk().clone(), vec![]))
+            .collect();
+
+        if parents.is_empty() {
+            return Ok(resolved);
+        }
+
+        let mut conn = acquire.acquire().await.map_err(QueryError::from)?;
+
+        for chunk in parents.chunks(query::chunk_size(1, sql::tenants::<Other>())) {
+            let query = sql::select_in::<Other>(Other::FOREIGN_KEY.as_col(), chunk.len());
+
+            let mut sql = sqlx::query(query.sql());
+
+            for parent in chunk {
+                sql = sql.bind(parent.pk());
+            }
+
+            let sql = query.bind_values(sql)?.persistent(false);
+
+            let context = query.context();
+
+            let execution = async {
+                sql.fetch_all(&mut *conn)
+                    .await
+                    .map_err(|err| context.error(err))
+            };
+
+            let rows = instrumented(&query, execution).await?;
+
+            for row in rows {
+                let parent: Self::PrimaryKey = row
+                    .try_get(Other::FOREIGN_KEY.sql)
+                    .map_err(|err| query.context().error(err))?;
+                let other = Other::from_row(&row).map_err(|err| query.context().error(err))?;
+
+                resolved.entry(parent).or_default().push(other);
+            }
+        }
+
+        Ok(resolved)
     }
 
     /// Resolves the referring entities based on the primary key of `Self`.
@@ -90,39 +193,49 @@ where
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let Query { builder, .. } = sql::select_by::<Other>(Other::FOREIGN_KEY.as_col());
-
-        sqlx::query_as(builder.sql())
-            .bind(pk)
-            .persistent(false)
-            .fetch_all(executor)
-            .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query)
+        let query = sql::select_by::<Other>(Other::FOREIGN_KEY.as_col());
+
+        let sql = sqlx::query_as(query.sql()).bind(pk);
+
+        let sql = query.bind_values(sql)?.persistent(false);
+
+        let context = query.context();
+
+        let execution = async move {
+            sql.fetch_all(executor)
+                .await
+                .map_err(|err| context.error(err))
+        };
+
+        instrumented(&query, execution).await
     }
 
     /// Deletes all `Other` entities referring to `Self`.
-    async fn delete_all<'e, E>(
-        &self,
-        executor: E,
-    ) -> Result<<crate::Driver as sqlx::Database>::QueryResult>
+    async fn delete_all<'e, E>(&self, executor: E) -> Result<WriteOutcome>
     where
+        Other: Deletable,
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let Query { builder, .. } = sql::delete_by::<Other>(Other::FOREIGN_KEY.as_col());
+        let query = sql::delete_by::<Other>(Other::FOREIGN_KEY.as_col());
 
-        let mut query = sqlx::query(builder.sql());
+        let mut sql = sqlx::query(query.sql());
 
         let pk = Self::PRIMARY_KEY.as_col();
-        query = self.bind(&pk, query).unwrap();
-
-        query
-            .persistent(false)
-            .execute(executor)
-            .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query)
+        sql = self.bind(&pk, sql)?;
+
+        let sql = query.bind_values(sql)?.persistent(false);
+
+        let context = query.context();
+
+        let execution = async move {
+            sql.execute(executor)
+                .await
+                .map(WriteOutcome::from)
+                .map_err(|err| context.error(err))
+        };
+
+        instrumented(&query, execution).await
     }
 }
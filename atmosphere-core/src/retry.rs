@@ -0,0 +1,157 @@
+//! Retries of queries failing with transient errors.
+//!
+//! Queries fail transiently if the connection to the database breaks (e.g. it is reset) or no
+//! connection could be acquired from the pool in time, see [`crate::Error::is_transient`]. Once a
+//! [`RetryPolicy`] has been installed using [`set_policy`], such failures are retried with
+//! exponential backoff and jitter:
+//!
+//! - [`crate::transaction`] retries acquiring its connection
+//! - [`retry`] retries any operation, e.g. a single CRUD call on a pool
+//!
+//! ```ignore
+//! atmosphere::retry::set_policy(RetryPolicy::new(3));
+//!
+//! let user = atmosphere::retry::retry(|| User::read(&pool, &0)).await?;
+//! ```
+//!
+//! The CRUD methods (and the other generated queries) do not retry on their own, even with a
+//! policy installed. They accept any executor, which is consumed by the query it executes, and
+//! can not tell whether it is a pool the query could run on again. Retrying takes a way to
+//! execute the query again instead: the pool of [`crate::transaction`] or the closure passed to
+//! [`retry`]. Queries within a transaction are never retried on their own, as the transaction is
+//! lost with its connection.
+//! Writes failing while their connection broke may have been applied already, only retry them if
+//! they are idempotent.
+
+use std::{future::Future, sync::RwLock, time::Duration};
+
+use rand::Rng;
+
+use crate::Result;
+
+/// How often and how fast failed operations are retried
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base: Duration,
+    max: Duration,
+    jitter: bool,
+}
+
+impl RetryPolicy {
+    /// Attempts operations up to `max_attempts` times (including the first attempt), backing off
+    /// exponentially from 50ms up to 5s with jitter
+    pub const fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            base: Duration::from_millis(50),
+            max: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+
+    /// Backs off `base` before the first retry, doubling the delay for each further retry up to
+    /// `max`
+    pub const fn backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.base = base;
+        self.max = max;
+        self
+    }
+
+    /// Backs off the exact delays, without randomizing them
+    pub const fn without_jitter(mut self) -> Self {
+        self.jitter = false;
+        self
+    }
+
+    /// The number of times an operation is attempted at most
+    pub const fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// The delay before the `retry`th retry (starting at 1). With jitter, the delay is picked
+    /// uniformly between half of the delay and the full delay.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .base
+            .saturating_mul(1 << retry.saturating_sub(1).min(31))
+            .min(self.max);
+
+        match self.jitter {
+            true => rand::thread_rng().gen_range(delay / 2..=delay),
+            false => delay,
+        }
+    }
+}
+
+static POLICY: RwLock<Option<RetryPolicy>> = RwLock::new(None);
+
+/// Installs the retry policy, replacing the previous one. Without a policy nothing is retried.
+pub fn set_policy(policy: RetryPolicy) {
+    *POLICY.write().expect("retry policy poisoned") = Some(policy);
+}
+
+/// The installed retry policy, if any
+pub fn policy() -> Option<RetryPolicy> {
+    *POLICY.read().expect("retry policy poisoned")
+}
+
+/// Runs `f` until it succeeds, fails with an error that is not transient or has been attempted
+/// as often as the installed [`RetryPolicy`] allows
+pub async fn retry<T, F, Fut>(f: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    retry_if(f, crate::Error::is_transient).await
+}
+
+/// Runs `f` like [`retry`], retrying the errors `transient` holds for
+pub(crate) async fn retry_if<T, E, F, Fut>(
+    mut f: F,
+    transient: impl Fn(&E) -> bool,
+) -> std::result::Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let policy = policy();
+    let mut attempt = 1;
+
+    loop {
+        match (f().await, policy) {
+            (Err(err), Some(policy)) if attempt < policy.max_attempts && transient(&err) => {
+                tokio::time::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            (res, _) => return res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryPolicy;
+
+    #[test]
+    fn delay() {
+        let policy = RetryPolicy::new(5)
+            .backoff(Duration::from_millis(100), Duration::from_millis(500))
+            .without_jitter();
+
+        let delays = (1..=5).map(|retry| policy.delay(retry)).collect::<Vec<_>>();
+
+        assert_eq!(delays, [100, 200, 400, 500, 500].map(Duration::from_millis));
+
+        assert_eq!(policy.delay(u32::MAX), Duration::from_millis(500));
+
+        let jittered = RetryPolicy::new(5).backoff(Duration::from_millis(100), Duration::MAX);
+
+        for _ in 0..100 {
+            let delay = jittered.delay(2);
+            assert!((Duration::from_millis(100)..=Duration::from_millis(200)).contains(&delay));
+        }
+    }
+}
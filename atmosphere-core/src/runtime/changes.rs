@@ -0,0 +1,144 @@
+//! Change events
+//!
+//! Every row created, updated, upserted or deleted through atmosphere is published as a
+//! [`ChangeEvent`] to the subscribers of its table, so other parts of an application can react to
+//! changes without polling the database.
+//!
+//! ```ignore
+//! let mut changes = atmosphere::changes::<User>();
+//!
+//! tokio::spawn(async move {
+//!     while let Ok(event) = changes.recv().await {
+//!         println!("{:?} user {}", event.op, event.pk);
+//!     }
+//! });
+//! ```
+//!
+//! Changes made inside of [`crate::transaction`] are published once the transaction has been
+//! committed; rolled back changes are never published. Only changes made through atmosphere are
+//! observed, this is not a replacement for change data capture on the database itself.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use lazy_static::lazy_static;
+use tokio::sync::broadcast;
+
+use crate::{query::Operation, Table};
+
+/// The number of events buffered per subscriber before it starts lagging behind
+const CAPACITY: usize = 1024;
+
+/// A row of `T` that was changed through atmosphere.
+pub struct ChangeEvent<T: Table> {
+    /// The operation that changed the row
+    pub op: Operation,
+    /// The primary key of the changed row
+    pub pk: T::PrimaryKey,
+    /// The row as written, `None` if it was deleted by its primary key only
+    pub row: Option<T>,
+}
+
+impl<T> Clone for ChangeEvent<T>
+where
+    T: Table + Clone,
+    T::PrimaryKey: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            op: self.op,
+            pk: self.pk.clone(),
+            row: self.row.clone(),
+        }
+    }
+}
+
+/// Subscribes to all changes of `T` made through atmosphere from now on.
+///
+/// Subscribers falling more than 1024 events behind skip the oldest events and receive a
+/// [`broadcast::error::RecvError::Lagged`] instead.
+pub fn changes<T>() -> broadcast::Receiver<ChangeEvent<T>>
+where
+    T: Table + Clone + Sync,
+    T::PrimaryKey: Clone,
+{
+    if let Some(channel) = CHANNELS
+        .read()
+        .expect("change channels poisoned")
+        .get(&TypeId::of::<T>())
+        .and_then(|channel| channel.downcast_ref::<Channel<T>>())
+    {
+        return channel.sender.subscribe();
+    }
+
+    let mut channels = CHANNELS.write().expect("change channels poisoned");
+
+    let channel = channels
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| Box::new(Channel::<T>::new()))
+        .downcast_ref::<Channel<T>>()
+        .expect("change channel of wrong type");
+
+    channel.sender.subscribe()
+}
+
+lazy_static! {
+    /// The channels of all tables with subscribers, a `Channel<T>` per table
+    static ref CHANNELS: RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>> = Default::default();
+}
+
+/// Clones a changed row into an event, captured when the first subscriber of a table subscribes
+type Event<T> =
+    dyn Fn(Operation, &<T as Table>::PrimaryKey, Option<&T>) -> ChangeEvent<T> + Send + Sync;
+
+struct Channel<T: Table> {
+    sender: broadcast::Sender<ChangeEvent<T>>,
+    event: Arc<Event<T>>,
+}
+
+impl<T> Channel<T>
+where
+    T: Table + Clone,
+    T::PrimaryKey: Clone,
+{
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+
+        Self {
+            sender,
+            event: Arc::new(|op, pk, row| ChangeEvent {
+                op,
+                pk: pk.clone(),
+                row: row.cloned(),
+            }),
+        }
+    }
+}
+
+/// Publishes a change to the subscribers of `T`, once the surrounding transaction (if any) has
+/// been committed
+pub(crate) fn publish<T: Table>(op: Operation, pk: &T::PrimaryKey, row: Option<&T>) {
+    let channels = CHANNELS.read().expect("change channels poisoned");
+
+    let Some(channel) = channels
+        .get(&TypeId::of::<T>())
+        .and_then(|channel| channel.downcast_ref::<Channel<T>>())
+    else {
+        return;
+    };
+
+    if channel.sender.receiver_count() == 0 {
+        return;
+    }
+
+    let event = (channel.event)(op, pk, row);
+    let sender = channel.sender.clone();
+
+    crate::runtime::transaction::on_commit(move || {
+        // there being no subscribers left is fine
+        let _ = sender.send(event);
+    });
+}
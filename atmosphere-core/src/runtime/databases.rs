@@ -0,0 +1,68 @@
+//! Named databases
+//!
+//! Applications working with several databases (e.g. a primary and an analytics database) assign
+//! tables to a named database using `#[table(.., database = "..")]`. [`Databases`] holds the pool of
+//! each database and returns the pool holding a table, tables without a database are held by the
+//! default pool.
+//!
+//! ```ignore
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "page_view", database = "analytics")]
+//! struct PageView {
+//!     #[sql(pk)]
+//!     id: i64,
+//! }
+//!
+//! let databases = Databases::new(primary).with("analytics", analytics);
+//!
+//! view.create(databases.of::<PageView>()?).await?; // executed on `analytics`
+//! user.create(databases.of::<User>()?).await?; // executed on `primary`
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{Error, Result, Table};
+
+/// The pools of the default and all named databases
+#[derive(Debug)]
+pub struct Databases {
+    default: crate::Pool,
+    named: HashMap<String, crate::Pool>,
+}
+
+impl Databases {
+    /// Creates a registry holding tables without a named database in `default`
+    pub fn new(default: crate::Pool) -> Self {
+        Self {
+            default,
+            named: HashMap::new(),
+        }
+    }
+
+    /// Registers the pool of the database `name`
+    pub fn with(mut self, name: impl Into<String>, pool: crate::Pool) -> Self {
+        self.named.insert(name.into(), pool);
+        self
+    }
+
+    /// The pool of the default database
+    pub const fn default_pool(&self) -> &crate::Pool {
+        &self.default
+    }
+
+    /// The pool of the database `name`, if registered
+    pub fn get(&self, name: &str) -> Option<&crate::Pool> {
+        self.named.get(name)
+    }
+
+    /// The pool of the database holding `T` (see [`Table::DATABASE`]).
+    ///
+    /// Fails with [`Error::Database`] if the database of `T` is not registered, rather than
+    /// silently using the default database.
+    pub fn of<T: Table>(&self) -> Result<&crate::Pool> {
+        match T::DATABASE {
+            Some(name) => self.get(name).ok_or(Error::Database(name)),
+            None => Ok(&self.default),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Instrumentation of generated queries
+//!
+//! Depending on the enabled features, every generated query records metrics (`metrics`, see
+//! [`super::metrics`]) and is traced in a span (`tracing`). The span is named `atmosphere.query`
+//! and carries the fields of the OpenTelemetry semantic conventions for database clients:
+//!
+//! - `db.system`: `postgresql`, `mysql` or `sqlite`
+//! - `db.namespace`: the schema of the table
+//! - `db.collection.name`: the name of the table
+//! - `db.operation.name`: the operation (`select`, `insert`, `update`, `upsert` or `delete`)
+//! - `db.query.text`: the generated sql
+//! - `db.rows_affected`: the number of rows affected or returned by a successful query
+//! - `error.type`: the class of the error a query failed with
+//!
+//! Queries of the CRUD traits are instrumented between their
+//! [`PreExec`](crate::hooks::HookStage::PreExec) and [`PostExec`](crate::hooks::HookStage::PostExec)
+//! hooks, queries executed without hooks (relationships and unique column finders) through
+//! [`instrumented`]. The [interceptors](crate::intercept) run within the instrumentation.
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    intercept::Outcome,
+    query::{Operation, Query, QueryError, QueryResult, WriteOutcome},
+    Bind, Error, Result, Table,
+};
+
+#[cfg(feature = "tracing")]
+use crate::runtime::sql::dialect::{Current, Dialect};
+
+/// The instrumentation of a query in flight
+pub(crate) struct Instrument {
+    #[cfg(feature = "metrics")]
+    op: Operation,
+    started: Instant,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Instrument {
+    /// Starts instrumenting the query `sql` performing `op` on `T`
+    #[allow(unused_variables, clippy::extra_unused_type_parameters)]
+    pub(crate) fn start<T: Table>(op: Operation, sql: &str) -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            op,
+            started: Instant::now(),
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "atmosphere.query",
+                otel.name = format!("{} {}", op.name(), T::TABLE),
+                otel.kind = "client",
+                otel.status_code = tracing::field::Empty,
+                db.system = Current::SYSTEM,
+                db.namespace = crate::SchemaContext::schema::<T>(),
+                db.collection.name = T::name().as_ref(),
+                db.operation.name = op.name(),
+                db.query.text = sql,
+                db.rows_affected = tracing::field::Empty,
+                error.type = tracing::field::Empty,
+            ),
+        }
+    }
+
+    /// The time elapsed since the query started
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Records the outcome of the query, the number of `rows` affected or returned if it
+    /// succeeded and its `error` if it failed
+    #[allow(unused_variables, clippy::extra_unused_type_parameters)]
+    pub(crate) fn finish<T: Table>(&self, rows: Option<u64>, error: Option<&QueryError>) {
+        #[cfg(feature = "metrics")]
+        super::metrics::record::<T>(self.op, self.started, rows, error);
+
+        #[cfg(feature = "tracing")]
+        match rows {
+            Some(rows) => {
+                self.span.record("db.rows_affected", rows);
+            }
+            None => {
+                self.span.record("otel.status_code", "ERROR");
+                self.span
+                    .record("error.type", error.map_or("other", QueryError::class));
+            }
+        }
+    }
+
+    /// Records the outcome of a query executed by the CRUD traits
+    pub(crate) fn finish_hooked<T: Table + Bind>(&self, res: &QueryResult<'_, T>) {
+        self.finish::<T>(rows(res), res.error());
+    }
+}
+
+/// The number of rows affected or returned by a query executed by the CRUD traits, if it succeeded
+pub(crate) fn rows<T: Table + Bind>(res: &QueryResult<'_, T>) -> Option<u64> {
+    match res {
+        QueryResult::Execution(res) => res.as_ref().ok().map(Rows::rows),
+        QueryResult::Optional(res) => res.as_ref().ok().map(Rows::rows),
+        QueryResult::One(res) => res.as_ref().ok().map(Rows::rows),
+        QueryResult::Many(res) => res.as_ref().ok().map(Rows::rows),
+    }
+}
+
+/// The results of a query, as counted by the instrumentation and
+/// [`ResultExt::created`](crate::ResultExt::created)
+pub trait Rows {
+    /// The number of rows affected or returned
+    fn rows(&self) -> u64;
+}
+
+impl<T: Table> Rows for T {
+    fn rows(&self) -> u64 {
+        1
+    }
+}
+
+impl<T: Table> Rows for Option<T> {
+    fn rows(&self) -> u64 {
+        u64::from(self.is_some())
+    }
+}
+
+impl<T: Table> Rows for Vec<T> {
+    fn rows(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+// named concretely, as coherence can not tell the projection of the driver apart from tables
+#[cfg(feature = "postgres")]
+type DriverRow = sqlx::postgres::PgRow;
+#[cfg(feature = "mysql")]
+type DriverRow = sqlx::mysql::MySqlRow;
+#[cfg(feature = "sqlite")]
+type DriverRow = sqlx::sqlite::SqliteRow;
+
+impl Rows for Vec<DriverRow> {
+    fn rows(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+impl Rows for WriteOutcome {
+    fn rows(&self) -> u64 {
+        self.rows_affected
+    }
+}
+
+/// Executes `query` instrumented, `execution` being its execution.
+///
+/// Used for queries executed without hooks, including the ones generated by `#[derive(Schema)]`.
+/// Queries writing rows clear the cache of `T`, as they do not know which rows they change.
+#[doc(hidden)]
+pub async fn instrumented<T, R, F>(query: &Query<T>, execution: F) -> Result<R>
+where
+    T: Table + Bind,
+    R: Rows,
+    F: Future<Output = Result<R>>,
+{
+    crate::cache::evict(query).await?;
+
+    let instrument = Instrument::start::<T>(query.op, query.sql());
+
+    crate::intercept::before(query).await?;
+
+    #[cfg(feature = "tracing")]
+    let res = tracing::Instrument::instrument(execution, instrument.span.clone()).await;
+
+    #[cfg(not(feature = "tracing"))]
+    let res = execution.await;
+
+    let rows = res.as_ref().ok().map(Rows::rows);
+    let error = res.as_ref().err().and_then(Error::query);
+
+    instrument.finish::<T>(rows, error);
+
+    let outcome = Outcome {
+        elapsed: instrument.elapsed(),
+        rows,
+        error,
+    };
+
+    #[cfg(feature = "tracing")]
+    crate::statement_log::log(query, &outcome);
+
+    crate::intercept::after(query, outcome).await?;
+
+    res
+}
@@ -0,0 +1,51 @@
+//! Query metrics
+//!
+//! With the `metrics` feature enabled, every generated query reports the following metrics
+//! through the [`metrics`] facade, labeled by `table` and `operation`:
+//!
+//! - `atmosphere_queries_total` (counter): the number of executed queries
+//! - `atmosphere_query_duration_seconds` (histogram): the time spent executing queries
+//! - `atmosphere_query_rows` (histogram): the number of rows affected or returned by successful
+//!   queries
+//! - `atmosphere_query_errors_total` (counter): the number of failed queries, additionally
+//!   labeled by the `error` class (`io`, `not_found`, `sql`, `violation`, `other` or `internal`)
+//!
+//! Install a recorder (e.g. `metrics-exporter-prometheus`) to collect them.
+
+use std::time::Instant;
+
+use metrics::{counter, histogram};
+
+use crate::{
+    query::{Operation, QueryError},
+    Table,
+};
+
+/// Records the metrics of a query performing `op` on `T` started at `started`, `rows` being the
+/// number of rows affected or returned if it succeeded and `error` the error it failed with
+pub(crate) fn record<T: Table>(
+    op: Operation,
+    started: Instant,
+    rows: Option<u64>,
+    error: Option<&QueryError>,
+) {
+    let labels = [("table", T::TABLE), ("operation", op.name())];
+
+    counter!("atmosphere_queries_total", &labels).increment(1);
+
+    histogram!("atmosphere_query_duration_seconds", &labels)
+        .record(started.elapsed().as_secs_f64());
+
+    if let Some(rows) = rows {
+        histogram!("atmosphere_query_rows", &labels).record(rows as f64);
+        return;
+    }
+
+    counter!(
+        "atmosphere_query_errors_total",
+        "table" => T::TABLE,
+        "operation" => op.name(),
+        "error" => error.map_or("other", QueryError::class)
+    )
+    .increment(1);
+}
@@ -7,5 +7,19 @@
 //! execution of queries, handling connections, and managing transactions. It acts as the backbone
 //! of the framework, ensuring smooth and efficient operations with the database at runtime.
 
+/// Change events
+pub mod changes;
+/// Named databases
+pub mod databases;
+/// Instrumentation of generated queries
+pub mod instrument;
+/// Query metrics
+#[cfg(feature = "metrics")]
+pub mod metrics;
+/// Read/write splitting
+pub mod pools;
+pub(crate) mod scoped;
 /// SQL code generator
 pub mod sql;
+/// Transaction helpers
+pub mod transaction;
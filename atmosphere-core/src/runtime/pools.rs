@@ -0,0 +1,139 @@
+//! Read/write splitting
+//!
+//! [`Pools`] bundles the pool of a primary database with the pools of its read replicas. A
+//! `&Pools` can be used as executor for all atmosphere operations (and plain sqlx queries): reads
+//! are spread over the replicas, everything else is executed on the primary.
+//!
+//! ```ignore
+//! let pools = Pools::new(primary, vec![replica_a, replica_b]);
+//!
+//! let user = User::read(&pools, &0).await?; // executed on a replica
+//! user.update(&pools).await?; // executed on the primary
+//! ```
+//!
+//! Replicas usually lag behind the primary. Code that has to read its own writes either reads from
+//! [`Pools::writer`] directly or runs within [`Pools::consistent`].
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use futures::{future::BoxFuture, stream::BoxStream};
+use sqlx::{database::HasStatement, Database, Describe, Either, Execute, Executor};
+
+use crate::runtime::scoped::Scoped;
+
+thread_local! {
+    static CONSISTENT: RefCell<Option<()>> = const { RefCell::new(None) };
+}
+
+/// The pools of a primary database and its read replicas
+#[derive(Debug)]
+pub struct Pools {
+    writer: crate::Pool,
+    readers: Vec<crate::Pool>,
+    next: AtomicUsize,
+}
+
+impl Pools {
+    /// Creates pools reading from `readers` and writing to `writer`. Without readers, all queries
+    /// are executed on `writer`.
+    pub fn new(writer: crate::Pool, readers: Vec<crate::Pool>) -> Self {
+        Self {
+            writer,
+            readers,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// The pool of the primary database
+    pub const fn writer(&self) -> &crate::Pool {
+        &self.writer
+    }
+
+    /// The next read replica to use, in a round robin fashion
+    pub fn reader(&self) -> &crate::Pool {
+        if self.readers.is_empty() {
+            return &self.writer;
+        }
+
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+
+        &self.readers[next % self.readers.len()]
+    }
+
+    /// Executes all queries of `f` on the primary database, so they read their own writes
+    pub fn consistent<F: Future>(f: F) -> impl Future<Output = F::Output> {
+        Scoped::new(&CONSISTENT, (), f)
+    }
+
+    /// Picks the pool to execute `sql` on
+    fn route(&self, sql: &str) -> &crate::Pool {
+        let consistent = CONSISTENT.with(|c| c.borrow().is_some());
+
+        if consistent || !is_read(sql) {
+            return &self.writer;
+        }
+
+        self.reader()
+    }
+}
+
+/// Whether `sql` only reads and can be executed on a replica
+fn is_read(sql: &str) -> bool {
+    let sql = sql.trim_start();
+
+    sql.get(..6)
+        .is_some_and(|keyword| keyword.eq_ignore_ascii_case("SELECT"))
+        && !sql.contains("FOR UPDATE")
+}
+
+type QueryResult = <crate::Driver as Database>::QueryResult;
+type Row = <crate::Driver as Database>::Row;
+type TypeInfo = <crate::Driver as Database>::TypeInfo;
+
+impl<'p> Executor<'p> for &'p Pools {
+    type Database = crate::Driver;
+
+    fn fetch_many<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxStream<'e, sqlx::Result<Either<QueryResult, Row>>>
+    where
+        'p: 'e,
+        E: 'q + Execute<'q, Self::Database>,
+    {
+        self.route(query.sql()).fetch_many(query)
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(self, query: E) -> BoxFuture<'e, sqlx::Result<Option<Row>>>
+    where
+        'p: 'e,
+        E: 'q + Execute<'q, Self::Database>,
+    {
+        self.route(query.sql()).fetch_optional(query)
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [TypeInfo],
+    ) -> BoxFuture<'e, sqlx::Result<<Self::Database as HasStatement<'q>>::Statement>>
+    where
+        'p: 'e,
+    {
+        self.route(sql).prepare_with(sql, parameters)
+    }
+
+    fn describe<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+    ) -> BoxFuture<'e, sqlx::Result<Describe<Self::Database>>>
+    where
+        'p: 'e,
+    {
+        self.writer.describe(sql)
+    }
+}
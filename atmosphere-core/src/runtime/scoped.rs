@@ -0,0 +1,45 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    thread::LocalKey,
+};
+
+/// Sets a thread local while polling the inner future.
+///
+/// The value is swapped into the thread local for the duration of each poll, this keeps it scoped
+/// to the future independently of the async runtime in use. Scopes can be nested, the innermost
+/// value wins.
+pub(crate) struct Scoped<F, V: 'static> {
+    inner: Pin<Box<F>>,
+    key: &'static LocalKey<RefCell<Option<V>>>,
+    value: Option<V>,
+}
+
+impl<F, V> Scoped<F, V> {
+    pub(crate) fn new(key: &'static LocalKey<RefCell<Option<V>>>, value: V, inner: F) -> Self {
+        Self {
+            inner: Box::pin(inner),
+            key,
+            value: Some(value),
+        }
+    }
+}
+
+impl<F: Future, V> Future for Scoped<F, V> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        let outer = this.key.with(|v| v.replace(this.value.take()));
+        let poll = this.inner.as_mut().poll(cx);
+        this.value = this.key.with(|v| v.replace(outer));
+
+        poll
+    }
+}
+
+// the inner future is boxed, so the value is never pinned
+impl<F, V> Unpin for Scoped<F, V> {}
@@ -11,6 +11,12 @@
 //!   queries for their respective operations. These builders ensure that queries are correctly formatted and
 //!   aligned with the structure and constraints of the target table.
 //!
+//! - Join Builders: Functions like `select_joined` and `select_joined_by`, which join a table with
+//!   the table referenced by one of its foreign keys, selecting fully qualified and aliased columns.
+//!
+//! - Dialects: The parts of the generated SQL which differ between databases are rendered by the
+//!   [`dialect::Dialect`] of the enabled driver.
+//!
 //! - Binding Management: The `Bindings` struct and its implementations, which manage the relationship between
 //!   table columns and the SQL queries they are bound to. This ensures that queries are executed with the correct
 //!   parameters and their values.
@@ -20,10 +26,16 @@ use std::fmt;
 use sqlx::QueryBuilder;
 
 use crate::{
+    column::{ColumnType, TimestampKind},
     query::{self, Query},
-    Bind, Column,
+    window::Window,
+    Bind, Column, DataColumn, ForeignKey, Index, TimestampColumn, UniqueConstraint,
 };
 
+use self::dialect::{Current, Dialect};
+
+pub mod dialect;
+
 /// Struct representing bindings for SQL queries.
 ///
 /// `Bindings` is responsible for holding a collection of columns that are bound to a specific SQL query.
@@ -57,6 +69,12 @@ impl<T: Bind> PartialEq for Bindings<T> {
 
 impl<T: Bind> Eq for Bindings<T> {}
 
+impl<T: Bind> Clone for Bindings<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
 impl<T: Bind> fmt::Debug for Bindings<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut f = f.debug_tuple("Bindings");
@@ -79,12 +97,8 @@ impl<T: Bind> Bindings<T> {
     }
 }
 
-fn table<T: Bind>() -> String {
-    #[cfg(not(feature = "sqlite"))]
-    return format!("\"{}\".\"{}\"", T::SCHEMA, T::TABLE);
-
-    #[cfg(feature = "sqlite")]
-    return format!("\"{}\"", T::TABLE);
+pub(crate) fn table<T: Bind>() -> String {
+    Current::table(&crate::SchemaContext::schema::<T>(), &T::name())
 }
 
 /// Generates a `SELECT` query to retrieve a single row from the table based on its primary key.
@@ -98,6 +112,14 @@ pub fn select<T: Bind>() -> Query<T> {
 ///
 /// SQL: `SELECT * FROM .. WHERE .. = $1`
 pub fn select_by<T: Bind>(c: Column<T>) -> Query<T> {
+    select_by_all(vec![c])
+}
+
+/// Creates a `SELECT` query to retrieve rows from the table matching the values of all columns
+/// `cs`, e.g. those of a [`UniqueConstraint`].
+///
+/// SQL: `SELECT * FROM .. WHERE .. = $1 AND .. = $2`
+pub fn select_by_all<T: Bind>(cs: Vec<Column<T>>) -> Query<T> {
     let mut query = QueryBuilder::new("SELECT\n  ");
 
     let mut separated = query.separated(",\n  ");
@@ -117,14 +139,101 @@ pub fn select_by<T: Bind>(c: Column<T>) -> Query<T> {
     }
 
     query.push(format!("\nFROM\n  {}\n", table::<T>()));
-    query.push(format!("WHERE {} = $1", c.sql()));
+
+    let conditions: Vec<String> = cs
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("{} = ${}", c.sql(), i + 1))
+        .collect();
+
+    query.push(format!("WHERE {}", conditions.join(" AND ")));
+
+    if let Some(deleted) = deleted::<T>() {
+        query.push(format!(" AND {} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        query.push(format!(" AND {} = ${}", tenant.sql, cs.len() + 1));
+    }
 
     Query::new(
         query::Operation::Select,
         query::Cardinality::One,
         query,
-        Bindings(vec![c]),
+        Bindings(cs),
+    )
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `SELECT` query retrieving a row by its primary key and locking it for the rest of the
+/// current transaction.
+///
+/// SQLite has no row level locks, so the locking clause is omitted there.
+///
+/// SQL: `SELECT * FROM .. WHERE .. = $1 FOR UPDATE`
+pub fn select_for_update<T: Bind>(lock: query::Lock) -> Query<T> {
+    let mut query = select::<T>();
+
+    if let Some(lock) = Current::lock(lock) {
+        query.builder.push(format!("\n{lock}"));
+    }
+
+    query
+}
+
+/// Creates a `SELECT` query to retrieve rows from the table where a specific column matches any of
+/// `n` values.
+///
+/// SQL: `SELECT * FROM .. WHERE .. IN ($1, $2, ..)`
+pub fn select_in<T: Bind>(c: Column<T>, n: usize) -> Query<T> {
+    let mut query = QueryBuilder::new("SELECT\n  ");
+
+    let mut separated = query.separated(",\n  ");
+
+    for column in columns::<T>() {
+        separated.push(column);
+    }
+
+    query.push(format!("\nFROM\n  {}\n", table::<T>()));
+    query.push(format!("WHERE {} IN (", c.sql()));
+
+    let mut separated = query.separated(", ");
+
+    for i in 1..=n {
+        separated.push(format!("${i}"));
+    }
+
+    query.push(")");
+
+    if let Some(deleted) = deleted::<T>() {
+        query.push(format!(" AND {} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        query.push(format!(" AND {} = ${}", tenant.sql, n + 1));
+    }
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
+        Bindings(vec![c; n]),
     )
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `SELECT` query like [`select_in`], locking the rows for the rest of the current
+/// transaction (see [`select_for_update`]).
+///
+/// SQL: `SELECT * FROM .. WHERE .. IN ($1, $2, ..) FOR UPDATE`
+pub fn select_in_for_update<T: Bind>(c: Column<T>, n: usize, lock: query::Lock) -> Query<T> {
+    let mut query = select_in::<T>(c, n);
+
+    if let Some(lock) = Current::lock(lock) {
+        query.builder.push(format!("\n{lock}"));
+    }
+
+    query
 }
 
 /// Constructs a `SELECT` query to fetch all rows from the table.
@@ -151,66 +260,416 @@ pub fn select_all<T: Bind>() -> Query<T> {
 
     query.push(format!("\nFROM\n  {}\n", table::<T>()));
 
+    let mut conditions = vec![];
+
+    if let Some(deleted) = deleted::<T>() {
+        conditions.push(format!("{} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        conditions.push(format!("{} = $1", tenant.sql));
+    }
+
+    if !conditions.is_empty() {
+        query.push(format!("WHERE {}", conditions.join(" AND ")));
+    }
+
+    let at = query.sql().len();
+
     Query::new(
         query::Operation::Select,
         query::Cardinality::Many,
         query,
         Bindings::empty(),
     )
+    .filtered(at, !conditions.is_empty())
+    .scoped(tenants::<T>())
+}
+
+/// Constructs a `SELECT` query counting the rows per value of a column, skipping groups of fewer
+/// than `at_least` rows.
+///
+/// SQL: `SELECT .., COUNT(*) FROM .. GROUP BY .. HAVING COUNT(*) >= .. ORDER BY ..`
+pub fn count_by<T: Bind>(c: Column<T>, at_least: Option<i64>) -> Query<T> {
+    let mut query = QueryBuilder::new(format!(
+        "SELECT\n  {},\n  COUNT(*)\nFROM\n  {}",
+        c.sql(),
+        table::<T>()
+    ));
+
+    let mut conditions = vec![];
+
+    if let Some(deleted) = deleted::<T>() {
+        conditions.push(format!("{} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        conditions.push(format!("{} = $1", tenant.sql));
+    }
+
+    if !conditions.is_empty() {
+        query.push(format!("\nWHERE {}", conditions.join(" AND ")));
+    }
+
+    let at = query.sql().len();
+
+    query.push(format!("\nGROUP BY {}", c.sql()));
+
+    if let Some(at_least) = at_least {
+        query.push(format!("\nHAVING COUNT(*) >= {at_least}"));
+    }
+
+    query.push(format!("\nORDER BY {}", c.sql()));
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
+        Bindings::empty(),
+    )
+    .filtered(at, !conditions.is_empty())
+    .scoped(tenants::<T>())
+}
+
+/// Returns the aliases under which the columns of `A` and `B` are selected when joining `A` with
+/// `B` over the foreign key `fk`.
+///
+/// The columns of `A` are aliased as `"<A::TABLE>.<column>"`, the columns of `B` are aliased as
+/// `"<fk.field>.<column>"` (see [`FromAliasedRow`](crate::FromAliasedRow)).
+pub const fn join_aliases<A: Bind>(fk: &ForeignKey<A>) -> (&'static str, &'static str) {
+    (A::TABLE, fk.field)
+}
+
+/// Constructs a `SELECT` query fetching all rows of `A` joined with the row of `B` they refer to
+/// through the foreign key `fk`. All columns are fully qualified and aliased according to
+/// [`join_aliases`].
+///
+/// SQL: `SELECT .. FROM .. JOIN .. ON ..`
+pub fn select_joined<A: Bind, B: Bind>(fk: &ForeignKey<A>) -> Query<A> {
+    let (query, filtered) = joined::<A, B>(fk, None);
+    let at = query.sql().len();
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
+        Bindings::empty(),
+    )
+    .filtered(at, filtered)
+    .scoped(tenants::<A>() + tenants::<B>())
+}
+
+/// Constructs a `SELECT` query fetching the rows of `A` joined with the row of `B` they refer to,
+/// filtered by a specific column of `A`.
+///
+/// SQL: `SELECT .. FROM .. JOIN .. ON .. WHERE .. = $1`
+pub fn select_joined_by<A: Bind, B: Bind>(fk: &ForeignKey<A>, c: Column<A>) -> Query<A> {
+    let (query, filtered) = joined::<A, B>(fk, Some(&c));
+    let at = query.sql().len();
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
+        Bindings(vec![c]),
+    )
+    .filtered(at, filtered)
+    .scoped(tenants::<A>() + tenants::<B>())
+}
+
+/// Builds the join of `A` and `B`, returning whether it has a `WHERE` clause
+fn joined<A: Bind, B: Bind>(
+    fk: &ForeignKey<A>,
+    filter: Option<&Column<A>>,
+) -> (QueryBuilder<'static, crate::Driver>, bool) {
+    let (a, b) = join_aliases(fk);
+
+    let mut query = QueryBuilder::new("SELECT\n  ");
+
+    let mut separated = query.separated(",\n  ");
+
+    for (alias, column) in columns::<A>()
+        .map(|c| (a, c))
+        .chain(columns::<B>().map(|c| (b, c)))
+    {
+        separated.push(format!("\"{alias}\".{column} AS \"{alias}.{column}\""));
+    }
+
+    query.push(format!("\nFROM\n  {} AS \"{a}\"\n", table::<A>()));
+    query.push(format!(
+        "  JOIN {} AS \"{b}\" ON \"{a}\".{} = \"{b}\".{}",
+        table::<B>(),
+        fk.sql,
+        B::PRIMARY_KEY.sql
+    ));
+
+    let mut conditions = vec![];
+
+    if let Some(c) = filter {
+        conditions.push(format!("\"{a}\".{} = $1", c.sql()));
+    }
+
+    if let Some(deleted) = deleted::<A>() {
+        conditions.push(format!("\"{a}\".{} IS NULL", deleted.sql));
+    }
+
+    if let Some(deleted) = deleted::<B>() {
+        conditions.push(format!("\"{b}\".{} IS NULL", deleted.sql));
+    }
+
+    // the tenant is bound after the filter, once per tenant table
+    let mut n = usize::from(filter.is_some());
+
+    if let Some(tenant) = tenant::<A>() {
+        n += 1;
+        conditions.push(format!("\"{a}\".{} = ${n}", tenant.sql));
+    }
+
+    if let Some(tenant) = tenant::<B>() {
+        n += 1;
+        conditions.push(format!("\"{b}\".{} = ${n}", tenant.sql));
+    }
+
+    if !conditions.is_empty() {
+        query.push(format!("\nWHERE {}", conditions.join(" AND ")));
+    }
+
+    (query, !conditions.is_empty())
+}
+
+/// Returns the soft delete column (`#[sql(timestamp = deleted)]`) of a table, if any.
+///
+/// Rows of tables with a soft delete column are never removed by generated queries: deleting
+/// sets the timestamp instead and all generated `SELECT` queries exclude rows where it is set.
+pub fn deleted<T: Bind>() -> Option<&'static TimestampColumn<T>> {
+    T::TIMESTAMP_COLUMNS
+        .iter()
+        .find(|ts| ts.kind == TimestampKind::Deleted)
+}
+
+/// Returns the tenant column (`#[sql(tenant)]`) of a table, if any.
+///
+/// Generated queries reading, updating or deleting rows of tables with a tenant column only match
+/// the rows of the tenant in scope (see [`crate::tenant`]).
+pub fn tenant<T: Bind>() -> Option<&'static DataColumn<T>> {
+    T::DATA_COLUMNS.iter().find(|data| data.tenant)
+}
+
+/// The number of tenant conditions of a table
+pub(crate) fn tenants<T: Bind>() -> usize {
+    usize::from(tenant::<T>().is_some())
+}
+
+/// Returns the tenant column of a table if a tenant is in scope. Inserted rows are written with
+/// the tenant in scope instead of the tenant they hold.
+pub(crate) fn scoped_tenant<T: Bind>() -> Option<&'static DataColumn<T>> {
+    tenant::<T>().filter(|_| crate::tenant::Tenant::current().is_some())
+}
+
+/// Iterates over the sql names of all columns of a table.
+pub(crate) fn columns<T: Bind>() -> impl Iterator<Item = &'static str> {
+    std::iter::once(T::PRIMARY_KEY.sql)
+        .chain(T::FOREIGN_KEYS.iter().map(|fk| fk.sql))
+        .chain(T::DATA_COLUMNS.iter().map(|data| data.sql))
+        .chain(T::TIMESTAMP_COLUMNS.iter().map(|meta| meta.sql))
 }
 
 /// Generates an `INSERT` query to add a new row to the table.
 ///
+/// If the primary key is generated by the database (`#[sql(pk, auto)]`) it is omitted from the
+/// inserted columns, as are columns set by their database default (`#[sql(default)]`) and columns
+/// maintained by the database (`#[sql(readonly)]`). The stored row is then read back using `RETURNING` (not supported on MySQL, where `LAST_INSERT_ID()` is used
+/// for the primary key instead).
+///
 /// SQL: `INSERT INTO .. VALUES ..`
 pub fn insert<T: Bind>() -> Query<T> {
+    let (mut builder, bindings) = insert_into::<T>(!T::PRIMARY_KEY.auto, false);
+    let at = builder.sql().len();
+
+    if generated::<T>() && Current::RETURNING {
+        returning::<T>(&mut builder);
+    }
+
+    Query::new(
+        query::Operation::Insert,
+        query::Cardinality::One,
+        builder,
+        Bindings(bindings),
+    )
+    .filtered(at, false)
+    .scoped(usize::from(scoped_tenant::<T>().is_some()))
+}
+
+/// Whether the database generates values of inserted rows, which are read back after inserting.
+/// This includes the tenant in scope, which replaces the tenant of the row.
+pub(crate) fn generated<T: Bind>() -> bool {
+    T::PRIMARY_KEY.auto
+        || T::DATA_COLUMNS
+            .iter()
+            .any(|data| data.default || data.readonly)
+        || scoped_tenant::<T>().is_some()
+}
+
+/// Appends a `RETURNING` clause selecting all columns of the table.
+pub(crate) fn returning<T: Bind>(builder: &mut QueryBuilder<'static, crate::Driver>) {
+    builder.push("\nRETURNING\n  ");
+
+    let mut separated = builder.separated(",\n  ");
+
+    separated.push(T::PRIMARY_KEY.sql);
+
+    for fk in T::FOREIGN_KEYS {
+        separated.push(fk.sql);
+    }
+
+    for data in T::DATA_COLUMNS {
+        separated.push(data.sql);
+    }
+
+    for meta in T::TIMESTAMP_COLUMNS {
+        separated.push(meta.sql);
+    }
+}
+
+fn insert_into<T: Bind>(
+    with_pk: bool,
+    with_defaults: bool,
+) -> (QueryBuilder<'static, crate::Driver>, Vec<Column<T>>) {
     let mut builder = QueryBuilder::new(format!("INSERT INTO {}\n  (", table::<T>()));
 
     let mut bindings = vec![];
+    // the placeholder of each column, `None` for the tenant in scope
+    let mut placeholders = vec![];
 
     let mut separated = builder.separated(", ");
 
-    separated.push(T::PRIMARY_KEY.sql.to_string());
-    bindings.push(Column::PrimaryKey(&T::PRIMARY_KEY));
+    if with_pk {
+        separated.push(T::PRIMARY_KEY.sql.to_string());
+        bindings.push(Column::PrimaryKey(&T::PRIMARY_KEY));
+        placeholders.push(Some(bindings.len()));
+    }
 
     for fk in T::FOREIGN_KEYS {
         separated.push(fk.sql.to_string());
         bindings.push(Column::ForeignKey(fk));
+        placeholders.push(Some(bindings.len()));
     }
 
-    for data in T::DATA_COLUMNS {
+    let inserted = T::DATA_COLUMNS
+        .iter()
+        .filter(|data| !data.readonly && (with_defaults || !data.default));
+
+    let scoped = scoped_tenant::<T>();
+
+    for data in inserted {
         separated.push(data.sql.to_string());
+
+        // rows are never inserted into other tenants than the one in scope
+        if scoped.is_some() && data.tenant {
+            placeholders.push(None);
+            continue;
+        }
+
         bindings.push(Column::Data(data));
+        placeholders.push(Some(bindings.len()));
     }
 
     for meta in T::TIMESTAMP_COLUMNS {
         separated.push(meta.sql.to_string());
         bindings.push(Column::Timestamp(meta));
+        placeholders.push(Some(bindings.len()));
     }
 
     separated.push_unseparated(")\nVALUES\n  (");
 
-    separated.push_unseparated("$1");
+    // the tenant in scope is bound after all columns
+    let placeholders = placeholders
+        .into_iter()
+        .map(|c| format!("${}", c.unwrap_or(bindings.len() + 1)))
+        .collect::<Vec<_>>();
+
+    separated.push_unseparated(placeholders.join(", "));
+
+    builder.push(")");
+
+    (builder, bindings)
+}
+
+/// Creates an `UPDATE` query to modify an existing row in the table.
+///
+/// Columns maintained by the database (`#[sql(readonly)]`) and columns only written on insertion
+/// (`#[sql(immutable)]`) are not updated.
+///
+/// SQL: `UPDATE .. SET .. WHERE ..`
+pub fn update<T: Bind>() -> Query<T> {
+    update_where::<T>(|_| true)
+}
+
+/// Creates an `UPDATE` query modifying only the foreign key and data columns of the given `fields`
+/// (by their rust field names) of an existing row, see [`Tracked`](crate::Tracked).
+///
+/// Timestamp columns are always updated, as they are maintained by atmosphere.
+///
+/// SQL: `UPDATE .. SET .. WHERE ..`
+pub fn update_of<T: Bind>(fields: &[&str]) -> Query<T> {
+    update_where::<T>(|field| fields.contains(&field))
+}
+
+/// Creates an `UPDATE` query setting the foreign key and data columns of the given `fields` (by
+/// their rust field names) of the row with the primary key bound to `$1`, see [`Patch`](crate::Patch).
+///
+/// There is no row to take the timestamps from, update timestamps are set by the database.
+///
+/// SQL: `UPDATE .. SET .. WHERE .. = $1`
+pub fn patch<T: Bind>(fields: &[&str]) -> Query<T> {
+    let mut builder = QueryBuilder::new(format!("UPDATE {} SET\n  ", table::<T>()));
+    let mut bindings = vec![Column::PrimaryKey(&T::PRIMARY_KEY)];
+
+    let mut separated = builder.separated(",\n  ");
 
-    let columns = 1 + T::FOREIGN_KEYS.len() + T::DATA_COLUMNS.len() + T::TIMESTAMP_COLUMNS.len();
+    let fks = T::FOREIGN_KEYS
+        .iter()
+        .filter(|fk| !fk.immutable && fields.contains(&fk.field));
 
-    for c in 2..=columns {
-        separated.push(format!("${c}"));
+    for fk in fks {
+        bindings.push(Column::ForeignKey(fk));
+        separated.push(format!("{} = ${}", fk.sql, bindings.len()));
     }
 
-    builder.push(")");
+    // rows never move to another tenant
+    let data = T::DATA_COLUMNS.iter().filter(|data| {
+        !data.readonly && !data.immutable && !data.tenant && fields.contains(&data.field)
+    });
+
+    for data in data {
+        bindings.push(Column::Data(data));
+        separated.push(format!("{} = ${}", data.sql, bindings.len()));
+    }
+
+    let updated = T::TIMESTAMP_COLUMNS
+        .iter()
+        .filter(|meta| meta.kind == TimestampKind::Updated);
+
+    for meta in updated {
+        separated.push(format!("{} = {}", meta.sql, Current::now()));
+    }
+
+    builder.push(format!("\nWHERE\n  {} = $1", T::PRIMARY_KEY.sql));
+
+    if let Some(tenant) = tenant::<T>() {
+        builder.push(format!(" AND {} = ${}", tenant.sql, bindings.len() + 1));
+    }
 
     Query::new(
-        query::Operation::Insert,
+        query::Operation::Update,
         query::Cardinality::One,
         builder,
         Bindings(bindings),
     )
+    .scoped(tenants::<T>())
 }
 
-/// Creates an `UPDATE` query to modify an existing row in the table.
-///
-/// SQL: `UPDATE .. SET .. WHERE ..`
-pub fn update<T: Bind>() -> Query<T> {
+fn update_where<T: Bind>(updated: impl Fn(&str) -> bool) -> Query<T> {
     let mut builder = QueryBuilder::new(format!("UPDATE {} SET\n  ", table::<T>()));
     let mut bindings = vec![];
 
@@ -221,13 +680,22 @@ pub fn update<T: Bind>() -> Query<T> {
 
     let mut col = 2;
 
-    for fk in T::FOREIGN_KEYS {
+    let fks = T::FOREIGN_KEYS
+        .iter()
+        .filter(|fk| !fk.immutable && updated(fk.field));
+
+    for fk in fks {
         separated.push(format!("{} = ${col}", fk.sql));
         bindings.push(Column::ForeignKey(fk));
         col += 1;
     }
 
-    for data in T::DATA_COLUMNS {
+    // rows never move to another tenant
+    let data = T::DATA_COLUMNS
+        .iter()
+        .filter(|data| !data.readonly && !data.immutable && !data.tenant && updated(data.field));
+
+    for data in data {
         separated.push(format!("{} = ${col}", data.sql));
         bindings.push(Column::Data(data));
         col += 1;
@@ -241,81 +709,804 @@ pub fn update<T: Bind>() -> Query<T> {
 
     builder.push(format!("\nWHERE\n  {} = $1", T::PRIMARY_KEY.sql));
 
+    if let Some(tenant) = tenant::<T>() {
+        builder.push(format!(" AND {} = ${}", tenant.sql, bindings.len() + 1));
+    }
+
     Query::new(
         query::Operation::Update,
         query::Cardinality::One,
         builder,
         Bindings(bindings),
     )
+    .scoped(tenants::<T>())
 }
 
 /// Constructs an `UPSERT` query (update or insert) for a row in the table.
 ///
 /// SQL: `UPDATE .. SET .. WHERE .. ON CONFLICT .. DO UPDATE SET`
 pub fn upsert<T: Bind>() -> Query<T> {
-    let Query {
-        mut builder,
-        bindings,
-        ..
-    } = insert::<T>();
-
-    builder.push("\nON CONFLICT(");
-    builder.push(T::PRIMARY_KEY.sql);
-    builder.push(")\nDO UPDATE SET\n  ");
-
-    let mut separated = builder.separated(",\n  ");
+    upsert_of::<T>(&[T::PRIMARY_KEY.sql])
+}
 
-    for fk in T::FOREIGN_KEYS {
-        separated.push(format!("{} = EXCLUDED.{}", fk.sql, fk.sql));
-    }
+/// Constructs an `UPSERT` query (update or insert) for a row in the table, updating the row with
+/// the same values of the columns of `constraint` instead of the same primary key.
+///
+/// SQL: `UPDATE .. SET .. WHERE .. ON CONFLICT (..) DO UPDATE SET`
+pub fn upsert_on<T: Bind>(constraint: &UniqueConstraint) -> Query<T> {
+    upsert_of::<T>(constraint.columns)
+}
 
-    for data in T::DATA_COLUMNS {
-        separated.push(format!("{} = EXCLUDED.{}", data.sql, data.sql));
+fn upsert_of<T: Bind>(target: &[&str]) -> Query<T> {
+    let (mut builder, bindings) = insert_into::<T>(true, true);
+
+    // the creation timestamp, immutable columns and the tenant of an existing row are kept
+    let updated: Vec<&str> = T::FOREIGN_KEYS
+        .iter()
+        .filter(|fk| !fk.immutable)
+        .map(|fk| fk.sql)
+        .chain(
+            T::DATA_COLUMNS
+                .iter()
+                .filter(|data| !data.readonly && !data.immutable && !data.tenant)
+                .map(|data| data.sql),
+        )
+        .chain(
+            T::TIMESTAMP_COLUMNS
+                .iter()
+                .filter(|meta| meta.kind != TimestampKind::Created)
+                .map(|meta| meta.sql),
+        )
+        .collect();
+
+    builder.push(Current::upsert(target, &updated));
+
+    // rows of other tenants are never overwritten
+    if let Some(tenant) = tenant::<T>() {
+        builder.push(format!(
+            "\nWHERE {}.{} = ${}",
+            table::<T>(),
+            tenant.sql,
+            bindings.len() + 1
+        ));
     }
 
-    for meta in T::TIMESTAMP_COLUMNS {
-        separated.push(format!("{} = EXCLUDED.{}", meta.sql, meta.sql));
-    }
+    let at = builder.sql().len();
 
     Query::new(
         query::Operation::Upsert,
         query::Cardinality::One,
         builder,
-        bindings,
+        Bindings(bindings),
     )
+    .filtered(at, tenant::<T>().is_some())
+    .scoped(tenants::<T>())
 }
 
-/// Generates a `DELETE` query to remove a row from the table based on its primary key.
+/// Creates a query claiming the oldest row (by primary key) whose `state` column equals `$2` by
+/// setting it to `$1` (both bindings of `state`). Rows locked by concurrent claims are skipped,
+/// the claimed row is returned.
 ///
-/// SQL: `DELETE FROM .. WHERE ..`
-pub fn delete<T: Bind>() -> Query<T> {
-    delete_by(T::PRIMARY_KEY.as_col())
-}
+/// SQL: `UPDATE .. SET .. = $1 WHERE .. = (SELECT .. WHERE .. = $2 .. FOR UPDATE SKIP LOCKED)`
+pub fn claim_next<T: Bind>(state: &'static DataColumn<T>) -> Query<T> {
+    let table = table::<T>();
+    let pk = T::PRIMARY_KEY.sql;
 
-/// Creates a `DELETE` query to remove rows from the table based on a specific column.
-///
-/// SQL: `DELETE FROM .. WHERE ..`
-pub fn delete_by<T: Bind>(c: Column<T>) -> Query<T> {
-    let mut builder = QueryBuilder::new(format!("DELETE FROM {} WHERE ", table::<T>()));
+    let mut builder = QueryBuilder::new(format!("UPDATE {table} SET {} = $1\n", state.sql));
 
-    builder.push(c.sql());
-    builder.push(" = $1");
+    builder.push(format!("WHERE {pk} = (\n  SELECT {pk} FROM {table}\n"));
+    builder.push(format!("  WHERE {} = $2", state.sql));
 
-    Query::new(
-        query::Operation::Delete,
+    if let Some(deleted) = deleted::<T>() {
+        builder.push(format!(" AND {} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        builder.push(format!(" AND {} = $3", tenant.sql));
+    }
+
+    // hook predicates narrow down the rows that can be claimed
+    let at = builder.sql().len();
+
+    builder.push(format!("\n  ORDER BY {pk}\n  LIMIT 1"));
+
+    // without row locks (sqlite), concurrent claims are serialized by the database instead
+    if let Some(lock) = Current::lock(query::Lock::SkipLocked) {
+        builder.push(format!("\n  {lock}"));
+    }
+
+    builder.push("\n)");
+
+    returning::<T>(&mut builder);
+
+    Query::new(
+        query::Operation::Update,
         query::Cardinality::One,
         builder,
+        Bindings(vec![Column::Data(state), Column::Data(state)]),
+    )
+    .filtered(at, true)
+    .scoped(tenants::<T>())
+}
+
+/// Creates a query merging the `JSONB` object bound to `$2` into the `JSONB` column `c` of the row
+/// with the primary key bound to `$1`, overwriting the keys present in both.
+///
+/// SQL: `UPDATE .. SET .. = .. || $2 WHERE .. = $1`
+#[cfg(feature = "postgres")]
+pub fn json_merge<T: Bind>(c: &'static DataColumn<T>) -> Query<T> {
+    json_update(c, format!("{} || $2", c.sql), 1)
+}
+
+/// Creates a query setting the value at the path bound to `$2` (a `TEXT[]`) within the `JSONB`
+/// column `c` of the row with the primary key bound to `$1` to the `JSONB` value bound to `$3`.
+///
+/// SQL: `UPDATE .. SET .. = jsonb_set(.., $2, $3) WHERE .. = $1`
+#[cfg(feature = "postgres")]
+pub fn json_set<T: Bind>(c: &'static DataColumn<T>) -> Query<T> {
+    json_update(c, format!("jsonb_set({}, $2, $3)", c.sql), 2)
+}
+
+/// Updates the `JSONB` column `c` to `value`, which uses `values` bindings of `c` after the
+/// primary key. Update timestamps are set by the database.
+#[cfg(feature = "postgres")]
+fn json_update<T: Bind>(c: &'static DataColumn<T>, value: String, values: usize) -> Query<T> {
+    let mut builder = QueryBuilder::new(format!("UPDATE {} SET\n  ", table::<T>()));
+
+    let mut separated = builder.separated(",\n  ");
+
+    separated.push(format!("{} = {value}", c.sql));
+
+    let updated = T::TIMESTAMP_COLUMNS
+        .iter()
+        .filter(|meta| meta.kind == TimestampKind::Updated);
+
+    for meta in updated {
+        separated.push(format!("{} = {}", meta.sql, Current::now()));
+    }
+
+    builder.push(format!("\nWHERE\n  {} = $1", T::PRIMARY_KEY.sql));
+
+    if let Some(tenant) = tenant::<T>() {
+        builder.push(format!(" AND {} = ${}", tenant.sql, values + 2));
+    }
+
+    let mut bindings = vec![Column::PrimaryKey(&T::PRIMARY_KEY)];
+    bindings.extend(vec![Column::Data(c); values]);
+
+    Query::new(
+        query::Operation::Update,
+        query::Cardinality::One,
+        builder,
+        Bindings(bindings),
+    )
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `SELECT` query retrieving the rows whose `JSONB` column `c` contains the `JSONB`
+/// value bound to `$1`.
+///
+/// SQL: `SELECT * FROM .. WHERE .. @> $1`
+#[cfg(feature = "postgres")]
+pub fn select_json_contains<T: Bind>(c: &'static DataColumn<T>) -> Query<T> {
+    let mut query = QueryBuilder::new("SELECT\n  ");
+
+    let mut separated = query.separated(",\n  ");
+
+    for column in columns::<T>() {
+        separated.push(column);
+    }
+
+    query.push(format!("\nFROM\n  {}\n", table::<T>()));
+    query.push(format!("WHERE {} @> $1", c.sql));
+
+    if let Some(deleted) = deleted::<T>() {
+        query.push(format!(" AND {} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        query.push(format!(" AND {} = $2", tenant.sql));
+    }
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
+        Bindings(vec![Column::Data(c)]),
+    )
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `SELECT` query retrieving the rows whose text column `c` matches the `LIKE` pattern
+/// bound to `$1` regardless of case, `!` escaping the wildcards `%` and `_` (see
+/// [`crate::like::escape`]).
+///
+/// SQL: `SELECT * FROM .. WHERE .. ILIKE $1 ESCAPE '!'`
+pub fn select_like<T: Bind>(c: &'static DataColumn<T>) -> Query<T> {
+    let mut query = QueryBuilder::new("SELECT\n  ");
+
+    let mut separated = query.separated(",\n  ");
+
+    for column in columns::<T>() {
+        separated.push(column);
+    }
+
+    query.push(format!("\nFROM\n  {}\n", table::<T>()));
+    query.push(format!("WHERE {} {} $1 ESCAPE '!'", c.sql, Current::ILIKE));
+
+    if let Some(deleted) = deleted::<T>() {
+        query.push(format!(" AND {} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        query.push(format!(" AND {} = $2", tenant.sql));
+    }
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
+        Bindings(vec![Column::Data(c)]),
+    )
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `SELECT` query retrieving the rows whose timestamp column `c` lies within `window`,
+/// ordered by the timestamp. Soft deleted rows are excluded unless `c` is the soft delete column.
+///
+/// SQL: `SELECT * FROM .. WHERE .. >= $1 AND .. < $2 ORDER BY ..`
+pub fn select_window<T: Bind, V>(c: &'static TimestampColumn<T>, window: &Window<V>) -> Query<T> {
+    let mut query = QueryBuilder::new("SELECT\n  ");
+
+    let mut separated = query.separated(",\n  ");
+
+    for column in columns::<T>() {
+        separated.push(column);
+    }
+
+    query.push(format!("\nFROM\n  {}\n", table::<T>()));
+    query.push(format!("WHERE {}", window.condition(c.sql)));
+
+    if let Some(deleted) = deleted::<T>().filter(|deleted| deleted.sql != c.sql) {
+        query.push(format!(" AND {} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        query.push(format!(" AND {} = ${}", tenant.sql, window.binds() + 1));
+    }
+
+    let at = query.sql().len();
+
+    query.push(format!("\nORDER BY {}", c.sql));
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
+        Bindings(vec![Column::Timestamp(c); window.binds()]),
+    )
+    .filtered(at, true)
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `SELECT` query retrieving the previous versions of the row with the primary key
+/// bound to `$1` from the history table (see [`crate::history`]), the oldest first.
+///
+/// SQL: `SELECT *, valid_from, valid_to FROM .._history WHERE .. = $1 ORDER BY valid_to`
+#[cfg(feature = "postgres")]
+pub fn select_history<T: Bind>() -> Query<T> {
+    let mut query = QueryBuilder::new("SELECT\n  ");
+
+    let mut separated = query.separated(",\n  ");
+
+    for column in columns::<T>().chain(["valid_from", "valid_to"]) {
+        separated.push(column);
+    }
+
+    query.push(format!("\nFROM\n  {}\n", crate::history::table::<T>()));
+    query.push(format!("WHERE {} = $1", T::PRIMARY_KEY.sql));
+
+    if let Some(tenant) = tenant::<T>() {
+        query.push(format!(" AND {} = $2", tenant.sql));
+    }
+
+    let at = query.sql().len();
+
+    query.push("\nORDER BY valid_to");
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
         Bindings(vec![Column::PrimaryKey(&T::PRIMARY_KEY)]),
     )
+    .filtered(at, true)
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `SELECT` query retrieving the version of the row with the primary key bound to `$1`
+/// that was valid at the time bound to `$2`, among its previous versions in the history table (see
+/// [`crate::history`]) and its current version.
+///
+/// SQL: `SELECT * FROM (SELECT .. FROM .._history UNION ALL SELECT .. FROM ..) WHERE ..`
+#[cfg(feature = "postgres")]
+pub fn select_as_of<T: Bind>() -> Query<T> {
+    let columns = columns::<T>().collect::<Vec<_>>().join(", ");
+
+    let mut current = format!(
+        "SELECT {columns}, {} AS valid_from, NULL::TIMESTAMPTZ AS valid_to FROM {} AS __current WHERE {} = $1",
+        crate::history::valid_from::<T>("__current"),
+        table::<T>(),
+        T::PRIMARY_KEY.sql,
+    );
+
+    let mut conditions = vec![
+        "(valid_from IS NULL OR valid_from <= $2)".to_owned(),
+        "(valid_to IS NULL OR valid_to > $2)".to_owned(),
+    ];
+
+    if let Some(deleted) = deleted::<T>() {
+        conditions.push(format!("{} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        conditions.push(format!("{} = $3", tenant.sql));
+        current.push_str(&format!(" AND {} = $3", tenant.sql));
+    }
+
+    let mut query = QueryBuilder::new(format!(
+        "SELECT\n  {columns}\nFROM (\n  SELECT {columns}, valid_from, valid_to FROM {} WHERE {} = $1\n  UNION ALL\n  {current}\n) AS __versions\nWHERE {}",
+        crate::history::table::<T>(),
+        T::PRIMARY_KEY.sql,
+        conditions.join(" AND "),
+    ));
+
+    let at = query.sql().len();
+
+    query.push("\nORDER BY valid_to NULLS LAST\nLIMIT 1");
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::One,
+        query,
+        Bindings(vec![Column::PrimaryKey(&T::PRIMARY_KEY)]),
+    )
+    .filtered(at, true)
+    .scoped(tenants::<T>())
+}
+
+/// Returns the `tsvector` document of the full-text searched columns (`#[sql(fulltext)]`) of a
+/// table, if any, along with their text search configuration.
+///
+/// The document is indexed by the `GIN` index `#[derive(Schema)]` declares, which only speeds up
+/// searches using the very same expression.
+#[cfg(feature = "postgres")]
+pub fn document<T: Bind>() -> Option<(String, &'static str)> {
+    let columns: Vec<(&str, &str)> = T::DATA_COLUMNS
+        .iter()
+        .filter_map(|data| Some((data.sql, data.fulltext?)))
+        .collect();
+
+    let config = columns.first()?.1;
+
+    let document = columns
+        .iter()
+        .map(|(sql, config)| format!("to_tsvector('{config}', coalesce({sql}, ''))"))
+        .collect::<Vec<_>>()
+        .join(" || ");
+
+    Some((document, config))
+}
+
+/// Creates a `SELECT` query retrieving the rows whose full-text searched columns match the search
+/// query bound to `$1`, the best matches first.
+///
+/// # Panics
+///
+/// If the table has no full-text searched columns.
+///
+/// SQL: `SELECT * FROM .. WHERE .. @@ plainto_tsquery(.., $1) ORDER BY ts_rank(..) DESC`
+#[cfg(feature = "postgres")]
+pub fn search<T: Bind>(syntax: crate::fulltext::Syntax) -> Query<T> {
+    let Some((document, config)) = document::<T>() else {
+        panic!("{} has no full-text searched columns", T::TABLE);
+    };
+
+    let tsquery = format!("{}('{config}', $1)", syntax.function());
+
+    let mut query = QueryBuilder::new("SELECT\n  ");
+
+    let mut separated = query.separated(",\n  ");
+
+    for column in columns::<T>() {
+        separated.push(column);
+    }
+
+    query.push(format!("\nFROM\n  {}\n", table::<T>()));
+    query.push(format!("WHERE {document} @@ {tsquery}"));
+
+    if let Some(deleted) = deleted::<T>() {
+        query.push(format!(" AND {} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        query.push(format!(" AND {} = $2", tenant.sql));
+    }
+
+    let at = query.sql().len();
+
+    query.push(format!("\nORDER BY ts_rank({document}, {tsquery}) DESC"));
+
+    Query::new(
+        query::Operation::Select,
+        query::Cardinality::Many,
+        query,
+        Bindings::empty(),
+    )
+    .filtered(at, true)
+    .scoped(tenants::<T>())
+}
+
+/// Generates a `DELETE` query to remove a row from the table based on its primary key.
+///
+/// If the table has a soft delete column, the row is marked as deleted instead (see [`deleted`]).
+///
+/// SQL: `DELETE FROM .. WHERE ..` or `UPDATE .. SET .. = CURRENT_TIMESTAMP WHERE ..`
+pub fn delete<T: Bind>() -> Query<T> {
+    delete_by(T::PRIMARY_KEY.as_col())
+}
+
+/// Creates a `DELETE` query to remove rows from the table based on a specific column.
+///
+/// If the table has a soft delete column, the rows are marked as deleted instead (see [`deleted`]).
+///
+/// SQL: `DELETE FROM .. WHERE ..` or `UPDATE .. SET .. = CURRENT_TIMESTAMP WHERE ..`
+pub fn delete_by<T: Bind>(c: Column<T>) -> Query<T> {
+    let Some(deleted) = deleted::<T>() else {
+        return hard_delete_by(c);
+    };
+
+    let mut builder = QueryBuilder::new(format!(
+        "UPDATE {} SET {} = {} WHERE ",
+        table::<T>(),
+        deleted.sql,
+        Current::now()
+    ));
+
+    builder.push(c.sql());
+    builder.push(" = $1");
+    builder.push(format!(" AND {} IS NULL", deleted.sql));
+
+    if let Some(tenant) = tenant::<T>() {
+        builder.push(format!(" AND {} = $2", tenant.sql));
+    }
+
+    Query::new(
+        query::Operation::Delete,
+        query::Cardinality::One,
+        builder,
+        Bindings(vec![Column::PrimaryKey(&T::PRIMARY_KEY)]),
+    )
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `DELETE` query to remove rows from the table where a specific column matches any of
+/// `n` values.
+///
+/// If the table has a soft delete column, the rows are marked as deleted instead (see [`deleted`]).
+///
+/// SQL: `DELETE FROM .. WHERE .. IN ($1, $2, ..)` or `UPDATE .. SET .. = CURRENT_TIMESTAMP WHERE ..`
+pub fn delete_in<T: Bind>(c: Column<T>, n: usize) -> Query<T> {
+    let mut builder = match deleted::<T>() {
+        Some(deleted) => QueryBuilder::new(format!(
+            "UPDATE {} SET {} = {} WHERE ",
+            table::<T>(),
+            deleted.sql,
+            Current::now()
+        )),
+        None => QueryBuilder::new(format!("DELETE FROM {} WHERE ", table::<T>())),
+    };
+
+    builder.push(format!("{} IN (", c.sql()));
+
+    let mut separated = builder.separated(", ");
+
+    for i in 1..=n {
+        separated.push(format!("${i}"));
+    }
+
+    builder.push(")");
+
+    if let Some(deleted) = deleted::<T>() {
+        builder.push(format!(" AND {} IS NULL", deleted.sql));
+    }
+
+    if let Some(tenant) = tenant::<T>() {
+        builder.push(format!(" AND {} = ${}", tenant.sql, n + 1));
+    }
+
+    Query::new(
+        query::Operation::Delete,
+        query::Cardinality::Many,
+        builder,
+        Bindings(vec![c; n]),
+    )
+    .scoped(tenants::<T>())
+}
+
+/// Creates a `DELETE` query like [`delete_in`], returning the deleted rows where the database
+/// supports `RETURNING` (not on MySQL).
+///
+/// SQL: `DELETE FROM .. WHERE .. IN ($1, $2, ..) RETURNING ..`
+pub fn delete_in_returning<T: Bind>(c: Column<T>, n: usize) -> Query<T> {
+    let mut query = delete_in::<T>(c, n);
+
+    if Current::RETURNING {
+        returning::<T>(&mut query.builder);
+    }
+
+    query
+}
+
+/// Generates a `DELETE` query to remove a row from the table based on its primary key, regardless
+/// of whether the table supports soft deletion.
+///
+/// SQL: `DELETE FROM .. WHERE ..`
+pub fn hard_delete<T: Bind>() -> Query<T> {
+    hard_delete_by(T::PRIMARY_KEY.as_col())
+}
+
+/// Creates a `DELETE` query to remove rows from the table based on a specific column, regardless
+/// of whether the table supports soft deletion.
+///
+/// SQL: `DELETE FROM .. WHERE ..`
+pub fn hard_delete_by<T: Bind>(c: Column<T>) -> Query<T> {
+    let mut builder = QueryBuilder::new(format!("DELETE FROM {} WHERE ", table::<T>()));
+
+    builder.push(c.sql());
+    builder.push(" = $1");
+
+    if let Some(tenant) = tenant::<T>() {
+        builder.push(format!(" AND {} = $2", tenant.sql));
+    }
+
+    Query::new(
+        query::Operation::Delete,
+        query::Cardinality::One,
+        builder,
+        Bindings(vec![Column::PrimaryKey(&T::PRIMARY_KEY)]),
+    )
+    .scoped(tenants::<T>())
+}
+
+/// Generates a query selecting all columns of a table without reading any rows, to check that
+/// the table can be queried.
+///
+/// SQL: `SELECT .. FROM .. LIMIT 0`
+pub fn ping<T: Bind>() -> String {
+    format!(
+        "SELECT {} FROM {} LIMIT 0",
+        columns::<T>().collect::<Vec<_>>().join(", "),
+        table::<T>()
+    )
+}
+
+/// Generates the `CREATE TABLE` statement of a table, including its primary key, unique, check and
+/// foreign key constraints, followed by the statements creating its indexes and describing the
+/// table and its columns (if supported by the database).
+///
+/// SQL: `CREATE TABLE IF NOT EXISTS .. (.., PRIMARY KEY (..), ..)`
+///
+/// # Panics
+///
+/// If the sql type of a column is unknown. `#[derive(Schema)]` sets the type of all columns.
+pub fn create_table<T: Bind>() -> String {
+    let pk = T::PRIMARY_KEY;
+
+    let mut definitions = vec![primary_key_definition::<T>()];
+
+    for fk in T::FOREIGN_KEYS {
+        definitions.push(column_definition::<T>(fk.sql, fk.ty));
+    }
+
+    for data in T::DATA_COLUMNS {
+        definitions.push(column_definition::<T>(data.sql, data.ty));
+    }
+
+    for ts in T::TIMESTAMP_COLUMNS {
+        definitions.push(column_definition::<T>(ts.sql, ts.ty));
+    }
+
+    if !pk.auto || Current::AUTO_PRIMARY_KEY_CONSTRAINT {
+        definitions.push(format!("PRIMARY KEY ({})", pk.sql));
+    }
+
+    let unique = T::FOREIGN_KEYS
+        .iter()
+        .filter(|fk| fk.unique)
+        .map(|fk| fk.sql)
+        .chain(T::DATA_COLUMNS.iter().filter(|d| d.unique).map(|d| d.sql));
+
+    for column in unique {
+        definitions.push(format!("UNIQUE ({column})"));
+    }
+
+    for constraint in T::UNIQUE {
+        definitions.push(format!("UNIQUE ({})", constraint.columns.join(", ")));
+    }
+
+    // named like the checks postgres generates, so violations are mapped back to the column
+    for data in T::DATA_COLUMNS {
+        if let Some(check) = data.check {
+            definitions.push(format!(
+                "CONSTRAINT {}_{}_check CHECK ({check})",
+                T::TABLE,
+                data.sql
+            ));
+        }
+    }
+
+    for check in T::CHECKS {
+        definitions.push(format!("CHECK ({check})"));
+    }
+
+    if Current::INLINE_INDEXES {
+        for index in T::INDEXES {
+            definitions.push(format!("INDEX {} ({})", index.name, index_columns(index)));
+        }
+    }
+
+    definitions.extend(T::FOREIGN_KEYS.iter().filter_map(foreign_key));
+
+    let mut statement = format!(
+        "CREATE TABLE IF NOT EXISTS {} (\n  {}\n)",
+        table::<T>(),
+        definitions.join(",\n  ")
+    );
+
+    if !Current::INLINE_INDEXES {
+        for index in T::INDEXES {
+            statement.push_str(";\n");
+            statement.push_str(&create_index::<T>(index));
+        }
+    }
+
+    if Current::COMMENTS {
+        if let Some(comment) = T::COMMENT {
+            statement.push_str(&format!(
+                ";\nCOMMENT ON TABLE {} IS {}",
+                table::<T>(),
+                literal(comment)
+            ));
+        }
+
+        let columns = std::iter::once((pk.sql, pk.comment))
+            .chain(T::FOREIGN_KEYS.iter().map(|fk| (fk.sql, fk.comment)))
+            .chain(T::DATA_COLUMNS.iter().map(|data| (data.sql, data.comment)))
+            .chain(T::TIMESTAMP_COLUMNS.iter().map(|ts| (ts.sql, ts.comment)));
+
+        for (column, comment) in columns {
+            if let Some(comment) = comment {
+                statement.push_str(";\n");
+                statement.push_str(&comment_on_column::<T>(column, comment));
+            }
+        }
+    }
+
+    statement
+}
+
+/// Generates the statement describing `column` with `comment`.
+///
+/// SQL: `COMMENT ON COLUMN .. IS '..'`
+pub(crate) fn comment_on_column<T: Bind>(column: &str, comment: &str) -> String {
+    format!(
+        "COMMENT ON COLUMN {}.{column} IS {}",
+        table::<T>(),
+        literal(comment)
+    )
+}
+
+/// `s` as a quoted sql string literal
+fn literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Generates the statement creating `index` unless it already exists.
+///
+/// SQL: `CREATE INDEX IF NOT EXISTS .. ON .. [USING ..] (..)`
+pub fn create_index<T: Bind>(index: &Index) -> String {
+    let using = index
+        .using
+        .map(|method| format!(" USING {method}"))
+        .unwrap_or_default();
+
+    format!(
+        "CREATE INDEX IF NOT EXISTS {} ON {}{using} ({})",
+        index.name,
+        table::<T>(),
+        index_columns(index)
+    )
+}
+
+fn index_columns(index: &Index) -> String {
+    index
+        .columns
+        .iter()
+        .map(|c| match c.descending {
+            true => format!("{} DESC", c.sql),
+            false => c.sql.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn primary_key_definition<T: Bind>() -> String {
+    let pk = T::PRIMARY_KEY;
+    let ty = type_name(&ColumnType::of::<T::PrimaryKey>());
+
+    match pk.auto {
+        true => Current::auto_primary_key(pk.sql, &ty),
+        false => format!("{} {ty} NOT NULL", pk.sql),
+    }
+}
+
+/// The `FOREIGN KEY` constraint of a foreign key, if the referenced table is known
+pub(crate) fn foreign_key<T: Bind>(fk: &ForeignKey<T>) -> Option<String> {
+    let references = fk.references?;
+
+    let table = Current::table(
+        &crate::SchemaContext::current().unwrap_or_else(|| references.schema.to_owned()),
+        references.table,
+    );
+
+    Some(format!(
+        "FOREIGN KEY ({}) REFERENCES {table} ({}) ON DELETE {} ON UPDATE {}",
+        fk.sql,
+        references.column,
+        fk.on_delete.sql(),
+        fk.on_update.sql(),
+    ))
+}
+
+/// The definition of a column (`name TYPE [NOT NULL]`)
+pub(crate) fn column_definition<T: Bind>(sql: &str, ty: Option<ColumnType>) -> String {
+    let ty = ty.unwrap_or_else(|| panic!("the type of {}.{sql} is unknown", T::TABLE));
+
+    match ty.nullable {
+        true => format!("{sql} {}", type_name(&ty)),
+        false => format!("{sql} {} NOT NULL", type_name(&ty)),
+    }
+}
+
+/// The name of a type in a column definition
+pub(crate) fn type_name(ty: &ColumnType) -> String {
+    Current::type_name(ty.name())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        runtime::sql::{self, Bindings},
+        column::{ColumnType, ReferentialAction, TimestampKind},
+        runtime::sql::{
+            self,
+            dialect::{Current, Dialect},
+            Bindings,
+        },
+        window::Window,
         Bind, Bindable, Column, DataColumn, ForeignKey, PrimaryKey, Table, TimestampColumn,
     };
 
+    #[cfg(not(feature = "sqlite"))]
+    use crate::query::Lock;
+    #[cfg(feature = "postgres")]
+    use crate::{Index, IndexColumn};
+
+    /// The name of `table` in the `public` schema as rendered by the dialect of the driver
+    fn table(table: &str) -> String {
+        Current::table("public", table)
+    }
+
     #[derive(sqlx::FromRow)]
     #[allow(unused)]
     struct TestTable {
@@ -353,64 +1544,376 @@ mod tests {
     }
 
     #[test]
-    fn select() {
+    fn select() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::select::<TestTable>();
+
+        assert_eq!(
+            builder.sql(),
+            format!("SELECT\n  id_sql_col,\n  fk_sql_col,\n  data_sql_col\nFROM\n  {test}\nWHERE id_sql_col = $1", test = table("test"))
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::PrimaryKey(&TestTable::PRIMARY_KEY),])
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "sqlite"))]
+    fn select_for_update() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::select_for_update::<TestTable>(Lock::SkipLocked);
+
+        assert_eq!(
+            builder.sql(),
+            format!("SELECT\n  id_sql_col,\n  fk_sql_col,\n  data_sql_col\nFROM\n  {test}\nWHERE id_sql_col = $1\nFOR UPDATE SKIP LOCKED", test = table("test"))
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::PrimaryKey(&TestTable::PRIMARY_KEY)])
+        );
+    }
+
+    #[test]
+    fn select_in() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::select_in::<TestTable>(TestTable::FOREIGN_KEYS[0].as_col(), 3);
+
+        assert_eq!(
+            builder.sql(),
+            format!("SELECT\n  id_sql_col,\n  fk_sql_col,\n  data_sql_col\nFROM\n  {test}\nWHERE fk_sql_col IN ($1, $2, $3)", test = table("test"))
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::ForeignKey(&TestTable::FOREIGN_KEYS[0]); 3])
+        );
+    }
+
+    #[test]
+    fn count_by() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::count_by::<TestTable>(TestTable::FOREIGN_KEYS[0].as_col(), Some(2));
+
+        assert_eq!(
+            builder.sql(),
+            format!("SELECT\n  fk_sql_col,\n  COUNT(*)\nFROM\n  {test}\nGROUP BY fk_sql_col\nHAVING COUNT(*) >= 2\nORDER BY fk_sql_col", test = table("test"))
+        );
+
+        assert_eq!(bindings, Bindings::empty());
+    }
+
+    #[test]
+    fn count_by_and_where() {
+        let mut query = sql::count_by::<TestTable>(TestTable::FOREIGN_KEYS[0].as_col(), None);
+
+        query.and_where("data_sql_col = ").push_bind(true);
+        query.and_where("id_sql_col > 0");
+
+        assert_eq!(
+            query.sql(),
+            format!("SELECT\n  fk_sql_col,\n  COUNT(*)\nFROM\n  {test}\nWHERE data_sql_col = $1 AND id_sql_col > 0\nGROUP BY fk_sql_col\nORDER BY fk_sql_col", test = table("test"))
+        );
+    }
+
+    #[test]
+    fn select_like() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::select_like::<TestTable>(&TestTable::DATA_COLUMNS[0]);
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                "SELECT\n  id_sql_col,\n  fk_sql_col,\n  data_sql_col\nFROM\n  {test}\nWHERE data_sql_col {like} $1 ESCAPE '!'",
+                test = table("test"),
+                like = Current::ILIKE
+            )
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::Data(&TestTable::DATA_COLUMNS[0])])
+        );
+    }
+
+    #[test]
+    fn select_joined() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::select_joined::<TestTable, TestTable>(&TestTable::FOREIGN_KEYS[0]);
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                concat!(
+                    "SELECT\n",
+                    "  \"test\".id_sql_col AS \"test.id_sql_col\",\n",
+                    "  \"test\".fk_sql_col AS \"test.fk_sql_col\",\n",
+                    "  \"test\".data_sql_col AS \"test.data_sql_col\",\n",
+                    "  \"fk\".id_sql_col AS \"fk.id_sql_col\",\n",
+                    "  \"fk\".fk_sql_col AS \"fk.fk_sql_col\",\n",
+                    "  \"fk\".data_sql_col AS \"fk.data_sql_col\"\n",
+                    "FROM\n",
+                    "  {test} AS \"test\"\n",
+                    "  JOIN {test} AS \"fk\" ON \"test\".fk_sql_col = \"fk\".id_sql_col"
+                ),
+                test = table("test")
+            )
+        );
+
+        assert_eq!(bindings, Bindings::empty());
+    }
+
+    #[test]
+    fn select_joined_by() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::select_joined_by::<TestTable, TestTable>(
+            &TestTable::FOREIGN_KEYS[0],
+            TestTable::PRIMARY_KEY.as_col(),
+        );
+
+        assert!(builder.sql().ends_with(
+            "ON \"test\".fk_sql_col = \"fk\".id_sql_col\nWHERE \"test\".id_sql_col = $1"
+        ));
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::PrimaryKey(&TestTable::PRIMARY_KEY)])
+        );
+    }
+
+    #[test]
+    fn insert() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::insert::<TestTable>();
+
+        assert_eq!(
+            builder.sql(),
+            format!("INSERT INTO {test}\n  (id_sql_col, fk_sql_col, data_sql_col)\nVALUES\n  ($1, $2, $3)", test = table("test"))
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![
+                Column::PrimaryKey(&TestTable::PRIMARY_KEY),
+                Column::ForeignKey(&TestTable::FOREIGN_KEYS[0]),
+                Column::Data(&TestTable::DATA_COLUMNS[0]),
+            ])
+        );
+    }
+
+    #[derive(sqlx::FromRow)]
+    #[allow(unused)]
+    struct AutoTable {
+        id: i32,
+        data: bool,
+    }
+
+    impl Table for AutoTable {
+        type PrimaryKey = i32;
+
+        const SCHEMA: &'static str = "public";
+        const TABLE: &'static str = "auto";
+
+        const PRIMARY_KEY: PrimaryKey<Self> = PrimaryKey::new("id", "id_sql_col").with_auto();
+        const FOREIGN_KEYS: &'static [ForeignKey<Self>] = &[];
+        const DATA_COLUMNS: &'static [DataColumn<Self>] =
+            &[DataColumn::new("data", "data_sql_col")];
+        const TIMESTAMP_COLUMNS: &'static [TimestampColumn<Self>] = &[];
+
+        fn pk(&self) -> &Self::PrimaryKey {
+            &self.id
+        }
+    }
+
+    impl Bind for AutoTable {
+        fn bind<'q, Q: Bindable<'q>>(&'q self, c: &'q Column<Self>, query: Q) -> crate::Result<Q> {
+            match c.field() {
+                "id" => Ok(query.dyn_bind(self.id)),
+                "data" => Ok(query.dyn_bind(self.data)),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    #[test]
+    fn insert_auto() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::insert::<AutoTable>();
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                "INSERT INTO {auto}\n  (data_sql_col)\nVALUES\n  ($1){returning}",
+                auto = table("auto"),
+                returning = match Current::RETURNING {
+                    true => "\nRETURNING\n  id_sql_col,\n  data_sql_col",
+                    false => "",
+                }
+            )
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::Data(&AutoTable::DATA_COLUMNS[0])])
+        );
+    }
+
+    #[test]
+    fn upsert_auto() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::upsert::<AutoTable>();
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                "INSERT INTO {auto}\n  (id_sql_col, data_sql_col)\nVALUES\n  ($1, $2){upsert}",
+                auto = table("auto"),
+                upsert = Current::upsert(&["id_sql_col"], &["data_sql_col"])
+            )
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![
+                Column::PrimaryKey(&AutoTable::PRIMARY_KEY),
+                Column::Data(&AutoTable::DATA_COLUMNS[0]),
+            ])
+        );
+    }
+
+    #[test]
+    fn claim_next() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::claim_next::<AutoTable>(&AutoTable::DATA_COLUMNS[0]);
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                "UPDATE {auto} SET data_sql_col = $1\nWHERE id_sql_col = (\n  SELECT id_sql_col FROM {auto}\n  WHERE data_sql_col = $2\n  ORDER BY id_sql_col\n  LIMIT 1{lock}\n)\nRETURNING\n  id_sql_col,\n  data_sql_col",
+                auto = table("auto"),
+                lock = Current::lock(crate::query::Lock::SkipLocked).map(|lock| format!("\n  {lock}")).unwrap_or_default()
+            )
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![
+                Column::Data(&AutoTable::DATA_COLUMNS[0]),
+                Column::Data(&AutoTable::DATA_COLUMNS[0]),
+            ])
+        );
+    }
+
+    #[test]
+    fn update() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::update::<TestTable>();
+
+        assert_eq!(
+            builder.sql(),
+            format!("UPDATE {test} SET\n  id_sql_col = $1,\n  fk_sql_col = $2,\n  data_sql_col = $3\nWHERE\n  id_sql_col = $1", test = table("test"))
+        );
+
+        assert_eq!(
+            bindings,
+            Bindings(vec![
+                Column::PrimaryKey(&TestTable::PRIMARY_KEY),
+                Column::ForeignKey(&TestTable::FOREIGN_KEYS[0]),
+                Column::Data(&TestTable::DATA_COLUMNS[0]),
+            ])
+        );
+    }
+
+    #[test]
+    fn update_of() {
         let sql::Query {
             builder, bindings, ..
-        } = sql::select::<TestTable>();
+        } = sql::update_of::<TestTable>(&["data"]);
 
         assert_eq!(
             builder.sql(),
-            "SELECT\n  id_sql_col,\n  fk_sql_col,\n  data_sql_col\nFROM\n  \"public\".\"test\"\nWHERE id_sql_col = $1"
+            format!("UPDATE {test} SET\n  id_sql_col = $1,\n  data_sql_col = $2\nWHERE\n  id_sql_col = $1", test = table("test"))
         );
 
         assert_eq!(
             bindings,
-            Bindings(vec![Column::PrimaryKey(&TestTable::PRIMARY_KEY),])
+            Bindings(vec![
+                Column::PrimaryKey(&TestTable::PRIMARY_KEY),
+                Column::Data(&TestTable::DATA_COLUMNS[0]),
+            ])
         );
     }
 
     #[test]
-    fn insert() {
+    fn patch() {
         let sql::Query {
             builder, bindings, ..
-        } = sql::insert::<TestTable>();
+        } = sql::patch::<TestTable>(&["data"]);
 
         assert_eq!(
             builder.sql(),
-            "INSERT INTO \"public\".\"test\"\n  (id_sql_col, fk_sql_col, data_sql_col)\nVALUES\n  ($1, $2, $3)"
+            format!(
+                "UPDATE {test} SET\n  data_sql_col = $2\nWHERE\n  id_sql_col = $1",
+                test = table("test")
+            )
         );
 
         assert_eq!(
             bindings,
             Bindings(vec![
                 Column::PrimaryKey(&TestTable::PRIMARY_KEY),
-                Column::ForeignKey(&TestTable::FOREIGN_KEYS[0]),
                 Column::Data(&TestTable::DATA_COLUMNS[0]),
             ])
         );
     }
 
     #[test]
-    fn update() {
+    #[cfg(feature = "postgres")]
+    fn json_merge() {
         let sql::Query {
             builder, bindings, ..
-        } = sql::update::<TestTable>();
+        } = sql::json_merge::<TestTable>(&TestTable::DATA_COLUMNS[0]);
 
         assert_eq!(
             builder.sql(),
-            "UPDATE \"public\".\"test\" SET\n  id_sql_col = $1,\n  fk_sql_col = $2,\n  data_sql_col = $3\nWHERE\n  id_sql_col = $1"
+            "UPDATE \"public\".\"test\" SET\n  data_sql_col = data_sql_col || $2\nWHERE\n  id_sql_col = $1"
         );
 
         assert_eq!(
             bindings,
             Bindings(vec![
                 Column::PrimaryKey(&TestTable::PRIMARY_KEY),
-                Column::ForeignKey(&TestTable::FOREIGN_KEYS[0]),
                 Column::Data(&TestTable::DATA_COLUMNS[0]),
             ])
         );
     }
 
+    #[test]
+    #[cfg(feature = "postgres")]
+    fn create_index_using() {
+        const INDEX: Index =
+            Index::new("test_data_idx", &[IndexColumn::asc("data_sql_col")]).using("GIN");
+
+        assert_eq!(
+            sql::create_index::<TestTable>(&INDEX),
+            "CREATE INDEX IF NOT EXISTS test_data_idx ON \"public\".\"test\" USING GIN (data_sql_col)"
+        );
+    }
+
     #[test]
     fn upsert() {
         let sql::Query {
@@ -419,7 +1922,11 @@ mod tests {
 
         assert_eq!(
                 builder.sql(),
-                "INSERT INTO \"public\".\"test\"\n  (id_sql_col, fk_sql_col, data_sql_col)\nVALUES\n  ($1, $2, $3)\nON CONFLICT(id_sql_col)\nDO UPDATE SET\n  fk_sql_col = EXCLUDED.fk_sql_col,\n  data_sql_col = EXCLUDED.data_sql_col"
+                format!(
+                    "INSERT INTO {test}\n  (id_sql_col, fk_sql_col, data_sql_col)\nVALUES\n  ($1, $2, $3){upsert}",
+                    test = table("test"),
+                    upsert = Current::upsert(&["id_sql_col"], &["fk_sql_col", "data_sql_col"])
+                )
             );
 
         assert_eq!(
@@ -432,6 +1939,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ping() {
+        assert_eq!(
+            sql::ping::<TestTable>(),
+            format!(
+                "SELECT id_sql_col, fk_sql_col, data_sql_col FROM {test} LIMIT 0",
+                test = table("test")
+            )
+        );
+    }
+
     #[test]
     fn delete() {
         let sql::Query {
@@ -440,11 +1958,410 @@ mod tests {
 
         assert_eq!(
             builder.sql(),
-            "DELETE FROM \"public\".\"test\" WHERE id_sql_col = $1"
+            format!(
+                "DELETE FROM {test} WHERE id_sql_col = $1",
+                test = table("test")
+            )
         );
         assert_eq!(
             bindings,
             Bindings(vec![Column::PrimaryKey(&TestTable::PRIMARY_KEY),])
         );
     }
+
+    #[test]
+    fn delete_in() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::delete_in::<TestTable>(TestTable::PRIMARY_KEY.as_col(), 2);
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                "DELETE FROM {test} WHERE id_sql_col IN ($1, $2)",
+                test = table("test")
+            )
+        );
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::PrimaryKey(&TestTable::PRIMARY_KEY); 2])
+        );
+    }
+
+    #[test]
+    fn delete_in_returning() {
+        let query = sql::delete_in_returning::<TestTable>(TestTable::PRIMARY_KEY.as_col(), 2);
+
+        let returning = match Current::RETURNING {
+            true => "\nRETURNING\n  id_sql_col,\n  fk_sql_col,\n  data_sql_col",
+            false => "",
+        };
+
+        assert_eq!(
+            query.sql(),
+            format!(
+                "DELETE FROM {test} WHERE id_sql_col IN ($1, $2){returning}",
+                test = table("test")
+            )
+        );
+    }
+
+    #[derive(sqlx::FromRow)]
+    #[allow(unused)]
+    struct SoftTable {
+        id: i32,
+        deleted: Option<i32>,
+    }
+
+    impl Table for SoftTable {
+        type PrimaryKey = i32;
+
+        const SCHEMA: &'static str = "public";
+        const TABLE: &'static str = "soft";
+
+        const PRIMARY_KEY: PrimaryKey<Self> = PrimaryKey::new("id", "id_sql_col");
+        const FOREIGN_KEYS: &'static [ForeignKey<Self>] = &[];
+        const DATA_COLUMNS: &'static [DataColumn<Self>] = &[];
+        const TIMESTAMP_COLUMNS: &'static [TimestampColumn<Self>] = &[TimestampColumn::new(
+            TimestampKind::Deleted,
+            "deleted",
+            "deleted_sql_col",
+        )];
+
+        fn pk(&self) -> &Self::PrimaryKey {
+            &self.id
+        }
+    }
+
+    impl Bind for SoftTable {
+        fn bind<'q, Q: Bindable<'q>>(&'q self, c: &'q Column<Self>, query: Q) -> crate::Result<Q> {
+            match c.field() {
+                "id" => Ok(query.dyn_bind(self.id)),
+                "deleted" => Ok(query.dyn_bind(self.deleted)),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    #[test]
+    fn select_soft_deleted() {
+        assert_eq!(
+            sql::select::<SoftTable>().builder.sql(),
+            format!("SELECT\n  id_sql_col,\n  deleted_sql_col\nFROM\n  {soft}\nWHERE id_sql_col = $1 AND deleted_sql_col IS NULL", soft = table("soft"))
+        );
+
+        assert_eq!(
+            sql::select_all::<SoftTable>().builder.sql(),
+            format!("SELECT\n  id_sql_col,\n  deleted_sql_col\nFROM\n  {soft}\nWHERE deleted_sql_col IS NULL", soft = table("soft"))
+        );
+    }
+
+    #[test]
+    fn delete_soft_deleted() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::delete::<SoftTable>();
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                "UPDATE {soft} SET deleted_sql_col = {now} WHERE id_sql_col = $1 AND deleted_sql_col IS NULL",
+                soft = table("soft"),
+                now = Current::now()
+            )
+        );
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::PrimaryKey(&SoftTable::PRIMARY_KEY)])
+        );
+
+        assert_eq!(
+            sql::hard_delete::<SoftTable>().builder.sql(),
+            format!(
+                "DELETE FROM {soft} WHERE id_sql_col = $1",
+                soft = table("soft")
+            )
+        );
+
+        assert_eq!(
+            sql::delete_in::<SoftTable>(SoftTable::PRIMARY_KEY.as_col(), 2).builder.sql(),
+            format!(
+                "UPDATE {soft} SET deleted_sql_col = {now} WHERE id_sql_col IN ($1, $2) AND deleted_sql_col IS NULL",
+                soft = table("soft"),
+                now = Current::now()
+            )
+        );
+    }
+
+    #[test]
+    fn select_window() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::select_window::<SoftTable, ()>(
+            &SoftTable::TIMESTAMP_COLUMNS[0],
+            &Window::Between((), ()),
+        );
+
+        assert_eq!(
+            builder.sql(),
+            format!("SELECT\n  id_sql_col,\n  deleted_sql_col\nFROM\n  {soft}\nWHERE deleted_sql_col >= $1 AND deleted_sql_col < $2\nORDER BY deleted_sql_col", soft = table("soft"))
+        );
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::Timestamp(&SoftTable::TIMESTAMP_COLUMNS[0]); 2])
+        );
+    }
+
+    #[test]
+    fn select_window_and_where() {
+        let mut query = sql::select_window::<SoftTable, ()>(
+            &SoftTable::TIMESTAMP_COLUMNS[0],
+            &Window::Between((), ()),
+        );
+
+        query.and_where("id_sql_col = ").push_bind(1);
+
+        assert_eq!(
+            query.sql(),
+            format!("SELECT\n  id_sql_col,\n  deleted_sql_col\nFROM\n  {soft}\nWHERE deleted_sql_col >= $1 AND deleted_sql_col < $2 AND id_sql_col = $3\nORDER BY deleted_sql_col", soft = table("soft"))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "postgres")]
+    fn select_history() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::select_history::<SoftTable>();
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT\n  id_sql_col,\n  deleted_sql_col,\n  valid_from,\n  valid_to\nFROM\n  \"public\".\"soft_history\"\nWHERE id_sql_col = $1\nORDER BY valid_to"
+        );
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::PrimaryKey(&SoftTable::PRIMARY_KEY)])
+        );
+    }
+
+    #[derive(sqlx::FromRow)]
+    #[allow(unused)]
+    struct TenantTable {
+        id: i32,
+        tenant: i32,
+    }
+
+    impl Table for TenantTable {
+        type PrimaryKey = i32;
+
+        const SCHEMA: &'static str = "public";
+        const TABLE: &'static str = "tenant";
+
+        const PRIMARY_KEY: PrimaryKey<Self> = PrimaryKey::new("id", "id_sql_col");
+        const FOREIGN_KEYS: &'static [ForeignKey<Self>] = &[];
+        const DATA_COLUMNS: &'static [DataColumn<Self>] =
+            &[DataColumn::new("tenant", "tenant_sql_col").with_tenant()];
+        const TIMESTAMP_COLUMNS: &'static [TimestampColumn<Self>] = &[];
+
+        fn pk(&self) -> &Self::PrimaryKey {
+            &self.id
+        }
+    }
+
+    impl Bind for TenantTable {
+        fn bind<'q, Q: Bindable<'q>>(&'q self, c: &'q Column<Self>, query: Q) -> crate::Result<Q> {
+            match c.field() {
+                "id" => Ok(query.dyn_bind(self.id)),
+                "tenant" => Ok(query.dyn_bind(self.tenant)),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    #[test]
+    fn select_schema_in_scope() {
+        let sql = futures::executor::block_on(crate::SchemaContext::scope("tenant_42", async {
+            sql::select::<TestTable>().builder.sql().to_owned()
+        }));
+
+        assert_eq!(
+            sql,
+            format!("SELECT\n  id_sql_col,\n  fk_sql_col,\n  data_sql_col\nFROM\n  {scoped}\nWHERE id_sql_col = $1", scoped = Current::table("tenant_42", "test"))
+        );
+
+        // the schema is only overridden within the scope
+        assert!(sql::select::<TestTable>()
+            .builder
+            .sql()
+            .contains(&table("test")));
+    }
+
+    #[derive(sqlx::FromRow)]
+    #[allow(unused)]
+    struct DynamicTable {
+        id: i32,
+    }
+
+    impl Table for DynamicTable {
+        type PrimaryKey = i32;
+
+        const SCHEMA: &'static str = "public";
+        const TABLE: &'static str = "dynamic";
+
+        const PRIMARY_KEY: PrimaryKey<Self> = PrimaryKey::new("id", "id_sql_col");
+        const FOREIGN_KEYS: &'static [ForeignKey<Self>] = &[];
+        const DATA_COLUMNS: &'static [DataColumn<Self>] = &[];
+        const TIMESTAMP_COLUMNS: &'static [TimestampColumn<Self>] = &[];
+
+        fn pk(&self) -> &Self::PrimaryKey {
+            &self.id
+        }
+
+        fn name() -> std::borrow::Cow<'static, str> {
+            "dynamic_2024_05".into()
+        }
+    }
+
+    impl Bind for DynamicTable {
+        fn bind<'q, Q: Bindable<'q>>(&'q self, c: &'q Column<Self>, query: Q) -> crate::Result<Q> {
+            match c.field() {
+                "id" => Ok(query.dyn_bind(self.id)),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    #[test]
+    fn select_dynamic_name() {
+        assert_eq!(
+            sql::select::<DynamicTable>().builder.sql(),
+            format!(
+                "SELECT\n  id_sql_col\nFROM\n  {dynamic}\nWHERE id_sql_col = $1",
+                dynamic = table("dynamic_2024_05")
+            )
+        );
+    }
+
+    #[test]
+    fn select_tenant() {
+        assert_eq!(
+            sql::select::<TenantTable>().builder.sql(),
+            format!("SELECT\n  id_sql_col,\n  tenant_sql_col\nFROM\n  {tenant}\nWHERE id_sql_col = $1 AND tenant_sql_col = $2", tenant = table("tenant"))
+        );
+
+        assert_eq!(
+            sql::select_all::<TenantTable>().builder.sql(),
+            format!("SELECT\n  id_sql_col,\n  tenant_sql_col\nFROM\n  {tenant}\nWHERE tenant_sql_col = $1", tenant = table("tenant"))
+        );
+    }
+
+    #[test]
+    fn update_tenant() {
+        let sql::Query {
+            builder, bindings, ..
+        } = sql::update::<TenantTable>();
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                "UPDATE {tenant} SET\n  id_sql_col = $1\nWHERE\n  id_sql_col = $1 AND tenant_sql_col = $2",
+                tenant = table("tenant")
+            )
+        );
+        assert_eq!(bindings.columns().len(), 1);
+
+        assert_eq!(
+            sql::delete::<TenantTable>().builder.sql(),
+            format!(
+                "DELETE FROM {tenant} WHERE id_sql_col = $1 AND tenant_sql_col = $2",
+                tenant = table("tenant")
+            )
+        );
+    }
+
+    #[test]
+    fn insert_tenant() {
+        assert_eq!(
+            sql::insert::<TenantTable>().builder.sql(),
+            format!(
+                "INSERT INTO {tenant}\n  (id_sql_col, tenant_sql_col)\nVALUES\n  ($1, $2)",
+                tenant = table("tenant")
+            )
+        );
+
+        // the tenant in scope is inserted instead of the tenant of the row, which is read back
+        let sql::Query {
+            builder, bindings, ..
+        } = futures::executor::block_on(crate::tenant::scope(1, async {
+            sql::insert::<TenantTable>()
+        }));
+
+        assert_eq!(
+            builder.sql(),
+            format!(
+                "INSERT INTO {tenant}\n  (id_sql_col, tenant_sql_col)\nVALUES\n  ($1, $2){returning}",
+                tenant = table("tenant"),
+                returning = match Current::RETURNING {
+                    true => "\nRETURNING\n  id_sql_col,\n  tenant_sql_col",
+                    false => "",
+                }
+            )
+        );
+        assert_eq!(
+            bindings,
+            Bindings(vec![Column::PrimaryKey(&TenantTable::PRIMARY_KEY)])
+        );
+    }
+
+    #[derive(sqlx::FromRow)]
+    #[allow(unused)]
+    struct DdlTable {
+        id: i64,
+        fk: Option<i32>,
+        data: String,
+        created: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
+    }
+
+    impl Table for DdlTable {
+        type PrimaryKey = i64;
+
+        const SCHEMA: &'static str = "public";
+        const TABLE: &'static str = "ddl";
+
+        const PRIMARY_KEY: PrimaryKey<Self> = PrimaryKey::new("id", "id_sql_col").with_auto();
+        const FOREIGN_KEYS: &'static [ForeignKey<Self>] = &[ForeignKey::new("fk", "fk_sql_col")
+            .with_type(ColumnType::of::<Option<i32>>().nullable())
+            .with_references::<TestTable>()
+            .with_on_delete(ReferentialAction::Cascade)];
+        const DATA_COLUMNS: &'static [DataColumn<Self>] =
+            &[DataColumn::new("data", "data_sql_col")
+                .with_type(ColumnType::of::<String>())
+                .with_unique()];
+        const TIMESTAMP_COLUMNS: &'static [TimestampColumn<Self>] =
+            &[
+                TimestampColumn::new(TimestampKind::Created, "created", "created_sql_col")
+                    .with_type(ColumnType::of::<
+                        sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
+                    >()),
+            ];
+
+        fn pk(&self) -> &Self::PrimaryKey {
+            &self.id
+        }
+    }
+
+    impl Bind for DdlTable {
+        fn bind<'q, Q: Bindable<'q>>(&'q self, _: &'q Column<Self>, _: Q) -> crate::Result<Q> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "postgres")]
+    fn create_table() {
+        assert_eq!(
+            sql::create_table::<DdlTable>(),
+            "CREATE TABLE IF NOT EXISTS \"public\".\"ddl\" (\n  id_sql_col BIGSERIAL NOT NULL,\n  fk_sql_col INT4,\n  data_sql_col TEXT NOT NULL,\n  created_sql_col TIMESTAMPTZ NOT NULL,\n  PRIMARY KEY (id_sql_col),\n  UNIQUE (data_sql_col),\n  FOREIGN KEY (fk_sql_col) REFERENCES \"public\".\"test\" (id_sql_col) ON DELETE CASCADE ON UPDATE NO ACTION\n)"
+        );
+    }
 }
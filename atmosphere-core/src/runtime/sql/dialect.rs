@@ -0,0 +1,298 @@
+//! SQL dialects of the supported databases
+//!
+//! The query builders of [`crate::runtime::sql`] share the structure of their statements between
+//! all databases. The parts which differ (table qualification, upserts, `RETURNING`, row locks,
+//! timestamps and column definitions) are rendered by the [`Dialect`] of the enabled driver, see
+//! [`Current`].
+
+use crate::query::Lock;
+
+/// Renders the database specific parts of generated sql
+pub trait Dialect {
+    /// The name of the database system, as used by OpenTelemetry (`db.system`)
+    const SYSTEM: &'static str;
+
+    /// Whether `INSERT` and `UPDATE` statements support a `RETURNING` clause
+    const RETURNING: bool;
+
+    /// Whether the primary key is declared as a separate constraint of a generated primary key
+    /// column (instead of within the column definition)
+    const AUTO_PRIMARY_KEY_CONSTRAINT: bool;
+
+    /// The maximum number of parameters bound to a single statement
+    const MAX_BINDS: usize;
+
+    /// The name of `table` within `schema`
+    fn table(schema: &str, table: &str) -> String;
+
+    /// The clause locking selected rows, if the database supports row locks
+    fn lock(lock: Lock) -> Option<&'static str>;
+
+    /// The expression evaluating to the current time, in the format timestamps are stored in
+    fn now() -> &'static str;
+
+    /// Whether indexes are declared within the `CREATE TABLE` statement instead of by separate
+    /// `CREATE INDEX IF NOT EXISTS` statements, which are not supported by every database
+    const INLINE_INDEXES: bool = false;
+
+    /// The operator matching a `LIKE` pattern regardless of case. `LIKE` ignores the case of
+    /// ASCII letters on sqlite and using the default collations of mysql.
+    const ILIKE: &'static str = "LIKE";
+
+    /// Whether tables and columns are described by `COMMENT ON` statements
+    const COMMENTS: bool = false;
+
+    /// The clause appended to an `INSERT` statement, updating `columns` to the inserted values if
+    /// a row with the same values of the unique columns `target` (e.g. the primary key) exists
+    fn upsert(target: &[&str], columns: &[&str]) -> String;
+
+    /// The definition of the primary key column `column` generated by the database, `ty` being
+    /// the name of its type
+    fn auto_primary_key(column: &str, ty: &str) -> String;
+
+    /// The name of the type `name` in a column definition
+    fn type_name(name: String) -> String {
+        name
+    }
+}
+
+/// The dialect of the enabled driver
+#[cfg(feature = "postgres")]
+pub type Current = Postgres;
+
+/// The dialect of the enabled driver
+#[cfg(feature = "mysql")]
+pub type Current = MySql;
+
+/// The dialect of the enabled driver
+#[cfg(feature = "sqlite")]
+pub type Current = Sqlite;
+
+/// The dialect of PostgreSQL
+pub struct Postgres;
+
+impl Dialect for Postgres {
+    const SYSTEM: &'static str = "postgresql";
+    const RETURNING: bool = true;
+    const AUTO_PRIMARY_KEY_CONSTRAINT: bool = true;
+    const MAX_BINDS: usize = 65535;
+    const ILIKE: &'static str = "ILIKE";
+    const COMMENTS: bool = true;
+
+    fn table(schema: &str, table: &str) -> String {
+        format!("\"{schema}\".\"{table}\"")
+    }
+
+    fn lock(lock: Lock) -> Option<&'static str> {
+        Some(lock.sql())
+    }
+
+    fn now() -> &'static str {
+        "CURRENT_TIMESTAMP"
+    }
+
+    fn upsert(target: &[&str], columns: &[&str]) -> String {
+        on_conflict(target, columns, "EXCLUDED")
+    }
+
+    fn auto_primary_key(column: &str, ty: &str) -> String {
+        let ty = match ty {
+            "INT2" => "SMALLSERIAL",
+            "INT8" => "BIGSERIAL",
+            _ => "SERIAL",
+        };
+
+        format!("{column} {ty} NOT NULL")
+    }
+}
+
+/// The dialect of MySQL
+pub struct MySql;
+
+impl Dialect for MySql {
+    const SYSTEM: &'static str = "mysql";
+    const RETURNING: bool = false;
+    const AUTO_PRIMARY_KEY_CONSTRAINT: bool = true;
+    const MAX_BINDS: usize = 65535;
+    const INLINE_INDEXES: bool = true;
+
+    fn table(schema: &str, table: &str) -> String {
+        format!("\"{schema}\".\"{table}\"")
+    }
+
+    fn lock(lock: Lock) -> Option<&'static str> {
+        Some(lock.sql())
+    }
+
+    fn now() -> &'static str {
+        "CURRENT_TIMESTAMP"
+    }
+
+    fn upsert(target: &[&str], columns: &[&str]) -> String {
+        // mysql detects the conflicting unique key itself, `target` is implied
+        //
+        // an assignment is required, assigning a key to itself keeps the row as is
+        let assignments = match columns.is_empty() {
+            true => format!("{0} = {0}", target[0]),
+            false => columns
+                .iter()
+                .map(|c| format!("{c} = VALUES({c})"))
+                .collect::<Vec<_>>()
+                .join(",\n  "),
+        };
+
+        format!("\nON DUPLICATE KEY UPDATE\n  {assignments}")
+    }
+
+    fn auto_primary_key(column: &str, ty: &str) -> String {
+        format!("{column} {ty} NOT NULL AUTO_INCREMENT")
+    }
+
+    fn type_name(name: String) -> String {
+        // mysql reports variable length types without their length
+        match name.as_str() {
+            "VARCHAR" | "CHAR" => "VARCHAR(255)".to_owned(),
+            "VARBINARY" | "BINARY" => "BLOB".to_owned(),
+            _ => name,
+        }
+    }
+}
+
+/// The dialect of SQLite
+pub struct Sqlite;
+
+impl Dialect for Sqlite {
+    const SYSTEM: &'static str = "sqlite";
+    const RETURNING: bool = true;
+    const AUTO_PRIMARY_KEY_CONSTRAINT: bool = false;
+    // `SQLITE_MAX_VARIABLE_NUMBER` of sqlite before 3.32, which builds may still be limited to
+    const MAX_BINDS: usize = 999;
+
+    /// SQLite has no schemas, tables are only qualified by their name
+    fn table(_: &str, table: &str) -> String {
+        format!("\"{table}\"")
+    }
+
+    /// SQLite locks the whole database for writes, rows are never locked individually
+    fn lock(_: Lock) -> Option<&'static str> {
+        None
+    }
+
+    /// SQLite has no timestamp type, timestamps are stored as RFC 3339 text by sqlx. The current
+    /// time is rendered in the same format (instead of `CURRENT_TIMESTAMP`, which omits the `T`
+    /// and offset) so stored timestamps remain comparable.
+    fn now() -> &'static str {
+        "strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')"
+    }
+
+    fn upsert(target: &[&str], columns: &[&str]) -> String {
+        on_conflict(target, columns, "excluded")
+    }
+
+    /// SQLite only generates keys of `INTEGER PRIMARY KEY` columns, which have to declare the
+    /// primary key within their definition
+    fn auto_primary_key(column: &str, _: &str) -> String {
+        format!("{column} INTEGER PRIMARY KEY AUTOINCREMENT")
+    }
+}
+
+/// An `ON CONFLICT` clause as supported by postgres and sqlite, `excluded` being the name of the
+/// row proposed for insertion
+fn on_conflict(target: &[&str], columns: &[&str], excluded: &str) -> String {
+    let target = target.join(", ");
+
+    // an empty `SET` is invalid, there is nothing to update without any other columns
+    if columns.is_empty() {
+        return format!("\nON CONFLICT({target})\nDO NOTHING");
+    }
+
+    let assignments = columns
+        .iter()
+        .map(|c| format!("{c} = {excluded}.{c}"))
+        .collect::<Vec<_>>()
+        .join(",\n  ");
+
+    format!("\nON CONFLICT({target})\nDO UPDATE SET\n  {assignments}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dialect, MySql, Postgres, Sqlite};
+    use crate::query::Lock;
+
+    #[test]
+    fn table() {
+        assert_eq!(Postgres::table("public", "test"), "\"public\".\"test\"");
+        assert_eq!(Sqlite::table("public", "test"), "\"test\"");
+    }
+
+    #[test]
+    fn lock() {
+        assert_eq!(
+            Postgres::lock(Lock::SkipLocked),
+            Some("FOR UPDATE SKIP LOCKED")
+        );
+        assert_eq!(Sqlite::lock(Lock::SkipLocked), None);
+    }
+
+    #[test]
+    fn upsert() {
+        assert_eq!(
+            Postgres::upsert(&["id"], &["a", "b"]),
+            "\nON CONFLICT(id)\nDO UPDATE SET\n  a = EXCLUDED.a,\n  b = EXCLUDED.b"
+        );
+
+        assert_eq!(
+            Sqlite::upsert(&["id"], &["a", "b"]),
+            "\nON CONFLICT(id)\nDO UPDATE SET\n  a = excluded.a,\n  b = excluded.b"
+        );
+
+        assert_eq!(
+            MySql::upsert(&["id"], &["a", "b"]),
+            "\nON DUPLICATE KEY UPDATE\n  a = VALUES(a),\n  b = VALUES(b)"
+        );
+
+        assert_eq!(
+            Postgres::upsert(&["name", "location"], &["a"]),
+            "\nON CONFLICT(name, location)\nDO UPDATE SET\n  a = EXCLUDED.a"
+        );
+    }
+
+    #[test]
+    fn upsert_without_columns() {
+        assert_eq!(
+            Postgres::upsert(&["id"], &[]),
+            "\nON CONFLICT(id)\nDO NOTHING"
+        );
+        assert_eq!(
+            Sqlite::upsert(&["id"], &[]),
+            "\nON CONFLICT(id)\nDO NOTHING"
+        );
+        assert_eq!(
+            MySql::upsert(&["id"], &[]),
+            "\nON DUPLICATE KEY UPDATE\n  id = id"
+        );
+    }
+
+    #[test]
+    fn auto_primary_key() {
+        assert_eq!(
+            Postgres::auto_primary_key("id", "INT8"),
+            "id BIGSERIAL NOT NULL"
+        );
+        assert_eq!(
+            Sqlite::auto_primary_key("id", "INTEGER"),
+            "id INTEGER PRIMARY KEY AUTOINCREMENT"
+        );
+        assert_eq!(
+            MySql::auto_primary_key("id", "INT"),
+            "id INT NOT NULL AUTO_INCREMENT"
+        );
+    }
+
+    #[test]
+    fn type_name() {
+        assert_eq!(Postgres::type_name("TEXT".to_owned()), "TEXT");
+        assert_eq!(MySql::type_name("VARCHAR".to_owned()), "VARCHAR(255)");
+    }
+}
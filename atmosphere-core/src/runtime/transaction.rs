@@ -0,0 +1,410 @@
+//! Transaction helpers
+//!
+//! Composing several entity operations into a single transaction with plain sqlx requires
+//! beginning, committing and rolling back by hand. [`transaction`] takes care of this: it runs a
+//! closure against a fresh transaction and commits if the closure succeeds or rolls back if it
+//! fails.
+//!
+//! ```ignore
+//! let user = atmosphere::transaction(&pool, |conn| {
+//!     Box::pin(async move {
+//!         let mut user = User::read(&mut *conn, &0).await?;
+//!         user.name = "renamed".to_owned();
+//!         user.update(&mut *conn).await?;
+//!
+//!         Post::delete_by(&mut *conn, &4).await?;
+//!
+//!         Ok::<_, atmosphere::Error>(user)
+//!     })
+//! })
+//! .await?;
+//! ```
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+use futures::future::BoxFuture;
+use sqlx::{Connection as _, Database};
+
+use crate::{
+    hooks::HookStage,
+    query::{transient, QueryError},
+    retry::retry_if,
+    Error,
+};
+
+/// The connection a transaction closure executes its queries on
+pub type Connection = <crate::Driver as Database>::Connection;
+
+/// Transaction isolation levels as defined by the SQL standard.
+///
+/// SQLite transactions are always serializable, the isolation level is ignored there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// The statement setting this isolation level for a transaction
+    pub const fn sql(&self) -> &'static str {
+        match self {
+            Self::ReadUncommitted => "SET TRANSACTION ISOLATION LEVEL READ UNCOMMITTED",
+            Self::ReadCommitted => "SET TRANSACTION ISOLATION LEVEL READ COMMITTED",
+            Self::RepeatableRead => "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ",
+            Self::Serializable => "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+        }
+    }
+}
+
+/// Runs `f` inside of a transaction using the default isolation level of the database.
+///
+/// The transaction is committed if `f` returns `Ok` and rolled back if it returns `Err`. Errors
+/// beginning, committing or rolling back the transaction are converted into `E`.
+///
+/// The [`HookStage::PreCommit`] and [`HookStage::PostCommit`] hooks of all tables written to
+/// within `f` run right before and after the commit. A failing `PreCommit` hook rolls the
+/// transaction back, a failing `PostCommit` hook is reported although the transaction has been
+/// committed already.
+///
+/// Acquiring the connection is retried according to the installed
+/// [`RetryPolicy`](crate::retry::RetryPolicy), `f` runs once.
+pub async fn transaction<T, E, F>(pool: &crate::Pool, f: F) -> std::result::Result<T, E>
+where
+    F: for<'c> FnOnce(&'c mut Connection) -> BoxFuture<'c, std::result::Result<T, E>> + Send,
+    T: Send,
+    E: From<Error> + Send,
+{
+    run(pool, None, f).await
+}
+
+/// Runs `f` inside of a transaction with the given isolation level, see [`transaction`].
+///
+/// Transactions at [`IsolationLevel::Serializable`] and [`IsolationLevel::RepeatableRead`] failing
+/// because of a serialization failure or a deadlock (SQLSTATE `40001` and `40P01`) are rolled back
+/// and run again, up to the number of attempts set using [`set_max_attempts`]. The failures are
+/// noticed in all queries of `f` converting their errors into [`QueryError`], also if `f` does not
+/// return them.
+pub async fn transaction_with<T, E, F>(
+    pool: &crate::Pool,
+    isolation: IsolationLevel,
+    mut f: F,
+) -> std::result::Result<T, E>
+where
+    F: for<'c> FnMut(&'c mut Connection) -> BoxFuture<'c, std::result::Result<T, E>> + Send,
+    T: Send,
+    E: From<Error> + Send,
+{
+    let attempts = match isolation {
+        IsolationLevel::Serializable | IsolationLevel::RepeatableRead => {
+            MAX_ATTEMPTS.load(Ordering::Relaxed)
+        }
+        _ => 1,
+    };
+
+    let mut attempt = 1;
+
+    loop {
+        let mut conn = acquire(pool).await?;
+
+        let retry = attempt < attempts;
+
+        match run_once(&mut conn, Some(isolation), |conn, _| f(conn), retry).await {
+            Ok(value) => return Ok(value),
+            Err(Failed::Err(err)) => return Err(err),
+            Err(Failed::Conflict) => attempt += 1,
+        }
+    }
+}
+
+static MAX_ATTEMPTS: AtomicU32 = AtomicU32::new(3);
+
+/// Sets how often [`transaction_with`] runs a transaction failing because of serialization
+/// failures or deadlocks at most, 3 by default. `1` disables running them again.
+pub fn set_max_attempts(attempts: u32) {
+    MAX_ATTEMPTS.store(attempts, Ordering::Relaxed);
+}
+
+async fn run<T, E, F>(
+    pool: &crate::Pool,
+    isolation: Option<IsolationLevel>,
+    f: F,
+) -> std::result::Result<T, E>
+where
+    F: for<'c> FnOnce(&'c mut Connection) -> BoxFuture<'c, std::result::Result<T, E>> + Send,
+    T: Send,
+    E: From<Error> + Send,
+{
+    let mut conn = acquire(pool).await?;
+
+    run_on(&mut conn, isolation, |conn, _| f(conn)).await
+}
+
+async fn acquire<E: From<Error>>(
+    pool: &crate::Pool,
+) -> std::result::Result<sqlx::pool::PoolConnection<crate::Driver>, E> {
+    retry_if(|| pool.acquire(), transient).await.map_err(error)
+}
+
+/// Runs `f` inside of a transaction on `conn`, see [`transaction`].
+///
+/// Inside of another transaction on the same connection, `f` runs within a savepoint and its
+/// commit hooks are deferred until the outer transaction commits. The isolation level can not be
+/// changed there. Transactions on other connections (e.g. of [`transaction`] called within the
+/// closure of another one) are independent, they commit and run their commit hooks on their own.
+///
+/// Unlike the closure of [`transaction`], `f` may borrow from its surroundings (`'a`).
+pub(crate) async fn run_on<'a, T, E, F>(
+    conn: &mut Connection,
+    isolation: Option<IsolationLevel>,
+    f: F,
+) -> std::result::Result<T, E>
+where
+    F: for<'c> FnOnce(
+            &'c mut Connection,
+            Outlives<'c, 'a>,
+        ) -> BoxFuture<'c, std::result::Result<T, E>>
+        + Send,
+    T: Send,
+    E: From<Error> + Send,
+{
+    match run_once(conn, isolation, f, false).await {
+        Ok(value) => Ok(value),
+        Err(Failed::Err(err)) => Err(err),
+        Err(Failed::Conflict) => unreachable!("conflicts are only reported when retrying"),
+    }
+}
+
+/// Passed to the closures of [`run_on`], implying that what they borrow (`'a`) outlives the
+/// transaction they run in (`'c`)
+pub(crate) type Outlives<'c, 'a> = PhantomData<&'c &'a ()>;
+
+/// Why running a transaction failed
+enum Failed<E> {
+    /// The transaction ran into a serialization failure or a deadlock and has to be run again
+    Conflict,
+    Err(E),
+}
+
+impl<E> From<E> for Failed<E> {
+    fn from(err: E) -> Self {
+        Self::Err(err)
+    }
+}
+
+/// Runs `f` inside of a transaction on `conn` once, see [`run_on`]. With `retry` set, a
+/// transaction running into a serialization failure or a deadlock is rolled back and fails with
+/// [`Failed::Conflict`].
+async fn run_once<'a, T, E, F>(
+    conn: &mut Connection,
+    isolation: Option<IsolationLevel>,
+    f: F,
+    retry: bool,
+) -> std::result::Result<T, Failed<E>>
+where
+    F: for<'c> FnOnce(
+            &'c mut Connection,
+            Outlives<'c, 'a>,
+        ) -> BoxFuture<'c, std::result::Result<T, E>>
+        + Send,
+    T: Send,
+    E: From<Error> + Send,
+{
+    let connection = address(conn);
+    let nested = SCOPE.with(|scope| {
+        scope
+            .borrow()
+            .as_ref()
+            .is_some_and(|scope| scope.connection == connection)
+    });
+
+    let mut tx = begin(conn, isolation).await.map_err(error::<E>)?;
+
+    let (res, scope) = Scoped::new(f(&mut tx, PhantomData), connection).await;
+
+    if nested && scope.conflicted {
+        conflict();
+    }
+
+    if retry && scope.conflicted {
+        tx.rollback().await.map_err(error::<E>)?;
+        return Err(Failed::Conflict);
+    }
+
+    let value = match res {
+        Ok(value) => value,
+        Err(err) => {
+            tx.rollback().await.map_err(error::<E>)?;
+            return Err(err.into());
+        }
+    };
+
+    if nested {
+        tx.commit().await.map_err(error::<E>)?;
+        scope.deferred.into_iter().for_each(defer);
+        return Ok(value);
+    }
+
+    for hooks in &scope.deferred {
+        if let Err(err) = hooks(HookStage::PreCommit).await {
+            tx.rollback().await.map_err(error::<E>)?;
+            return Err(E::from(err).into());
+        }
+    }
+
+    // serializable transactions may fail on commit only
+    if let Err(err) = tx.commit().await {
+        let err = QueryError::from(err);
+
+        if retry && err.is_serialization_failure() {
+            return Err(Failed::Conflict);
+        }
+
+        return Err(E::from(Error::Query(err)).into());
+    }
+
+    for hooks in &scope.deferred {
+        hooks(HookStage::PostCommit).await.map_err(E::from)?;
+    }
+
+    Ok(value)
+}
+
+/// Begins a transaction on `conn`, setting its isolation level if requested.
+pub(crate) async fn begin(
+    conn: &mut Connection,
+    isolation: Option<IsolationLevel>,
+) -> sqlx::Result<sqlx::Transaction<'_, crate::Driver>> {
+    // mysql applies the isolation level to the next transaction, postgres to the current one
+    if let (Some(isolation), true) = (isolation, cfg!(feature = "mysql")) {
+        sqlx::query(isolation.sql()).execute(&mut *conn).await?;
+    }
+
+    let mut tx = conn.begin().await?;
+
+    if let (Some(isolation), true) = (isolation, cfg!(feature = "postgres")) {
+        sqlx::query(isolation.sql()).execute(&mut *tx).await?;
+    }
+
+    Ok(tx)
+}
+
+fn error<E: From<Error>>(err: sqlx::Error) -> E {
+    Error::Query(QueryError::from(err)).into()
+}
+
+/// Hooks of a single query, deferred until the surrounding transaction commits
+pub(crate) type Deferred =
+    Arc<dyn Fn(HookStage) -> BoxFuture<'static, crate::Result<()>> + Send + Sync>;
+
+/// The address of a connection, identifying the connection of a transaction while it is borrowed
+fn address(conn: &Connection) -> usize {
+    conn as *const Connection as usize
+}
+
+/// The state of the transaction running on a task
+#[derive(Default)]
+struct Scope {
+    /// The connection the transaction runs on, see [`address`]
+    connection: usize,
+    /// The hooks deferred until the transaction commits
+    deferred: Vec<Deferred>,
+    /// Whether a query ran into a serialization failure or a deadlock
+    conflicted: bool,
+}
+
+thread_local! {
+    static SCOPE: RefCell<Option<Scope>> = const { RefCell::new(None) };
+}
+
+/// Defers hooks until the transaction currently running on this task commits. Hooks deferred
+/// outside of [`transaction`] are dropped.
+pub(crate) fn defer(hooks: Deferred) {
+    SCOPE.with(|scope| {
+        if let Some(scope) = scope.borrow_mut().as_mut() {
+            scope.deferred.push(hooks);
+        }
+    })
+}
+
+/// Records that a query of the transaction currently running on this task ran into a
+/// serialization failure or a deadlock, see [`transaction_with`]
+pub(crate) fn conflict() {
+    SCOPE.with(|scope| {
+        if let Some(scope) = scope.borrow_mut().as_mut() {
+            scope.conflicted = true;
+        }
+    })
+}
+
+/// Whether this task is running inside of [`transaction`]
+pub(crate) fn scoped() -> bool {
+    SCOPE.with(|scope| scope.borrow().is_some())
+}
+
+/// Runs `f` once the transaction currently running on this task has been committed, or right away
+/// outside of [`transaction`].
+pub(crate) fn on_commit(f: impl FnOnce() + Send + 'static) {
+    if !scoped() {
+        return f();
+    }
+
+    let f = Mutex::new(Some(f));
+
+    defer(Arc::new(move |stage| {
+        if stage == HookStage::PostCommit {
+            if let Some(f) = f.lock().expect("commit callback poisoned").take() {
+                f();
+            }
+        }
+
+        Box::pin(async { Ok(()) })
+    }));
+}
+
+/// Collects the state of the transaction (e.g. all hooks deferred) while polling the inner
+/// future.
+///
+/// The state is swapped into a thread local for the duration of each poll, this keeps it scoped to
+/// the transaction independently of the async runtime in use.
+struct Scoped<'c, R> {
+    inner: BoxFuture<'c, R>,
+    scope: Scope,
+}
+
+impl<'c, R> Scoped<'c, R> {
+    fn new(inner: BoxFuture<'c, R>, connection: usize) -> Self {
+        Self {
+            inner,
+            scope: Scope {
+                connection,
+                ..Scope::default()
+            },
+        }
+    }
+}
+
+impl<'c, R> Future for Scoped<'c, R> {
+    type Output = (R, Scope);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        let outer = SCOPE.with(|s| s.replace(Some(std::mem::take(&mut this.scope))));
+        let poll = this.inner.as_mut().poll(cx);
+        this.scope = SCOPE.with(|s| s.replace(outer)).unwrap_or_default();
+
+        poll.map(|res| (res, std::mem::take(&mut this.scope)))
+    }
+}
@@ -0,0 +1,37 @@
+use std::{cell::RefCell, future::Future};
+
+use crate::{runtime::scoped::Scoped, Table};
+
+thread_local! {
+    static SCHEMA: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Selects the database schema of all tables at runtime.
+///
+/// [`Table::SCHEMA`] is fixed at compile time. Deployments using a schema per tenant can override
+/// it for everything executed within a future using [`SchemaContext::scope`]; the generated queries
+/// then qualify all table names with the schema in scope instead.
+///
+/// ```ignore
+/// let user = SchemaContext::scope("tenant_42", User::read(&pool, &0)).await?;
+/// ```
+///
+/// SQLite has no schemas, the schema in scope is ignored there.
+pub struct SchemaContext;
+
+impl SchemaContext {
+    /// Qualifies all tables used by queries executed by `f` with `schema`
+    pub fn scope<F: Future>(schema: impl Into<String>, f: F) -> impl Future<Output = F::Output> {
+        Scoped::new(&SCHEMA, schema.into(), f)
+    }
+
+    /// Returns the schema in scope, if any
+    pub fn current() -> Option<String> {
+        SCHEMA.with(|schema| schema.borrow().clone())
+    }
+
+    /// Returns the schema of `T`, taking the schema in scope into account
+    pub fn schema<T: Table>() -> String {
+        Self::current().unwrap_or_else(|| T::SCHEMA.to_owned())
+    }
+}
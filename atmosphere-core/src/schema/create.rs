@@ -1,8 +1,9 @@
 use crate::{
     hooks::{self, HookInput, HookStage, Hooks},
-    query::{QueryError, QueryResult},
-    schema::Table,
-    Bind, Error, Result,
+    query::{QueryContext, QueryResult, WriteOutcome},
+    runtime::changes,
+    schema::{Table, Writable},
+    Bind, Result,
 };
 
 use async_trait::async_trait;
@@ -15,14 +16,15 @@ use sqlx::{database::HasArguments, Executor, IntoArguments};
 /// using a given executor. The trait ensures that all necessary hooks are executed at the appropriate stages
 /// of the operation.
 #[async_trait]
-pub trait Create: Table + Bind + Hooks + Sync + 'static {
+pub trait Create: Table + Writable + Bind + Hooks + Sync + 'static {
     /// Creates a new row in the database. This method builds the SQL insert query,
     /// binds the necessary values, executes the query, and triggers the relevant hooks at different stages
     /// (pre-binding and post-execution).
-    async fn create<'e, E>(
-        &mut self,
-        executor: E,
-    ) -> Result<<crate::Driver as sqlx::Database>::QueryResult>
+    ///
+    /// If the primary key is generated by the database (`#[sql(pk, auto)]`), the generated key is
+    /// written back into `self` after the insertion. The same applies to columns set by their
+    /// database default (`#[sql(default)]`), except on MySQL which does not support `RETURNING`.
+    async fn create<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
@@ -32,33 +34,59 @@ pub trait Create: Table + Bind + Hooks + Sync + 'static {
 #[async_trait]
 impl<T> Create for T
 where
-    T: Table + Bind + Hooks + Sync + 'static,
+    T: Table + Writable + Bind + Hooks + Sync + 'static,
 {
-    async fn create<'e, E>(
-        &mut self,
-        executor: E,
-    ) -> Result<<crate::Driver as sqlx::Database>::QueryResult>
+    async fn create<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let query = crate::runtime::sql::insert::<T>();
+        let mut query = crate::runtime::sql::insert::<T>();
 
-        hooks::execute(HookStage::PreBind, &query, HookInput::Row(self)).await?;
+        hooks::prepare(&mut query, HookInput::Row(self)).await?;
 
         let mut builder = sqlx::query(query.sql());
 
         for c in query.bindings().columns() {
-            builder = self.bind(c, builder).unwrap();
+            builder = self.bind(c, builder)?;
         }
 
-        let res = builder
-            .persistent(false)
-            .execute(executor)
-            .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
+        let builder = query.bind_values(builder)?;
+
+        hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+
+        let res = if crate::runtime::sql::generated::<T>() {
+            returning(builder, executor, query.context()).await
+        } else {
+            builder
+                .persistent(false)
+                .execute(executor)
+                .await
+                .map_err(|err| query.context().error(err))
+                .map(|res| (res, None))
+        };
+
+        let res = match res {
+            Ok((res, row)) => {
+                if let Some(row) = row {
+                    *self = row;
+                }
+
+                let outcome = match T::PRIMARY_KEY.auto {
+                    true => WriteOutcome::inserted(res),
+                    false => WriteOutcome::from(res),
+                };
+
+                #[cfg(feature = "mysql")]
+                if let Some(id) = outcome.last_insert_id {
+                    self.set_last_insert_id(id)?;
+                }
+
+                Ok(outcome)
+            }
+            Err(e) => Err(e),
+        };
 
         hooks::execute(
             HookStage::PostExec,
@@ -67,6 +95,42 @@ where
         )
         .await?;
 
+        if res.is_ok() {
+            changes::publish(query.op, self.pk(), Some(self));
+        }
+
         res
     }
 }
+
+/// Executes an insertion and collects the query result as well as the row returned by the
+/// `RETURNING` clause (if any).
+async fn returning<'q, 'c, T, E>(
+    query: sqlx::query::Query<'q, crate::Driver, <crate::Driver as HasArguments<'q>>::Arguments>,
+    executor: E,
+    context: QueryContext,
+) -> Result<(<crate::Driver as sqlx::Database>::QueryResult, Option<T>)>
+where
+    T: Table,
+    E: Executor<'c, Database = crate::Driver> + 'q,
+{
+    use futures::TryStreamExt;
+    use sqlx::Either;
+
+    // `fetch_many` is deprecated because of multi statement support, we only ever execute a
+    // single statement here but require both the affected rows and the returned row.
+    #[allow(deprecated)]
+    let mut stream = query.persistent(false).fetch_many(executor);
+
+    let mut result = <crate::Driver as sqlx::Database>::QueryResult::default();
+    let mut row = None;
+
+    while let Some(step) = stream.try_next().await.map_err(|err| context.error(err))? {
+        match step {
+            Either::Left(res) => result.extend([res]),
+            Either::Right(r) => row = Some(T::from_row(&r).map_err(|err| context.error(err))?),
+        }
+    }
+
+    Ok((result, row))
+}
@@ -0,0 +1,41 @@
+use crate::{
+    query::QueryError,
+    schema::{Table, Writable},
+    Bind, Error, Result,
+};
+
+use async_trait::async_trait;
+use sqlx::{Database, Executor};
+
+/// Data definition (DDL) of a table.
+///
+/// Derives the `CREATE TABLE` statement of a table from its columns, so simple tables don't need
+/// a migration that has to be kept in sync with the struct by hand. Column types are the types the
+/// driver uses for the rust types of the fields, `Option` fields are nullable.
+///
+/// ```ignore
+/// User::create_table(&pool).await?;
+/// ```
+#[async_trait]
+pub trait Ddl: Table + Writable + Bind + Sync + 'static {
+    /// Returns the `CREATE TABLE IF NOT EXISTS` statement of this table, including its primary
+    /// key, unique, check and foreign key constraints, followed by the statements creating its
+    /// indexes.
+    fn create_table_sql() -> String {
+        crate::runtime::sql::create_table::<Self>()
+    }
+
+    /// Creates this table unless it already exists.
+    async fn create_table<'e, E>(executor: E) -> Result<<crate::Driver as Database>::QueryResult>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+    {
+        executor
+            .execute(Self::create_table_sql().as_str())
+            .await
+            .map_err(QueryError::from)
+            .map_err(Error::Query)
+    }
+}
+
+impl<T: Table + Writable + Bind + Sync + 'static> Ddl for T {}
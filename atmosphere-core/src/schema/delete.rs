@@ -1,12 +1,20 @@
 use crate::{
     hooks::{self, Hooks},
-    query::{QueryError, QueryResult},
-    schema::Table,
-    Bind, Error, Result,
+    query::{self, Lock, Query, QueryError, QueryResult, WriteOutcome},
+    runtime::{
+        changes,
+        sql::{
+            self,
+            dialect::{Current, Dialect},
+        },
+        transaction,
+    },
+    schema::{Deletable, Table},
+    Bind, Result,
 };
 
 use async_trait::async_trait;
-use sqlx::{database::HasArguments, Database, Executor, IntoArguments};
+use sqlx::{database::HasArguments, Acquire, Executor, IntoArguments};
 
 /// Trait for deleting rows from a database.
 ///
@@ -14,15 +22,16 @@ use sqlx::{database::HasArguments, Database, Executor, IntoArguments};
 /// trait can delete entities either by their instance or by their primary key. The trait ensures
 /// proper execution of hooks at various stages of the delete operation, enhancing flexibility and
 /// allowing for custom behavior during the deletion process.
+///
+/// Tables with a `#[sql(timestamp = deleted)]` column are soft deleted: [`Delete::delete`] and
+/// [`Delete::delete_by`] set the deletion timestamp rather than removing the row, and generated
+/// `SELECT` queries skip rows where it is set. Use [`Delete::hard_delete`] to remove rows for good.
 #[async_trait]
-pub trait Delete: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
+pub trait Delete: Table + Deletable + Bind + Hooks + Send + Sync + Unpin + 'static {
     /// Deletes the row represented by the instance from the database. Builds and executes a delete
     /// query and triggers hooks at appropriate stages (e.g., before binding, before execution,
     /// after execution).
-    async fn delete<'e, E>(
-        &mut self,
-        executor: E,
-    ) -> Result<<crate::Driver as Database>::QueryResult>
+    async fn delete<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
@@ -30,10 +39,38 @@ pub trait Delete: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
 
     /// Deletes a row from the database based on its primary key. This method is particularly
     /// useful for deleting entities when only the primary key is available.
-    async fn delete_by<'e, E>(
-        executor: E,
-        pk: &Self::PrimaryKey,
-    ) -> Result<<crate::Driver as Database>::QueryResult>
+    async fn delete_by<'e, E>(executor: E, pk: &Self::PrimaryKey) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send;
+
+    /// Deletes the rows with any of the primary keys `pks`, soft deleting them like
+    /// [`Delete::delete_by`].
+    ///
+    /// Large batches are split into several statements within the bind parameter limit of the
+    /// driver (see [`crate::query::batch_size`]), executed in a single transaction on a connection
+    /// of `acquire` (a savepoint if `acquire` is a transaction already): either all rows are
+    /// deleted or none. A change event is published for each deleted row once the transaction
+    /// commits. On MySQL, which does not support `RETURNING`, the deleted rows are read and locked
+    /// before deleting them, ignoring predicates hooks add to the delete statement.
+    async fn delete_many<'a, A>(acquire: A, pks: &[Self::PrimaryKey]) -> Result<WriteOutcome>
+    where
+        A: Acquire<'a, Database = crate::Driver> + Send,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send;
+
+    /// Permanently deletes the row represented by the instance from the database, even if the
+    /// table supports soft deletion.
+    async fn hard_delete<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send;
+
+    /// Permanently deletes a row from the database based on its primary key, even if the table
+    /// supports soft deletion.
+    async fn hard_delete_by<'e, E>(executor: E, pk: &Self::PrimaryKey) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
@@ -43,90 +80,232 @@ pub trait Delete: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
 #[async_trait]
 impl<T> Delete for T
 where
-    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    T: Table + Deletable + Bind + Hooks + Send + Sync + Unpin + 'static,
 {
-    async fn delete<'e, E>(
-        &mut self,
-        executor: E,
-    ) -> Result<<crate::Driver as Database>::QueryResult>
+    async fn delete<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let query = crate::runtime::sql::delete::<T>();
-
-        hooks::execute(
-            hooks::HookStage::PreBind,
-            &query,
-            hooks::HookInput::Row(self),
-        )
-        .await?;
+        delete_row(self, crate::runtime::sql::delete::<T>(), executor).await
+    }
 
-        let mut sql = sqlx::query(query.sql());
+    async fn delete_by<'e, E>(executor: E, pk: &Self::PrimaryKey) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        delete_pk::<T, E>(crate::runtime::sql::delete::<T>(), executor, pk).await
+    }
 
-        for c in query.bindings.columns() {
-            sql = self.bind(c, sql).unwrap();
+    async fn delete_many<'a, A>(acquire: A, pks: &[Self::PrimaryKey]) -> Result<WriteOutcome>
+    where
+        A: Acquire<'a, Database = crate::Driver> + Send,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        if pks.is_empty() {
+            return Ok(WriteOutcome::default());
         }
 
-        hooks::execute(hooks::HookStage::PreExec, &query, hooks::HookInput::None).await?;
+        let mut conn = acquire.acquire().await.map_err(QueryError::from)?;
+
+        transaction::run_on(&mut conn, None, |conn, _| {
+            Box::pin(async move {
+                let mut outcome = WriteOutcome::default();
+
+                for chunk in pks.chunks(query::chunk_size(1, sql::tenants::<T>())) {
+                    let mut query =
+                        sql::delete_in_returning::<T>(T::PRIMARY_KEY.as_col(), chunk.len());
+
+                    hooks::prepare(&mut query, hooks::HookInput::None).await?;
+
+                    // without `RETURNING` (MySQL), the rows are read and locked before deleting
+                    let locked = match Current::RETURNING {
+                        true => None,
+                        false => Some(locked::<T>(&mut *conn, chunk).await?),
+                    };
+
+                    hooks::execute(hooks::HookStage::PreExec, &query, hooks::HookInput::None)
+                        .await?;
+
+                    let mut sql = sqlx::query(query.sql());
+
+                    for pk in chunk {
+                        sql = sql.bind(pk);
+                    }
+
+                    let sql = query.bind_values(sql)?.persistent(false);
+
+                    let (res, deleted) = match locked {
+                        Some(rows) => {
+                            let res = sql.execute(&mut *conn).await.map(WriteOutcome::from);
+                            (res, rows)
+                        }
+                        None => {
+                            let rows = sql.fetch_all(&mut *conn).await.and_then(|rows| {
+                                rows.iter()
+                                    .map(T::from_row)
+                                    .collect::<sqlx::Result<Vec<T>>>()
+                            });
+
+                            match rows {
+                                Ok(rows) => {
+                                    let outcome = WriteOutcome {
+                                        rows_affected: rows.len() as u64,
+                                        ..WriteOutcome::default()
+                                    };
+
+                                    (Ok(outcome), rows)
+                                }
+                                Err(err) => (Err(err), vec![]),
+                            }
+                        }
+                    };
+
+                    let res = res.map_err(|err| query.context().error(err));
+
+                    hooks::execute(
+                        hooks::HookStage::PostExec,
+                        &query,
+                        QueryResult::Execution(&res).into(),
+                    )
+                    .await?;
+
+                    outcome.rows_affected += res?.rows_affected;
 
-        let res = sql
-            .persistent(false)
-            .execute(executor)
-            .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
+                    for row in &deleted {
+                        changes::publish::<T>(query.op, row.pk(), None);
+                    }
+                }
 
-        hooks::execute(
-            hooks::HookStage::PostExec,
-            &query,
-            QueryResult::Execution(&res).into(),
-        )
-        .await?;
+                Ok(outcome)
+            })
+        })
+        .await
+    }
 
-        res
+    async fn hard_delete<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        delete_row(self, crate::runtime::sql::hard_delete::<T>(), executor).await
     }
 
-    async fn delete_by<'e, E>(
-        executor: E,
-        pk: &Self::PrimaryKey,
-    ) -> Result<<crate::Driver as Database>::QueryResult>
+    async fn hard_delete_by<'e, E>(executor: E, pk: &Self::PrimaryKey) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let query = crate::runtime::sql::delete::<T>();
-
-        hooks::execute(
-            hooks::HookStage::PreBind,
-            &query,
-            hooks::HookInput::PrimaryKey(pk),
-        )
-        .await?;
-
-        assert!(query.bindings().columns().len() == 1);
-        assert!(query.bindings().columns()[0].field() == Self::PRIMARY_KEY.field);
-        assert!(query.bindings().columns()[0].sql() == Self::PRIMARY_KEY.sql);
-
-        hooks::execute(hooks::HookStage::PreExec, &query, hooks::HookInput::None).await?;
-
-        let res = sqlx::query(query.sql())
-            .bind(pk)
-            .persistent(false)
-            .execute(executor)
-            .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
-
-        hooks::execute(
-            hooks::HookStage::PostExec,
-            &query,
-            QueryResult::Execution(&res).into(),
-        )
-        .await?;
-
-        res
+        delete_pk::<T, E>(crate::runtime::sql::hard_delete::<T>(), executor, pk).await
+    }
+}
+
+async fn delete_row<'e, T, E>(row: &mut T, mut query: Query<T>, executor: E) -> Result<WriteOutcome>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    hooks::prepare(&mut query, hooks::HookInput::Row(row)).await?;
+
+    let mut sql = sqlx::query(query.sql());
+
+    for c in query.bindings.columns() {
+        sql = row.bind(c, sql)?;
+    }
+
+    let sql = query.bind_values(sql)?;
+
+    hooks::execute(hooks::HookStage::PreExec, &query, hooks::HookInput::None).await?;
+
+    let res = sql
+        .persistent(false)
+        .execute(executor)
+        .await
+        .map(WriteOutcome::from)
+        .map_err(|err| query.context().error(err));
+
+    hooks::execute(
+        hooks::HookStage::PostExec,
+        &query,
+        QueryResult::Execution(&res).into(),
+    )
+    .await?;
+
+    if matches!(&res, Ok(done) if done.rows_affected > 0) {
+        changes::publish(query.op, row.pk(), Some(row));
+    }
+
+    res
+}
+
+async fn delete_pk<'e, T, E>(
+    mut query: Query<T>,
+    executor: E,
+    pk: &T::PrimaryKey,
+) -> Result<WriteOutcome>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    hooks::prepare(&mut query, hooks::HookInput::PrimaryKey(pk)).await?;
+
+    assert!(query.bindings().columns().len() == 1);
+    assert!(query.bindings().columns()[0].field() == T::PRIMARY_KEY.field);
+    assert!(query.bindings().columns()[0].sql() == T::PRIMARY_KEY.sql);
+
+    hooks::execute(hooks::HookStage::PreExec, &query, hooks::HookInput::None).await?;
+
+    let sql = sqlx::query(query.sql()).bind(pk);
+
+    let res = query
+        .bind_values(sql)?
+        .persistent(false)
+        .execute(executor)
+        .await
+        .map(WriteOutcome::from)
+        .map_err(|err| query.context().error(err));
+
+    hooks::execute(
+        hooks::HookStage::PostExec,
+        &query,
+        QueryResult::Execution(&res).into(),
+    )
+    .await?;
+
+    if matches!(&res, Ok(done) if done.rows_affected > 0) {
+        changes::publish::<T>(query.op, pk, None);
+    }
+
+    res
+}
+
+/// Reads the rows with any of the primary keys `pks` and locks them until the transaction running
+/// on `conn` ends. An internal read of [`Delete::delete_many`], which is not passed to hooks.
+async fn locked<T>(conn: &mut transaction::Connection, pks: &[T::PrimaryKey]) -> Result<Vec<T>>
+where
+    T: Table + Bind + Send + Unpin + 'static,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    let query = sql::select_in_for_update::<T>(T::PRIMARY_KEY.as_col(), pks.len(), Lock::Wait);
+
+    let mut sql = sqlx::query_as(query.sql());
+
+    for pk in pks {
+        sql = sql.bind(pk);
     }
+
+    query
+        .bind_values(sql)?
+        .persistent(false)
+        .fetch_all(conn)
+        .await
+        .map_err(|err| query.context().error(err))
 }
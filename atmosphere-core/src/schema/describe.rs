@@ -0,0 +1,377 @@
+//! Runtime schema introspection
+//!
+//! [`TableDescription`] describes a table and its columns without being generic over the table,
+//! so tooling like admin interfaces can enumerate and inspect tables at runtime. The description
+//! of a single table is returned by [`Table::describe`], the descriptions of all tables deriving
+//! `Schema` within the binary by [`tables`].
+//!
+//! ```ignore
+//! for table in atmosphere::describe::tables() {
+//!     println!("{}.{} ({} columns)", table.schema, table.table, table.columns.len());
+//! }
+//! ```
+//!
+//! Tables and the relationships between them can also be rendered as text based diagrams, which
+//! can be embedded into documentation or reviews without maintaining a separate model:
+//!
+//! - [`mermaid`] renders a Mermaid `erDiagram`
+//! - [`dbml`] renders DBML, as used by dbdiagram.io and similar tools
+//!
+//! ```ignore
+//! println!("{}", atmosphere::describe::mermaid::<(Forest, Tree)>());
+//! ```
+
+use std::fmt::Write;
+
+use crate::{
+    column::{ColumnType, ReferentialAction, TimestampKind},
+    Bind, SchemaContext, Table, TableVisitor, Tables,
+};
+
+/// A table, as returned by [`Table::describe`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableDescription {
+    /// The schema of the table, taking [`SchemaContext`] into account
+    pub schema: String,
+    /// The name of the table, see [`Table::name`]
+    pub table: String,
+    /// The columns of the table, starting with its primary key
+    pub columns: Vec<ColumnDescription>,
+    /// The comment describing the table, if any
+    pub comment: Option<&'static str>,
+}
+
+impl TableDescription {
+    /// Describes the table `T`
+    pub fn of<T: Table>() -> Self {
+        let pk = ColumnDescription {
+            auto: T::PRIMARY_KEY.auto,
+            comment: T::PRIMARY_KEY.comment,
+            ..ColumnDescription::new(
+                T::PRIMARY_KEY.field,
+                T::PRIMARY_KEY.sql,
+                ColumnKind::PrimaryKey,
+                Some(ColumnType::of::<T::PrimaryKey>()),
+            )
+        };
+
+        let mut columns = vec![pk];
+
+        for fk in T::FOREIGN_KEYS {
+            columns.push(ColumnDescription {
+                unique: fk.unique,
+                references: fk.references.map(|references| Reference {
+                    schema: SchemaContext::current()
+                        .unwrap_or_else(|| references.schema.to_owned()),
+                    table: references.table,
+                    column: references.column,
+                    on_delete: fk.on_delete,
+                    on_update: fk.on_update,
+                }),
+                comment: fk.comment,
+                ..ColumnDescription::new(fk.field, fk.sql, ColumnKind::ForeignKey, fk.ty)
+            });
+        }
+
+        for data in T::DATA_COLUMNS {
+            columns.push(ColumnDescription {
+                unique: data.unique,
+                comment: data.comment,
+                ..ColumnDescription::new(data.field, data.sql, ColumnKind::Data, data.ty)
+            });
+        }
+
+        for ts in T::TIMESTAMP_COLUMNS {
+            columns.push(ColumnDescription {
+                comment: ts.comment,
+                ..ColumnDescription::new(ts.field, ts.sql, ColumnKind::Timestamp(ts.kind), ts.ty)
+            });
+        }
+
+        Self {
+            schema: SchemaContext::schema::<T>(),
+            table: T::name().into_owned(),
+            columns,
+            comment: T::COMMENT,
+        }
+    }
+}
+
+/// A column of a [`TableDescription`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnDescription {
+    /// The rust field name of the model
+    pub field: &'static str,
+    /// The sql column name
+    pub name: &'static str,
+    /// The role of the column
+    pub kind: ColumnKind,
+    /// The name of the sql type, if known
+    pub ty: Option<String>,
+    /// Whether the column accepts `NULL`
+    pub nullable: bool,
+    /// Whether the column holds unique values
+    pub unique: bool,
+    /// Whether the value is generated by the database
+    pub auto: bool,
+    /// The column referenced by a foreign key
+    pub references: Option<Reference>,
+    /// The comment describing the column, if any
+    pub comment: Option<&'static str>,
+}
+
+impl ColumnDescription {
+    fn new(
+        field: &'static str,
+        name: &'static str,
+        kind: ColumnKind,
+        ty: Option<ColumnType>,
+    ) -> Self {
+        Self {
+            field,
+            name,
+            kind,
+            ty: ty.map(|ty| ty.name()),
+            nullable: ty.is_some_and(|ty| ty.nullable),
+            unique: false,
+            auto: false,
+            references: None,
+            comment: None,
+        }
+    }
+}
+
+/// The role of a column within its table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnKind {
+    PrimaryKey,
+    ForeignKey,
+    Data,
+    Timestamp(TimestampKind),
+}
+
+/// The primary key column referenced by a foreign key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reference {
+    /// The schema of the referenced table
+    pub schema: String,
+    /// The name of the referenced table
+    pub table: &'static str,
+    /// The sql name of the referenced primary key column
+    pub column: &'static str,
+    /// The action taken when the referenced row is deleted
+    pub on_delete: ReferentialAction,
+    /// The action taken when the referenced row is updated
+    pub on_update: ReferentialAction,
+}
+
+/// Returns the descriptions of all tables deriving `Schema` within the binary, sorted by schema
+/// and name.
+pub fn tables() -> Vec<TableDescription> {
+    let mut tables: Vec<TableDescription> = inventory::iter::<Registration>
+        .into_iter()
+        .map(|registration| (registration.describe)())
+        .collect();
+
+    tables.sort_by(|a, b| (&a.schema, &a.table).cmp(&(&b.schema, &b.table)));
+
+    tables
+}
+
+/// Registers a table with [`tables`], submitted by `#[derive(Schema)]`
+#[doc(hidden)]
+pub struct Registration {
+    describe: fn() -> TableDescription,
+}
+
+impl Registration {
+    pub const fn new<T: Table>() -> Self {
+        Self {
+            describe: TableDescription::of::<T>,
+        }
+    }
+}
+
+inventory::collect!(Registration);
+
+/// Renders the tables `T` (a tuple of tables) as a Mermaid `erDiagram`.
+pub fn mermaid<T: Tables>() -> String {
+    let tables = descriptions::<T>();
+
+    let mut diagram = String::from("erDiagram\n");
+
+    for table in &tables {
+        let _ = writeln!(diagram, "    {} {{", table.table);
+
+        for column in &table.columns {
+            let keys = [
+                (column.kind == ColumnKind::PrimaryKey, "PK"),
+                (column.references.is_some(), "FK"),
+                (column.unique, "UK"),
+            ]
+            .into_iter()
+            .filter_map(|(set, key)| set.then_some(key))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+            let _ = write!(diagram, "        {} {}", type_name(column), column.name);
+
+            if !keys.is_empty() {
+                let _ = write!(diagram, " {keys}");
+            }
+
+            if column.nullable {
+                let _ = write!(diagram, " \"nullable\"");
+            }
+
+            diagram.push('\n');
+        }
+
+        diagram.push_str("    }\n");
+    }
+
+    for table in &tables {
+        for column in &table.columns {
+            let Some(reference) = &column.references else {
+                continue;
+            };
+
+            let parent = match column.nullable {
+                true => "|o",
+                false => "||",
+            };
+
+            let child = match column.unique {
+                true => "o|",
+                false => "o{",
+            };
+
+            let _ = writeln!(
+                diagram,
+                "    {} {parent}--{child} {} : \"{}\"",
+                reference.table, table.table, column.name
+            );
+        }
+    }
+
+    diagram
+}
+
+/// Renders the tables `T` (a tuple of tables) as DBML.
+pub fn dbml<T: Tables>() -> String {
+    let tables = descriptions::<T>();
+
+    let mut diagram = String::new();
+
+    for table in &tables {
+        let _ = writeln!(diagram, "Table {}.{} {{", table.schema, table.table);
+
+        for column in &table.columns {
+            let primary_key = column.kind == ColumnKind::PrimaryKey;
+
+            let mut settings = vec![];
+
+            if primary_key {
+                settings.push("pk");
+            }
+
+            if column.auto {
+                settings.push("increment");
+            }
+
+            if !column.nullable && !primary_key {
+                settings.push("not null");
+            }
+
+            if column.unique {
+                settings.push("unique");
+            }
+
+            let note = column
+                .comment
+                .map(|comment| format!("note: {}", quoted(comment)));
+
+            if let Some(note) = &note {
+                settings.push(note);
+            }
+
+            let ty = type_name(column);
+
+            let ty = match ty.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                true => ty.to_owned(),
+                false => format!("\"{ty}\""),
+            };
+
+            let _ = write!(diagram, "  {} {ty}", column.name);
+
+            if !settings.is_empty() {
+                let _ = write!(diagram, " [{}]", settings.join(", "));
+            }
+
+            diagram.push('\n');
+        }
+
+        if let Some(comment) = table.comment {
+            let _ = writeln!(diagram, "\n  Note: {}", quoted(comment));
+        }
+
+        diagram.push_str("}\n\n");
+    }
+
+    for table in &tables {
+        for column in &table.columns {
+            let Some(reference) = &column.references else {
+                continue;
+            };
+
+            let relation = match column.unique {
+                true => "-",
+                false => ">",
+            };
+
+            let _ = writeln!(
+                diagram,
+                "Ref: {}.{}.{} {relation} {}.{}.{} [delete: {}, update: {}]",
+                table.schema,
+                table.table,
+                column.name,
+                reference.schema,
+                reference.table,
+                reference.column,
+                action(reference.on_delete),
+                action(reference.on_update),
+            );
+        }
+    }
+
+    diagram
+}
+
+/// `s` as a DBML string
+fn quoted(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn type_name(column: &ColumnDescription) -> &str {
+    column.ty.as_deref().unwrap_or("unknown")
+}
+
+fn action(action: ReferentialAction) -> String {
+    action.sql().to_lowercase()
+}
+
+fn descriptions<T: Tables>() -> Vec<TableDescription> {
+    let mut descriptions = Descriptions(vec![]);
+
+    T::visit(&mut descriptions);
+
+    descriptions.0
+}
+
+struct Descriptions(Vec<TableDescription>);
+
+impl TableVisitor for Descriptions {
+    fn visit<T: Bind>(&mut self) {
+        self.0.push(T::describe());
+    }
+}
@@ -5,17 +5,36 @@
 //! structures, column details, and primary and foreign key relationships. This is essential
 //! for representing and manipulating database schema in a type-safe and Rust-idiomatic way.
 
+use std::borrow::Cow;
+
 use sqlx::{Database, Encode, FromRow, Type};
 
+use crate::Bind;
+
+mod context;
 mod create;
+mod ddl;
 mod delete;
+pub mod describe;
+mod patch;
+mod preview;
 mod read;
+mod tracked;
 mod update;
+#[cfg(feature = "postgres")]
+mod view;
 
+pub use context::SchemaContext;
 pub use create::Create;
+pub use ddl::Ddl;
 pub use delete::Delete;
+pub use patch::Patch;
+pub use preview::{Preview, SqlPreview};
 pub use read::Read;
+pub use tracked::{Diff, Tracked};
 pub use update::Update;
+#[cfg(feature = "postgres")]
+pub use view::MaterializedView;
 
 pub use self::column::{Column, DataColumn, ForeignKey, PrimaryKey, TimestampColumn};
 
@@ -37,6 +56,9 @@ where
     const SCHEMA: &'static str;
     /// The name of the table.
     const TABLE: &'static str;
+    /// The named database holding the table (`#[table(.., database = "..")]`), `None` for the
+    /// default database. See [`Databases`](crate::Databases).
+    const DATABASE: Option<&'static str> = None;
 
     /// The primary key column of the table.
     const PRIMARY_KEY: PrimaryKey<Self>;
@@ -46,9 +68,225 @@ where
     const DATA_COLUMNS: &'static [DataColumn<Self>];
     /// An array of timestamp columns.
     const TIMESTAMP_COLUMNS: &'static [TimestampColumn<Self>];
+    /// The uniqueness constraints spanning multiple columns (`#[table(.., unique(a, b))]`).
+    const UNIQUE: &'static [UniqueConstraint] = &[];
+    /// The conditions of the check constraints of the table (`#[table(.., check = "..")]`), in
+    /// addition to the checks of its data columns.
+    const CHECKS: &'static [&'static str] = &[];
+    /// The indexes of the table (`#[sql(index)]` and `#[table(.., index(a, b))]`).
+    const INDEXES: &'static [Index] = &[];
+    /// The comment describing the table (`#[table(.., comment = "..")]`), if any.
+    const COMMENT: Option<&'static str> = None;
 
     /// Returns a reference to the primary key of the table instance.
     fn pk(&self) -> &Self::PrimaryKey;
+
+    /// The name of the table queries are executed against.
+    ///
+    /// This is [`Table::TABLE`] unless the name is decided at runtime by a [`TableName`] provider
+    /// (`#[table(.., dynamic)]`).
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed(Self::TABLE)
+    }
+
+    /// The cache serving reads by primary key (`#[table(.., cache(..))]`), see [`crate::cache`].
+    fn cache() -> Option<&'static dyn crate::cache::RowCache<Self>> {
+        None
+    }
+
+    /// Describes this table and its columns, see [`describe`].
+    fn describe() -> describe::TableDescription {
+        describe::TableDescription::of::<Self>()
+    }
+
+    /// Assigns the id generated by the database for an `auto` primary key.
+    ///
+    /// MySQL does not support `RETURNING`, so the generated key has to be taken from
+    /// `LAST_INSERT_ID()` instead. This is implemented by `#[derive(Schema)]` for tables with an
+    /// `auto` primary key.
+    #[cfg(feature = "mysql")]
+    fn set_last_insert_id(&mut self, id: u64) -> crate::Result<()> {
+        let _ = id;
+        Err(crate::Error::Internal)
+    }
+}
+
+/// Marks tables whose rows are written, as opposed to views.
+///
+/// Implemented by `#[derive(Schema)]` for all tables but views (`#[table(.., view)]` or
+/// `#[table(.., materialized)]`), which are only read. [`Create`] and [`Ddl`] require it, so
+/// writing to a view fails to compile.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is a view, which can only be read",
+    label = "views are read only"
+)]
+pub trait Writable: Table {}
+
+/// Marks tables whose rows are updated, required by [`Update`].
+///
+/// Implemented by `#[derive(Schema)]` for all tables but views and tables opting out using
+/// `#[table(.., deny(update))]`, e.g. append-only tables.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can not be updated",
+    label = "views and tables using `deny(update)` are not updated"
+)]
+pub trait Updatable: Writable {}
+
+/// Marks tables whose rows are deleted, required by [`Delete`].
+///
+/// Implemented by `#[derive(Schema)]` for all tables but views and tables opting out using
+/// `#[table(.., deny(delete))]`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can not be deleted from",
+    label = "views and tables using `deny(delete)` are not deleted from"
+)]
+pub trait Deletable: Writable {}
+
+/// A uniqueness constraint spanning multiple columns of a table.
+///
+/// Declared using `#[table(.., unique(name, location))]`. Besides being part of the `CREATE TABLE`
+/// statement, constraints serve as conflict target of [`Update::upsert_on`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UniqueConstraint {
+    /// The sql names of the columns, in order of declaration
+    pub columns: &'static [&'static str],
+}
+
+impl UniqueConstraint {
+    pub const fn new(columns: &'static [&'static str]) -> Self {
+        Self { columns }
+    }
+}
+
+/// An index of a table.
+///
+/// Declared on single columns using `#[sql(index)]` or on the table using
+/// `#[table(.., index(created_at, desc))]`, where `asc` or `desc` following a column set its order.
+/// Indexes are created along with their table (see [`Ddl`]), added by migrations and checked by
+/// [`validate_schema`](crate::migrate::validate_schema). Tables with full-text searched columns
+/// additionally declare a `GIN` index of their document (see [`crate::fulltext`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Index {
+    /// The name of the index, `<table>_<columns>_idx`
+    pub name: &'static str,
+    /// The indexed columns, in order
+    pub columns: &'static [IndexColumn],
+    /// The index method (e.g. `GIN`), `None` for the default of the database
+    pub using: Option<&'static str>,
+}
+
+impl Index {
+    pub const fn new(name: &'static str, columns: &'static [IndexColumn]) -> Self {
+        Self {
+            name,
+            columns,
+            using: None,
+        }
+    }
+
+    /// Use the index method `method` (e.g. `GIN`) instead of the default of the database
+    pub const fn using(mut self, method: &'static str) -> Self {
+        self.using = Some(method);
+        self
+    }
+}
+
+/// A column of an [`Index`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IndexColumn {
+    /// The sql name of the column, or a parenthesized expression
+    pub sql: &'static str,
+    /// Whether the column is sorted in descending order
+    pub descending: bool,
+}
+
+impl IndexColumn {
+    /// The column `sql`, sorted in ascending order
+    pub const fn asc(sql: &'static str) -> Self {
+        Self {
+            sql,
+            descending: false,
+        }
+    }
+
+    /// The column `sql`, sorted in descending order
+    pub const fn desc(sql: &'static str) -> Self {
+        Self {
+            sql,
+            descending: true,
+        }
+    }
+}
+
+/// Decides the name of a table at runtime.
+///
+/// Tables declared with `#[table(schema = "..", name = "..", dynamic)]` consult this trait whenever
+/// a query is generated instead of using the `name` given, e.g. to address monthly partitions:
+///
+/// ```ignore
+/// #[derive(Schema)]
+/// #[table(schema = "public", name = "events", dynamic)]
+/// struct Event {
+///     #[sql(pk)]
+///     id: i32,
+/// }
+///
+/// impl TableName for Event {
+///     fn table_name() -> Cow<'static, str> {
+///         format!("events_{}", Utc::now().format("%Y_%m")).into()
+///     }
+/// }
+/// ```
+pub trait TableName: Table {
+    /// The name of the table to execute the next query against
+    fn table_name() -> Cow<'static, str>;
+}
+
+/// A set of tables as a tuple, e.g. `(User, Post)`.
+///
+/// Used by APIs operating on multiple tables at once, like [`crate::migrate::validate_schema`].
+pub trait Tables {
+    /// Visits all tables, in order
+    fn visit<V: TableVisitor>(visitor: &mut V);
+}
+
+/// Operates on each table of [`Tables`].
+pub trait TableVisitor {
+    /// Visits the table `T`
+    fn visit<T: Bind>(&mut self);
+}
+
+macro_rules! tables {
+    ($($table:ident),+) => {
+        impl<$($table: Bind),+> Tables for ($($table,)+) {
+            fn visit<V: TableVisitor>(visitor: &mut V) {
+                $(visitor.visit::<$table>();)+
+            }
+        }
+    };
+}
+
+tables!(A);
+tables!(A, B);
+tables!(A, B, C);
+tables!(A, B, C, D);
+tables!(A, B, C, D, E);
+tables!(A, B, C, D, E, F);
+tables!(A, B, C, D, E, F, G);
+tables!(A, B, C, D, E, F, G, H);
+tables!(A, B, C, D, E, F, G, H, I);
+tables!(A, B, C, D, E, F, G, H, I, J);
+tables!(A, B, C, D, E, F, G, H, I, J, K);
+tables!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Decodes a table row from columns that were selected under an alias.
+///
+/// Aliased columns are labeled as `"<alias>.<column>"`. This allows to decode entities of multiple
+/// tables from the rows of a single query (e.g. a `JOIN`), where the column names would otherwise
+/// be ambiguous. This trait is implemented by `#[derive(Schema)]`.
+pub trait FromAliasedRow: Table {
+    /// Decodes `Self` from the columns labeled with the given `alias`
+    fn from_aliased_row(row: &<crate::Driver as Database>::Row, alias: &str) -> sqlx::Result<Self>;
 }
 
 /// Trait representing an Entity that maps to a database table.
@@ -68,7 +306,66 @@ impl<E: Create + Read + Update + Delete> Entity for E {}
 /// and execution within the framework.
 pub mod column {
     use crate::Table;
-    use std::marker::PhantomData;
+    use std::{fmt, marker::PhantomData};
+
+    use sqlx::{Database, Type, TypeInfo};
+
+    /// The sql type of a column, as reported by the driver for the rust type of its field.
+    #[derive(Clone, Copy)]
+    pub struct ColumnType {
+        info: fn() -> <crate::Driver as Database>::TypeInfo,
+        /// Whether the column accepts `NULL` (the field is an `Option`)
+        pub nullable: bool,
+    }
+
+    impl ColumnType {
+        /// The type of columns holding values of `V`
+        pub const fn of<V: Type<crate::Driver>>() -> Self {
+            Self {
+                info: V::type_info,
+                nullable: false,
+            }
+        }
+
+        /// Mark this type as accepting `NULL`
+        pub const fn nullable(mut self) -> Self {
+            self.nullable = true;
+            self
+        }
+
+        /// The name of the sql type (e.g. `INT4` or `TEXT` on postgres)
+        pub fn name(&self) -> String {
+            (self.info)().name().to_owned()
+        }
+    }
+
+    impl fmt::Debug for ColumnType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ColumnType")
+                .field("name", &self.name())
+                .field("nullable", &self.nullable)
+                .finish()
+        }
+    }
+
+    impl PartialEq for ColumnType {
+        fn eq(&self, other: &Self) -> bool {
+            self.name() == other.name() && self.nullable == other.nullable
+        }
+    }
+
+    impl Eq for ColumnType {}
+
+    /// The primary key column referenced by a foreign key
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Reference {
+        /// The schema of the referenced table
+        pub schema: &'static str,
+        /// The name of the referenced table
+        pub table: &'static str,
+        /// The sql name of the referenced primary key column
+        pub column: &'static str,
+    }
 
     /// An enum that encapsulates different column types of a table.
     #[derive(Copy, Debug, PartialEq, Eq)]
@@ -112,6 +409,15 @@ pub mod column {
                 Self::Timestamp(ts) => ts.sql,
             }
         }
+
+        pub const fn comment(&self) -> Option<&'static str> {
+            match self {
+                Self::PrimaryKey(pk) => pk.comment,
+                Self::ForeignKey(fk) => fk.comment,
+                Self::Data(data) => data.comment,
+                Self::Timestamp(ts) => ts.comment,
+            }
+        }
     }
 
     /// Describes the primary key column of a table.
@@ -119,6 +425,10 @@ pub mod column {
     pub struct PrimaryKey<T: Table> {
         pub field: &'static str,
         pub sql: &'static str,
+        /// Whether the key is generated by the database (`SERIAL`, `AUTOINCREMENT`, ..)
+        pub auto: bool,
+        /// The comment describing the column (`#[sql(comment = "..")]`), if any
+        pub comment: Option<&'static str>,
         table: PhantomData<T>,
     }
 
@@ -127,10 +437,24 @@ pub mod column {
             Self {
                 field,
                 sql,
+                auto: false,
+                comment: None,
                 table: PhantomData,
             }
         }
 
+        /// Mark this primary key as generated by the database on insertion
+        pub const fn with_auto(mut self) -> Self {
+            self.auto = true;
+            self
+        }
+
+        /// Describe this column with `comment`
+        pub const fn with_comment(mut self, comment: &'static str) -> Self {
+            self.comment = Some(comment);
+            self
+        }
+
         pub const fn as_col(&'static self) -> Column<T> {
             Column::PrimaryKey(self)
         }
@@ -141,11 +465,37 @@ pub mod column {
             Self {
                 field: self.field,
                 sql: self.sql,
+                auto: self.auto,
+                comment: self.comment,
                 table: PhantomData,
             }
         }
     }
 
+    /// The action taken on referring rows when the referenced row is deleted or updated
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum ReferentialAction {
+        #[default]
+        NoAction,
+        Restrict,
+        Cascade,
+        SetNull,
+        SetDefault,
+    }
+
+    impl ReferentialAction {
+        /// The sql representation of this action (e.g. `ON DELETE CASCADE`)
+        pub const fn sql(&self) -> &'static str {
+            match self {
+                Self::NoAction => "NO ACTION",
+                Self::Restrict => "RESTRICT",
+                Self::Cascade => "CASCADE",
+                Self::SetNull => "SET NULL",
+                Self::SetDefault => "SET DEFAULT",
+            }
+        }
+    }
+
     /// Represents a foreign key column, establishing a relationship to another table.
     #[derive(Copy, Debug, PartialEq, Eq)]
     pub struct ForeignKey<T: Table> {
@@ -153,6 +503,20 @@ pub mod column {
         pub field: &'static str,
         /// The associated sql column name
         pub sql: &'static str,
+        /// The action taken when the referenced row is deleted
+        pub on_delete: ReferentialAction,
+        /// The action taken when the referenced row is updated
+        pub on_update: ReferentialAction,
+        /// The sql type of the column, if known
+        pub ty: Option<ColumnType>,
+        /// Whether the column holds unique values
+        pub unique: bool,
+        /// The primary key referenced by this foreign key, if known
+        pub references: Option<Reference>,
+        /// Whether the column is only written on insertion
+        pub immutable: bool,
+        /// The comment describing the column (`#[sql(comment = "..")]`), if any
+        pub comment: Option<&'static str>,
         table: PhantomData<T>,
     }
 
@@ -161,10 +525,63 @@ pub mod column {
             Self {
                 field,
                 sql,
+                on_delete: ReferentialAction::NoAction,
+                on_update: ReferentialAction::NoAction,
+                ty: None,
+                unique: false,
+                references: None,
+                immutable: false,
+                comment: None,
                 table: PhantomData,
             }
         }
 
+        /// Set the action taken when the referenced row is deleted
+        pub const fn with_on_delete(mut self, action: ReferentialAction) -> Self {
+            self.on_delete = action;
+            self
+        }
+
+        /// Set the action taken when the referenced row is updated
+        pub const fn with_on_update(mut self, action: ReferentialAction) -> Self {
+            self.on_update = action;
+            self
+        }
+
+        /// Set the sql type of this column
+        pub const fn with_type(mut self, ty: ColumnType) -> Self {
+            self.ty = Some(ty);
+            self
+        }
+
+        /// Mark this column as holding unique values
+        pub const fn with_unique(mut self) -> Self {
+            self.unique = true;
+            self
+        }
+
+        /// Mark this column as only written on insertion, it is never updated
+        pub const fn with_immutable(mut self) -> Self {
+            self.immutable = true;
+            self
+        }
+
+        /// Set the table whose primary key is referenced by this foreign key
+        pub const fn with_references<O: Table>(mut self) -> Self {
+            self.references = Some(Reference {
+                schema: O::SCHEMA,
+                table: O::TABLE,
+                column: O::PRIMARY_KEY.sql,
+            });
+            self
+        }
+
+        /// Describe this column with `comment`
+        pub const fn with_comment(mut self, comment: &'static str) -> Self {
+            self.comment = Some(comment);
+            self
+        }
+
         pub const fn as_col(&'static self) -> Column<T> {
             Column::ForeignKey(self)
         }
@@ -176,6 +593,7 @@ pub mod column {
         /// is memory safe as `Self<A>` and `Self<B>` have the exact same memory layout,
         /// we do not store any data (A or B) but only a `PhantomData` instance which
         /// is here transmuted.
+        #[deprecated(note = "construct joins using `runtime::sql::select_joined` instead")]
         pub const unsafe fn transmute<I: Table>(&'static self) -> &'static ForeignKey<I> {
             std::mem::transmute(self)
         }
@@ -186,6 +604,13 @@ pub mod column {
             Self {
                 field: self.field,
                 sql: self.sql,
+                on_delete: self.on_delete,
+                on_update: self.on_update,
+                ty: self.ty,
+                unique: self.unique,
+                references: self.references,
+                immutable: self.immutable,
+                comment: self.comment,
                 table: PhantomData,
             }
         }
@@ -198,6 +623,26 @@ pub mod column {
         pub field: &'static str,
         /// The associated sql column name
         pub sql: &'static str,
+        /// Whether this column holds the tenant of a row (see [`crate::tenant`])
+        pub tenant: bool,
+        /// The sql type of the column, if known
+        pub ty: Option<ColumnType>,
+        /// Whether the column holds unique values
+        pub unique: bool,
+        /// Whether the value of the column is set by the database default on insertion
+        pub default: bool,
+        /// Whether the column is maintained by the database and never inserted or updated
+        pub readonly: bool,
+        /// Whether the column is only written on insertion
+        pub immutable: bool,
+        /// The condition of the check constraint of the column, if any
+        pub check: Option<&'static str>,
+        /// The comment describing the column (`#[sql(comment = "..")]`), if any
+        pub comment: Option<&'static str>,
+        /// Whether the values of the column are redacted (see [`crate::redact`])
+        pub sensitive: bool,
+        /// The text search configuration of a full-text searched column (`#[sql(fulltext)]`), if any
+        pub fulltext: Option<&'static str>,
         table: PhantomData<T>,
     }
 
@@ -206,10 +651,80 @@ pub mod column {
             Self {
                 field,
                 sql,
+                tenant: false,
+                ty: None,
+                unique: false,
+                default: false,
+                readonly: false,
+                immutable: false,
+                check: None,
+                comment: None,
+                sensitive: false,
+                fulltext: None,
                 table: PhantomData,
             }
         }
 
+        /// Mark this column as the tenant discriminator of the table
+        pub const fn with_tenant(mut self) -> Self {
+            self.tenant = true;
+            self
+        }
+
+        /// Set the sql type of this column
+        pub const fn with_type(mut self, ty: ColumnType) -> Self {
+            self.ty = Some(ty);
+            self
+        }
+
+        /// Mark this column as holding unique values
+        pub const fn with_unique(mut self) -> Self {
+            self.unique = true;
+            self
+        }
+
+        /// Mark this column as set by the database default on insertion
+        pub const fn with_default(mut self) -> Self {
+            self.default = true;
+            self
+        }
+
+        /// Mark this column as maintained by the database, it is only ever selected
+        pub const fn with_readonly(mut self) -> Self {
+            self.readonly = true;
+            self
+        }
+
+        /// Mark this column as only written on insertion, it is never updated
+        pub const fn with_immutable(mut self) -> Self {
+            self.immutable = true;
+            self
+        }
+
+        /// Constrain the values of this column to those satisfying the sql `condition`
+        pub const fn with_check(mut self, condition: &'static str) -> Self {
+            self.check = Some(condition);
+            self
+        }
+
+        /// Describe this column with `comment`
+        pub const fn with_comment(mut self, comment: &'static str) -> Self {
+            self.comment = Some(comment);
+            self
+        }
+
+        /// Mark this column as holding values which are redacted from logs and diagnostics
+        pub const fn with_sensitive(mut self) -> Self {
+            self.sensitive = true;
+            self
+        }
+
+        /// Mark this column as full-text searched using the text search configuration `config`
+        pub const fn with_fulltext(mut self, config: &'static str) -> Self {
+            self.fulltext = Some(config);
+            self
+        }
+
         pub const fn as_col(&'static self) -> Column<T> {
             Column::Data(self)
         }
@@ -220,6 +735,16 @@ pub mod column {
             Self {
                 field: self.field,
                 sql: self.sql,
+                tenant: self.tenant,
+                ty: self.ty,
+                unique: self.unique,
+                default: self.default,
+                readonly: self.readonly,
+                immutable: self.immutable,
+                check: self.check,
+                comment: self.comment,
+                sensitive: self.sensitive,
+                fulltext: self.fulltext,
                 table: PhantomData,
             }
         }
@@ -242,6 +767,10 @@ pub mod column {
         pub field: &'static str,
         /// The associated sql column name
         pub sql: &'static str,
+        /// The sql type of the column, if known
+        pub ty: Option<ColumnType>,
+        /// The comment describing the column (`#[sql(comment = "..")]`), if any
+        pub comment: Option<&'static str>,
         table: PhantomData<T>,
     }
 
@@ -251,9 +780,23 @@ pub mod column {
                 kind,
                 field,
                 sql,
+                ty: None,
+                comment: None,
                 table: PhantomData,
             }
         }
+
+        /// Set the sql type of this column
+        pub const fn with_type(mut self, ty: ColumnType) -> Self {
+            self.ty = Some(ty);
+            self
+        }
+
+        /// Describe this column with `comment`
+        pub const fn with_comment(mut self, comment: &'static str) -> Self {
+            self.comment = Some(comment);
+            self
+        }
     }
 
     impl<T: Table> Clone for TimestampColumn<T> {
@@ -262,6 +805,8 @@ pub mod column {
                 kind: self.kind,
                 field: self.field,
                 sql: self.sql,
+                ty: self.ty,
+                comment: self.comment,
                 table: PhantomData,
             }
         }
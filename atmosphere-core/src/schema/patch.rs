@@ -0,0 +1,30 @@
+use crate::{bind::Bindable, Column, Result, Table};
+
+/// A partial update of a row, setting only some of its columns.
+///
+/// Generated by `#[derive(Schema)]` for tables using `#[table(.., patch)]` as `<Table>Patch`,
+/// holding an `Option` for each updated column (foreign keys and data columns which are neither
+/// `readonly` nor `immutable`). Columns left `None` are not touched, which is the shape of the
+/// body of a PATCH request. Patches are applied using [`Update::patch`](crate::Update::patch).
+///
+/// ```ignore
+/// let patch = UserPatch {
+///     name: Some("ada".to_owned()),
+///     ..Default::default()
+/// };
+///
+/// // UPDATE "public"."user" SET name = $2 WHERE id = $1
+/// User::patch(&pool, &id, patch).await?;
+/// ```
+pub trait Patch<T: Table>: Send + Sync {
+    /// The rust field names of the columns set by this patch
+    fn fields(&self) -> Vec<&'static str>;
+
+    /// Binds the value this patch sets the column `c` to
+    fn bind<'q, Q: Bindable<'q>>(&'q self, c: &'q Column<T>, query: Q) -> Result<Q>;
+
+    /// Runs the validations (`#[sql(validate = "..")]`) of the columns set by this patch
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+
+use crate::{
+    hooks::{self, Hooks},
+    query::Query,
+    runtime::sql,
+    schema::Table,
+    Bind, Result,
+};
+
+/// The sql a query would execute, as returned by [`Preview`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SqlPreview {
+    /// The rendered statement, including the changes of
+    /// [`PreBind`](crate::hooks::HookStage::PreBind) hooks
+    pub sql: String,
+    /// The values bound to the placeholders of the statement, in order
+    pub bindings: Vec<String>,
+}
+
+impl SqlPreview {
+    fn of<T: Hooks + Sync>(mut query: Query<T>) -> Result<Self> {
+        hooks::modify(&mut query)?;
+
+        Ok(Self::query(&query))
+    }
+
+    /// The statement of `query` as is
+    pub(crate) fn query<T: Bind>(query: &Query<T>) -> Self {
+        let columns = query
+            .bindings()
+            .columns()
+            .iter()
+            .map(|c| match c.field() == c.sql() {
+                true => c.field().to_owned(),
+                false => format!("{} (column {})", c.field(), c.sql()),
+            });
+
+        // values pushed by hooks and the tenant in scope, which are unknown to the preview
+        let values = (0..query.values.len()).map(|_| "additional value".to_owned());
+
+        Self {
+            sql: query.sql().to_owned(),
+            bindings: columns.chain(values).collect(),
+        }
+    }
+}
+
+impl fmt::Display for SqlPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.sql)?;
+
+        for (i, binding) in self.bindings.iter().enumerate() {
+            write!(f, "\n-- ${}: {binding}", i + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders the sql of the CRUD operations without executing it.
+///
+/// Meant for debugging and reviewing the statements generated for a table: each preview holds the
+/// statement (as modified by the [`PreBind`](crate::hooks::HookStage::PreBind) hooks of the
+/// table, other hooks are not run) and the columns bound to its placeholders.
+///
+/// ```ignore
+/// println!("{}", user.preview_create()?);
+///
+/// // INSERT INTO "public"."user"
+/// //   (id, name)
+/// // VALUES
+/// //   ($1, $2)
+/// // -- $1: id
+/// // -- $2: name
+/// ```
+pub trait Preview: Table + Bind + Hooks + Sync + 'static {
+    /// Previews [`Create::create`](crate::Create::create)
+    fn preview_create(&self) -> Result<SqlPreview>;
+
+    /// Previews [`Read::read`](crate::Read::read)
+    fn preview_read() -> Result<SqlPreview>;
+
+    /// Previews [`Read::read_all`](crate::Read::read_all)
+    fn preview_read_all() -> Result<SqlPreview>;
+
+    /// Previews [`Update::update`](crate::Update::update)
+    fn preview_update(&self) -> Result<SqlPreview>;
+
+    /// Previews [`Update::upsert`](crate::Update::upsert)
+    fn preview_upsert(&self) -> Result<SqlPreview>;
+
+    /// Previews [`Delete::delete`](crate::Delete::delete)
+    fn preview_delete(&self) -> Result<SqlPreview>;
+}
+
+impl<T: Table + Bind + Hooks + Sync + 'static> Preview for T {
+    fn preview_create(&self) -> Result<SqlPreview> {
+        SqlPreview::of(sql::insert::<T>())
+    }
+
+    fn preview_read() -> Result<SqlPreview> {
+        SqlPreview::of(sql::select::<T>())
+    }
+
+    fn preview_read_all() -> Result<SqlPreview> {
+        SqlPreview::of(sql::select_all::<T>())
+    }
+
+    fn preview_update(&self) -> Result<SqlPreview> {
+        SqlPreview::of(sql::update::<T>())
+    }
+
+    fn preview_upsert(&self) -> Result<SqlPreview> {
+        SqlPreview::of(sql::upsert::<T>())
+    }
+
+    fn preview_delete(&self) -> Result<SqlPreview> {
+        SqlPreview::of(sql::delete::<T>())
+    }
+}
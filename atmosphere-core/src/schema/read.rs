@@ -1,12 +1,16 @@
 use crate::{
+    cache,
+    group::{GroupBy, Key},
     hooks::{self, HookInput, HookStage, Hooks},
-    query::{QueryError, QueryResult},
-    schema::Table,
-    Bind, Error, Result,
+    query::{self, Lock, QueryError, QueryResult},
+    rel::RefersTo,
+    runtime::{instrument::instrumented, sql, transaction},
+    schema::{FromAliasedRow, Table},
+    Bind, Result,
 };
 
 use async_trait::async_trait;
-use sqlx::{database::HasArguments, Executor, IntoArguments};
+use sqlx::{database::HasArguments, Acquire, Executor, IntoArguments};
 
 /// Trait for reading rows from a database.
 ///
@@ -34,6 +38,31 @@ pub trait Read: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send;
 
+    /// Finds and retrieves the rows with any of the primary keys `pks`, in no particular order.
+    /// Primary keys without a row are skipped.
+    ///
+    /// Large batches are split into several statements within the bind parameter limit of the
+    /// driver (see [`crate::query::batch_size`]), executed in a single transaction on a connection
+    /// of `acquire` (a savepoint if `acquire` is a transaction already), so that all of them read
+    /// the same snapshot where the isolation level provides one.
+    async fn find_many<'a, A>(acquire: A, pks: &[Self::PrimaryKey]) -> Result<Vec<Self>>
+    where
+        A: Acquire<'a, Database = crate::Driver> + Send,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send;
+
+    /// Finds and retrieves a row by its primary key and locks it until the surrounding transaction
+    /// ends (`SELECT .. FOR UPDATE`). The `lock` mode determines what happens if the row is
+    /// already locked by another transaction; with [`Lock::SkipLocked`] a locked row is reported
+    /// as not found.
+    ///
+    /// Outside of a transaction the lock is released as soon as the query completes.
+    async fn read_for_update<'e, E>(executor: E, pk: &Self::PrimaryKey, lock: Lock) -> Result<Self>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send;
+
     /// Retrieves all rows from the table. This method is useful for fetching the complete
     /// dataset of a table, executing a query to return all rows, and applying hooks as needed.
     async fn read_all<'e, E>(executor: E) -> Result<Vec<Self>>
@@ -42,6 +71,12 @@ pub trait Read: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send;
 
+    /// Groups the rows by the values of `key` to aggregate them, e.g. counting the rows per
+    /// foreign key using `Tree::group_by(Tree::FOREST).count(&pool)`.
+    fn group_by<K>(key: Key<Self, K>) -> GroupBy<Self, K> {
+        GroupBy::new(key)
+    }
+
     /// Reloads the current entity from the database. This method is designed to update the entity
     /// instance with the latest data from the database, ensuring that it reflects the current
     /// state of the corresponding row.
@@ -50,6 +85,22 @@ pub trait Read: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send;
+
+    /// Retrieves all rows from the table together with the `Other` entity each row refers to.
+    /// This issues a single `JOIN` query instead of resolving the relationship row by row, rows
+    /// without a referenced entity are omitted.
+    ///
+    /// ```ignore
+    /// let posts: Vec<(Post, User)> = Post::read_all_with::<User>(&pool).await?;
+    /// ```
+    async fn read_all_with<'e, Other>(
+        executor: impl Executor<'e, Database = crate::Driver> + 'e,
+    ) -> Result<Vec<(Self, Other)>>
+    where
+        Self: RefersTo<Other> + FromAliasedRow,
+        Other: Table + Bind + FromAliasedRow + Unpin + Sync,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send;
 }
 
 #[async_trait]
@@ -63,9 +114,19 @@ where
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let query = crate::runtime::sql::select::<T>();
+        let cache = cache::of::<T>();
+
+        if let Some(cache) = cache {
+            if let Some(row) = cache.get(pk).await? {
+                return Ok(row);
+            }
+        }
+
+        let generation = cache.map(|cache| cache.generation());
+
+        let mut query = crate::runtime::sql::select::<T>();
 
-        hooks::execute(HookStage::PreBind, &query, HookInput::PrimaryKey(pk)).await?;
+        hooks::prepare(&mut query, HookInput::PrimaryKey(pk)).await?;
 
         assert!(query.bindings().columns().len() == 1);
         assert!(query.bindings().columns()[0].field() == Self::PRIMARY_KEY.field);
@@ -73,13 +134,14 @@ where
 
         hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
 
-        let res = sqlx::query_as(query.sql())
-            .bind(pk)
+        let sql = sqlx::query_as(query.sql()).bind(pk);
+
+        let res = query
+            .bind_values(sql)?
             .persistent(false)
             .fetch_one(executor)
             .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
+            .map_err(|err| query.context().error(err));
 
         hooks::execute(
             hooks::HookStage::PostExec,
@@ -88,6 +150,10 @@ where
         )
         .await?;
 
+        if let (Some(cache), Some(generation), Ok(row)) = (cache, generation, &res) {
+            cache.insert(row, generation).await?;
+        }
+
         res
     }
 
@@ -97,9 +163,19 @@ where
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let query = crate::runtime::sql::select::<T>();
+        let cache = cache::of::<T>();
+
+        if let Some(cache) = cache {
+            if let Some(row) = cache.get(pk).await? {
+                return Ok(Some(row));
+            }
+        }
+
+        let generation = cache.map(|cache| cache.generation());
+
+        let mut query = crate::runtime::sql::select::<T>();
 
-        hooks::execute(HookStage::PreBind, &query, HookInput::PrimaryKey(pk)).await?;
+        hooks::prepare(&mut query, HookInput::PrimaryKey(pk)).await?;
 
         assert!(query.bindings().columns().len() == 1);
         assert!(query.bindings().columns()[0].field() == Self::PRIMARY_KEY.field);
@@ -107,13 +183,14 @@ where
 
         hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
 
-        let res = sqlx::query_as(query.sql())
-            .bind(pk)
+        let sql = sqlx::query_as(query.sql()).bind(pk);
+
+        let res = query
+            .bind_values(sql)?
             .persistent(false)
             .fetch_optional(executor)
             .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
+            .map_err(|err| query.context().error(err));
 
         hooks::execute(
             hooks::HookStage::PostExec,
@@ -122,6 +199,96 @@ where
         )
         .await?;
 
+        if let (Some(cache), Some(generation), Ok(Some(row))) = (cache, generation, &res) {
+            cache.insert(row, generation).await?;
+        }
+
+        res
+    }
+
+    async fn find_many<'a, A>(acquire: A, pks: &[Self::PrimaryKey]) -> Result<Vec<Self>>
+    where
+        A: Acquire<'a, Database = crate::Driver> + Send,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        if pks.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut conn = acquire.acquire().await.map_err(QueryError::from)?;
+
+        transaction::run_on(&mut conn, None, |conn, _| {
+            Box::pin(async move {
+                let mut rows = Vec::with_capacity(pks.len());
+
+                for chunk in pks.chunks(query::chunk_size(1, sql::tenants::<T>())) {
+                    let mut query = sql::select_in::<T>(T::PRIMARY_KEY.as_col(), chunk.len());
+
+                    hooks::prepare(&mut query, HookInput::None).await?;
+                    hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+
+                    let mut sql = sqlx::query_as(query.sql());
+
+                    for pk in chunk {
+                        sql = sql.bind(pk);
+                    }
+
+                    let res = query
+                        .bind_values(sql)?
+                        .persistent(false)
+                        .fetch_all(&mut *conn)
+                        .await
+                        .map_err(|err| query.context().error(err));
+
+                    hooks::execute(
+                        hooks::HookStage::PostExec,
+                        &query,
+                        QueryResult::Many(&res).into(),
+                    )
+                    .await?;
+
+                    rows.extend(res?);
+                }
+
+                Ok(rows)
+            })
+        })
+        .await
+    }
+
+    async fn read_for_update<'e, E>(executor: E, pk: &Self::PrimaryKey, lock: Lock) -> Result<Self>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let mut query = crate::runtime::sql::select_for_update::<T>(lock);
+
+        hooks::prepare(&mut query, HookInput::PrimaryKey(pk)).await?;
+
+        assert!(query.bindings().columns().len() == 1);
+        assert!(query.bindings().columns()[0].field() == Self::PRIMARY_KEY.field);
+        assert!(query.bindings().columns()[0].sql() == Self::PRIMARY_KEY.sql);
+
+        hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+
+        let sql = sqlx::query_as(query.sql()).bind(pk);
+
+        let res = query
+            .bind_values(sql)?
+            .persistent(false)
+            .fetch_one(executor)
+            .await
+            .map_err(|err| query.context().error(err));
+
+        hooks::execute(
+            hooks::HookStage::PostExec,
+            &query,
+            QueryResult::One(&res).into(),
+        )
+        .await?;
+
         res
     }
 
@@ -131,17 +298,17 @@ where
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let query = crate::runtime::sql::select_all::<T>();
+        let mut query = crate::runtime::sql::select_all::<T>();
 
-        hooks::execute(HookStage::PreBind, &query, HookInput::None).await?;
+        hooks::prepare(&mut query, HookInput::None).await?;
         hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
 
-        let res = sqlx::query_as(query.sql())
+        let res = query
+            .bind_values(sqlx::query_as(query.sql()))?
             .persistent(false)
             .fetch_all(executor)
             .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
+            .map_err(|err| query.context().error(err));
 
         hooks::execute(
             hooks::HookStage::PostExec,
@@ -159,24 +326,25 @@ where
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let query = crate::runtime::sql::select_by::<T>(T::PRIMARY_KEY.as_col());
+        let mut query = crate::runtime::sql::select_by::<T>(T::PRIMARY_KEY.as_col());
 
-        hooks::execute(HookStage::PreBind, &query, HookInput::Row(self)).await?;
+        hooks::prepare(&mut query, HookInput::Row(self)).await?;
 
         let mut sql = sqlx::query_as(query.sql());
 
         for c in query.bindings().columns() {
-            sql = self.bind(c, sql).unwrap();
+            sql = self.bind(c, sql)?;
         }
 
+        let sql = query.bind_values(sql)?;
+
         hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
 
         let res = sql
             .persistent(false)
             .fetch_one(executor)
             .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
+            .map_err(|err| query.context().error(err));
 
         hooks::execute(
             hooks::HookStage::PostExec,
@@ -189,4 +357,42 @@ where
 
         Ok(())
     }
+
+    async fn read_all_with<'e, Other>(
+        executor: impl Executor<'e, Database = crate::Driver> + 'e,
+    ) -> Result<Vec<(Self, Other)>>
+    where
+        Self: RefersTo<Other> + FromAliasedRow,
+        Other: Table + Bind + FromAliasedRow + Unpin + Sync,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let fk = <Self as RefersTo<Other>>::FOREIGN_KEY;
+        let query = crate::runtime::sql::select_joined::<T, Other>(&fk);
+        let (this, other) = crate::runtime::sql::join_aliases(&fk);
+
+        let sql = query
+            .bind_values(sqlx::query(query.sql()))?
+            .persistent(false);
+
+        let context = query.context();
+
+        let execution = async move {
+            sql.fetch_all(executor)
+                .await
+                .map_err(|err| context.error(err))
+        };
+
+        let rows = instrumented(&query, execution).await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    T::from_aliased_row(row, this)?,
+                    Other::from_aliased_row(row, other)?,
+                ))
+            })
+            .collect::<sqlx::Result<_>>()
+            .map_err(|err| query.context().error(err))
+    }
 }
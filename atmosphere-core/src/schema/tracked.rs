@@ -0,0 +1,97 @@
+use std::ops::{Deref, DerefMut};
+
+use sqlx::{database::HasArguments, Executor, IntoArguments};
+
+use crate::{query::WriteOutcome, schema::update::update_row, Result, Table, Update};
+
+/// Compares the columns of two rows of a table.
+///
+/// Implemented by `#[derive(Schema)]` for tables using `#[table(.., tracked)]`, which requires all
+/// updated columns to implement `PartialEq`.
+pub trait Diff: Table {
+    /// The rust field names of the updated columns (foreign keys and data columns which are
+    /// neither `readonly` nor `immutable`) whose values differ between `self` and `original`
+    fn diff(&self, original: &Self) -> Vec<&'static str>;
+}
+
+/// A row which remembers the values it was loaded with, so updates only write the changed columns.
+///
+/// Dereferences to the row, so it is modified like the row itself. [`Tracked::update`] emits a
+/// `SET` clause containing only the modified columns (and the timestamps), leaving concurrent
+/// updates of other columns in place and writing less. Updates without changes are skipped.
+///
+/// ```ignore
+/// let mut user = Tracked::new(User::read(&pool, &id).await?);
+///
+/// user.name = "ada".to_owned();
+///
+/// // UPDATE "public"."user" SET id = $1, name = $2 WHERE id = $1
+/// user.update(&pool).await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Tracked<T> {
+    row: T,
+    original: T,
+}
+
+impl<T: Diff + Clone> Tracked<T> {
+    /// Tracks the changes of `row`, as loaded from the database
+    pub fn new(row: T) -> Self {
+        Self {
+            original: row.clone(),
+            row,
+        }
+    }
+
+    /// The rust field names of the columns changed since the row was loaded or last updated
+    pub fn changes(&self) -> Vec<&'static str> {
+        self.row.diff(&self.original)
+    }
+
+    /// Whether any column changed since the row was loaded or last updated
+    pub fn is_dirty(&self) -> bool {
+        !self.changes().is_empty()
+    }
+
+    /// Returns the row, discarding the tracked changes
+    pub fn into_inner(self) -> T {
+        self.row
+    }
+
+    /// Updates the changed columns of the row, see [`Update::update`]
+    pub async fn update<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
+    where
+        T: Update,
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let changes = self.changes();
+
+        if changes.is_empty() {
+            return Ok(WriteOutcome::default());
+        }
+
+        let query = crate::runtime::sql::update_of::<T>(&changes);
+
+        let res = update_row(&mut self.row, executor, query).await?;
+
+        self.original = self.row.clone();
+
+        Ok(res)
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.row
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.row
+    }
+}
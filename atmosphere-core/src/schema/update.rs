@@ -1,12 +1,13 @@
 use crate::{
     hooks::{self, HookInput, HookStage, Hooks},
-    query::{QueryError, QueryResult},
-    schema::Table,
-    Bind, Error, Result,
+    query::{Query, QueryResult, WriteOutcome},
+    runtime::changes,
+    schema::{Patch, Table, UniqueConstraint, Updatable},
+    Bind, Result,
 };
 
 use async_trait::async_trait;
-use sqlx::{database::HasArguments, Database, Executor, IntoArguments};
+use sqlx::{database::HasArguments, Executor, IntoArguments};
 
 /// Update rows in a database.
 ///
@@ -15,14 +16,11 @@ use sqlx::{database::HasArguments, Database, Executor, IntoArguments};
 /// or upserts (update or insert if not exists). It ensures that hooks are executed at various
 /// stages, enabling custom logic to be integrated into the update process.
 #[async_trait]
-pub trait Update: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
+pub trait Update: Table + Updatable + Bind + Hooks + Send + Sync + Unpin + 'static {
     /// Updates an existing row in the database. This method constructs an update query, binds the
     /// necessary values, executes the query, and applies hooks at predefined stages (e.g., before
     /// binding, before execution, after execution).
-    async fn update<'e, E>(
-        &mut self,
-        executor: E,
-    ) -> Result<<crate::Driver as Database>::QueryResult>
+    async fn update<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
@@ -30,48 +28,113 @@ pub trait Update: Table + Bind + Hooks + Send + Sync + Unpin + 'static {
 
     /// Similar to `update`, but either updates an existing row or inserts a new one if it does not
     /// exist, depending on the primary key's presence and uniqueness.
-    async fn upsert<'e, E>(
+    async fn upsert<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send;
+
+    /// Similar to `upsert`, but updates the existing row with the same values of the columns of
+    /// `constraint` (one of [`Table::UNIQUE`]) instead of the same primary key.
+    async fn upsert_on<'e, E>(
         &mut self,
         executor: E,
-    ) -> Result<<crate::Driver as Database>::QueryResult>
+        constraint: &UniqueConstraint,
+    ) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send;
+
+    /// Updates only the columns set by `patch` of the row with the primary key `pk`, see [`Patch`].
+    ///
+    /// There is no row the hooks of the table could observe, they receive the primary key instead.
+    /// Patches which set no columns are not executed.
+    async fn patch<'e, E, P>(executor: E, pk: &Self::PrimaryKey, patch: P) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        P: Patch<Self> + 'static,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send;
 }
 
 #[async_trait]
 impl<T> Update for T
 where
-    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    T: Table + Updatable + Bind + Hooks + Send + Sync + Unpin + 'static,
 {
-    async fn update<'e, E>(
+    async fn update<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        update_row(self, executor, crate::runtime::sql::update::<T>()).await
+    }
+
+    async fn upsert<'e, E>(&mut self, executor: E) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        upsert(self, executor, crate::runtime::sql::upsert::<T>()).await
+    }
+
+    async fn upsert_on<'e, E>(
         &mut self,
         executor: E,
-    ) -> Result<<crate::Driver as Database>::QueryResult>
+        constraint: &UniqueConstraint,
+    ) -> Result<WriteOutcome>
     where
         E: Executor<'e, Database = crate::Driver>,
         for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
             IntoArguments<'q, crate::Driver> + Send,
     {
-        let query = crate::runtime::sql::update::<T>();
+        upsert(
+            self,
+            executor,
+            crate::runtime::sql::upsert_on::<T>(constraint),
+        )
+        .await
+    }
 
-        hooks::execute(HookStage::PreBind, &query, HookInput::Row(self)).await?;
+    async fn patch<'e, E, P>(executor: E, pk: &Self::PrimaryKey, patch: P) -> Result<WriteOutcome>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+        P: Patch<Self> + 'static,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let fields = patch.fields();
+
+        if fields.is_empty() {
+            return Ok(WriteOutcome::default());
+        }
+
+        patch.validate()?;
 
-        let mut sql = sqlx::query(query.sql());
+        let mut query = crate::runtime::sql::patch::<T>(&fields);
 
-        for c in query.bindings().columns() {
-            sql = self.bind(c, sql).unwrap();
+        hooks::prepare(&mut query, HookInput::PrimaryKey(pk)).await?;
+
+        let mut sql = sqlx::query(query.sql()).bind(pk);
+
+        // the primary key is bound above, the other columns are set by the patch
+        for c in &query.bindings().columns()[1..] {
+            sql = patch.bind(c, sql)?;
         }
 
+        let sql = query.bind_values(sql)?;
+
         hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
 
         let res = sql
             .persistent(false)
             .execute(executor)
             .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
+            .map(WriteOutcome::from)
+            .map_err(|err| query.context().error(err));
 
         hooks::execute(
             hooks::HookStage::PostExec,
@@ -80,44 +143,93 @@ where
         )
         .await?;
 
+        if matches!(&res, Ok(done) if done.rows_affected > 0) {
+            changes::publish::<T>(query.op, pk, None);
+        }
+
         res
     }
+}
 
-    async fn upsert<'e, E>(
-        &mut self,
-        executor: E,
-    ) -> Result<<crate::Driver as Database>::QueryResult>
-    where
-        E: Executor<'e, Database = crate::Driver>,
-        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
-            IntoArguments<'q, crate::Driver> + Send,
-    {
-        let query = crate::runtime::sql::upsert::<T>();
+/// Updates `row` using `query`, an update of all or some of its columns
+pub(crate) async fn update_row<'e, T, E>(
+    row: &mut T,
+    executor: E,
+    mut query: Query<T>,
+) -> Result<WriteOutcome>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    hooks::prepare(&mut query, HookInput::Row(row)).await?;
 
-        hooks::execute(HookStage::PreBind, &query, HookInput::Row(self)).await?;
+    let mut sql = sqlx::query(query.sql());
 
-        let mut sql = sqlx::query(query.sql());
+    for c in query.bindings().columns() {
+        sql = row.bind(c, sql)?;
+    }
 
-        for c in query.bindings().columns() {
-            sql = self.bind(c, sql).unwrap();
-        }
+    let sql = query.bind_values(sql)?;
 
-        hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+    hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
 
-        let res = sql
-            .persistent(false)
-            .execute(executor)
-            .await
-            .map_err(QueryError::from)
-            .map_err(Error::Query);
+    let res = sql
+        .persistent(false)
+        .execute(executor)
+        .await
+        .map(WriteOutcome::from)
+        .map_err(|err| query.context().error(err));
 
-        hooks::execute(
-            hooks::HookStage::PostExec,
-            &query,
-            QueryResult::Execution(&res).into(),
-        )
-        .await?;
+    hooks::execute(
+        hooks::HookStage::PostExec,
+        &query,
+        QueryResult::Execution(&res).into(),
+    )
+    .await?;
 
-        res
+    if matches!(&res, Ok(done) if done.rows_affected > 0) {
+        changes::publish(query.op, row.pk(), Some(row));
+    }
+
+    res
+}
+
+async fn upsert<'e, T, E>(row: &mut T, executor: E, mut query: Query<T>) -> Result<WriteOutcome>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+{
+    hooks::prepare(&mut query, HookInput::Row(row)).await?;
+
+    let mut sql = sqlx::query(query.sql());
+
+    for c in query.bindings().columns() {
+        sql = row.bind(c, sql)?;
+    }
+
+    let sql = query.bind_values(sql)?;
+
+    hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+
+    let res = sql
+        .persistent(false)
+        .execute(executor)
+        .await
+        .map(WriteOutcome::from)
+        .map_err(|err| query.context().error(err));
+
+    hooks::execute(
+        hooks::HookStage::PostExec,
+        &query,
+        QueryResult::Execution(&res).into(),
+    )
+    .await?;
+
+    if matches!(&res, Ok(done) if done.rows_affected > 0) {
+        changes::publish(query.op, row.pk(), Some(row));
     }
+
+    res
 }
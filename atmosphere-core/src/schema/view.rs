@@ -0,0 +1,46 @@
+use crate::{query::QueryError, schema::Table, Bind, Error, Result};
+
+use async_trait::async_trait;
+use sqlx::Executor;
+
+/// A postgres materialized view (`#[table(.., materialized)]`).
+///
+/// Materialized views are only read, like views, and hold the result of their query as of their
+/// last refresh. Reporting pipelines refresh them through the same type they are read with.
+///
+/// ```ignore
+/// MonthlyReport::refresh(&pool, true).await?;
+///
+/// let reports = MonthlyReport::read_all(&pool).await?;
+/// ```
+#[async_trait]
+pub trait MaterializedView: Table + Bind + Sync + 'static {
+    /// Returns the `REFRESH MATERIALIZED VIEW` statement of this view
+    fn refresh_sql(concurrently: bool) -> String {
+        let concurrently = match concurrently {
+            true => " CONCURRENTLY",
+            false => "",
+        };
+
+        format!(
+            "REFRESH MATERIALIZED VIEW{concurrently} {}",
+            crate::runtime::sql::table::<Self>()
+        )
+    }
+
+    /// Replaces the contents of this view by the current result of its query.
+    ///
+    /// Concurrent refreshes don't lock out reads, but require a unique index on the view.
+    async fn refresh<'e, E>(executor: E, concurrently: bool) -> Result<()>
+    where
+        E: Executor<'e, Database = crate::Driver>,
+    {
+        executor
+            .execute(Self::refresh_sql(concurrently).as_str())
+            .await
+            .map_err(QueryError::from)
+            .map_err(Error::Query)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+//! Loads seed data from JSON, e.g. for development environments and integration tests.
+//!
+//! Seed data holds the rows to insert per table, keyed by the table name. The tables are
+//! registered using [`Seed::table`], which deserializes their rows using `serde`. [`Seed::load`]
+//! inserts the rows of all tables within a single transaction, tables referenced by foreign keys
+//! before the tables referring to them.
+//!
+//! ```ignore
+//! // { "forest": [{ "id": 0, "name": "grunewald" }], "tree": [{ "id": 0, "forest": 0 }] }
+//! atmosphere::seed::from_path("seed/dev.json")?
+//!     .table::<Tree>()
+//!     .table::<Forest>()
+//!     .load(&pool)
+//!     .await?;
+//! ```
+
+use std::path::Path;
+
+use futures::future::BoxFuture;
+use miette::Diagnostic;
+use serde::de::DeserializeOwned;
+use sqlx::types::JsonValue;
+use thiserror::Error;
+
+use crate::{runtime::transaction::Connection, Create, Result};
+
+/// Errors of loading seed data
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum SeedError {
+    /// The seed data is no JSON object of tables
+    #[error("malformed seed data")]
+    #[diagnostic(code(atmosphere::seed::malformed))]
+    Malformed(#[source] serde_json::Error),
+
+    /// The seed data holds rows of a table which has not been registered using [`Seed::table`]
+    #[error("rows of the unknown table `{0}`")]
+    #[diagnostic(code(atmosphere::seed::table))]
+    UnknownTable(String),
+
+    /// The rows of a table could not be deserialized
+    #[error("invalid rows of `{table}`")]
+    #[diagnostic(code(atmosphere::seed::rows))]
+    Rows {
+        table: &'static str,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// The foreign keys of the seeded tables refer to each other, so there is no order to insert
+    /// them in
+    #[error("the foreign keys of the seeded tables form a cycle")]
+    #[diagnostic(code(atmosphere::seed::cycle))]
+    Cycle,
+}
+
+/// Parses the seed data `json`, an object holding an array of rows per table name
+pub fn from_json(json: &str) -> Result<Seed> {
+    let data = serde_json::from_str(json).map_err(SeedError::Malformed)?;
+
+    Ok(Seed {
+        data,
+        tables: vec![],
+    })
+}
+
+/// Reads and parses the seed data stored at `path`, see [`from_json`]
+pub fn from_path(path: impl AsRef<Path>) -> Result<Seed> {
+    from_json(&std::fs::read_to_string(path)?)
+}
+
+/// Inserts the rows of a table, returning their number
+type Loader = Box<
+    dyn for<'c> Fn(&'c mut Connection, JsonValue) -> BoxFuture<'c, Result<usize>> + Send + Sync,
+>;
+
+/// A table registered using [`Seed::table`]
+struct Table {
+    schema: &'static str,
+    name: &'static str,
+    /// The tables referenced by foreign keys, as `(schema, table)`
+    references: Vec<(&'static str, &'static str)>,
+    load: Loader,
+}
+
+/// Seed data and the tables it is loaded into, see [`from_json`]
+pub struct Seed {
+    data: serde_json::Map<String, JsonValue>,
+    tables: Vec<Table>,
+}
+
+impl Seed {
+    /// Registers the table `T`, whose rows are stored under its name (`T::TABLE`)
+    pub fn table<T>(mut self) -> Self
+    where
+        T: Create + DeserializeOwned + Send,
+    {
+        self.tables.push(Table {
+            schema: T::SCHEMA,
+            name: T::TABLE,
+            references: references::<T>(),
+            load: Box::new(|conn, rows| Box::pin(load::<T>(conn, rows))),
+        });
+
+        self
+    }
+
+    /// Inserts the rows of all tables within a single transaction, returning the number of rows
+    /// inserted. Fails without inserting any row if the data holds rows of unregistered tables.
+    pub async fn load(mut self, pool: &crate::Pool) -> Result<usize> {
+        if let Some(unknown) = self
+            .data
+            .keys()
+            .find(|name| !self.tables.iter().any(|table| table.name == name.as_str()))
+        {
+            return Err(SeedError::UnknownTable(unknown.clone()).into());
+        }
+
+        let dependencies = self
+            .tables
+            .iter()
+            .map(|table| (table.schema, table.name, table.references.as_slice()))
+            .collect::<Vec<_>>();
+
+        let order = order(&dependencies).ok_or(SeedError::Cycle)?;
+
+        let mut tables: Vec<Option<Table>> = self.tables.into_iter().map(Some).collect();
+
+        let batches: Vec<(Loader, JsonValue)> = order
+            .into_iter()
+            .filter_map(|i| tables[i].take())
+            .filter_map(|table| Some((table.load, self.data.remove(table.name)?)))
+            .collect();
+
+        crate::transaction(pool, |conn| {
+            Box::pin(async move {
+                let mut inserted = 0;
+
+                for (load, rows) in batches {
+                    inserted += load(&mut *conn, rows).await?;
+                }
+
+                Ok(inserted)
+            })
+        })
+        .await
+    }
+}
+
+/// The tables referenced by the foreign keys of `T`, as `(schema, table)`
+pub(crate) fn references<T: crate::Table>() -> Vec<(&'static str, &'static str)> {
+    T::FOREIGN_KEYS
+        .iter()
+        .filter_map(|fk| fk.references.as_ref())
+        .map(|reference| (reference.schema, reference.table))
+        .collect()
+}
+
+/// A table as `(schema, name, references)`, see [`references`]
+pub(crate) type Dependencies<'a> = (
+    &'static str,
+    &'static str,
+    &'a [(&'static str, &'static str)],
+);
+
+/// Orders `tables` so that referenced tables come first. Returns their indices, or `None` if their
+/// foreign keys form a cycle.
+pub(crate) fn order(tables: &[Dependencies<'_>]) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = vec![];
+
+    while order.len() < tables.len() {
+        let next = (0..tables.len()).find(|i| {
+            let (schema, name, references) = tables[*i];
+
+            !order.contains(i)
+                && references.iter().all(|&reference| {
+                    // references of the table itself and of tables not contained are satisfied
+                    reference == (schema, name)
+                        || tables
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, t)| (t.0, t.1) == reference)
+                            .all(|(j, _)| order.contains(&j))
+                })
+        });
+
+        order.push(next?);
+    }
+
+    Some(order)
+}
+
+async fn load<T>(conn: &mut Connection, rows: JsonValue) -> Result<usize>
+where
+    T: Create + DeserializeOwned + Send,
+{
+    let mut rows: Vec<T> = serde_json::from_value(rows).map_err(|source| SeedError::Rows {
+        table: T::TABLE,
+        source,
+    })?;
+
+    for row in rows.iter_mut() {
+        row.create(&mut *conn).await?;
+    }
+
+    Ok(rows.len())
+}
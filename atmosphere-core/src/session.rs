@@ -0,0 +1,271 @@
+//! Units of work: an identity map of the rows read and the changes to flush.
+//!
+//! A [`Session`] wraps a connection (usually the one of [`crate::transaction`]) and remembers the
+//! rows it has read. Reading the same primary key again returns the very same instance (an
+//! [`Arc`]) instead of querying the database again, so all parts of a unit of work observe the
+//! same row.
+//!
+//! Changes are not executed right away but recorded using [`Session::create`],
+//! [`Session::update`] and [`Session::delete`]. [`Session::commit`] flushes them within a single
+//! transaction (a savepoint inside of another one): the created and updated rows of referenced
+//! tables before the ones of the tables referring to them, deleted rows the other way around.
+//! Changes of a single table are flushed in the order they were recorded, and so are the changes
+//! of tables whose foreign keys form a cycle. Dropping a session discards its changes.
+//!
+//! ```ignore
+//! let mut session = Session::new(&mut conn);
+//!
+//! let author = session.read::<User>(&post.author).await?;
+//! let again = session.read::<User>(&post.author).await?; // not queried again
+//!
+//! assert!(Arc::ptr_eq(&author, &again));
+//!
+//! session.create(Comment { id: 0, post: post.id, text: "first".to_owned() });
+//! session.create(Post { id: 1, author: author.id, title: "second".to_owned() });
+//! session.delete::<Post>(&0);
+//!
+//! // INSERT INTO post .., INSERT INTO comment .., DELETE FROM post ..
+//! session.commit().await?;
+//! ```
+//!
+//! Updated rows replace the instance in the identity map and deleted rows are not found anymore,
+//! even before the session is committed. Created rows are not part of the identity map, since
+//! their primary key may be generated by the database. Rows written through [`Session::conn`]
+//! keep their previous version in the identity map until they are evicted using
+//! [`Session::evict`].
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    hash::Hash,
+    sync::Arc,
+};
+
+use futures::future::BoxFuture;
+
+use crate::{
+    runtime::transaction::{self, Connection},
+    seed, Create, Delete, Error, Read, Result, Table, Update,
+};
+
+/// Flushes a recorded change
+type Flush = Box<dyn for<'c> FnOnce(&'c mut Connection) -> BoxFuture<'c, Result<()>> + Send>;
+
+/// A change recorded by a [`Session`]
+struct Change {
+    schema: &'static str,
+    name: &'static str,
+    /// The tables referenced by foreign keys, as `(schema, table)`
+    references: Vec<(&'static str, &'static str)>,
+    delete: bool,
+    flush: Flush,
+}
+
+/// A unit of work reading each row at most once and flushing its changes on commit, see
+/// [`crate::session`]
+pub struct Session<'c> {
+    conn: &'c mut Connection,
+    /// The rows read per table, a `HashMap<T::PrimaryKey, Option<Arc<T>>>` per table holding
+    /// `None` for deleted rows
+    rows: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    changes: Vec<Change>,
+}
+
+impl<'c> Session<'c> {
+    /// Starts a session executing its queries on `conn`
+    pub fn new(conn: &'c mut Connection) -> Self {
+        Self {
+            conn,
+            rows: HashMap::new(),
+            changes: vec![],
+        }
+    }
+
+    /// The connection of the session, for queries not going through the identity map
+    pub fn conn(&mut self) -> &mut Connection {
+        self.conn
+    }
+
+    /// Reads the row with the primary key `pk`, or returns it if it has been read before
+    pub async fn read<T>(&mut self, pk: &T::PrimaryKey) -> Result<Arc<T>>
+    where
+        T: Read,
+        T::PrimaryKey: Hash + Eq + Clone + Send,
+    {
+        self.find::<T>(pk)
+            .await?
+            .ok_or_else(|| crate::query::QueryError::from(sqlx::Error::RowNotFound).into())
+    }
+
+    /// Finds the row with the primary key `pk`, or returns it if it has been read before. Missing
+    /// rows are not remembered, finding them again queries the database again.
+    pub async fn find<T>(&mut self, pk: &T::PrimaryKey) -> Result<Option<Arc<T>>>
+    where
+        T: Read,
+        T::PrimaryKey: Hash + Eq + Clone + Send,
+    {
+        if let Some(row) = self.rows::<T>().get(pk) {
+            return Ok(row.clone());
+        }
+
+        let Some(row) = T::find(&mut *self.conn, pk).await? else {
+            return Ok(None);
+        };
+
+        let row = Arc::new(row);
+
+        self.rows::<T>().insert(pk.clone(), Some(row.clone()));
+
+        Ok(Some(row))
+    }
+
+    /// Records the creation of `row`
+    pub fn create<T>(&mut self, mut row: T)
+    where
+        T: Create + 'static,
+    {
+        self.record::<T>(
+            false,
+            Box::new(move |conn| {
+                Box::pin(async move {
+                    row.create(conn).await?;
+                    Ok(())
+                })
+            }),
+        );
+    }
+
+    /// Records the update of `row`, replacing the instance of the row in the identity map
+    pub fn update<T>(&mut self, row: T) -> Arc<T>
+    where
+        T: Update + Clone + 'static,
+        T::PrimaryKey: Hash + Eq + Clone + Send,
+    {
+        let row = Arc::new(row);
+
+        self.rows::<T>().insert(row.pk().clone(), Some(row.clone()));
+
+        let mut update = (*row).clone();
+
+        self.record::<T>(
+            false,
+            Box::new(move |conn| {
+                Box::pin(async move {
+                    update.update(conn).await?;
+                    Ok(())
+                })
+            }),
+        );
+
+        row
+    }
+
+    /// Records the deletion of the row with the primary key `pk`, which is not found by the
+    /// session anymore
+    pub fn delete<T>(&mut self, pk: &T::PrimaryKey)
+    where
+        T: Delete + 'static,
+        T::PrimaryKey: Hash + Eq + Clone + Send,
+    {
+        self.rows::<T>().insert(pk.clone(), None);
+
+        let pk = pk.clone();
+
+        self.record::<T>(
+            true,
+            Box::new(move |conn| {
+                Box::pin(async move {
+                    T::delete_by(conn, &pk).await?;
+                    Ok(())
+                })
+            }),
+        );
+    }
+
+    /// Flushes the recorded changes within a single transaction, see [`crate::session`]. Fails
+    /// without applying any change if one of them fails.
+    pub async fn commit(self) -> Result<()> {
+        let Self {
+            conn, mut changes, ..
+        } = self;
+
+        let mut tables: Vec<seed::Dependencies> = vec![];
+
+        for change in &changes {
+            if !tables
+                .iter()
+                .any(|t| (t.0, t.1) == (change.schema, change.name))
+            {
+                tables.push((change.schema, change.name, change.references.as_slice()));
+            }
+        }
+
+        // tables whose foreign keys form a cycle are flushed in the order they were changed in
+        let order = seed::order(&tables).unwrap_or_else(|| (0..tables.len()).collect());
+
+        let ranks: HashMap<(&'static str, &'static str), isize> = order
+            .into_iter()
+            .enumerate()
+            .map(|(rank, i)| ((tables[i].0, tables[i].1), rank as isize))
+            .collect();
+
+        // rows of referenced tables are written first and deleted last, the sort is stable
+        changes.sort_by_key(|change| {
+            let rank = ranks[&(change.schema, change.name)];
+
+            match change.delete {
+                false => (false, rank),
+                true => (true, -rank),
+            }
+        });
+
+        let flushes: Vec<Flush> = changes.into_iter().map(|change| change.flush).collect();
+
+        transaction::run_on(conn, None, |conn, _| {
+            Box::pin(async move {
+                for flush in flushes {
+                    flush(&mut *conn).await?;
+                }
+
+                Ok::<_, Error>(())
+            })
+        })
+        .await
+    }
+
+    /// Removes the row with the primary key `pk` from the identity map, so it is read again
+    pub fn evict<T>(&mut self, pk: &T::PrimaryKey)
+    where
+        T: Table + Send + Sync,
+        T::PrimaryKey: Hash + Eq + Send,
+    {
+        self.rows::<T>().remove(pk);
+    }
+
+    /// Removes all rows from the identity map, recorded changes are kept
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    fn record<T: Table>(&mut self, delete: bool, flush: Flush) {
+        self.changes.push(Change {
+            schema: T::SCHEMA,
+            name: T::TABLE,
+            references: seed::references::<T>(),
+            delete,
+            flush,
+        });
+    }
+
+    fn rows<T>(&mut self) -> &mut HashMap<T::PrimaryKey, Option<Arc<T>>>
+    where
+        T: Table + Send + Sync,
+        T::PrimaryKey: Hash + Eq + Send,
+    {
+        self.rows
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::<HashMap<T::PrimaryKey, Option<Arc<T>>>>::default())
+            .downcast_mut()
+            .expect("identity map entry of wrong type")
+    }
+}
@@ -0,0 +1,156 @@
+//! Horizontal sharding keyed on primary keys.
+//!
+//! Large tables can be partitioned across several databases, each holding the rows of a subset of
+//! primary keys. A [`ShardRouter`] decides which database (shard) a row lives in, the [`Sharded`]
+//! variants of the CRUD operations consult it to execute each query on the right shard.
+//!
+//! ```ignore
+//! struct ByUserId(Vec<Pool>);
+//!
+//! impl ShardRouter<User> for ByUserId {
+//!     fn shard(&self, pk: &i32) -> &Pool {
+//!         &self.0[*pk as usize % self.0.len()]
+//!     }
+//!
+//!     fn shards(&self) -> &[Pool] {
+//!         &self.0
+//!     }
+//! }
+//!
+//! let router = ByUserId(vec![shard_a, shard_b]);
+//!
+//! user.create_sharded(&router).await?;
+//! let user = User::read_sharded(&router, &user.id).await?;
+//! ```
+//!
+//! Each query is executed on a single shard, operations spanning multiple rows are not atomic
+//! across shards. Moving rows between shards (resharding) is left to the application.
+
+use async_trait::async_trait;
+use sqlx::{database::HasArguments, IntoArguments};
+
+use crate::{query::WriteOutcome, Create, Delete, Read, Result, Table, Update};
+
+/// Maps the primary keys of `T` to the shards holding their rows.
+pub trait ShardRouter<T: Table>: Send + Sync {
+    /// The pool of the shard holding the row with the primary key `pk`
+    fn shard(&self, pk: &T::PrimaryKey) -> &crate::Pool;
+
+    /// The pools of all shards
+    fn shards(&self) -> &[crate::Pool];
+}
+
+/// CRUD operations routed to the shard of each row.
+///
+/// This is implemented for every table implementing the regular CRUD traits, each operation
+/// behaves like its unsharded counterpart executed on [`ShardRouter::shard`].
+#[async_trait]
+pub trait Sharded: Create + Read + Update + Delete {
+    /// Reads a row by its primary key from its shard, see [`Read::read`]
+    async fn read_sharded<R>(router: &R, pk: &Self::PrimaryKey) -> Result<Self>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        Self::read(router.shard(pk), pk).await
+    }
+
+    /// Finds a row by its primary key on its shard, see [`Read::find`]
+    async fn find_sharded<R>(router: &R, pk: &Self::PrimaryKey) -> Result<Option<Self>>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        Self::find(router.shard(pk), pk).await
+    }
+
+    /// Reads all rows of all shards, one shard after another, see [`Read::read_all`]
+    async fn read_all_sharded<R>(router: &R) -> Result<Vec<Self>>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let mut rows = vec![];
+
+        for shard in router.shards() {
+            rows.extend(Self::read_all(shard).await?);
+        }
+
+        Ok(rows)
+    }
+
+    /// Reloads the row from its shard, see [`Read::reload`]
+    async fn reload_sharded<R>(&mut self, router: &R) -> Result<()>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let shard = router.shard(self.pk());
+
+        self.reload(shard).await
+    }
+
+    /// Inserts the row into its shard, see [`Create::create`]
+    async fn create_sharded<R>(&mut self, router: &R) -> Result<WriteOutcome>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let shard = router.shard(self.pk());
+
+        self.create(shard).await
+    }
+
+    /// Updates the row on its shard, see [`Update::update`]
+    async fn update_sharded<R>(&mut self, router: &R) -> Result<WriteOutcome>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let shard = router.shard(self.pk());
+
+        self.update(shard).await
+    }
+
+    /// Upserts the row on its shard, see [`Update::upsert`]
+    async fn upsert_sharded<R>(&mut self, router: &R) -> Result<WriteOutcome>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let shard = router.shard(self.pk());
+
+        self.upsert(shard).await
+    }
+
+    /// Deletes the row from its shard, see [`Delete::delete`]
+    async fn delete_sharded<R>(&mut self, router: &R) -> Result<WriteOutcome>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        let shard = router.shard(self.pk());
+
+        self.delete(shard).await
+    }
+
+    /// Deletes a row by its primary key from its shard, see [`Delete::delete_by`]
+    async fn delete_by_sharded<R>(router: &R, pk: &Self::PrimaryKey) -> Result<WriteOutcome>
+    where
+        R: ShardRouter<Self>,
+        for<'q> <crate::Driver as HasArguments<'q>>::Arguments:
+            IntoArguments<'q, crate::Driver> + Send,
+    {
+        Self::delete_by(router.shard(pk), pk).await
+    }
+}
+
+impl<T: Create + Read + Update + Delete> Sharded for T {}
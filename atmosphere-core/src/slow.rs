@@ -0,0 +1,65 @@
+//! Slow query logging
+//!
+//! [`SlowQueryLog`] is a [`HookStage::PostExec`] hook logging every query of a table which took
+//! longer than a threshold as a `tracing` warning, including the table, the operation and the
+//! generated sql. It is registered per table:
+//!
+//! ```ignore
+//! User::register_hook(Arc::new(SlowQueryLog::new(Duration::from_millis(250))));
+//! ```
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    hooks::{Hook, HookInput, HookStage},
+    query::Query,
+    Bind, Result, Table,
+};
+
+/// The hook logging queries taking longer than its threshold
+#[derive(Clone, Copy, Debug)]
+pub struct SlowQueryLog {
+    threshold: Duration,
+}
+
+impl SlowQueryLog {
+    /// Logs queries taking longer than `threshold`
+    pub const fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+
+    /// The duration above which queries are logged
+    pub const fn threshold(&self) -> Duration {
+        self.threshold
+    }
+}
+
+#[async_trait]
+impl<T: Table + Bind + Sync + 'static> Hook<T> for SlowQueryLog {
+    fn stage(&self) -> HookStage {
+        HookStage::PostExec
+    }
+
+    async fn apply(&self, ctx: &Query<T>, _: &mut HookInput<'_, T>) -> Result<()> {
+        let Some(elapsed) = ctx.elapsed() else {
+            return Ok(());
+        };
+
+        if elapsed <= self.threshold {
+            return Ok(());
+        }
+
+        tracing::warn!(
+            table = T::TABLE,
+            operation = ctx.op.name(),
+            elapsed_ms = elapsed.as_millis() as u64,
+            threshold_ms = self.threshold.as_millis() as u64,
+            sql = ctx.sql(),
+            "slow query",
+        );
+
+        Ok(())
+    }
+}
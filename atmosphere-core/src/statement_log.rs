@@ -0,0 +1,194 @@
+//! Logging of generated statements without their values
+//!
+//! Once enabled using [`enable`], every generated statement is logged as a `tracing` event (target
+//! `atmosphere::statements`, level `INFO`) after it has been executed. The event holds the table,
+//! the operation, the sql, the outcome and the bound columns, but never the bound values:
+//!
+//! - [`Values::Hashed`] replaces each value by a fingerprint (a hash of its encoding), so equal
+//!   values can be told apart from different ones without revealing them
+//! - [`Values::Redacted`] replaces all values by `«redacted»`
+//!
+//! The values of sensitive columns (see [`crate::redact`]) are redacted either way, as hashes of
+//! values of small domains (flags, small numbers, ..) are easily guessed. Values atmosphere does
+//! not know (e.g. those bound by finders generated for unique columns) are logged as `?`.
+//!
+//! ```ignore
+//! atmosphere::statement_log::enable(Values::Hashed);
+//!
+//! user.update(&pool).await?;
+//!
+//! // INFO atmosphere::statements: table="user" operation="update" rows=1 elapsed_ms=2
+//! //     sql="UPDATE .. SET name = $2, email = $3 WHERE id = $1"
+//! //     bindings="$1 id = #5b2c1e09b2a9f85d, $2 name = #c9a1f0d7d3e2b46a, $3 email = «redacted»"
+//! ```
+
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use sqlx::{database::HasArguments, Encode, Type};
+
+use crate::{
+    hooks::HookInput, intercept::Outcome, query::Query, redact::REDACTED, Bind, Bindable, Column,
+};
+
+/// How the values bound to logged statements appear, see [`crate::statement_log`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Values {
+    /// Values are replaced by a fingerprint, a hash of their encoding
+    Hashed,
+    /// Values are replaced by `«redacted»`
+    Redacted,
+}
+
+const DISABLED: u8 = 0;
+const HASHED: u8 = 1;
+const REDACTED_VALUES: u8 = 2;
+
+static MODE: AtomicU8 = AtomicU8::new(DISABLED);
+
+/// Logs all generated statements from now on, with their values as given by `values`
+pub fn enable(values: Values) {
+    let mode = match values {
+        Values::Hashed => HASHED,
+        Values::Redacted => REDACTED_VALUES,
+    };
+
+    MODE.store(mode, Ordering::Relaxed);
+}
+
+/// Stops logging statements
+pub fn disable() {
+    MODE.store(DISABLED, Ordering::Relaxed);
+}
+
+fn mode() -> Option<Values> {
+    match MODE.load(Ordering::Relaxed) {
+        HASHED => Some(Values::Hashed),
+        REDACTED_VALUES => Some(Values::Redacted),
+        _ => None,
+    }
+}
+
+/// Captures the fingerprints of the values `input` binds to the columns of `query`
+pub(crate) fn capture<T: Bind>(query: &Query<T>, input: &HookInput<'_, T>) {
+    if mode() != Some(Values::Hashed) {
+        return;
+    }
+
+    let fingerprints = query
+        .bindings()
+        .columns()
+        .iter()
+        .map(|c| match (input, c) {
+            (HookInput::Row(row), c) => row
+                .bind(c, Fingerprints(None))
+                .ok()
+                .and_then(|fingerprints| fingerprints.0),
+            (HookInput::PrimaryKey(pk), Column::PrimaryKey(_)) => Some(fingerprint(*pk)),
+            _ => None,
+        })
+        .collect();
+
+    let _ = query.fingerprints.set(fingerprints);
+}
+
+/// Logs `query` after it has been executed
+pub(crate) fn log<T: Bind>(query: &Query<T>, outcome: &Outcome<'_>) {
+    let Some(values) = mode() else {
+        return;
+    };
+
+    let columns = query.bindings().columns();
+    let fingerprints = query.fingerprints.get();
+
+    let mut bindings = String::new();
+
+    for (i, c) in columns.iter().enumerate() {
+        let value = match (values, c) {
+            (_, Column::Data(data)) if data.sensitive => None,
+            (Values::Hashed, _) => match fingerprints.and_then(|f| f.get(i).copied().flatten()) {
+                Some(fingerprint) => Some(format!("#{fingerprint:016x}")),
+                None => Some("?".to_owned()),
+            },
+            (Values::Redacted, _) => None,
+        };
+
+        let separator = if i == 0 { "" } else { ", " };
+        let value = value.as_deref().unwrap_or(REDACTED);
+
+        let _ = write!(bindings, "{separator}${} {} = {value}", i + 1, c.sql());
+    }
+
+    // values pushed by hooks and the tenant in scope
+    for (i, value) in query.values.iter().enumerate() {
+        let value = match values {
+            Values::Hashed => format!("#{:016x}", value.fingerprint()),
+            Values::Redacted => REDACTED.to_owned(),
+        };
+
+        let separator = if columns.is_empty() && i == 0 {
+            ""
+        } else {
+            ", "
+        };
+
+        let _ = write!(bindings, "{separator}${} = {value}", columns.len() + i + 1);
+    }
+
+    tracing::info!(
+        target: "atmosphere::statements",
+        table = T::TABLE,
+        operation = query.op.name(),
+        rows = outcome.rows,
+        error = outcome.error.map(|err| err.class()),
+        elapsed_ms = outcome.elapsed.as_millis() as u64,
+        sql = query.sql(),
+        bindings,
+    );
+}
+
+/// Collects the fingerprint of the value bound to a column
+struct Fingerprints(Option<u64>);
+
+impl<'q> Bindable<'q> for Fingerprints {
+    fn dyn_bind<V: 'q + Send + Encode<'q, crate::Driver> + Type<crate::Driver>>(
+        self,
+        value: V,
+    ) -> Self {
+        Self(Some(fingerprint(&value)))
+    }
+}
+
+/// Hashes the encoding of `value` (64 bit FNV-1a), which is stable across processes
+pub(crate) fn fingerprint<'q, V: Encode<'q, crate::Driver>>(value: &V) -> u64 {
+    let mut buffer = <crate::Driver as HasArguments<'q>>::ArgumentBuffer::default();
+
+    let _ = value.encode_by_ref(&mut buffer);
+
+    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    let bytes = buffer.as_slice();
+
+    #[cfg(feature = "sqlite")]
+    let bytes = format!("{buffer:?}");
+
+    #[cfg(feature = "sqlite")]
+    let bytes = bytes.as_bytes();
+
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fingerprint;
+
+    #[test]
+    fn fingerprints() {
+        assert_eq!(fingerprint(&"ada"), fingerprint(&"ada".to_owned()));
+        assert_ne!(fingerprint(&"ada"), fingerprint(&"grace"));
+        assert_ne!(fingerprint(&1_i32), fingerprint(&2_i32));
+    }
+}
@@ -0,0 +1,106 @@
+//! Row scoped multi-tenancy
+//!
+//! A data column marked with `#[sql(tenant)]` holds the tenant a row belongs to. All generated
+//! `SELECT`, `UPDATE` and `DELETE` statements (as well as the `UPDATE` of upserts) of such a table
+//! are restricted to the rows of the tenant in scope by an additional `AND tenant = $n`, so rows of
+//! other tenants can't be read or modified by accident.
+//!
+//! The tenant is set for everything executed within a future using [`scope`]. Querying a tenant
+//! table outside of a scope fails with [`Error::Tenant`](crate::Error::Tenant). Rows created
+//! within a scope are inserted with the tenant in scope (which is read back into the row), the
+//! tenant of existing rows is never updated. Outside of a scope, rows are inserted with the tenant
+//! they hold.
+//!
+//! ```ignore
+//! #[derive(Schema)]
+//! #[table(schema = "public", name = "post")]
+//! struct Post {
+//!     #[sql(pk)]
+//!     id: i32,
+//!     #[sql(tenant)]
+//!     org: i32,
+//!     title: String,
+//! }
+//!
+//! let posts = atmosphere::tenant::scope(42, Post::read_all(&pool)).await?;
+//! ```
+
+use std::{cell::RefCell, future::Future, sync::Arc};
+
+use sqlx::{database::HasArguments, encode::IsNull, Database, Encode, Type};
+
+use crate::runtime::scoped::Scoped;
+
+type ArgumentBuffer<'q> = <crate::Driver as HasArguments<'q>>::ArgumentBuffer;
+type TypeInfo = <crate::Driver as Database>::TypeInfo;
+
+thread_local! {
+    static TENANT: RefCell<Option<Tenant>> = const { RefCell::new(None) };
+}
+
+/// Restricts all queries on tenant tables executed by `f` to the rows of `tenant`.
+pub fn scope<V, F>(tenant: V, f: F) -> impl Future<Output = F::Output>
+where
+    V: for<'q> Encode<'q, crate::Driver> + Type<crate::Driver> + Send + Sync + 'static,
+    F: Future,
+{
+    Scoped::new(&TENANT, Tenant(Arc::new(tenant)), f)
+}
+
+/// The tenant in scope, bound to the tenant condition of generated queries
+#[derive(Clone)]
+pub(crate) struct Tenant(Arc<dyn Erased>);
+
+impl Tenant {
+    /// Returns the tenant of the current scope, if any
+    pub(crate) fn current() -> Option<Self> {
+        TENANT.with(|tenant| tenant.borrow().clone())
+    }
+}
+
+impl<'q> Encode<'q, crate::Driver> for Tenant {
+    fn encode_by_ref(&self, buf: &mut ArgumentBuffer<'q>) -> IsNull {
+        self.0.encode(buf)
+    }
+
+    fn produces(&self) -> Option<TypeInfo> {
+        Some(self.0.type_info())
+    }
+
+    fn size_hint(&self) -> usize {
+        self.0.size_hint()
+    }
+}
+
+// the type of a tenant is only known at runtime, drivers take it from `Encode::produces` instead
+impl Type<crate::Driver> for Tenant {
+    fn type_info() -> TypeInfo {
+        <String as Type<crate::Driver>>::type_info()
+    }
+}
+
+/// A tenant value of any type
+trait Erased: Send + Sync {
+    fn encode<'q>(&self, buf: &mut ArgumentBuffer<'q>) -> IsNull;
+
+    fn type_info(&self) -> TypeInfo;
+
+    fn size_hint(&self) -> usize;
+}
+
+impl<V> Erased for V
+where
+    V: for<'q> Encode<'q, crate::Driver> + Type<crate::Driver> + Send + Sync,
+{
+    fn encode<'q>(&self, buf: &mut ArgumentBuffer<'q>) -> IsNull {
+        <V as Encode<'q, crate::Driver>>::encode_by_ref(self, buf)
+    }
+
+    fn type_info(&self) -> TypeInfo {
+        <V as Encode<'_, crate::Driver>>::produces(self).unwrap_or_else(V::type_info)
+    }
+
+    fn size_hint(&self) -> usize {
+        <V as Encode<'_, crate::Driver>>::size_hint(self)
+    }
+}
@@ -4,8 +4,34 @@
 //! operations on database entities. It ensures that these operations are executed correctly and that
 //! the data integrity is maintained throughout the process.
 
-use crate::Entity;
-use std::fmt::Debug;
+#[cfg(all(
+    feature = "testcontainers",
+    any(feature = "postgres", feature = "mysql")
+))]
+pub mod container;
+pub mod hooks;
+
+#[cfg(all(
+    feature = "testcontainers",
+    any(feature = "postgres", feature = "mysql")
+))]
+pub use container::{container, Container};
+
+use crate::{
+    column::ReferentialAction,
+    rel::{ReferredBy, RefersTo},
+    runtime::{
+        sql,
+        transaction::{self, Connection, IsolationLevel},
+    },
+    Bind, Create, Deletable, Entity, Result, Table,
+};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::{
+    fmt::Debug,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 /// Tests entity creation in the database.
 ///
@@ -134,6 +160,11 @@ where
         "instance was found (find) after deletion"
     );
 
+    // soft deleted rows are still present and have to be removed before re-inserting
+    E::hard_delete_by(pool, instance.pk())
+        .await
+        .expect("hard deletion did not work");
+
     instance.create(pool).await.expect("insertion did not work");
 
     E::delete_by(pool, instance.pk())
@@ -146,5 +177,776 @@ where
         .expect_err("instance could be reloaded from db after deletion");
 }
 
-// TODO: provide helpers to autogenerate uuids, pks, strings, emails, etc – maybe reexport another
-// crate?
+/// Tests the relationship of `children` referring to `parent` by their foreign key.
+///
+/// Asserts that
+///
+/// - a child can not be created before its parent (the foreign key is enforced)
+/// - the children resolve their parent and the parent resolves its children, using both
+///   `ReferredBy::resolve` and `ReferredBy::resolve_by`
+/// - `ReferredBy::delete_all` deletes all children
+/// - deleting the parent applies the declared `on_delete` action to the children: they are deleted
+///   (`cascade`), their foreign keys are cleared (`set null`, `set default`) or the deletion fails
+///   (`restrict`, `no action`)
+///
+/// The children have to refer to `parent`, which must not exist yet.
+pub async fn relationship<P, C>(pool: &crate::Pool, parent: P, children: Vec<C>)
+where
+    P: Entity + ReferredBy<C> + Clone + Debug + Eq + Send + Unpin,
+    C: Entity + RefersTo<P> + Deletable + Clone + Debug + Eq + Send + Unpin,
+{
+    let mut parent = parent;
+    let mut children = children;
+
+    if let Some(child) = children.first_mut() {
+        assert!(
+            child.clone().create(pool).await.is_err(),
+            "{child:?} was created before its parent"
+        );
+    }
+
+    parent.create(pool).await.expect("insertion did not work");
+
+    for child in children.iter_mut() {
+        child.create(pool).await.expect("insertion did not work");
+    }
+
+    for child in &children {
+        let resolved = child
+            .resolve_optional(pool)
+            .await
+            .expect("resolving the parent did not work");
+
+        assert_eq!(
+            resolved.as_ref(),
+            Some(&parent),
+            "{child:?} resolved another parent"
+        );
+    }
+
+    let resolved = <P as ReferredBy<C>>::resolve(&parent, pool)
+        .await
+        .expect("resolving the children did not work");
+
+    assert_same(&resolved, &children);
+
+    let resolved = <P as ReferredBy<C>>::resolve_by(pool, parent.pk())
+        .await
+        .expect("resolving the children by primary key did not work");
+
+    assert_same(&resolved, &children);
+
+    let deleted = parent
+        .delete_all(pool)
+        .await
+        .expect("deleting the children did not work");
+
+    assert_eq!(deleted.rows_affected, children.len() as u64);
+
+    assert!(
+        <P as ReferredBy<C>>::resolve(&parent, pool)
+            .await
+            .unwrap()
+            .is_empty(),
+        "children were found after deleting all of them"
+    );
+
+    for child in children.iter_mut() {
+        // soft deleted children are still present
+        C::hard_delete_by(pool, child.pk())
+            .await
+            .expect("hard deletion did not work");
+
+        child.create(pool).await.expect("insertion did not work");
+    }
+
+    // soft deletion does not delete the row, which is what triggers the action
+    let deletion = P::hard_delete_by(pool, parent.pk()).await;
+
+    match <C as RefersTo<P>>::FOREIGN_KEY.on_delete {
+        ReferentialAction::Cascade => {
+            deletion.expect("deleting the parent did not work");
+
+            for child in &children {
+                assert!(
+                    C::find(pool, child.pk()).await.unwrap().is_none(),
+                    "{child:?} was not deleted along with its parent"
+                );
+            }
+        }
+        ReferentialAction::SetNull | ReferentialAction::SetDefault => {
+            deletion.expect("deleting the parent did not work");
+
+            for child in &children {
+                let child = C::read(pool, child.pk())
+                    .await
+                    .expect("child not found after deleting its parent");
+
+                assert!(
+                    child.resolve_optional(pool).await.unwrap().is_none(),
+                    "{child:?} refers to its deleted parent"
+                );
+            }
+        }
+        ReferentialAction::Restrict | ReferentialAction::NoAction => {
+            if children.is_empty() {
+                deletion.expect("deleting the parent did not work");
+                return;
+            }
+
+            assert!(
+                deletion.is_err(),
+                "the parent was deleted while children referred to it"
+            );
+
+            for child in &children {
+                assert!(
+                    C::find(pool, child.pk()).await.unwrap().is_some(),
+                    "{child:?} was deleted along with its parent"
+                );
+            }
+        }
+    }
+}
+
+/// Asserts that `resolved` holds the same entities as `expected`, in any order
+fn assert_same<E: Debug + Eq>(resolved: &[E], expected: &[E]) {
+    assert_eq!(
+        resolved.len(),
+        expected.len(),
+        "resolved {resolved:?} instead of {expected:?}"
+    );
+
+    for entity in expected {
+        assert!(
+            resolved.contains(entity),
+            "{entity:?} was not resolved, resolved {resolved:?}"
+        );
+    }
+}
+
+/// Tests the round trip of generated entities through the database.
+///
+/// Creates, reads, updates and deletes each of the `instances`, asserting that every read returns
+/// the instance as written. The instances can come from any generator, e.g. a `proptest` strategy
+/// (see `roundtrip_prop`) or `arbitrary`, which have to respect the constraints of the table:
+/// foreign keys have to reference existing rows and unique columns must not collide with rows
+/// already present. Primary keys may repeat, as each instance is removed before the next one is
+/// created.
+pub async fn roundtrip<E, I>(pool: &crate::Pool, instances: I)
+where
+    E: Entity + Clone + Debug + Eq + Send,
+    I: IntoIterator<Item = E>,
+{
+    for instance in instances {
+        if let Err(failure) = check_roundtrip(pool, instance).await {
+            panic!("{failure}");
+        }
+    }
+}
+
+/// The round trip of a single instance, see [`roundtrip`]. Removes the instance even if the round
+/// trip failed, so further instances may reuse its primary key.
+async fn check_roundtrip<E>(pool: &crate::Pool, mut instance: E) -> std::result::Result<(), String>
+where
+    E: Entity + Clone + Debug + Eq + Send,
+{
+    let result = async {
+        instance
+            .create(pool)
+            .await
+            .map_err(|err| format!("insertion of {instance:?} did not work: {err}"))?;
+
+        let retrieved = E::read(pool, instance.pk())
+            .await
+            .map_err(|err| format!("{instance:?} not found after insertion: {err}"))?;
+
+        if retrieved != instance {
+            return Err(format!("{instance:?} was read as {retrieved:?}"));
+        }
+
+        instance
+            .update(pool)
+            .await
+            .map_err(|err| format!("updating {instance:?} did not work: {err}"))?;
+
+        let retrieved = E::read(pool, instance.pk())
+            .await
+            .map_err(|err| format!("{instance:?} not found after update: {err}"))?;
+
+        if retrieved != instance {
+            return Err(format!(
+                "{instance:?} was read as {retrieved:?} after update"
+            ));
+        }
+
+        instance
+            .delete(pool)
+            .await
+            .map_err(|err| format!("deletion of {instance:?} did not work: {err}"))?;
+
+        match E::find(pool, instance.pk()).await {
+            Ok(None) => Ok(()),
+            Ok(Some(_)) => Err(format!("{instance:?} was found after deletion")),
+            Err(err) => Err(format!("finding {instance:?} did not work: {err}")),
+        }
+    }
+    .await;
+
+    // soft deleted rows are still present and would collide with a repeated primary key
+    E::hard_delete_by(pool, instance.pk())
+        .await
+        .expect("hard deletion did not work");
+
+    result
+}
+
+/// Tests the round trip of `cases` entities generated by a `proptest` strategy through the
+/// database, see [`roundtrip`].
+///
+/// A failing entity is shrunk to the simplest entity of the strategy still failing, which is
+/// reported by the panic. Foreign keys are pointed to existing rows using [`references`],
+/// entities implementing `arbitrary::Arbitrary` are generated by `arbitrary` (feature
+/// `arbitrary`).
+///
+/// ```ignore
+/// let forests = Forest::factory().create_many(&pool, 3).await?;
+///
+/// let trees = (any::<i32>(), references(&forests)).prop_map(|(id, forest)| Tree { id, forest });
+///
+/// atmosphere::testing::roundtrip_prop(&pool, trees, 64).await;
+/// ```
+#[cfg(feature = "proptest")]
+pub async fn roundtrip_prop<E, S>(pool: &crate::Pool, strategy: S, cases: u32)
+where
+    E: Entity + Clone + Debug + Eq + Send,
+    S: proptest::strategy::Strategy<Value = E>,
+{
+    use proptest::{strategy::ValueTree, test_runner::TestRunner};
+
+    let mut runner = TestRunner::default();
+
+    for _ in 0..cases {
+        let mut tree = strategy
+            .new_tree(&mut runner)
+            .unwrap_or_else(|err| panic!("strategy did not generate an entity: {err}"));
+
+        let Err(mut failure) = check_roundtrip(pool, tree.current()).await else {
+            continue;
+        };
+
+        // simplify the entity while it keeps failing, undo simplifications making it pass
+        let mut passed = false;
+        let mut iterations = 0;
+
+        while iterations < runner.config().max_shrink_iters {
+            iterations += 1;
+
+            let shrunk = match passed {
+                false => tree.simplify(),
+                true => tree.complicate(),
+            };
+
+            if !shrunk {
+                break;
+            }
+
+            match check_roundtrip(pool, tree.current()).await {
+                Ok(()) => passed = true,
+                Err(err) => {
+                    failure = err;
+                    passed = false;
+                }
+            }
+        }
+
+        panic!("{failure}");
+    }
+}
+
+/// A strategy choosing the primary key of one of `parents`, pointing the foreign keys of generated
+/// entities to existing rows. Panics if `parents` is empty.
+#[cfg(feature = "proptest")]
+pub fn references<P>(parents: &[P]) -> impl proptest::strategy::Strategy<Value = P::PrimaryKey>
+where
+    P: Table,
+    P::PrimaryKey: Clone + Debug,
+{
+    proptest::sample::select(parents.iter().map(|p| p.pk().clone()).collect::<Vec<_>>())
+}
+
+/// A strategy generating entities implementing `arbitrary::Arbitrary` from random bytes, shrinking
+/// them by shrinking the bytes
+#[cfg(feature = "arbitrary")]
+pub fn arbitrary<E>() -> impl proptest::strategy::Strategy<Value = E>
+where
+    E: for<'a> ::arbitrary::Arbitrary<'a> + Debug,
+{
+    use proptest::strategy::Strategy;
+
+    proptest::collection::vec(proptest::num::u8::ANY, 0..256)
+        .prop_filter_map("not enough bytes", |bytes| {
+            E::arbitrary(&mut ::arbitrary::Unstructured::new(&bytes)).ok()
+        })
+}
+
+/// Runs `f` inside of a transaction with the given isolation level.
+///
+/// The transaction is always rolled back, so tests can exercise concurrent behavior at a specific
+/// isolation level without leaving any rows behind.
+pub async fn isolated<T, F>(pool: &crate::Pool, isolation: IsolationLevel, f: F) -> T
+where
+    F: for<'c> FnOnce(&'c mut Connection) -> BoxFuture<'c, T> + Send,
+    T: Send,
+{
+    let mut conn = pool
+        .acquire()
+        .await
+        .expect("could not acquire a connection");
+
+    let mut tx = transaction::begin(&mut conn, Some(isolation))
+        .await
+        .expect("could not begin transaction");
+
+    let value = f(&mut tx).await;
+
+    tx.rollback()
+        .await
+        .expect("could not roll back transaction");
+
+    value
+}
+
+/// Dumps the statements generated for the table `T`, for snapshot tests of the sql.
+///
+/// Lists the statements of reading, writing and deleting rows, of the unique and relation finders
+/// and the `CREATE TABLE` statement, each preceded by a `-- <operation>` comment. The dump is
+/// deterministic, so comparing it to a stored snapshot shows how a change of the schema affects
+/// the generated sql. It covers all operations, including those a view or a table using
+/// `deny(..)` does not implement.
+///
+/// ```ignore
+/// insta::assert_snapshot!(atmosphere::testing::sql_snapshot::<User>());
+/// ```
+pub fn sql_snapshot<T: Bind>() -> String {
+    let mut statements: Vec<(String, String)> = vec![
+        ("select".to_owned(), sql::select::<T>().sql().to_owned()),
+        (
+            "select_all".to_owned(),
+            sql::select_all::<T>().sql().to_owned(),
+        ),
+        ("insert".to_owned(), sql::insert::<T>().sql().to_owned()),
+        ("update".to_owned(), sql::update::<T>().sql().to_owned()),
+        ("upsert".to_owned(), sql::upsert::<T>().sql().to_owned()),
+        ("delete".to_owned(), sql::delete::<T>().sql().to_owned()),
+    ];
+
+    // soft deleted rows are removed by a statement of its own
+    if sql::deleted::<T>().is_some() {
+        statements.push((
+            "hard_delete".to_owned(),
+            sql::hard_delete::<T>().sql().to_owned(),
+        ));
+    }
+
+    for data in T::DATA_COLUMNS.iter().filter(|data| data.unique) {
+        statements.push((
+            format!("find_by_{}", data.field),
+            sql::select_by::<T>(data.as_col()).sql().to_owned(),
+        ));
+    }
+
+    for constraint in T::UNIQUE {
+        statements.push((
+            format!("upsert_on {}", constraint.columns.join(", ")),
+            sql::upsert_on::<T>(constraint).sql().to_owned(),
+        ));
+    }
+
+    for fk in T::FOREIGN_KEYS {
+        statements.push((
+            format!("find_by_{}", fk.field),
+            sql::select_by::<T>(fk.as_col()).sql().to_owned(),
+        ));
+        statements.push((
+            format!("delete_by_{}", fk.field),
+            sql::delete_by::<T>(fk.as_col()).sql().to_owned(),
+        ));
+    }
+
+    statements.push(("create_table".to_owned(), sql::create_table::<T>()));
+
+    statements
+        .into_iter()
+        .map(|(operation, sql)| format!("-- {operation}\n{}\n", sql.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+static SEQUENCE: AtomicU64 = AtomicU64::new(1);
+
+/// The next number of the sequence the values of fixtures are generated from, unique within the
+/// process
+pub fn sequence() -> u64 {
+    SEQUENCE.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A value generated for a field of a fixture.
+///
+/// The values of a row are generated from the same number of the [`sequence`], so integers and
+/// strings (`"<field>-<n>"`, or email addresses, urls and phone numbers named like such) are unique
+/// across rows. Nullable fields are `None` and collections are
+/// empty. Implement this for the custom types of fields (e.g. enums) to generate their fixtures.
+///
+/// With the `fake` feature, strings and timestamps are realistic values of the [`fake`] crate
+/// instead: names, cities and companies for fields named like such, and words otherwise. Strings
+/// still end with the number of the row, so they stay unique, except for phone numbers.
+pub trait Generate {
+    /// Generates the value of `field` for the row numbered `seq`
+    fn generate(field: &'static str, seq: u64) -> Self;
+}
+
+macro_rules! generate_numbers {
+    ($($ty:ty),*) => {
+        $(
+            impl Generate for $ty {
+                fn generate(_: &'static str, seq: u64) -> Self {
+                    seq as $ty
+                }
+            }
+        )*
+    };
+}
+
+generate_numbers!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+impl Generate for bool {
+    fn generate(_: &'static str, _: u64) -> Self {
+        false
+    }
+}
+
+impl Generate for String {
+    /// Generates `"<field>-<n>"`, formatted as an email address, url or phone number if the name
+    /// of the field suggests so
+    fn generate(field: &'static str, seq: u64) -> Self {
+        let name = field.to_lowercase();
+
+        if name.contains("email") || name.contains("mail_address") {
+            return strings::email(field, seq);
+        }
+
+        if name.contains("url") || name.contains("website") || name.contains("link") {
+            return strings::url(field, seq);
+        }
+
+        if name.contains("phone") {
+            return strings::phone(seq);
+        }
+
+        strings::text(field, &name, seq)
+    }
+}
+
+/// The strings generated without the `fake` feature
+#[cfg(not(feature = "fake"))]
+mod strings {
+    pub fn email(field: &str, seq: u64) -> String {
+        format!("{field}-{seq}@example.org")
+    }
+
+    pub fn url(field: &str, seq: u64) -> String {
+        format!("https://example.org/{field}/{seq}")
+    }
+
+    pub fn phone(seq: u64) -> String {
+        format!("+1555{seq:07}")
+    }
+
+    pub fn text(field: &str, _: &str, seq: u64) -> String {
+        format!("{field}-{seq}")
+    }
+}
+
+/// The strings generated using the `fake` crate
+#[cfg(feature = "fake")]
+mod strings {
+    use fake::{
+        faker::{
+            address::en::CityName,
+            company::en::CompanyName,
+            internet::en::{DomainSuffix, SafeEmail},
+            lorem::en::Word,
+            name::en::{FirstName, LastName, Name},
+            phone_number::en::PhoneNumber,
+        },
+        Fake,
+    };
+
+    pub fn email(_: &str, seq: u64) -> String {
+        let email: String = SafeEmail().fake();
+
+        match email.split_once('@') {
+            Some((user, domain)) => format!("{user}.{seq}@{domain}"),
+            None => format!("{email}.{seq}@example.org"),
+        }
+    }
+
+    pub fn url(_: &str, seq: u64) -> String {
+        let host: String = Word().fake();
+        let suffix: String = DomainSuffix().fake();
+
+        format!("https://{host}.{suffix}/{seq}")
+    }
+
+    pub fn phone(_: u64) -> String {
+        PhoneNumber().fake()
+    }
+
+    pub fn text(_: &str, name: &str, seq: u64) -> String {
+        let value: String = if name.contains("first_name") || name.contains("firstname") {
+            FirstName().fake()
+        } else if name.contains("last_name") || name.contains("lastname") {
+            LastName().fake()
+        } else if name.contains("city") {
+            CityName().fake()
+        } else if name.contains("company") || name.contains("organization") {
+            CompanyName().fake()
+        } else if name.contains("name") {
+            Name().fake()
+        } else {
+            Word().fake()
+        };
+
+        format!("{value}-{seq}")
+    }
+}
+
+impl<T> Generate for Option<T> {
+    fn generate(_: &'static str, _: u64) -> Self {
+        None
+    }
+}
+
+impl<T> Generate for Vec<T> {
+    fn generate(_: &'static str, _: u64) -> Self {
+        Vec::new()
+    }
+}
+
+impl Generate for sqlx::types::JsonValue {
+    fn generate(_: &'static str, _: u64) -> Self {
+        Self::Object(Default::default())
+    }
+}
+
+impl<T: Default> Generate for sqlx::types::Json<T> {
+    fn generate(_: &'static str, _: u64) -> Self {
+        Self(T::default())
+    }
+}
+
+#[cfg(not(feature = "fake"))]
+impl Generate for sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc> {
+    fn generate(_: &'static str, _: u64) -> Self {
+        sqlx::types::chrono::Utc::now()
+    }
+}
+
+/// A point in time within the last year
+#[cfg(feature = "fake")]
+impl Generate for sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc> {
+    fn generate(_: &'static str, _: u64) -> Self {
+        use fake::{faker::chrono::en::DateTimeBetween, Fake};
+        use std::time::Duration;
+
+        let now = sqlx::types::chrono::Utc::now();
+        let at: Self = DateTimeBetween(now - Duration::from_secs(365 * 86400), now).fake();
+
+        // databases store microseconds at most
+        Self::from_timestamp_micros(at.timestamp_micros()).unwrap_or(at)
+    }
+}
+
+impl Generate for sqlx::types::chrono::NaiveDateTime {
+    fn generate(field: &'static str, seq: u64) -> Self {
+        sqlx::types::chrono::DateTime::<sqlx::types::chrono::Utc>::generate(field, seq).naive_utc()
+    }
+}
+
+impl Generate for sqlx::types::chrono::NaiveDate {
+    fn generate(field: &'static str, seq: u64) -> Self {
+        sqlx::types::chrono::DateTime::<sqlx::types::chrono::Utc>::generate(field, seq).date_naive()
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl Generate for sqlx::types::Decimal {
+    fn generate(_: &'static str, seq: u64) -> Self {
+        Self::from(seq)
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl Generate for sqlx::types::BigDecimal {
+    fn generate(_: &'static str, seq: u64) -> Self {
+        Self::from(seq)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Generate for uuid::Uuid {
+    fn generate(_: &'static str, _: u64) -> Self {
+        Self::new_v4()
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Generate for crate::interval::Interval {
+    fn generate(_: &'static str, _: u64) -> Self {
+        Self::default()
+    }
+}
+
+/// A table whose rows can be generated for tests, implemented by `#[table(.., factory)]`
+#[async_trait]
+pub trait Fixture: Table + Create + Sized {
+    /// A row holding generated values for all fields, see [`Generate`]
+    fn generate() -> Self;
+
+    /// Creates a generated parent row for each foreign key which is not nullable and points the key
+    /// to it
+    async fn create_parents(&mut self, pool: &crate::Pool) -> Result<()>;
+}
+
+/// A change of generated rows, see [`Factory::with`]
+type Edit<T> = Box<dyn Fn(&mut T) + Send + Sync>;
+
+/// Builds and creates generated rows of `T`, replacing hand-rolled fixtures.
+///
+/// Fields are generated (see [`Generate`]) unless set using [`Factory::with`], parents referenced
+/// by foreign keys are created first.
+///
+/// ```ignore
+/// #[derive(Schema)]
+/// #[table(schema = "public", name = "user", factory)]
+/// struct User {
+///     #[sql(pk)]
+///     id: i32,
+///     #[sql(fk -> Org)]
+///     org: i32,
+///     email: String,
+/// }
+///
+/// let user = User::factory()
+///     .with(|u| u.email = "ada@example.org".to_owned())
+///     .create(&pool)
+///     .await?;
+/// ```
+pub struct Factory<T> {
+    edits: Vec<Edit<T>>,
+    parents: bool,
+}
+
+impl<T: Fixture> Factory<T> {
+    /// A factory of generated rows
+    pub fn new() -> Self {
+        Self {
+            edits: vec![],
+            parents: true,
+        }
+    }
+
+    /// Edits the generated rows, e.g. to set fields to specific values. Applied in order, after
+    /// the foreign keys have been pointed to their parents.
+    pub fn with(mut self, edit: impl Fn(&mut T) + Send + Sync + 'static) -> Self {
+        self.edits.push(Box::new(edit));
+        self
+    }
+
+    /// Does not create parents, the foreign keys have to be set to existing rows using
+    /// [`Factory::with`]
+    pub fn without_parents(mut self) -> Self {
+        self.parents = false;
+        self
+    }
+
+    /// Builds a row without creating it or its parents
+    pub fn build(&self) -> T {
+        let mut row = T::generate();
+
+        self.edits.iter().for_each(|edit| edit(&mut row));
+
+        row
+    }
+
+    /// Creates a row, after creating its parents
+    pub async fn create(&self, pool: &crate::Pool) -> Result<T> {
+        let mut row = T::generate();
+
+        if self.parents {
+            row.create_parents(pool).await?;
+        }
+
+        self.edits.iter().for_each(|edit| edit(&mut row));
+
+        row.create(pool).await?;
+
+        Ok(row)
+    }
+
+    /// Creates `n` rows, each with its own parents
+    pub async fn create_many(&self, pool: &crate::Pool, n: usize) -> Result<Vec<T>> {
+        let mut rows = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            rows.push(self.create(pool).await?);
+        }
+
+        Ok(rows)
+    }
+}
+
+impl<T: Fixture> Default for Factory<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Generate;
+
+    #[test]
+    #[cfg(not(feature = "fake"))]
+    fn generate_strings() {
+        assert_eq!(String::generate("name", 7), "name-7");
+        assert_eq!(String::generate("email", 7), "email-7@example.org");
+        assert_eq!(
+            String::generate("contactEmail", 7),
+            "contactEmail-7@example.org"
+        );
+        assert_eq!(
+            String::generate("avatar_url", 7),
+            "https://example.org/avatar_url/7"
+        );
+        assert_eq!(String::generate("phone", 7), "+15550000007");
+    }
+
+    #[test]
+    #[cfg(feature = "fake")]
+    fn generate_fake() {
+        use sqlx::types::chrono::{DateTime, Utc};
+        use std::time::Duration;
+
+        let email = String::generate("email", 7);
+        assert!(email.contains(".7@"), "{email}");
+
+        assert!(String::generate("avatar_url", 7).starts_with("https://"));
+        assert!(String::generate("first_name", 7).ends_with("-7"));
+        assert_ne!(String::generate("name", 7), "name-7");
+
+        let at = DateTime::<Utc>::generate("created_at", 7);
+        assert!(at <= Utc::now() && at >= Utc::now() - Duration::from_secs(366 * 86400));
+    }
+}
@@ -0,0 +1,101 @@
+//! Throwaway databases in containers, for tests running outside of `sqlx::test`.
+//!
+//! [`container`] starts a Postgres (feature `postgres`) or MySQL (feature `mysql`) container using
+//! `testcontainers`, applies the migrations and connects a pool to it. The container is removed
+//! once the returned [`Container`] is dropped, so it has to outlive the test:
+//!
+//! ```ignore
+//! let db = atmosphere::testing::container(&sqlx::migrate!("./migrations")).await;
+//!
+//! user.create(db.pool()).await?;
+//! ```
+//!
+//! A docker daemon has to be reachable, e.g. as configured by `DOCKER_HOST`.
+
+use std::ops::Deref;
+
+use sqlx::migrate::Migrator;
+use testcontainers_modules::testcontainers::{runners::AsyncRunner, ContainerAsync};
+
+#[cfg(feature = "postgres")]
+type Image = testcontainers_modules::postgres::Postgres;
+
+#[cfg(feature = "postgres")]
+const PORT: u16 = 5432;
+
+#[cfg(feature = "mysql")]
+type Image = testcontainers_modules::mysql::Mysql;
+
+#[cfg(feature = "mysql")]
+const PORT: u16 = 3306;
+
+/// A database running in a container, removed once dropped, see [`container`]
+pub struct Container {
+    pool: crate::Pool,
+    url: String,
+    /// Dropping the container stops and removes it
+    _container: ContainerAsync<Image>,
+}
+
+impl Container {
+    /// The pool connected to the database
+    pub fn pool(&self) -> &crate::Pool {
+        &self.pool
+    }
+
+    /// The url of the database, e.g. to connect further pools
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Deref for Container {
+    type Target = crate::Pool;
+
+    fn deref(&self) -> &Self::Target {
+        &self.pool
+    }
+}
+
+/// Starts a throwaway database in a container, applies the migrations of `migrator` and connects a
+/// pool to it.
+///
+/// Panics if the container can not be started (e.g. as no docker daemon is reachable) or the
+/// migrations fail.
+pub async fn container(migrator: &Migrator) -> Container {
+    let container = Image::default()
+        .start()
+        .await
+        .expect("starting the database container did not work");
+
+    let host = container
+        .get_host()
+        .await
+        .expect("the host of the database container is unknown");
+
+    let port = container
+        .get_host_port_ipv4(PORT)
+        .await
+        .expect("the port of the database container is not exposed");
+
+    #[cfg(feature = "postgres")]
+    let url = format!("postgres://postgres:postgres@{host}:{port}/postgres");
+
+    #[cfg(feature = "mysql")]
+    let url = format!("mysql://root@{host}:{port}/test");
+
+    let pool = crate::Pool::connect(&url)
+        .await
+        .expect("connecting to the database container did not work");
+
+    migrator
+        .run(&pool)
+        .await
+        .expect("migrating the database container did not work");
+
+    Container {
+        pool,
+        url,
+        _container: container,
+    }
+}
@@ -0,0 +1,156 @@
+//! Records the hooks executed for a table, to test when hooks fire.
+//!
+//! [`record`] registers a recording hook for every [`HookStage`] of a table, which captures the
+//! stage, operation and input of each call. As the recording hooks run after the hooks of the
+//! table, the recorded calls show the stages its `#[hooks(..)]` were executed in.
+//!
+//! ```ignore
+//! let recording = atmosphere::testing::hooks::record::<Forest>();
+//!
+//! forest.create(&pool).await?;
+//!
+//! assert_eq!(
+//!     recording.stages(Operation::Insert),
+//!     vec![HookStage::PreBind, HookStage::PreExec, HookStage::PostExec]
+//! );
+//! ```
+
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    hooks::{Hook, HookInput, HookStage, Hooks},
+    query::{Operation, Query},
+    Bind, Result, Table,
+};
+
+/// The input a hook was called with, see [`HookInput`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordedInput {
+    /// No input
+    None,
+    /// A row, formatted using `Debug`
+    Row(String),
+    /// A primary key
+    PrimaryKey,
+    /// The result of the query and whether it succeeded
+    QueryResult { ok: bool },
+    /// The error the query failed with, formatted using `Display`
+    Error(String),
+}
+
+/// A call of a hook, captured by a [`Recording`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HookCall {
+    /// The stage of the hook
+    pub stage: HookStage,
+    /// The operation of the query
+    pub op: Operation,
+    /// The sql of the query
+    pub sql: String,
+    /// The input of the hook
+    pub input: RecordedInput,
+}
+
+/// The hook calls recorded for the table `T`, see [`record`]
+pub struct Recording<T> {
+    calls: Arc<Mutex<Vec<HookCall>>>,
+    table: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T> Recording<T> {
+    /// All calls recorded so far, in order
+    pub fn calls(&self) -> Vec<HookCall> {
+        self.calls.lock().expect("recording poisoned").clone()
+    }
+
+    /// Returns and clears the calls recorded so far
+    pub fn take(&self) -> Vec<HookCall> {
+        std::mem::take(&mut *self.calls.lock().expect("recording poisoned"))
+    }
+
+    /// The stages of the calls recorded for queries performing `op`, in order
+    pub fn stages(&self, op: Operation) -> Vec<HookStage> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.op == op)
+            .map(|call| call.stage)
+            .collect()
+    }
+}
+
+/// Starts recording the hook calls of the table `T`.
+///
+/// The recording hooks are registered using [`Hooks::register_hook`] and remain registered, so
+/// each recording should be started once per table and cleared using [`Recording::take`]. As
+/// the calls of all queries on `T` are recorded, tests using the same table concurrently record
+/// each other's calls.
+pub fn record<T>() -> Recording<T>
+where
+    T: Hooks + Debug + Send + Sync + 'static,
+{
+    let calls: Arc<Mutex<Vec<HookCall>>> = Arc::default();
+
+    let stages = [
+        HookStage::PreBind,
+        HookStage::PreExec,
+        HookStage::PostExec,
+        HookStage::OnError,
+        HookStage::PreCommit,
+        HookStage::PostCommit,
+    ];
+
+    for stage in stages {
+        T::register_hook(Arc::new(Recorder {
+            stage,
+            calls: calls.clone(),
+        }));
+    }
+
+    Recording {
+        calls,
+        table: std::marker::PhantomData,
+    }
+}
+
+/// Records the calls of a single stage
+struct Recorder {
+    stage: HookStage,
+    calls: Arc<Mutex<Vec<HookCall>>>,
+}
+
+#[async_trait]
+impl<T> Hook<T> for Recorder
+where
+    T: Table + Bind + Debug + Send + Sync,
+{
+    fn stage(&self) -> HookStage {
+        self.stage
+    }
+
+    async fn apply(&self, ctx: &Query<T>, input: &mut HookInput<'_, T>) -> Result<()> {
+        let input = match input {
+            HookInput::None => RecordedInput::None,
+            HookInput::Row(row) => RecordedInput::Row(format!("{row:?}")),
+            HookInput::PrimaryKey(_) => RecordedInput::PrimaryKey,
+            HookInput::QueryResult(res) => RecordedInput::QueryResult { ok: res.is_ok() },
+            HookInput::Error(err) => RecordedInput::Error(err.to_string()),
+        };
+
+        self.calls
+            .lock()
+            .expect("recording poisoned")
+            .push(HookCall {
+                stage: self.stage,
+                op: ctx.op,
+                sql: ctx.sql().to_owned(),
+                input,
+            });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+//! Time windows over timestamp columns.
+//!
+//! `#[derive(Schema)]` generates `find_<kind>_before`, `find_<kind>_after` and
+//! `find_<kind>_between` for the timestamp columns of a table (`find_created_between` for a
+//! `#[sql(timestamp = created)]` column), which find the rows whose timestamp lies within a
+//! [`Window`], ordered by the timestamp.
+//!
+//! Windows are half-open: `between(from, to)` includes `from` but excludes `to`, so consecutive
+//! windows never select a row twice. Soft deleted rows are excluded, except when searching the
+//! deletion timestamp itself (`find_deleted_between`).
+//!
+//! ```ignore
+//! let today = Order::find_created_between(&pool, &midnight, &now).await?;
+//! let stale = Order::find_updated_before(&pool, &(now - Duration::days(30))).await?;
+//! ```
+
+use sqlx::{database::HasArguments, Encode, Executor, IntoArguments, Type};
+
+use crate::{
+    hooks::{self, HookInput, HookStage, Hooks},
+    query::QueryResult,
+    Bind, Result, Table, TimestampColumn,
+};
+
+/// A range of time rows are selected in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Window<V> {
+    /// Strictly before the time (`< ..`)
+    Before(V),
+    /// Strictly after the time (`> ..`)
+    After(V),
+    /// From the first time (inclusive) to the second one (exclusive) (`>= .. AND < ..`)
+    Between(V, V),
+}
+
+impl<V> Window<V> {
+    /// The condition on `column`, its times bound starting at `$1`
+    pub fn condition(&self, column: &str) -> String {
+        match self {
+            Self::Before(_) => format!("{column} < $1"),
+            Self::After(_) => format!("{column} > $1"),
+            Self::Between(..) => format!("{column} >= $1 AND {column} < $2"),
+        }
+    }
+
+    /// The number of times bound by the condition
+    pub const fn binds(&self) -> usize {
+        match self {
+            Self::Before(_) | Self::After(_) => 1,
+            Self::Between(..) => 2,
+        }
+    }
+}
+
+/// Finds all rows whose timestamp held by `column` lies within `window`, ordered by the timestamp.
+pub async fn find_in<'e, T, E, V>(
+    executor: E,
+    column: &'static TimestampColumn<T>,
+    window: Window<V>,
+) -> Result<Vec<T>>
+where
+    T: Table + Bind + Hooks + Send + Sync + Unpin + 'static,
+    E: Executor<'e, Database = crate::Driver>,
+    for<'q> <crate::Driver as HasArguments<'q>>::Arguments: IntoArguments<'q, crate::Driver> + Send,
+    V: for<'q> Encode<'q, crate::Driver> + Type<crate::Driver> + Send + Sync,
+{
+    let mut query = crate::runtime::sql::select_window::<T, V>(column, &window);
+
+    hooks::prepare(&mut query, HookInput::None).await?;
+    hooks::execute(HookStage::PreExec, &query, HookInput::None).await?;
+
+    let sql = match window {
+        Window::Before(time) | Window::After(time) => sqlx::query_as(query.sql()).bind(time),
+        Window::Between(from, to) => sqlx::query_as(query.sql()).bind(from).bind(to),
+    };
+
+    let res = query
+        .bind_values(sql)?
+        .persistent(false)
+        .fetch_all(executor)
+        .await
+        .map_err(|err| query.context().error(err));
+
+    hooks::execute(HookStage::PostExec, &query, QueryResult::Many(&res).into()).await?;
+
+    res
+}
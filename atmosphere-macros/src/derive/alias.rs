@@ -0,0 +1,48 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::schema::{column::Column, table::Table};
+
+pub fn alias(table: &Table) -> TokenStream {
+    let ident = &table.ident;
+
+    let columns = std::iter::once(Column::PrimaryKey(table.primary_key.clone()))
+        .chain(table.foreign_keys.iter().cloned().map(Column::ForeignKey))
+        .chain(table.data_columns.iter().cloned().map(Column::Data))
+        .chain(
+            table
+                .timestamp_columns
+                .iter()
+                .cloned()
+                .map(Column::Timestamp),
+        );
+
+    let fields = columns.map(|column| {
+        let field = column.name().field();
+        let sql = column.name().sql().to_string();
+
+        quote!(#field: row.try_get(format!("{alias}.{}", #sql).as_str())?)
+    });
+
+    let skipped = table
+        .skipped
+        .iter()
+        .map(|field| quote!(#field: ::std::default::Default::default()));
+
+    quote!(
+        #[automatically_derived]
+        impl ::atmosphere::FromAliasedRow for #ident {
+            fn from_aliased_row(
+                row: &<::atmosphere::Driver as ::atmosphere::sqlx::Database>::Row,
+                alias: &str,
+            ) -> ::atmosphere::sqlx::Result<Self> {
+                use ::atmosphere::sqlx::Row;
+
+                Ok(Self {
+                    #(#fields,)*
+                    #(#skipped,)*
+                })
+            }
+        }
+    )
+}
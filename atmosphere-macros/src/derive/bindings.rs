@@ -35,6 +35,18 @@ pub fn bindings(table: &Table) -> TokenStream {
     for data in &table.data_columns {
         let field = data.name.field();
 
+        if data.modifiers.encrypted {
+            binds.extend(quote!(
+                if #col.field() == stringify!(#field) {
+                    use ::atmosphere::Bindable;
+                    let sealed = ::atmosphere::crypto::seal(&self.#field)?;
+                    return Ok(#query.dyn_bind(sealed));
+                }
+            ));
+
+            continue;
+        }
+
         binds.extend(quote!(
             if #col.field() == stringify!(#field) {
                 use ::atmosphere::Bindable;
@@ -0,0 +1,194 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{spanned::Spanned, Ident, Type};
+
+use crate::schema::table::Table;
+
+/// Verifies the columns of tables using `#[table(checked)]` at compile time.
+///
+/// The statements are equivalent to the ones generated at runtime (selecting, inserting, updating
+/// and deleting a row by its primary key, unless denied or a view) and are passed to
+/// `sqlx::query!`, which checks them against the database at `DATABASE_URL` (or the offline query
+/// data of `cargo sqlx prepare`). The selected columns are assigned to the types of their fields,
+/// so both the parameters and the results are type checked. The emitted function is never called.
+pub fn checked(table: &Table) -> TokenStream {
+    if !table.id.checked {
+        return quote!();
+    }
+
+    let ident = &table.ident;
+
+    #[cfg(not(feature = "sqlite"))]
+    let name = format!("\"{}\".\"{}\"", table.id.schema, table.id.table);
+
+    #[cfg(feature = "sqlite")]
+    let name = format!("\"{}\"", table.id.table);
+
+    let pk = &table.primary_key;
+    let pk_field = pk.name.field();
+    let pk_sql = pk.name.sql().to_string();
+
+    let columns: Vec<(&Ident, String, &Type)> = std::iter::once(&pk.name)
+        .map(|name| (name, &pk.ty))
+        .chain(table.foreign_keys.iter().map(|fk| (&fk.name, &fk.ty)))
+        .chain(table.data_columns.iter().map(|data| (&data.name, &data.ty)))
+        .chain(table.timestamp_columns.iter().map(|ts| (&ts.name, &ts.ty)))
+        .map(|(name, ty)| (name.field(), name.sql().to_string(), ty))
+        .collect();
+
+    let others = &columns[1..];
+
+    let select = {
+        let selected = columns
+            .iter()
+            .map(|(field, sql, _)| match *field == sql {
+                true => sql.clone(),
+                false => format!("{sql} AS {field}"),
+            })
+            .collect::<Vec<_>>()
+            .join(",\n  ");
+
+        let sql = format!(
+            "SELECT\n  {selected}\nFROM\n  {name}\nWHERE {pk_sql} = {}",
+            placeholder(1)
+        );
+
+        // spanned on the field types, so mismatches point to the offending field
+        let assertions = columns
+            .iter()
+            .map(|(field, _, ty)| quote_spanned!(ty.span()=> let _: &#ty = &selected.#field;));
+
+        quote!(
+            let selected = ::sqlx::query!(#sql, row.#pk_field)
+                .fetch_one(&mut *executor)
+                .await?;
+
+            #(#assertions)*
+        )
+    };
+
+    // columns left to the database on insertion, and columns never updated
+    let defaults: Vec<&Ident> = table
+        .data_columns
+        .iter()
+        .filter(|data| data.modifiers.default || data.modifiers.readonly)
+        .map(|data| data.name.field())
+        .collect();
+
+    let fixed: Vec<&Ident> = table
+        .data_columns
+        .iter()
+        .filter(|data| data.modifiers.readonly || data.modifiers.immutable)
+        .map(|data| data.name.field())
+        .chain(
+            table
+                .foreign_keys
+                .iter()
+                .filter(|fk| fk.modifiers.immutable)
+                .map(|fk| fk.name.field()),
+        )
+        .collect();
+
+    let insert = {
+        let inserted: Vec<_> = match pk.modifiers.auto {
+            true => others.iter(),
+            false => columns.iter(),
+        }
+        .filter(|(field, _, _)| !defaults.contains(field))
+        .collect();
+
+        let names = inserted
+            .iter()
+            .map(|(_, sql, _)| sql.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let values = (1..=inserted.len())
+            .map(placeholder)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let sql = format!("INSERT INTO {name}\n  ({names})\nVALUES\n  ({values})");
+
+        let fields = inserted.iter().map(|(field, _, _)| field);
+
+        quote!(
+            ::sqlx::query!(#sql, #(row.#fields),*)
+                .execute(&mut *executor)
+                .await?;
+        )
+    };
+
+    let updated: Vec<_> = others
+        .iter()
+        .filter(|(field, _, _)| !fixed.contains(field))
+        .collect();
+
+    let update = match updated.is_empty() {
+        true => quote!(),
+        false => {
+            let set = updated
+                .iter()
+                .enumerate()
+                .map(|(i, (_, sql, _))| format!("{sql} = {}", placeholder(i + 1)))
+                .collect::<Vec<_>>()
+                .join(",\n  ");
+
+            let sql = format!(
+                "UPDATE {name} SET\n  {set}\nWHERE {pk_sql} = {}",
+                placeholder(updated.len() + 1)
+            );
+
+            let fields = updated.iter().map(|(field, _, _)| field);
+
+            quote!(
+                ::sqlx::query!(#sql, #(row.#fields,)* row.#pk_field)
+                    .execute(&mut *executor)
+                    .await?;
+            )
+        }
+    };
+
+    let delete = {
+        let sql = format!("DELETE FROM {name}\nWHERE {pk_sql} = {}", placeholder(1));
+
+        quote!(
+            ::sqlx::query!(#sql, row.#pk_field)
+                .execute(&mut *executor)
+                .await?;
+        )
+    };
+
+    // views are only read, tables may deny updates and deletes
+    let insert = (!table.id.view).then_some(insert);
+    let update = table.id.updatable().then_some(update);
+    let delete = table.id.deletable().then_some(delete);
+
+    quote!(
+        const _: () = {
+            #[allow(dead_code, clippy::all)]
+            async fn check(
+                executor: &mut <::atmosphere::Driver as ::sqlx::Database>::Connection,
+                row: &#ident,
+            ) -> ::sqlx::Result<()> {
+                #select
+
+                #insert
+
+                #update
+
+                #delete
+
+                Ok(())
+            }
+        };
+    )
+}
+
+/// The placeholder of the `n`-th parameter of a statement
+fn placeholder(n: usize) -> String {
+    match cfg!(feature = "mysql") {
+        true => "?".to_owned(),
+        false => format!("${n}"),
+    }
+}
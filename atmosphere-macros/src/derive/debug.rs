@@ -0,0 +1,39 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::schema::table::Table;
+
+/// Implements `Debug` for tables with sensitive columns, printing `«redacted»` for their values
+pub fn debug(table: &Table) -> TokenStream {
+    if !table.data_columns.iter().any(|c| c.modifiers.sensitive) {
+        return TokenStream::new();
+    }
+
+    let ident = &table.ident;
+    let name = ident.to_string();
+
+    let fields = table.fields.iter().map(|field| {
+        let sensitive = table
+            .data_columns
+            .iter()
+            .any(|c| c.name.field() == field && c.modifiers.sensitive);
+
+        let name = field.to_string();
+
+        match sensitive {
+            true => quote!(.field(#name, &::atmosphere::redact::Redacted)),
+            false => quote!(.field(#name, &self.#field)),
+        }
+    });
+
+    quote!(
+        #[automatically_derived]
+        impl ::std::fmt::Debug for #ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(#name)
+                    #(#fields)*
+                    .finish()
+            }
+        }
+    )
+}
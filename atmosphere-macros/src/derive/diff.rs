@@ -0,0 +1,46 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::schema::table::Table;
+
+/// Implements `Diff` for tables using `#[table(tracked)]`, comparing the updated columns
+pub fn diff(table: &Table) -> TokenStream {
+    if !table.id.tracked {
+        return TokenStream::new();
+    }
+
+    let ident = &table.ident;
+
+    let fks = table
+        .foreign_keys
+        .iter()
+        .filter(|fk| !fk.modifiers.immutable)
+        .map(|fk| fk.name.field());
+
+    let data = table
+        .data_columns
+        .iter()
+        .filter(|data| !data.modifiers.readonly && !data.modifiers.immutable)
+        .map(|data| data.name.field());
+
+    let compared = fks.chain(data).map(|field| {
+        quote!(
+            if self.#field != original.#field {
+                changed.push(stringify!(#field));
+            }
+        )
+    });
+
+    quote!(
+        #[automatically_derived]
+        impl ::atmosphere::Diff for #ident {
+            fn diff(&self, original: &Self) -> Vec<&'static str> {
+                let mut changed = vec![];
+
+                #(#compared)*
+
+                changed
+            }
+        }
+    )
+}
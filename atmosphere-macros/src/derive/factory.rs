@@ -0,0 +1,81 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::schema::table::Table;
+
+/// Implements `atmosphere::testing::Fixture` for tables using `#[table(factory)]`
+pub fn factory(table: &Table) -> TokenStream {
+    if !table.id.factory {
+        return TokenStream::new();
+    }
+
+    let ident = &table.ident;
+    let vis = &table.vis;
+
+    let generated = std::iter::once(table.primary_key.name.field())
+        .chain(table.foreign_keys.iter().map(|fk| fk.name.field()))
+        .chain(table.data_columns.iter().map(|data| data.name.field()))
+        .chain(table.timestamp_columns.iter().map(|ts| ts.name.field()))
+        .map(|field| {
+            quote!(
+                #field: ::atmosphere::testing::Generate::generate(stringify!(#field), seq)
+            )
+        });
+
+    let skipped = table
+        .skipped
+        .iter()
+        .map(|field| quote!(#field: ::core::default::Default::default()));
+
+    // nullable keys are generated as `None`, keys referencing the table itself have no parent
+    let parents = table
+        .foreign_keys
+        .iter()
+        .filter(|fk| !fk.nullable() && fk.on != *ident)
+        .map(|fk| {
+            let field = fk.name.field();
+            let other = &fk.on;
+
+            quote!(
+                let parent = ::atmosphere::testing::Factory::<#other>::new()
+                    .create(pool)
+                    .await?;
+
+                self.#field = ::core::convert::Into::into(::core::clone::Clone::clone(
+                    <#other as ::atmosphere::Table>::pk(&parent),
+                ));
+            )
+        });
+
+    let doc =
+        format!("A factory of generated [`{ident}`] rows, see `atmosphere::testing::Factory`");
+
+    quote!(
+        #[automatically_derived]
+        #[::atmosphere::prelude::async_trait]
+        impl ::atmosphere::testing::Fixture for #ident {
+            fn generate() -> Self {
+                let seq = ::atmosphere::testing::sequence();
+
+                Self {
+                    #(#generated,)*
+                    #(#skipped,)*
+                }
+            }
+
+            async fn create_parents(&mut self, pool: &::atmosphere::Pool) -> ::atmosphere::Result<()> {
+                #(#parents)*
+
+                Ok(())
+            }
+        }
+
+        #[automatically_derived]
+        impl #ident {
+            #[doc = #doc]
+            #vis fn factory() -> ::atmosphere::testing::Factory<Self> {
+                ::atmosphere::testing::Factory::new()
+            }
+        }
+    )
+}
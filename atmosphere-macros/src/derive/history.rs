@@ -0,0 +1,47 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::schema::table::Table;
+
+/// Generates the accessors of the history of tables using `#[table(.., history)]`
+pub fn history(table: &Table) -> TokenStream {
+    if !table.id.history {
+        return TokenStream::new();
+    }
+
+    let ident = &table.ident;
+
+    quote!(
+        #[automatically_derived]
+        impl #ident {
+            /// Returns the previous versions of the row with the primary key `pk`, the oldest
+            /// first, see `atmosphere::history`
+            pub async fn history<'e, E>(
+                executor: E,
+                pk: &<Self as ::atmosphere::Table>::PrimaryKey,
+            ) -> ::atmosphere::Result<Vec<::atmosphere::history::Version<Self>>>
+            where
+                E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                    ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+            {
+                ::atmosphere::history::history(executor, pk).await
+            }
+
+            /// Returns the version of the row with the primary key `pk` that was valid at `at`,
+            /// see `atmosphere::history`
+            pub async fn as_of<'e, E>(
+                executor: E,
+                pk: &<Self as ::atmosphere::Table>::PrimaryKey,
+                at: ::atmosphere::sqlx::types::chrono::DateTime<::atmosphere::sqlx::types::chrono::Utc>,
+            ) -> ::atmosphere::Result<Option<Self>>
+            where
+                E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                    ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+            {
+                ::atmosphere::history::as_of(executor, pk, at).await
+            }
+        }
+    )
+}
@@ -1,57 +1,321 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
+use syn::Ident;
 
-use crate::schema::table::Table;
+use crate::schema::{
+    column::{is_option, TimestampKind, UuidVersion},
+    keys::PrimaryKey,
+    table::Table,
+};
 
 pub fn hooks(table: &Table) -> TokenStream {
     let ident = &table.ident;
-    let registered = &table.hooks.registered;
-
-    //let mut derived: Vec<syn::Ident> = vec![];
-    //let mut hooks = TokenStream::new();
-
-    //for timestamp in table.timestamp_columns.iter() {
-    //let field = timestamp.name.field();
-
-    //let hook = syn::Ident::new(
-    //&format!(
-    //"__{}TimestampSetter{}",
-    //ident.to_string(),
-    //field.to_string()
-    //),
-    //field.span(),
-    //);
-
-    //hooks.extend(quote!(
-    //struct #hook;
-
-    //#[async_trait::async_trait]
-    //impl Hook<#ident> for #hook {
-    //fn stage(&self) -> HookStage { HookStage::PreBind }
-
-    //async fn apply(&self, ctx: &Query<#ident>, input: &mut HookInput<'_, #ident>) -> Result<()> {
-    //println!(
-    //"atmosphere::set::{}.{} because {:?} {:?}",
-    //stringify!(#ident), stringify!(#field),
-    //ctx.op,
-    //ctx.cardinality,
-    //);
-
-    //Ok(())
-    //}
-    //}
-    //));
-
-    ////derived.push(hook);
-    //}
-    //#(&#derived,),*
+    let mut registered: Vec<TokenStream> = vec![];
+
+    // generated before the other hooks, so they observe the key of the row
+    let keys = keys(table);
+
+    if !keys.is_empty() {
+        let hook = generated_hook(table, "Keys");
+        registered.push(quote!(&#hook));
+    }
+
+    let validation = validation(table);
+
+    if !validation.is_empty() {
+        let hook = generated_hook(table, "Validation");
+        registered.push(quote!(&#hook));
+    }
+
+    let timestamps = timestamps(table);
+
+    if !timestamps.is_empty() {
+        let hook = generated_hook(table, "Timestamps");
+        registered.push(quote!(&#hook));
+    }
+
+    if table.id.cache.is_some() {
+        registered.push(quote!(&::atmosphere::cache::Invalidate));
+    }
+
+    registered.extend(table.hooks.registered.iter().map(|hook| hook.quote()));
+
+    // rewrites the query, so it has to run after all other hooks modifying it
+    if table.audit {
+        registered.push(quote!(&::atmosphere::audit::Audit));
+    }
+
+    // extends the rewritten query of `Audit`
+    if table.id.history {
+        registered.push(quote!(&::atmosphere::history::History));
+    }
 
     quote!(
+        #keys
+        #validation
+        #timestamps
+
         #[automatically_derived]
         impl ::atmosphere::hooks::Hooks for #ident {
             const HOOKS: &'static [&'static dyn ::atmosphere::hooks::Hook<#ident>] = &[
-                #(&#registered,),*
+                #(#registered),*
             ];
         }
     )
 }
+
+fn generated_hook(table: &Table, name: &str) -> Ident {
+    Ident::new(&format!("__{}{name}", table.ident), Span::mixed_site())
+}
+
+/// Generates a hook running the `#[sql(validate = "..")]` functions of a table, as well as
+/// `validator::Validate::validate` if the `validator` feature is enabled and the table uses it
+fn validation(table: &Table) -> TokenStream {
+    let ident = &table.ident;
+
+    let pk = &table.primary_key;
+
+    let mut validators: Vec<(&Ident, &syn::Path, bool)> =
+        std::iter::once((&pk.modifiers, pk.name.field()))
+            .chain(
+                table
+                    .foreign_keys
+                    .iter()
+                    .map(|fk| (&fk.modifiers, fk.name.field())),
+            )
+            .chain(
+                table
+                    .data_columns
+                    .iter()
+                    .map(|data| (&data.modifiers, data.name.field())),
+            )
+            .chain(
+                table
+                    .timestamp_columns
+                    .iter()
+                    .map(|ts| (&ts.modifiers, ts.name.field())),
+            )
+            .filter_map(|(modifiers, field)| {
+                Some((field, &modifiers.validate.as_ref()?.0, modifiers.sensitive))
+            })
+            .collect();
+
+    let derived = cfg!(feature = "validator") && table.validator;
+
+    if validators.is_empty() && !derived {
+        return TokenStream::new();
+    }
+
+    validators.sort_by_key(|(field, _, _)| field.to_string());
+
+    let mut checks: Vec<TokenStream> = vec![];
+
+    if derived {
+        checks.push(quote!(
+            if let Err(err) = ::atmosphere::validator::Validate::validate(&**row) {
+                fields.extend(::atmosphere::ValidationError::from(err).fields);
+            }
+        ));
+    }
+
+    checks.extend(validators.iter().map(|(field, validator, sensitive)| {
+        let name = field.to_string();
+
+        // the message might quote the value
+        let (err, message) = match sensitive {
+            true => (quote!(_), quote!(::atmosphere::redact::REDACTED)),
+            false => (quote!(err), quote!(err)),
+        };
+
+        quote!(
+            if let Err(#err) = #validator(&row.#field) {
+                fields.push(::atmosphere::FieldError::new(#name, #message));
+            }
+        )
+    }));
+
+    let hook = generated_hook(table, "Validation");
+
+    quote!(
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        struct #hook;
+
+        #[automatically_derived]
+        #[::atmosphere::prelude::async_trait]
+        impl ::atmosphere::hooks::Hook<#ident> for #hook {
+            fn stage(&self) -> ::atmosphere::hooks::HookStage {
+                ::atmosphere::hooks::HookStage::PreBind
+            }
+
+            async fn apply(
+                &self,
+                ctx: &::atmosphere::query::Query<#ident>,
+                input: &mut ::atmosphere::hooks::HookInput<'_, #ident>,
+            ) -> ::atmosphere::Result<()> {
+                use ::atmosphere::{hooks::HookInput, query::Operation};
+
+                let HookInput::Row(row) = input else {
+                    return Ok(());
+                };
+
+                if !matches!(ctx.op, Operation::Insert | Operation::Update | Operation::Upsert) {
+                    return Ok(());
+                }
+
+                let mut fields = vec![];
+
+                #(#checks)*
+
+                if !fields.is_empty() {
+                    return Err(::atmosphere::ValidationError::new(fields).into());
+                }
+
+                Ok(())
+            }
+        }
+    )
+}
+
+/// Generates a hook maintaining the creation and update timestamps of a table
+fn timestamps(table: &Table) -> TokenStream {
+    let ident = &table.ident;
+
+    let fields = |kind: TimestampKind| -> Vec<Ident> {
+        let mut fields: Vec<Ident> = table
+            .timestamp_columns
+            .iter()
+            .filter(|ts| ts.kind == kind)
+            .map(|ts| ts.name.field().clone())
+            .collect();
+
+        fields.sort();
+        fields
+    };
+
+    let created = fields(TimestampKind::Created);
+    let updated = fields(TimestampKind::Updated);
+
+    if created.is_empty() && updated.is_empty() {
+        return TokenStream::new();
+    }
+
+    let hook = generated_hook(table, "Timestamps");
+
+    quote!(
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        struct #hook;
+
+        #[automatically_derived]
+        #[::atmosphere::prelude::async_trait]
+        impl ::atmosphere::hooks::Hook<#ident> for #hook {
+            fn stage(&self) -> ::atmosphere::hooks::HookStage {
+                ::atmosphere::hooks::HookStage::PreBind
+            }
+
+            async fn apply(
+                &self,
+                ctx: &::atmosphere::query::Query<#ident>,
+                input: &mut ::atmosphere::hooks::HookInput<'_, #ident>,
+            ) -> ::atmosphere::Result<()> {
+                use ::atmosphere::{hooks::{HookInput, Timestamp}, query::Operation};
+
+                let HookInput::Row(row) = input else {
+                    return Ok(());
+                };
+
+                let now = ::atmosphere::sqlx::types::chrono::Utc::now();
+
+                if matches!(ctx.op, Operation::Insert | Operation::Upsert) {
+                    #(row.#created = Timestamp::at(now);)*
+                }
+
+                if matches!(ctx.op, Operation::Insert | Operation::Update | Operation::Upsert) {
+                    #(row.#updated = Timestamp::at(now);)*
+                }
+
+                Ok(())
+            }
+        }
+    )
+}
+
+/// Generates a hook assigning a new key to unset primary keys on insertion, either a uuid
+/// (`#[sql(pk, uuid)]`) or a key of the `IdGenerator` of the table (`#[table(ids = ..)]`)
+fn keys(table: &Table) -> TokenStream {
+    let ident = &table.ident;
+    let pk = &table.primary_key;
+
+    let field = pk.name.field();
+    let ty = &pk.ty;
+
+    let assign = match (pk.modifiers.uuid, &table.id.ids) {
+        (Some(version), _) => uuid(pk, version),
+        (None, Some(ids)) => quote!(
+            if row.#field == <#ty as ::core::default::Default>::default() {
+                row.#field = ::core::convert::Into::into(
+                    ::atmosphere::id::IdGenerator::generate(&#ids)
+                );
+            }
+        ),
+        (None, None) => return TokenStream::new(),
+    };
+
+    let hook = generated_hook(table, "Keys");
+
+    quote!(
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        struct #hook;
+
+        #[automatically_derived]
+        #[::atmosphere::prelude::async_trait]
+        impl ::atmosphere::hooks::Hook<#ident> for #hook {
+            fn stage(&self) -> ::atmosphere::hooks::HookStage {
+                ::atmosphere::hooks::HookStage::PreBind
+            }
+
+            async fn apply(
+                &self,
+                ctx: &::atmosphere::query::Query<#ident>,
+                input: &mut ::atmosphere::hooks::HookInput<'_, #ident>,
+            ) -> ::atmosphere::Result<()> {
+                use ::atmosphere::{hooks::HookInput, query::Operation};
+
+                let HookInput::Row(row) = input else {
+                    return Ok(());
+                };
+
+                if matches!(ctx.op, Operation::Insert | Operation::Upsert) {
+                    #assign
+                }
+
+                Ok(())
+            }
+        }
+    )
+}
+
+/// Assigns a new uuid to an unset (nil or `None`) primary key
+fn uuid(pk: &PrimaryKey, version: UuidVersion) -> TokenStream {
+    let field = pk.name.field();
+
+    let generated = match version {
+        UuidVersion::V4 => quote!(::atmosphere::uuid::Uuid::new_v4()),
+        UuidVersion::V7 => quote!(::atmosphere::uuid::Uuid::now_v7()),
+    };
+
+    match is_option(&pk.ty) {
+        true => quote!(
+            if row.#field.is_none() {
+                row.#field = Some(#generated);
+            }
+        ),
+        false => quote!(
+            if row.#field.is_nil() {
+                row.#field = #generated;
+            }
+        ),
+    }
+}
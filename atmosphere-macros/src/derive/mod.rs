@@ -3,14 +3,30 @@ use quote::quote;
 
 use crate::schema::table::Table;
 
+mod alias;
 mod bindings;
+mod checked;
+mod debug;
+mod diff;
+mod factory;
+mod history;
 mod hooks;
+mod openapi;
+mod patch;
 mod queries;
 mod relationships;
 mod table;
 
 pub fn all(table: &Table) -> TokenStream {
+    let alias = alias::alias(table);
     let bindings = bindings::bindings(table);
+    let checked = checked::checked(table);
+    let debug = debug::debug(table);
+    let diff = diff::diff(table);
+    let factory = factory::factory(table);
+    let history = history::history(table);
+    let openapi = openapi::openapi(table);
+    let patch = patch::patch(table);
     let queries = queries::queries(table);
     let relationships = relationships::relationships(table);
     let hooks = hooks::hooks(table);
@@ -21,10 +37,26 @@ pub fn all(table: &Table) -> TokenStream {
 
         #bindings
 
+        #alias
+
         #queries
 
         #relationships
 
         #hooks
+
+        #checked
+
+        #debug
+
+        #diff
+
+        #patch
+
+        #openapi
+
+        #factory
+
+        #history
     )
 }
@@ -0,0 +1,56 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::schema::table::Table;
+
+/// Implements `atmosphere::openapi::Documented` with the `utoipa` feature
+pub fn openapi(table: &Table) -> TokenStream {
+    if !cfg!(feature = "utoipa") {
+        return TokenStream::new();
+    }
+
+    let ident = &table.ident;
+    let pk = &table.primary_key.ty;
+
+    let create = !table.id.view;
+    let update = table.id.updatable();
+    let delete = table.id.deletable();
+
+    let patch = table.id.patch.then(|| {
+        let patch = Ident::new(&format!("{ident}Patch"), Span::call_site());
+
+        quote!(
+            fn patch_schema() -> Option<(
+                String,
+                ::atmosphere::utoipa::openapi::RefOr<::atmosphere::utoipa::openapi::Schema>,
+            )> {
+                Some((
+                    <#patch as ::atmosphere::utoipa::ToSchema>::name().into_owned(),
+                    <#patch as ::atmosphere::utoipa::PartialSchema>::schema(),
+                ))
+            }
+        )
+    });
+
+    quote!(
+        #[automatically_derived]
+        impl ::atmosphere::openapi::Documented for #ident {
+            const CREATE: bool = #create;
+            const UPDATE: bool = #update;
+            const DELETE: bool = #delete;
+
+            fn pk_schema(
+            ) -> ::atmosphere::utoipa::openapi::RefOr<::atmosphere::utoipa::openapi::Schema> {
+                // the derive describes types like `Uuid` which do not implement `PartialSchema`
+                #[derive(::atmosphere::utoipa::ToSchema)]
+                    #[allow(dead_code)]
+                struct PrimaryKey(#pk);
+
+                <PrimaryKey as ::atmosphere::utoipa::PartialSchema>::schema()
+            }
+
+            #patch
+        }
+    )
+}
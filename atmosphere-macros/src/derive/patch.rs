@@ -0,0 +1,187 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Ident, Type};
+
+use crate::schema::{column::ColumnModifiers, table::Table};
+
+/// Generates the `<Table>Patch` type of tables using `#[table(patch)]`, implementing `Patch`
+pub fn patch(table: &Table) -> TokenStream {
+    if !table.id.patch {
+        return TokenStream::new();
+    }
+
+    let ident = &table.ident;
+    let vis = &table.vis;
+    let patch = Ident::new(&format!("{ident}Patch"), ident.span());
+
+    let columns = columns(table);
+
+    let fields = columns.iter().map(|(field, ty, modifiers)| {
+        let doc = format!("Sets `{ident}::{field}`, unless `None`");
+
+        // see `#[table]`, which describes json columns the same way
+        let schema = (cfg!(feature = "utoipa") && modifiers.json)
+            .then(|| quote!(#[schema(value_type = Option<Object>)]));
+
+        quote!(
+            #[doc = #doc]
+            #schema
+            #vis #field: ::core::option::Option<#ty>
+        )
+    });
+
+    let debug = columns.iter().map(|(field, _, modifiers)| {
+        let name = field.to_string();
+
+        match modifiers.sensitive {
+            true => {
+                quote!(.field(#name, &self.#field.as_ref().map(|_| ::atmosphere::redact::Redacted)))
+            }
+            false => quote!(.field(#name, &self.#field)),
+        }
+    });
+
+    let set = columns.iter().map(|(field, _, _)| {
+        quote!(
+            if self.#field.is_some() {
+                fields.push(stringify!(#field));
+            }
+        )
+    });
+
+    let col = Ident::new("col", Span::call_site());
+    let query = Ident::new("query", Span::call_site());
+
+    let binds = columns.iter().map(|(field, _, modifiers)| {
+        let bind = match modifiers.encrypted {
+            true => quote!(
+                let sealed = ::atmosphere::crypto::seal(value)?;
+                return Ok(#query.dyn_bind(sealed));
+            ),
+            false => quote!(return Ok(#query.dyn_bind(value));),
+        };
+
+        quote!(
+            if #col.field() == stringify!(#field) {
+                if let Some(value) = &self.#field {
+                    use ::atmosphere::Bindable;
+                    #bind
+                }
+            }
+        )
+    });
+
+    let validate = validate(&columns);
+    let doc = format!("A partial update of a [`{ident}`], see `atmosphere::Patch`");
+    let name = patch.to_string();
+
+    let schema =
+        cfg!(feature = "utoipa").then(|| quote!(#[derive(::atmosphere::utoipa::ToSchema)]));
+
+    quote!(
+        #[doc = #doc]
+        #[derive(Clone, Default)]
+        #schema
+        #vis struct #patch {
+            #(#fields),*
+        }
+
+        #[automatically_derived]
+        impl ::std::fmt::Debug for #patch {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(#name)
+                    #(#debug)*
+                    .finish()
+            }
+        }
+
+        #[automatically_derived]
+        impl ::atmosphere::Patch<#ident> for #patch {
+            fn fields(&self) -> Vec<&'static str> {
+                let mut fields = vec![];
+
+                #(#set)*
+
+                fields
+            }
+
+            fn bind<
+                'q,
+                Q: ::atmosphere::Bindable<'q>
+            >(
+                &'q self,
+                #col: &'q ::atmosphere::Column<#ident>,
+                #query: Q
+            ) -> ::atmosphere::Result<Q> {
+                #(#binds)*
+
+                Err(::atmosphere::Error::Bind(
+                    ::atmosphere::bind::BindError::Unknown(#col.field())
+                ))
+            }
+
+            #validate
+        }
+    )
+}
+
+/// The updated columns of a table: foreign keys and data columns which are neither `readonly` nor
+/// `immutable`
+fn columns(table: &Table) -> Vec<(&Ident, &Type, &ColumnModifiers)> {
+    let fks = table
+        .foreign_keys
+        .iter()
+        .filter(|fk| !fk.modifiers.immutable)
+        .map(|fk| (fk.name.field(), &fk.ty, &fk.modifiers));
+
+    let data = table
+        .data_columns
+        .iter()
+        .filter(|data| !data.modifiers.readonly && !data.modifiers.immutable)
+        .map(|data| (data.name.field(), &data.ty, &data.modifiers));
+
+    fks.chain(data).collect()
+}
+
+/// Runs the `#[sql(validate = "..")]` functions of the columns set by a patch
+fn validate(columns: &[(&Ident, &Type, &ColumnModifiers)]) -> TokenStream {
+    let checks: Vec<TokenStream> = columns
+        .iter()
+        .filter_map(|(field, _, modifiers)| {
+            let validator = &modifiers.validate.as_ref()?.0;
+            let name = field.to_string();
+
+            // the message might quote the value
+            let (err, message) = match modifiers.sensitive {
+                true => (quote!(_), quote!(::atmosphere::redact::REDACTED)),
+                false => (quote!(err), quote!(err)),
+            };
+
+            Some(quote!(
+                if let Some(value) = &self.#field {
+                    if let Err(#err) = #validator(value) {
+                        fields.push(::atmosphere::FieldError::new(#name, #message));
+                    }
+                }
+            ))
+        })
+        .collect();
+
+    if checks.is_empty() {
+        return TokenStream::new();
+    }
+
+    quote!(
+        fn validate(&self) -> ::atmosphere::Result<()> {
+            let mut fields = vec![];
+
+            #(#checks)*
+
+            if !fields.is_empty() {
+                return Err(::atmosphere::ValidationError::new(fields).into());
+            }
+
+            Ok(())
+        }
+    )
+}
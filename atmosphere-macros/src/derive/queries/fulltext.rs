@@ -0,0 +1,56 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::schema::table::Table;
+
+/// Generates the `search` finders of tables with `#[sql(fulltext)]` columns, see
+/// `atmosphere::fulltext`
+pub fn queries(table: &Table) -> TokenStream {
+    if !table
+        .data_columns
+        .iter()
+        .any(|data| data.modifiers.fulltext.is_some())
+    {
+        return TokenStream::new();
+    }
+
+    let ident = &table.ident;
+
+    quote!(
+        #[automatically_derived]
+        impl #ident {
+            /// Finds the rows matching the plain text search `query`, the best matches first. See
+            /// `atmosphere::fulltext`
+            pub async fn search<'e, E>(
+                executor: E,
+                query: &str,
+            ) -> ::atmosphere::Result<Vec<#ident>>
+            where
+                E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                    ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+            {
+                ::atmosphere::fulltext::search(
+                    executor,
+                    query,
+                    ::atmosphere::fulltext::Syntax::Plain,
+                ).await
+            }
+
+            /// Finds the rows matching the search `query` of the given syntax, the best matches
+            /// first. See `atmosphere::fulltext`
+            pub async fn search_with<'e, E>(
+                executor: E,
+                query: &str,
+                syntax: ::atmosphere::fulltext::Syntax,
+            ) -> ::atmosphere::Result<Vec<#ident>>
+            where
+                E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                    ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+            {
+                ::atmosphere::fulltext::search(executor, query, syntax).await
+            }
+        }
+    )
+}
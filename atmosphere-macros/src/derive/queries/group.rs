@@ -0,0 +1,61 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::schema::table::Table;
+
+/// The constants of `atmosphere::Table`, which keys named alike would shadow
+const RESERVED: &[&str] = &[
+    "SCHEMA",
+    "TABLE",
+    "DATABASE",
+    "PRIMARY_KEY",
+    "FOREIGN_KEYS",
+    "DATA_COLUMNS",
+    "TIMESTAMP_COLUMNS",
+    "UNIQUE",
+    "CHECKS",
+    "INDEXES",
+    "COMMENT",
+];
+
+/// Generates the `atmosphere::group::Key` of every foreign key and data column, named after its
+/// field in upper case
+pub fn queries(table: &Table) -> TokenStream {
+    let ident = &table.ident;
+
+    let fks = table
+        .foreign_keys
+        .iter()
+        .map(|fk| (fk.name.field(), &fk.ty, fk.quote()));
+
+    let data = table
+        .data_columns
+        .iter()
+        .map(|data| (data.name.field(), &data.ty, data.quote()));
+
+    let keys = fks.chain(data).filter_map(|(field, ty, column)| {
+        let name = field.to_string();
+        let name = name.trim_start_matches("r#").to_uppercase();
+
+        if RESERVED.contains(&name.as_str()) {
+            return None;
+        }
+
+        let key = Ident::new(&name, field.span());
+        let doc = format!("Groups the rows by `{field}`, see `atmosphere::Read::group_by`");
+
+        Some(quote!(
+            #[doc = #doc]
+            pub const #key: ::atmosphere::group::Key<#ident, #ty> =
+                ::atmosphere::group::Key::new(#column.as_col());
+        ))
+    });
+
+    quote!(
+        #[automatically_derived]
+        impl #ident {
+            #(#keys)*
+        }
+    )
+}
@@ -0,0 +1,91 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::schema::table::Table;
+
+/// Generates the `JSONB` helpers of `#[sql(json)]` columns, see `atmosphere::json`
+pub fn queries(table: &Table) -> TokenStream {
+    let mut stream = TokenStream::new();
+
+    let ident = &table.ident;
+
+    for data in table.data_columns.iter().filter(|data| data.modifiers.json) {
+        let field = data.name.field();
+        let col = field.to_string().to_lowercase();
+        let column = data.quote();
+
+        let merge_col = Ident::new(&format!("merge_{col}"), Span::mixed_site());
+        let set_col_path = Ident::new(&format!("set_{col}_path"), Span::mixed_site());
+        let find_by_col_contains =
+            Ident::new(&format!("find_by_{col}_contains"), Span::mixed_site());
+
+        let merge_doc =
+            format!("Merges the object `partial` into `{field}`, see `atmosphere::json::merge`");
+        let set_doc =
+            format!("Sets the value at `path` within `{field}`, see `atmosphere::json::set_path`");
+        let find_doc = format!("Finds the rows whose `{field}` contains `value`, see `atmosphere::json::find_contains`");
+
+        // views and tables denying updates are never updated
+        let update = table.id.updatable().then(|| {
+            quote!(
+                #[doc = #merge_doc]
+                pub async fn #merge_col<'e, E>(
+                    executor: E,
+                    pk: &<Self as ::atmosphere::Table>::PrimaryKey,
+                    partial: &::atmosphere::sqlx::types::JsonValue,
+                ) -> ::atmosphere::Result<::atmosphere::query::WriteOutcome>
+                where
+                    E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                    for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                        ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+                {
+                    const COLUMN: &::atmosphere::DataColumn<#ident> = &#column;
+
+                    ::atmosphere::json::merge(executor, COLUMN, pk, partial).await
+                }
+
+                #[doc = #set_doc]
+                pub async fn #set_col_path<'e, E>(
+                    executor: E,
+                    pk: &<Self as ::atmosphere::Table>::PrimaryKey,
+                    path: &[&str],
+                    value: &::atmosphere::sqlx::types::JsonValue,
+                ) -> ::atmosphere::Result<::atmosphere::query::WriteOutcome>
+                where
+                    E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                    for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                        ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+                {
+                    const COLUMN: &::atmosphere::DataColumn<#ident> = &#column;
+
+                    ::atmosphere::json::set_path(executor, COLUMN, pk, path, value).await
+                }
+            )
+        });
+
+        stream.extend(quote!(
+            #[automatically_derived]
+            impl #ident {
+                #[doc = #find_doc]
+                pub async fn #find_by_col_contains<'e, E>(
+                    executor: E,
+                    value: &::atmosphere::sqlx::types::JsonValue,
+                ) -> ::atmosphere::Result<Vec<#ident>>
+                where
+                    E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                    for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                        ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+                {
+                    const COLUMN: &::atmosphere::DataColumn<#ident> = &#column;
+
+                    ::atmosphere::json::find_contains(executor, COLUMN, value).await
+                }
+
+                #update
+            }
+        ))
+    }
+
+    stream
+}
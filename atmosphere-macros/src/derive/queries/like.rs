@@ -0,0 +1,51 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::schema::{column::is_text, table::Table};
+
+/// Generates the substring searches of text columns, see `atmosphere::like`
+pub fn queries(table: &Table) -> TokenStream {
+    let mut stream = TokenStream::new();
+
+    let ident = &table.ident;
+
+    // encrypted columns hold the ciphertext
+    for data in table
+        .data_columns
+        .iter()
+        .filter(|data| is_text(&data.ty) && !data.modifiers.encrypted)
+    {
+        let field = data.name.field();
+        let col = field.to_string().to_lowercase();
+        let column = data.quote();
+
+        let search_by_col = Ident::new(&format!("search_by_{col}"), Span::mixed_site());
+
+        let doc = format!(
+            "Finds the rows whose `{field}` contains `pattern` regardless of case, see `atmosphere::like::search_by`"
+        );
+
+        stream.extend(quote!(
+            #[automatically_derived]
+            impl #ident {
+                #[doc = #doc]
+                pub async fn #search_by_col<'e, E>(
+                    executor: E,
+                    pattern: &str,
+                ) -> ::atmosphere::Result<Vec<#ident>>
+                where
+                    E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                    for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                        ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+                {
+                    const COLUMN: &::atmosphere::DataColumn<#ident> = &#column;
+
+                    ::atmosphere::like::search_by(executor, COLUMN, pattern).await
+                }
+            }
+        ))
+    }
+
+    stream
+}
@@ -1,9 +1,34 @@
 use proc_macro2::TokenStream;
+use quote::quote;
 
 use crate::schema::table::Table;
 
+mod fulltext;
+mod group;
+mod json;
+mod like;
 mod unique;
+mod window;
 
 pub fn queries(table: &Table) -> TokenStream {
-    unique::queries(table)
+    let unique = unique::queries(table);
+    let json = json::queries(table);
+    let like = like::queries(table);
+    let fulltext = fulltext::queries(table);
+    let group = group::queries(table);
+    let window = window::queries(table);
+
+    quote!(
+        #unique
+
+        #json
+
+        #like
+
+        #fulltext
+
+        #group
+
+        #window
+    )
 }
@@ -33,6 +33,41 @@ pub fn queries(table: &Table) -> TokenStream {
         let find_by_col = Ident::new(&format!("find_by_{col}"), Span::mixed_site());
         let delete_by_col = Ident::new(&format!("delete_by_{col}"), Span::mixed_site());
 
+        // views and tables denying deletes are never deleted from
+        let delete = table.id.deletable().then(|| {
+            quote!(
+                pub async fn #delete_by_col<'e, E>(
+                    executor: E,
+                    value: &#ty,
+                ) -> ::atmosphere::Result<::atmosphere::query::WriteOutcome>
+                where
+                    E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                    for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                        ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+                {
+                    use ::atmosphere::runtime::{instrument::instrumented, sql};
+
+                    const COLUMN: ::atmosphere::Column<#ident> = #column.as_col();
+
+                    let query = sql::delete_by::<#ident>(COLUMN.clone());
+
+                    let sql = ::atmosphere::sqlx::query(query.sql()).bind(value);
+                    let sql = query.bind_values(sql)?.persistent(false);
+
+                    let context = query.context();
+
+                    let execution = async move {
+                        sql.execute(executor)
+                            .await
+                            .map(::atmosphere::query::WriteOutcome::from)
+                            .map_err(|err| context.error(err))
+                    };
+
+                    instrumented(&query, execution).await
+                }
+            )
+        });
+
         stream.extend(quote!(
             #[automatically_derived]
             impl #ident {
@@ -45,51 +80,75 @@ pub fn queries(table: &Table) -> TokenStream {
                     for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
                         ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
                 {
-                    use ::atmosphere::{
-                        query::{Query, QueryError},
-                        runtime::sql,
-                        Error
-                    };
+                    use ::atmosphere::runtime::{instrument::instrumented, sql};
 
                     const COLUMN: ::atmosphere::Column<#ident> = #column.as_col();
 
                     let query = sql::select_by::<#ident>(COLUMN.clone());
 
-                    ::atmosphere::sqlx::query_as(query.sql())
-                        .bind(value)
-                        .persistent(false)
-                        .fetch_optional(executor)
-                        .await
-                        .map_err(QueryError::from)
-                        .map_err(Error::Query)
+                    let sql = ::atmosphere::sqlx::query_as(query.sql()).bind(value);
+                    let sql = query.bind_values(sql)?.persistent(false);
+
+                    let context = query.context();
+
+                    let execution = async move {
+                        sql.fetch_optional(executor)
+                            .await
+                            .map_err(|err| context.error(err))
+                    };
+
+                    instrumented(&query, execution).await
                 }
 
-                pub async fn #delete_by_col<'e, E>(
+                #delete
+            }
+        ))
+    }
+
+    for columns in &table.unique {
+        let name = columns
+            .iter()
+            .map(|c| c.name().field().to_string().to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_and_");
+
+        let find_by_cols = Ident::new(&format!("find_by_{name}"), Span::mixed_site());
+
+        let values: Vec<Ident> = columns.iter().map(|c| c.name().field().clone()).collect();
+        let tys = columns.iter().map(|c| c.ty());
+        let columns = columns.iter().map(|c| c.quote());
+
+        stream.extend(quote!(
+            #[automatically_derived]
+            impl #ident {
+                pub async fn #find_by_cols<'e, E>(
                     executor: E,
-                    value: &#ty,
-                ) -> ::atmosphere::Result<<::atmosphere::Driver as ::atmosphere::sqlx::Database>::QueryResult>
+                    #(#values: &#tys),*
+                ) -> ::atmosphere::Result<Option<#ident>>
                 where
                     E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
                     for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
                         ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
                 {
-                    use ::atmosphere::{
-                        query::{Query, QueryError},
-                        runtime::sql,
-                        Error
-                    };
+                    use ::atmosphere::runtime::{instrument::instrumented, sql};
 
-                    const COLUMN: ::atmosphere::Column<#ident> = #column.as_col();
+                    const COLUMNS: &[::atmosphere::Column<#ident>] = &[#(#columns.as_col()),*];
 
-                    let query = sql::delete_by::<#ident>(COLUMN.clone());
+                    let query = sql::select_by_all::<#ident>(COLUMNS.to_vec());
+
+                    let sql = ::atmosphere::sqlx::query_as(query.sql())
+                        #(.bind(#values))*;
+                    let sql = query.bind_values(sql)?.persistent(false);
+
+                    let context = query.context();
+
+                    let execution = async move {
+                        sql.fetch_optional(executor)
+                            .await
+                            .map_err(|err| context.error(err))
+                    };
 
-                    ::atmosphere::sqlx::query(query.sql())
-                        .bind(value)
-                        .persistent(false)
-                        .execute(executor)
-                        .await
-                        .map_err(QueryError::from)
-                        .map_err(Error::Query)
+                    instrumented(&query, execution).await
                 }
             }
         ))
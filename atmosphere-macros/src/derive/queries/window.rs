@@ -0,0 +1,99 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::schema::{
+    column::{value_type, TimestampKind},
+    table::Table,
+};
+
+/// Generates the time window finders of timestamp columns, see `atmosphere::window`
+pub fn queries(table: &Table) -> TokenStream {
+    let mut stream = TokenStream::new();
+
+    let ident = &table.ident;
+
+    let mut kinds = vec![];
+
+    for ts in &table.timestamp_columns {
+        // the finders are named after the kind, only its first column gets them
+        if kinds.contains(&ts.kind) {
+            continue;
+        }
+
+        kinds.push(ts.kind);
+
+        let kind = match ts.kind {
+            TimestampKind::Created => "created",
+            TimestampKind::Updated => "updated",
+            TimestampKind::Deleted => "deleted",
+        };
+
+        let field = ts.name.field();
+        let ty = value_type(&ts.ty);
+        let column = ts.quote();
+
+        let before = Ident::new(&format!("find_{kind}_before"), Span::mixed_site());
+        let after = Ident::new(&format!("find_{kind}_after"), Span::mixed_site());
+        let between = Ident::new(&format!("find_{kind}_between"), Span::mixed_site());
+
+        let before_doc =
+            format!("Finds the rows whose `{field}` is before `time`, see `atmosphere::window`");
+        let after_doc =
+            format!("Finds the rows whose `{field}` is after `time`, see `atmosphere::window`");
+        let between_doc = format!(
+            "Finds the rows whose `{field}` is at or after `from` and before `to`, see `atmosphere::window`"
+        );
+
+        let finder = |name: &Ident, doc: &str, params: TokenStream, window: TokenStream| {
+            quote!(
+                #[doc = #doc]
+                pub async fn #name<'e, E>(
+                    executor: E,
+                    #params
+                ) -> ::atmosphere::Result<Vec<#ident>>
+                where
+                    E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                    for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                        ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+                {
+                    const COLUMN: &::atmosphere::TimestampColumn<#ident> = &#column;
+
+                    ::atmosphere::window::find_in(executor, COLUMN, #window).await
+                }
+            )
+        };
+
+        let before = finder(
+            &before,
+            &before_doc,
+            quote!(time: &#ty),
+            quote!(::atmosphere::window::Window::Before(time)),
+        );
+        let after = finder(
+            &after,
+            &after_doc,
+            quote!(time: &#ty),
+            quote!(::atmosphere::window::Window::After(time)),
+        );
+        let between = finder(
+            &between,
+            &between_doc,
+            quote!(from: &#ty, to: &#ty),
+            quote!(::atmosphere::window::Window::Between(from, to)),
+        );
+
+        stream.extend(quote!(
+            #[automatically_derived]
+            impl #ident {
+                #before
+
+                #after
+
+                #between
+            }
+        ))
+    }
+
+    stream
+}
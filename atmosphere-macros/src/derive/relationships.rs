@@ -2,7 +2,7 @@ use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::Ident;
 
-use crate::schema::table::Table;
+use crate::schema::{keys::ForeignKey, table::Table};
 
 pub fn relationships(table: &Table) -> TokenStream {
     let mut stream = TokenStream::new();
@@ -10,6 +10,11 @@ pub fn relationships(table: &Table) -> TokenStream {
     let ident = &table.ident;
 
     for fk in table.foreign_keys.iter() {
+        if let Some(relation) = &fk.relation {
+            stream.extend(named(ident, fk, relation, table.id.deletable()));
+            continue;
+        }
+
         let col = fk.quote();
 
         let other = &fk.on;
@@ -34,18 +39,39 @@ pub fn relationships(table: &Table) -> TokenStream {
             Span::mixed_site(),
         );
 
+        // views and tables denying deletes are never deleted from
+        let delete_self = table.id.deletable().then(|| {
+            quote!(
+                pub async fn #delete_self<'e, E>(
+                    &self,
+                    executor: E,
+                ) -> ::atmosphere::Result<::atmosphere::query::WriteOutcome>
+                where
+                    E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                    for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                        ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send {
+                    <#other as ::atmosphere::rel::ReferredBy<#ident>>::delete_all(&self, executor).await
+                }
+            )
+        });
+
+        let (resolved, resolve) = match fk.nullable() {
+            true => (quote!(Option<#other>), quote!(resolve_optional)),
+            false => (quote!(#other), quote!(resolve)),
+        };
+
         stream.extend(quote!(
             #[automatically_derived]
             impl #ident {
                 pub async fn #find_other<'e, E>(
                     &self,
                     executor: E,
-                ) -> ::atmosphere::Result<#other>
+                ) -> ::atmosphere::Result<#resolved>
                 where
                     E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
                     for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
                         ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send {
-                    <#ident as ::atmosphere::rel::RefersTo<#other>>::resolve(&self, executor).await
+                    <#ident as ::atmosphere::rel::RefersTo<#other>>::#resolve(&self, executor).await
                 }
 
                 pub async fn #find_by_other<'e, E>(
@@ -74,16 +100,7 @@ pub fn relationships(table: &Table) -> TokenStream {
                     <#other as ::atmosphere::rel::ReferredBy<#ident>>::resolve(&self, executor).await
                 }
 
-                pub async fn #delete_self<'e, E>(
-                    &self,
-                    executor: E,
-                ) -> ::atmosphere::Result<<::atmosphere::Driver as ::atmosphere::sqlx::Database>::QueryResult>
-                where
-                    E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
-                    for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
-                        ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send {
-                    <#other as ::atmosphere::rel::ReferredBy<#ident>>::delete_all(&self, executor).await
-                }
+                #delete_self
             }
 
             #[automatically_derived]
@@ -98,3 +115,172 @@ pub fn relationships(table: &Table) -> TokenStream {
 
     stream
 }
+
+/// Generates the relationship queries of a named relation (`#[sql(fk -> Other, relation = "..")]`).
+///
+/// Named relations allow multiple foreign keys pointing to the same table. As the relationship
+/// traits can only be implemented once per pair of tables, the queries are generated directly.
+fn named(ident: &Ident, fk: &ForeignKey, relation: &Ident, deletable: bool) -> TokenStream {
+    let col = fk.quote();
+    let other = &fk.on;
+
+    let relation = Ident::new(&relation.to_string().to_lowercase(), relation.span());
+
+    let find_by_relation = Ident::new(&format!("find_by_{relation}"), relation.span());
+
+    let inverse = match &fk.inverse {
+        Some(inverse) => Ident::new(&inverse.to_string().to_lowercase(), inverse.span()),
+        None => Ident::new(
+            &format!("{}s_by_{relation}", ident.to_string().to_lowercase()),
+            relation.span(),
+        ),
+    };
+
+    let delete_inverse = Ident::new(&format!("delete_{inverse}"), inverse.span());
+
+    let delete_inverse = deletable.then(|| {
+        quote!(
+            pub async fn #delete_inverse<'e, E>(
+                &self,
+                executor: E,
+            ) -> ::atmosphere::Result<::atmosphere::query::WriteOutcome>
+            where
+                E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                    ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+            {
+                use ::atmosphere::{
+                    runtime::{instrument::instrumented, sql},
+                    Table,
+                };
+
+                const COLUMN: ::atmosphere::Column<#ident> = #col.as_col();
+
+                let query = sql::delete_by::<#ident>(COLUMN.clone());
+
+                let sql = ::atmosphere::sqlx::query(query.sql()).bind(Table::pk(self));
+                let sql = query.bind_values(sql)?.persistent(false);
+
+                let context = query.context();
+
+                let execution = async move {
+                    sql.execute(executor)
+                        .await
+                        .map(::atmosphere::query::WriteOutcome::from)
+                        .map_err(|err| context.error(err))
+                };
+
+                instrumented(&query, execution).await
+            }
+        )
+    });
+
+    let (resolved, fetch) = match fk.nullable() {
+        true => (quote!(Option<#other>), quote!(fetch_optional)),
+        false => (quote!(#other), quote!(fetch_one)),
+    };
+
+    quote!(
+        #[automatically_derived]
+        impl #ident {
+            pub async fn #relation<'e, E>(
+                &self,
+                executor: E,
+            ) -> ::atmosphere::Result<#resolved>
+            where
+                E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                    ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+            {
+                use ::atmosphere::{
+                    runtime::{instrument::instrumented, sql},
+                    Bind,
+                };
+
+                const COLUMN: ::atmosphere::Column<#ident> = #col.as_col();
+
+                let query = sql::select::<#other>();
+
+                let sql = ::atmosphere::sqlx::query_as(query.sql());
+                let sql = self.bind(&COLUMN, sql)?;
+                let sql = query.bind_values(sql)?.persistent(false);
+
+                let context = query.context();
+
+                let execution = async move {
+                    sql.#fetch(executor)
+                        .await
+                        .map_err(|err| context.error(err))
+                };
+
+                instrumented(&query, execution).await
+            }
+
+            pub async fn #find_by_relation<'e, E>(
+                executor: E,
+                pk: &<#other as ::atmosphere::Table>::PrimaryKey,
+            ) -> ::atmosphere::Result<Vec<#ident>>
+            where
+                E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                    ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+            {
+                use ::atmosphere::runtime::{instrument::instrumented, sql};
+
+                const COLUMN: ::atmosphere::Column<#ident> = #col.as_col();
+
+                let query = sql::select_by::<#ident>(COLUMN.clone());
+
+                let sql = ::atmosphere::sqlx::query_as(query.sql()).bind(pk);
+                let sql = query.bind_values(sql)?.persistent(false);
+
+                let context = query.context();
+
+                let execution = async move {
+                    sql.fetch_all(executor)
+                        .await
+                        .map_err(|err| context.error(err))
+                };
+
+                instrumented(&query, execution).await
+            }
+        }
+
+        #[automatically_derived]
+        impl #other {
+            pub async fn #inverse<'e, E>(
+                &self,
+                executor: E,
+            ) -> ::atmosphere::Result<Vec<#ident>>
+            where
+                E: ::atmosphere::sqlx::Executor<'e, Database = ::atmosphere::Driver>,
+                for<'q> <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::Arguments:
+                    ::atmosphere::sqlx::IntoArguments<'q, ::atmosphere::Driver> + Send
+            {
+                use ::atmosphere::{
+                    runtime::{instrument::instrumented, sql},
+                    Table,
+                };
+
+                const COLUMN: ::atmosphere::Column<#ident> = #col.as_col();
+
+                let query = sql::select_by::<#ident>(COLUMN.clone());
+
+                let sql = ::atmosphere::sqlx::query_as(query.sql()).bind(Table::pk(self));
+                let sql = query.bind_values(sql)?.persistent(false);
+
+                let context = query.context();
+
+                let execution = async move {
+                    sql.fetch_all(executor)
+                        .await
+                        .map_err(|err| context.error(err))
+                };
+
+                instrumented(&query, execution).await
+            }
+
+            #delete_inverse
+        }
+    )
+}
@@ -20,6 +20,146 @@ pub fn table(table: &Table) -> TokenStream {
     let pk_ty = &table.primary_key.ty;
     let pk_field = &table.primary_key.name.field();
 
+    #[cfg(feature = "mysql")]
+    let last_insert_id = match primary_key.modifiers.auto {
+        true => quote!(
+            fn set_last_insert_id(&mut self, id: u64) -> ::atmosphere::Result<()> {
+                self.#pk_field = <#pk_ty as ::std::convert::TryFrom<u64>>::try_from(id)
+                    .map_err(|_| ::atmosphere::Error::Internal)?;
+
+                Ok(())
+            }
+        ),
+        false => quote!(),
+    };
+
+    #[cfg(not(feature = "mysql"))]
+    let last_insert_id = quote!();
+
+    let name = match id.dynamic {
+        true => quote!(
+            fn name() -> ::std::borrow::Cow<'static, str> {
+                <Self as ::atmosphere::TableName>::table_name()
+            }
+        ),
+        false => quote!(),
+    };
+
+    let database = id.database.as_ref().map(|database| {
+        quote!(
+            const DATABASE: Option<&'static str> = Some(#database);
+        )
+    });
+
+    let unique = (!table.unique.is_empty()).then(|| {
+        let constraints = table.unique.iter().map(|columns| {
+            let columns = columns.iter().map(|c| c.name().sql().to_string());
+            quote!(::atmosphere::UniqueConstraint::new(&[#(#columns),*]))
+        });
+
+        quote!(
+            const UNIQUE: &'static [::atmosphere::UniqueConstraint] = &[#(#constraints),*];
+        )
+    });
+
+    let checks = (!id.checks.is_empty()).then(|| {
+        let checks = &id.checks;
+
+        quote!(
+            const CHECKS: &'static [&'static str] = &[#(#checks),*];
+        )
+    });
+
+    let indexes = (!table.indexes.is_empty()).then(|| {
+        let indexes = table.indexes.iter().map(|index| {
+            let name = &index.name;
+
+            let columns = index
+                .columns
+                .iter()
+                .map(|(sql, descending)| match descending {
+                    true => quote!(::atmosphere::IndexColumn::desc(#sql)),
+                    false => quote!(::atmosphere::IndexColumn::asc(#sql)),
+                });
+
+            let using = index.using.as_ref().map(|method| quote!(.using(#method)));
+
+            quote!(::atmosphere::Index::new(#name, &[#(#columns),*])#using)
+        });
+
+        quote!(
+            const INDEXES: &'static [::atmosphere::Index] = &[#(#indexes),*];
+        )
+    });
+
+    let comment = id.comment.as_ref().map(|comment| {
+        quote!(
+            const COMMENT: Option<&'static str> = Some(#comment);
+        )
+    });
+
+    let cache = id.cache.as_ref().map(|cache| {
+        let ttl = cache.ttl;
+
+        let capacity = match cache.capacity {
+            Some(capacity) => quote!(#capacity),
+            None => quote!(::atmosphere::cache::CAPACITY),
+        };
+
+        let (ty, new) = match cache.shared {
+            true => (
+                quote!(::atmosphere::cache::Shared<#ident>),
+                quote!(::atmosphere::cache::Shared::new(
+                    ::std::time::Duration::from_millis(#ttl)
+                )),
+            ),
+            false => (
+                quote!(::atmosphere::cache::Cache<#ident>),
+                quote!(::atmosphere::cache::Cache::new(
+                    ::std::time::Duration::from_millis(#ttl),
+                    #capacity,
+                )),
+            ),
+        };
+
+        quote!(
+            fn cache() -> Option<&'static dyn ::atmosphere::cache::RowCache<Self>> {
+                static CACHE: #ty = #new;
+
+                Some(&CACHE)
+            }
+        )
+    });
+
+    // views are only read
+    let writable = (!id.view).then(|| {
+        quote!(
+            #[automatically_derived]
+            impl ::atmosphere::Writable for #ident {}
+        )
+    });
+
+    let updatable = id.updatable().then(|| {
+        quote!(
+            #[automatically_derived]
+            impl ::atmosphere::Updatable for #ident {}
+        )
+    });
+
+    let deletable = id.deletable().then(|| {
+        quote!(
+            #[automatically_derived]
+            impl ::atmosphere::Deletable for #ident {}
+        )
+    });
+
+    let materialized = id.materialized.then(|| {
+        quote!(
+            #[automatically_derived]
+            impl ::atmosphere::MaterializedView for #ident {}
+        )
+    });
+
     let primary_key = primary_key.quote();
     let foreign_keys = foreign_keys.iter().map(|r| r.quote());
     let data = data_columns.iter().map(|d| d.quote());
@@ -32,15 +172,38 @@ pub fn table(table: &Table) -> TokenStream {
 
             const SCHEMA: &'static str = #schema;
             const TABLE: &'static str = #table_name;
+            #database
 
             const PRIMARY_KEY: ::atmosphere::PrimaryKey<#ident> = #primary_key;
             const FOREIGN_KEYS: &'static [::atmosphere::ForeignKey<#ident>] = &[#(#foreign_keys),*];
             const DATA_COLUMNS: &'static [::atmosphere::DataColumn<#ident>] = &[#(#data),*];
             const TIMESTAMP_COLUMNS: &'static [::atmosphere::TimestampColumn<#ident>] = &[#(#timestamps),*];
+            #unique
+            #checks
+            #indexes
+            #comment
 
             fn pk(&self) -> &Self::PrimaryKey {
                 &self.#pk_field
             }
+
+            #name
+
+            #cache
+
+            #last_insert_id
+        }
+
+        #writable
+
+        #updatable
+
+        #deletable
+
+        #materialized
+
+        ::atmosphere::inventory::submit! {
+            ::atmosphere::describe::Registration::new::<#ident>()
         }
     )
 }
@@ -1,6 +1,70 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parenthesized, punctuated::Punctuated, Ident, Token};
+
 #[derive(Clone, Debug, Default)]
 pub struct Hooks {
-    pub registered: Vec<syn::Expr>,
+    pub registered: Vec<Hook>,
+}
+
+/// A registered hook, optionally restricted to some operations using `on(..)`
+#[derive(Clone, Debug)]
+pub struct Hook {
+    pub expr: syn::Expr,
+    pub on: Option<Vec<Operation>>,
+}
+
+/// Operations a hook can be restricted to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Create,
+    Read,
+    Update,
+    Upsert,
+    Delete,
+}
+
+impl syn::parse::Parse for Operation {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+
+        match ident.to_string().as_str() {
+            "create" => Ok(Self::Create),
+            "read" => Ok(Self::Read),
+            "update" => Ok(Self::Update),
+            "upsert" => Ok(Self::Upsert),
+            "delete" => Ok(Self::Delete),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected one of `create`, `read`, `update`, `upsert` or `delete`",
+            )),
+        }
+    }
+}
+
+impl ToTokens for Operation {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let op = match self {
+            Self::Create => quote!(Insert),
+            Self::Read => quote!(Select),
+            Self::Update => quote!(Update),
+            Self::Upsert => quote!(Upsert),
+            Self::Delete => quote!(Delete),
+        };
+
+        tokens.extend(quote!(::atmosphere::query::Operation::#op));
+    }
+}
+
+impl Hook {
+    pub fn quote(&self) -> TokenStream {
+        let expr = &self.expr;
+
+        match &self.on {
+            Some(ops) => quote!(&::atmosphere::hooks::On::new(&#expr, &[#(#ops),*])),
+            None => quote!(&#expr),
+        }
+    }
 }
 
 impl syn::parse::Parse for Hooks {
@@ -11,9 +75,7 @@ impl syn::parse::Parse for Hooks {
             let expr: syn::Expr = input.parse()?;
 
             match expr {
-                syn::Expr::Path(_) | syn::Expr::Struct(_) => {
-                    hooks.push(expr);
-                }
+                syn::Expr::Path(_) | syn::Expr::Struct(_) => {}
                 _ => {
                     return Err(syn::Error::new_spanned(
                         expr,
@@ -22,6 +84,35 @@ impl syn::parse::Parse for Hooks {
                 }
             }
 
+            let on = if input.peek(Ident) {
+                let ident: Ident = input.parse()?;
+
+                if ident != "on" {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "expected `on(..)` or `,` after a hook",
+                    ));
+                }
+
+                let content;
+                parenthesized!(content in input);
+
+                let ops = Punctuated::<Operation, Token![,]>::parse_terminated(&content)?;
+
+                if ops.is_empty() {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`on(..)` requires at least one operation",
+                    ));
+                }
+
+                Some(ops.into_iter().collect())
+            } else {
+                None
+            };
+
+            hooks.push(Hook { expr, on });
+
             if input.peek(syn::Token![,]) {
                 input.parse::<syn::Token![,]>()?;
             }
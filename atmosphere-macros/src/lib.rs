@@ -13,11 +13,13 @@
 
 use proc_macro::TokenStream;
 use quote::{quote, ToTokens};
-use syn::{parse_macro_input, ItemStruct};
+use syn::{parse_macro_input, ItemEnum, ItemStruct};
 
 mod derive;
 mod hooks;
+mod primary_key;
 mod schema;
+mod sql_enum;
 
 use schema::table::Table;
 
@@ -28,14 +30,82 @@ use schema::table::Table;
 /// Entity attributes:
 ///
 /// - `#[table(schema = "schema_name", name = "table_name")]` - Set schema and table name
+/// - `#[table(.., database = "name")]` - Assign the table to a named database, see `atmosphere::Databases`
+/// - `#[table(.., dynamic)]` - Decide the table name at runtime using `atmosphere::TableName`
+/// - `#[table(.., checked)]` - Verify the generated statements at compile time using `sqlx::query!`
+/// - `#[table(.., view)]` - Map a view, which is only read: `Create`, `Update`, `Delete` and `Ddl`
+///   are not implemented
+/// - `#[table(.., tracked)]` - Implement `atmosphere::Diff`, so `atmosphere::Tracked` rows only
+///   update their changed columns. Requires the updated columns to implement `PartialEq`
+/// - `#[table(.., patch)]` - Generate `<Table>Patch`, holding an `Option` per updated column, which
+///   is applied using `Update::patch`
+/// - `#[table(.., factory)]` - Generate rows for tests using `<Table>::factory()`, creating the parents
+///   of foreign keys. Requires the field types to implement `atmosphere::testing::Generate`
+/// - `#[table(.., history)]` - Keep the previous versions of rows in a `<table>_history` companion
+///   table, read using `<Table>::history` and `<Table>::as_of`, see `atmosphere::history` (postgres
+///   only)
+/// - `#[table(.., cache(ttl = "30s", capacity = 1000))]` - Serve `Read::read` and `Read::find` from an
+///   in-process cache of rows by primary key, invalidated by writes, see `atmosphere::cache`. `shared`
+///   caches the rows in the installed `atmosphere::cache::CacheBackend` instead
+/// - `#[table(.., deny(update, delete))]` - Opt out of `Update` and / or `Delete`, e.g. for
+///   append-only tables
+/// - `#[table(.., materialized)]` - Map a postgres materialized view, which is only read and
+///   refreshed using `atmosphere::MaterializedView::refresh`
+/// - `#[table(.., rename_all = "camelCase")]` - Rename all columns which are not renamed explicitly
+/// - `#[table(.., unique(name, location))]` - Declare a uniqueness constraint spanning multiple columns
+/// - `#[table(.., index(created_at, desc))]` - Declare an index, `asc` or `desc` order the preceding column
+/// - `#[table(.., comment = "..")]` - Describe the table, the comment is part of the generated ddl
+/// - `#[table(.., check = "starts_at < ends_at")]` - Declare a check constraint of the table
+/// - `#[table(.., ids = "ORDER_IDS")]` - Assign unset primary keys using the `atmosphere::id::IdGenerator` `ORDER_IDS`
+///   (`lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`, `snake_case` or `SCREAMING_SNAKE_CASE`)
 ///
 /// Field attributes:
 ///
 /// - `#[sql(pk)]` - Mark a column as primary key
+/// - `#[sql(pk, auto)]` - Mark a column as primary key generated by the database (e.g. `SERIAL`)
+/// - `#[sql(pk, uuid)]` - Mark a column as `Uuid` (or `Option<Uuid>`) primary key, unset keys
+///   (nil or `None`) are generated on insertion. Version 4 by default, `uuid = "v7"` for version 7.
+///   Requires the `uuid` feature
 /// - `#[sql(fk -> OtherModel)]` - Mark a column as foreign key on `OtherModel`
+/// - `#[sql(fk -> OtherModel, relation = "name", inverse = "others")]` - Name the relationship
+///   queries, required when there are multiple foreign keys on `OtherModel`
+/// - `#[sql(fk -> OtherModel, on_delete = "cascade", on_update = "restrict")]` - Declare the
+///   referential actions of a foreign key (`no_action`, `restrict`, `cascade`, `set_null` or
+///   `set_default`)
 /// - `#[sql(unique)]` - Mark a column as unique
-/// - `#[sql(timestamp = [create|update|delete])]` - Mark a column as timestamp
+/// - `#[sql(tenant)]` - Mark a column as tenant discriminator, generated queries then only match
+///   the rows of the tenant set using `atmosphere::tenant::scope`
+/// - `#[sql(timestamp = [created|updated|deleted])]` - Mark a column as timestamp. Creation and
+///   update timestamps are set automatically, deletion timestamps enable soft deletes
 /// - `#[sql(.., rename = "renamed_sql_col")]` - Rename a column in the generated sql
+/// - `#[sql(default)]` - Leave the value of a column to its database default when inserting, the
+///   stored value is read back into the struct (except on MySQL)
+/// - `#[sql(readonly)]` - Mark a column maintained by the database (e.g. by a trigger), it is
+///   selected but never inserted or updated
+/// - `#[sql(immutable)]` - Mark a column (or foreign key) which is written on insertion but never
+///   updated, e.g. `created_by`
+/// - `#[sql(skip)]` - Exclude a field which is not a column (e.g. computed in memory) from all
+///   generated queries, it is set to its `Default` when reading rows
+/// - `#[sql(.., validate = "path::to::fn")]` - Validate a column before it is written, the function
+///   is called with a reference to the value and returns a `Result<(), impl ToString>`
+/// - `#[sql(index)]` - Index a column
+/// - `#[sql(encrypted)]` - Encrypt a column on the client (requires the `encryption` feature)
+/// - `#[sql(sensitive)]` - Redact the values of a column from `Debug`, validation errors and audit
+///   records, see `atmosphere::redact`. Tables with sensitive columns must not derive `Debug`
+/// - `#[sql(json)]` - Mark a `JSONB` column, generating `merge_<col>`, `set_<col>_path` and
+///   `find_by_<col>_contains`, see `atmosphere::json` (postgres only)
+/// - `#[sql(fulltext)]` / `#[sql(fulltext = "english")]` - Search a text column using the full-text
+///   search of postgres, generating `search`, `search_with` and a `GIN` index, see
+///   `atmosphere::fulltext`
+/// - `#[sql(.., comment = "..")]` - Describe a column, the comment is part of the generated ddl
+/// - `#[sql(.., check = "price > 0")]` - Declare a check constraint of a column, violations name
+///   the column
+///
+/// With the `validator` feature enabled, tables that also derive `validator::Validate` are
+/// validated using `Validate::validate` before every write.
+///
+/// With the `utoipa` feature enabled, tables and their patch types derive `utoipa::ToSchema` and
+/// implement `atmosphere::openapi::Documented`, see `atmosphere::openapi`.
 ///
 /// Usage:
 ///
@@ -72,6 +142,21 @@ pub fn schema(input: TokenStream) -> TokenStream {
 ///
 /// - `schema` - sets schema name.
 /// - `name` - sets table name.
+/// - `rename_all` - renames all columns which are not renamed explicitly.
+/// - `ids` - names the `IdGenerator` assigning unset primary keys.
+/// - `unique(a, b)` - declares a uniqueness constraint spanning the columns `a` and `b`.
+/// - `check` - declares a check constraint of the table.
+/// - `index(a, b, desc)` - declares an index of the columns `a` and `b` (descending).
+/// - `comment` - describes the table.
+/// - `view` - marks the table as a view, which is only read.
+/// - `deny(update, delete)` - opts out of updating and / or deleting rows.
+/// - `tracked` - compares rows, so `Tracked` rows only update their changed columns.
+/// - `patch` - generates a `<Table>Patch` type for partial updates.
+/// - `factory` - generates rows for tests using `<Table>::factory()`.
+/// - `history` - keeps the previous versions of rows in a `<table>_history` companion table.
+/// - `cache(ttl = "30s", capacity = 1000)` - caches rows read by primary key in memory, or in the
+///   shared `CacheBackend` using `cache(ttl = "30s", shared)`.
+/// - `materialized` - marks the table as a materialized view, which is only read and refreshed.
 ///
 /// Usage:
 ///
@@ -87,39 +172,86 @@ pub fn schema(input: TokenStream) -> TokenStream {
 /// # }
 /// ```
 #[proc_macro_attribute]
-pub fn table(_: TokenStream, input: TokenStream) -> TokenStream {
+pub fn table(attr: TokenStream, input: TokenStream) -> TokenStream {
     let mut model = parse_macro_input!(input as ItemStruct);
 
+    // invalid arguments are reported by `#[derive(Schema)]`
+    let rename_all = syn::parse::<schema::table::TableId>(attr)
+        .ok()
+        .and_then(|id| id.rename_all);
+
+    struct Extract {
+        attribute: syn::Attribute,
+    }
+
+    impl syn::parse::Parse for Extract {
+        fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+            Ok(Self {
+                attribute: input
+                    .call(syn::Attribute::parse_outer)?
+                    .into_iter()
+                    .next()
+                    .unwrap(),
+            })
+        }
+    }
+
     for ref mut field in model.fields.iter_mut() {
-        let attribute = field
+        let attribute: Option<schema::column::attribute::Attribute> = field
             .attrs
             .iter()
-            .find(|a| a.path().is_ident(schema::column::attribute::PATH));
+            .find(|a| a.path().is_ident(schema::column::attribute::PATH))
+            .map(|a| a.parse_args().unwrap());
+
+        if attribute
+            .as_ref()
+            .is_some_and(|a| a.kind == schema::column::attribute::ColumnKind::Skip)
+        {
+            let Extract { attribute: skip } = syn::parse_str("#[sqlx(skip)]").unwrap();
+
+            field.attrs.push(skip);
 
-        let Some(attribute) = attribute else {
             continue;
-        };
-
-        let attribute: schema::column::attribute::Attribute = attribute.parse_args().unwrap();
-
-        if let Some(rename) = attribute.renamed {
-            struct Extract {
-                rename: syn::Attribute,
-            }
-
-            impl syn::parse::Parse for Extract {
-                fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-                    Ok(Self {
-                        rename: input
-                            .call(syn::Attribute::parse_outer)?
-                            .into_iter()
-                            .next()
-                            .unwrap(),
-                    })
-                }
-            }
-
-            let Extract { rename } =
+        }
+
+        if attribute.as_ref().is_some_and(|a| a.modifiers.encrypted) {
+            let Extract { attribute: decrypt } =
+                syn::parse_str("#[sqlx(try_from = \"::atmosphere::crypto::Decrypted\")]").unwrap();
+
+            field.attrs.push(decrypt);
+        }
+
+        // `Json<T>` is not described by utoipa, json columns are described as objects instead
+        if cfg!(feature = "utoipa")
+            && attribute.as_ref().is_some_and(|a| a.modifiers.json)
+            && !field.attrs.iter().any(|a| a.path().is_ident("schema"))
+        {
+            let optional = matches!(
+                &field.ty,
+                syn::Type::Path(ty) if ty.path.segments.last().is_some_and(|s| s.ident == "Option")
+            );
+
+            let value_type = match optional {
+                true => "Option<Object>",
+                false => "Object",
+            };
+
+            let Extract { attribute: object } =
+                syn::parse_str(&format!("#[schema(value_type = {value_type})]")).unwrap();
+
+            field.attrs.push(object);
+        }
+
+        let rename = attribute
+            .and_then(|a| a.renamed)
+            .map(|renamed| renamed.to_string())
+            .or_else(|| {
+                let field = field.ident.as_ref()?.to_string();
+                rename_all.map(|rule| rule.apply(&field))
+            });
+
+        if let Some(rename) = rename {
+            let Extract { attribute: rename } =
                 syn::parse_str(&format!("#[sqlx(rename = \"{}\")]", rename)).unwrap();
 
             field.attrs.push(rename);
@@ -128,8 +260,12 @@ pub fn table(_: TokenStream, input: TokenStream) -> TokenStream {
 
     let model = model.to_token_stream();
 
+    let schema =
+        cfg!(feature = "utoipa").then(|| quote!(#[derive(::atmosphere::utoipa::ToSchema)]));
+
     quote! {
         #[derive(::atmosphere::sqlx::FromRow)]
+        #schema
         #model
     }
     .into()
@@ -137,7 +273,9 @@ pub fn table(_: TokenStream, input: TokenStream) -> TokenStream {
 
 /// An attribute macro for registering on a table. Must be used after `#[derive(Schema)]`.
 ///
-/// Takes as argument a type which implements `Hook<Self>` for the entity type.
+/// Takes as argument a type which implements `Hook<Self>` for the entity type. Hooks can be
+/// restricted to some operations (`create`, `read`, `update`, `upsert`, `delete`) using
+/// `#[hooks(Audit on(create, delete), Validate on(update))]`.
 ///
 /// Usage:
 ///
@@ -168,3 +306,108 @@ pub fn hooks(attr: TokenStream, input: TokenStream) -> TokenStream {
     let _ = parse_macro_input!(attr as hooks::Hooks);
     quote! { #model }.into()
 }
+
+/// An attribute macro recording all changes to a table into its `<table>_audit` companion table
+/// (see `atmosphere::audit`). Must be used after `#[derive(Schema)]`. Only supported on postgres.
+///
+/// Usage:
+///
+/// ```ignore
+/// # use atmosphere::prelude::*;
+/// #[derive(Schema)]
+/// #[table(schema = "public", name = "user")]
+/// #[audit]
+/// struct User {
+///     #[sql(pk)]
+///     id: i32,
+///     #[sql(unique)]
+///     username: String,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn audit(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let model = parse_macro_input!(input as ItemStruct);
+
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "`#[audit]` takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if !cfg!(feature = "postgres") {
+        return syn::Error::new(
+            model.ident.span(),
+            "`#[audit]` is only supported on postgres",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    quote! { #model }.into()
+}
+
+/// A derive macro implementing `sqlx::Type`, `sqlx::Encode` and `sqlx::Decode` for enums with unit
+/// variants, so they can be used as column types.
+///
+/// Enums are stored as text by default, each variant as its name. Attributes:
+///
+/// - `#[sql(rename_all = "snake_case")]` - Rename all values, following serde (`lowercase`,
+///   `UPPERCASE`, `PascalCase`, `camelCase`, `snake_case` or `SCREAMING_SNAKE_CASE`)
+/// - `#[sql(type_name = "mood")]` - Map the enum to a postgres enum type instead of text
+/// - `#[sql(repr = i32)]` - Store the discriminants of the variants as integers instead
+/// - `#[sql(rename = "value")]` on a variant - Rename a single value
+///
+/// Usage:
+///
+/// ```ignore
+/// # use atmosphere::prelude::*;
+/// #[derive(SqlEnum)]
+/// #[sql(type_name = "status", rename_all = "snake_case")]
+/// enum Status {
+///     Pending,
+///     InProgress,
+///     #[sql(rename = "done")]
+///     Completed,
+/// }
+/// ```
+#[proc_macro_derive(SqlEnum, attributes(sql))]
+pub fn sql_enum(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as ItemEnum);
+
+    sql_enum::sql_enum(&item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// A derive macro for newtype primary keys (e.g. `struct UserId(i64)`), which keep the keys of
+/// different tables from being interchangeable.
+///
+/// Implements `sqlx::Type`, `sqlx::Encode` and `sqlx::Decode` by delegating to the wrapped type,
+/// as well as the conversions from and into it. Keys used in relationships additionally derive
+/// `Clone`, `PartialEq`, `Eq` and `Hash`.
+///
+/// Usage:
+///
+/// ```ignore
+/// # use atmosphere::prelude::*;
+/// #[derive(PrimaryKey, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// struct UserId(i64);
+///
+/// #[derive(Schema)]
+/// #[table(schema = "public", name = "user")]
+/// struct User {
+///     #[sql(pk)]
+///     id: UserId,
+/// }
+/// ```
+#[proc_macro_derive(PrimaryKey)]
+pub fn primary_key(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as ItemStruct);
+
+    primary_key::primary_key(&item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
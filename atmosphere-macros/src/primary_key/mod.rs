@@ -0,0 +1,103 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Fields, ItemStruct};
+
+pub fn primary_key(item: &ItemStruct) -> syn::Result<TokenStream> {
+    let ident = &item.ident;
+
+    let inner = match &item.fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+        _ => {
+            return Err(Error::new(
+                ident.span(),
+                format!("{ident} must be a newtype (`struct {ident}(i64)`) to derive `PrimaryKey`"),
+            ))
+        }
+    };
+
+    if !item.generics.params.is_empty() {
+        return Err(Error::new(
+            ident.span(),
+            "`PrimaryKey` can not be derived for generic types",
+        ));
+    }
+
+    let schema = cfg!(feature = "utoipa").then(|| {
+        quote!(
+            #[automatically_derived]
+            impl ::atmosphere::utoipa::PartialSchema for #ident {
+                fn schema() -> ::atmosphere::utoipa::openapi::RefOr<
+                    ::atmosphere::utoipa::openapi::schema::Schema,
+                > {
+                    // see `Documented::pk_schema`
+                    #[derive(::atmosphere::utoipa::ToSchema)]
+                    #[allow(dead_code)]
+                    struct Inner(#inner);
+
+                    <Inner as ::atmosphere::utoipa::PartialSchema>::schema()
+                }
+            }
+
+            #[automatically_derived]
+            impl ::atmosphere::utoipa::ToSchema for #ident {}
+        )
+    });
+
+    Ok(quote!(
+        #schema
+
+        #[automatically_derived]
+        impl ::atmosphere::sqlx::Type<::atmosphere::Driver> for #ident {
+            fn type_info() -> <::atmosphere::Driver as ::atmosphere::sqlx::Database>::TypeInfo {
+                <#inner as ::atmosphere::sqlx::Type<::atmosphere::Driver>>::type_info()
+            }
+
+            fn compatible(
+                ty: &<::atmosphere::Driver as ::atmosphere::sqlx::Database>::TypeInfo,
+            ) -> bool {
+                <#inner as ::atmosphere::sqlx::Type<::atmosphere::Driver>>::compatible(ty)
+            }
+        }
+
+        #[automatically_derived]
+        impl<'q> ::atmosphere::sqlx::Encode<'q, ::atmosphere::Driver> for #ident {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::ArgumentBuffer,
+            ) -> ::atmosphere::sqlx::encode::IsNull {
+                <#inner as ::atmosphere::sqlx::Encode<'q, ::atmosphere::Driver>>::encode_by_ref(&self.0, buf)
+            }
+
+            fn produces(&self) -> Option<<::atmosphere::Driver as ::atmosphere::sqlx::Database>::TypeInfo> {
+                <#inner as ::atmosphere::sqlx::Encode<'q, ::atmosphere::Driver>>::produces(&self.0)
+            }
+
+            fn size_hint(&self) -> usize {
+                <#inner as ::atmosphere::sqlx::Encode<'q, ::atmosphere::Driver>>::size_hint(&self.0)
+            }
+        }
+
+        #[automatically_derived]
+        impl<'r> ::atmosphere::sqlx::Decode<'r, ::atmosphere::Driver> for #ident {
+            fn decode(
+                value: <::atmosphere::Driver as ::atmosphere::sqlx::database::HasValueRef<'r>>::ValueRef,
+            ) -> ::std::result::Result<Self, ::atmosphere::sqlx::error::BoxDynError> {
+                <#inner as ::atmosphere::sqlx::Decode<'r, ::atmosphere::Driver>>::decode(value).map(Self)
+            }
+        }
+
+        #[automatically_derived]
+        impl ::std::convert::From<#inner> for #ident {
+            fn from(value: #inner) -> Self {
+                Self(value)
+            }
+        }
+
+        #[automatically_derived]
+        impl ::std::convert::From<#ident> for #inner {
+            fn from(value: #ident) -> Self {
+                value.0
+            }
+        }
+    ))
+}
@@ -5,6 +5,7 @@ use quote::{quote, ToTokens};
 use syn::{Field, Ident, Type};
 
 use super::keys::{ForeignKey, PrimaryKey};
+use super::rename::RenameRule;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct NameSet {
@@ -24,11 +25,149 @@ impl NameSet {
     pub fn sql(&self) -> &Ident {
         self.sql.as_ref().unwrap_or(&self.field)
     }
+
+    /// Renames the sql column using `rule`, unless it has been renamed explicitly
+    pub fn rename_all(&mut self, rule: RenameRule) {
+        if self.sql.is_none() {
+            let renamed = rule.apply(&self.field.to_string());
+            self.sql = Some(Ident::new(&renamed, self.field.span()));
+        }
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// Whether a field type is an `Option<T>`, making its column nullable
+pub fn is_option(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+
+    path.qself.is_none()
+        && path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option")
+}
+
+/// The type of the values of a column holding `ty`, `T` of an `Option<T>`
+pub fn value_type(ty: &Type) -> &Type {
+    let Type::Path(path) = ty else {
+        return ty;
+    };
+
+    match path.path.segments.last().map(|segment| &segment.arguments) {
+        Some(syn::PathArguments::AngleBracketed(args)) if is_option(ty) => {
+            match args.args.first() {
+                Some(syn::GenericArgument::Type(inner)) => inner,
+                _ => ty,
+            }
+        }
+        _ => ty,
+    }
+}
+
+/// Whether a field type is a `String` or an `Option<String>`, making its column a text column
+pub fn is_text(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+
+    let Some(segment) = path.path.segments.last() else {
+        return false;
+    };
+
+    if path.qself.is_some() {
+        return false;
+    }
+
+    if segment.ident == "String" {
+        return true;
+    }
+
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if segment.ident == "Option" => {
+            matches!(args.args.first(), Some(syn::GenericArgument::Type(inner)) if is_text(inner))
+        }
+        _ => false,
+    }
+}
+
+/// The `ColumnType` of a column holding values of `ty`, which is `nullable` if `ty` is optional
+pub fn column_type(ty: &Type, nullable: bool) -> TokenStream {
+    let nullable = nullable.then(|| quote!(.nullable()));
+
+    quote!(::atmosphere::column::ColumnType::of::<#ty>()#nullable)
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct ColumnModifiers {
     pub unique: bool,
+    pub auto: bool,
+    pub tenant: bool,
+    pub default: bool,
+    pub readonly: bool,
+    pub immutable: bool,
+    pub index: bool,
+    /// Whether the column is encrypted on the client (`#[sql(encrypted)]`)
+    pub encrypted: bool,
+    /// Whether the values of the column are redacted from logs and diagnostics (`#[sql(sensitive)]`)
+    pub sensitive: bool,
+    /// Whether the column holds `JSONB` documents (`#[sql(json)]`)
+    pub json: bool,
+    /// The text search configuration of a full-text searched column (`#[sql(fulltext)]`)
+    pub fulltext: Option<String>,
+    pub uuid: Option<UuidVersion>,
+    pub validate: Option<Validator>,
+    /// The condition of a check constraint (`#[sql(check = "price > 0")]`)
+    pub check: Option<String>,
+    /// The comment describing the column (`#[sql(comment = "..")]`)
+    pub comment: Option<String>,
+}
+
+/// The version of the uuids generated for a primary key (`#[sql(pk, uuid = "v7")]`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UuidVersion {
+    V4,
+    V7,
+}
+
+impl UuidVersion {
+    pub fn parse(value: &syn::LitStr) -> syn::Result<Self> {
+        match value.value().as_str() {
+            "v4" => Ok(Self::V4),
+            "v7" => Ok(Self::V7),
+            _ => Err(syn::Error::new(
+                value.span(),
+                "`uuid` supports only the versions `v4` and `v7`",
+            )),
+        }
+    }
+}
+
+/// The path of a function validating a column value (`#[sql(validate = "path::to::fn")]`)
+#[derive(Clone)]
+pub struct Validator(pub syn::Path);
+
+impl std::fmt::Debug for Validator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Validator")
+            .field(&self.0.to_token_stream().to_string())
+            .finish()
+    }
+}
+
+impl PartialEq for Validator {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_token_stream().to_string() == other.0.to_token_stream().to_string()
+    }
+}
+
+impl Eq for Validator {}
+
+impl Hash for Validator {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_token_stream().to_string().hash(state);
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -63,12 +202,18 @@ impl TimestampColumn {
         let kind = self.kind;
         let field = self.name.field().to_string();
         let sql = self.name.sql().to_string();
+        let ty = column_type(&self.ty, is_option(&self.ty));
+        let comment = self
+            .modifiers
+            .comment
+            .as_ref()
+            .map(|c| quote!(.with_comment(#c)));
 
         quote!(::atmosphere::TimestampColumn::new(
             #kind,
             #field,
             #sql
-        ))
+        ).with_type(#ty) #comment)
     }
 }
 
@@ -84,10 +229,40 @@ impl DataColumn {
         let field = self.name.field();
         let sql = self.name.sql();
 
+        // encrypted columns hold the ciphertext
+        let ty = match self.modifiers.encrypted {
+            true => {
+                let nullable = is_option(&self.ty).then(|| quote!(.nullable()));
+                quote!(::atmosphere::column::ColumnType::of::<Vec<u8>>()#nullable)
+            }
+            false => column_type(&self.ty, is_option(&self.ty)),
+        };
+        let tenant = self.modifiers.tenant.then(|| quote!(.with_tenant()));
+        let unique = self.modifiers.unique.then(|| quote!(.with_unique()));
+        let default = self.modifiers.default.then(|| quote!(.with_default()));
+        let readonly = self.modifiers.readonly.then(|| quote!(.with_readonly()));
+        let immutable = self.modifiers.immutable.then(|| quote!(.with_immutable()));
+        let sensitive = self.modifiers.sensitive.then(|| quote!(.with_sensitive()));
+        let fulltext = self
+            .modifiers
+            .fulltext
+            .as_ref()
+            .map(|config| quote!(.with_fulltext(#config)));
+        let check = self
+            .modifiers
+            .check
+            .as_ref()
+            .map(|c| quote!(.with_check(#c)));
+        let comment = self
+            .modifiers
+            .comment
+            .as_ref()
+            .map(|c| quote!(.with_comment(#c)));
+
         quote!(::atmosphere::DataColumn::new(
             stringify!(#field),
             stringify!(#sql)
-        ))
+        ).with_type(#ty) #tenant #unique #default #readonly #immutable #check #comment #sensitive #fulltext)
     }
 }
 
@@ -128,14 +303,27 @@ impl Column {
 pub mod attribute {
     use syn::{parse::Parse, Error, Ident, LitStr, Token};
 
-    use super::{ColumnModifiers, TimestampKind};
+    use super::{ColumnModifiers, TimestampKind, UuidVersion, Validator};
+    use crate::schema::keys::ReferentialAction;
 
     pub const PATH: &str = "sql";
 
     const PRIMARY_KEY: &str = "pk";
     const FOREIGN_KEY: &str = "fk";
     const UNIQUE: &str = "unique";
+    const AUTO: &str = "auto";
+    const TENANT: &str = "tenant";
+    const DEFAULT: &str = "default";
+    const READONLY: &str = "readonly";
+    const IMMUTABLE: &str = "immutable";
+    const INDEX: &str = "index";
+    const ENCRYPTED: &str = "encrypted";
+    const SENSITIVE: &str = "sensitive";
+    const JSON: &str = "json";
+    const FULLTEXT: &str = "fulltext";
+    const UUID: &str = "uuid";
     const TIMESTAMP: &str = "timestamp";
+    const SKIP: &str = "skip";
 
     const TIMESTAMP_CREATED: &str = "created";
     const TIMESTAMP_UPDATED: &str = "updated";
@@ -144,9 +332,15 @@ pub mod attribute {
     #[derive(Clone, Debug, PartialEq, Eq, Hash)]
     pub enum ColumnKind {
         PrimaryKey,
-        ForeignKey { on: Ident },
+        ForeignKey {
+            on: Ident,
+        },
         Data,
-        Timestamp { kind: TimestampKind },
+        Timestamp {
+            kind: TimestampKind,
+        },
+        /// Not a column at all (`#[sql(skip)]`)
+        Skip,
     }
 
     impl Parse for ColumnKind {
@@ -191,6 +385,18 @@ pub mod attribute {
 
                         kind = ColumnKind::Timestamp { kind: ty }
                     }
+                    SKIP => {
+                        let skip: Ident = input.parse()?;
+
+                        if !input.is_empty() {
+                            return Err(syn::Error::new_spanned(
+                                skip,
+                                "`#[sql(skip)]` can not be combined with other options, skipped fields are not columns",
+                            ));
+                        }
+
+                        kind = ColumnKind::Skip
+                    }
                     _ => {}
                 };
 
@@ -208,28 +414,115 @@ pub mod attribute {
         pub kind: ColumnKind,
         pub modifiers: ColumnModifiers,
         pub renamed: Option<Ident>,
+        pub relation: Option<Ident>,
+        pub inverse: Option<Ident>,
+        pub on_delete: Option<ReferentialAction>,
+        pub on_update: Option<ReferentialAction>,
     }
 
     impl Parse for Attribute {
         fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
             let kind: ColumnKind = input.parse()?;
 
-            let mut modifiers = ColumnModifiers { unique: false };
+            let mut modifiers = ColumnModifiers::default();
             let mut renamed = None;
+            let mut relation = None;
+            let mut inverse = None;
+            let mut on_delete = None;
+            let mut on_update = None;
 
             while !input.is_empty() {
                 let ident: syn::Ident = input.parse()?;
 
+                // either a tag (v4) or a kv pair naming the version
+                if ident == UUID {
+                    if modifiers.uuid.is_some() {
+                        return Err(Error::new(ident.span(), "found redundant `uuid` modifier"));
+                    }
+
+                    modifiers.uuid = match input.peek(Token![=]) {
+                        true => {
+                            input.parse::<Token![=]>()?;
+                            Some(UuidVersion::parse(&input.parse()?)?)
+                        }
+                        false => Some(UuidVersion::V4),
+                    };
+
+                    if !input.peek(Token![,]) {
+                        break;
+                    }
+
+                    input.parse::<Token![,]>()?;
+
+                    continue;
+                }
+
+                // either a tag (`simple`) or a kv pair naming the text search configuration
+                if ident == FULLTEXT {
+                    if modifiers.fulltext.is_some() {
+                        return Err(Error::new(
+                            ident.span(),
+                            "found redundant `fulltext` modifier",
+                        ));
+                    }
+
+                    modifiers.fulltext = match input.peek(Token![=]) {
+                        true => {
+                            input.parse::<Token![=]>()?;
+
+                            let config: syn::LitStr = input.parse()?;
+
+                            // part of the generated sql
+                            if config.value().is_empty()
+                                || !config
+                                    .value()
+                                    .chars()
+                                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+                            {
+                                return Err(Error::new(
+                                    config.span(),
+                                    "expected the name of a text search configuration, e.g. `english`",
+                                ));
+                            }
+
+                            Some(config.value())
+                        }
+                        false => Some("simple".to_owned()),
+                    };
+
+                    if !input.peek(Token![,]) {
+                        break;
+                    }
+
+                    input.parse::<Token![,]>()?;
+
+                    continue;
+                }
+
                 // we found a tag
-                if ident.to_string().as_str() == UNIQUE {
-                    if modifiers.unique {
+                let tag = match ident.to_string().as_str() {
+                    UNIQUE => Some(&mut modifiers.unique),
+                    AUTO => Some(&mut modifiers.auto),
+                    TENANT => Some(&mut modifiers.tenant),
+                    DEFAULT => Some(&mut modifiers.default),
+                    READONLY => Some(&mut modifiers.readonly),
+                    IMMUTABLE => Some(&mut modifiers.immutable),
+                    INDEX => Some(&mut modifiers.index),
+                    ENCRYPTED => Some(&mut modifiers.encrypted),
+                    SENSITIVE => Some(&mut modifiers.sensitive),
+                    JSON => Some(&mut modifiers.json),
+                    _ => None,
+                };
+
+                if let Some(tag) = tag {
+                    if *tag {
                         return Err(Error::new(
                             ident.span(),
-                            "found redundant `unique` modifier",
+                            format!("found redundant `{ident}` modifier"),
                         ));
                     }
 
-                    modifiers.unique = true;
+                    *tag = true;
 
                     if !input.peek(Token![,]) {
                         break;
@@ -246,6 +539,31 @@ pub mod attribute {
 
                 match ident.to_string().as_str() {
                     "rename" => renamed = Some(Ident::new(&value.value(), value.span())),
+                    "relation" => relation = Some(Ident::new(&value.value(), value.span())),
+                    "inverse" => inverse = Some(Ident::new(&value.value(), value.span())),
+                    "on_delete" => on_delete = Some(ReferentialAction::parse(&value)?),
+                    "on_update" => on_update = Some(ReferentialAction::parse(&value)?),
+                    "validate" => {
+                        if modifiers.validate.is_some() {
+                            return Err(Error::new(
+                                ident.span(),
+                                "found redundant `validate` modifier",
+                            ));
+                        }
+
+                        modifiers.validate = Some(Validator(value.parse()?));
+                    }
+                    "check" => {
+                        if modifiers.check.is_some() {
+                            return Err(Error::new(
+                                ident.span(),
+                                "found redundant `check` modifier",
+                            ));
+                        }
+
+                        modifiers.check = Some(value.value());
+                    }
+                    "comment" => modifiers.comment = Some(value.value()),
                     _ => return Err(syn::Error::new_spanned(ident, "")),
                 }
 
@@ -260,11 +578,28 @@ pub mod attribute {
                 kind,
                 modifiers,
                 renamed,
+                relation,
+                inverse,
+                on_delete,
+                on_update,
             })
         }
     }
 }
 
+/// Whether a field is excluded from the table using `#[sql(skip)]`.
+///
+/// Fields failing to parse are not skipped, so their errors are reported when converting them into
+/// columns.
+pub fn is_skipped(field: &Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident(attribute::PATH))
+        .and_then(|a| a.parse_args::<attribute::Attribute>().ok())
+        .is_some_and(|a| a.kind == attribute::ColumnKind::Skip)
+}
+
 impl TryFrom<Field> for Column {
     type Error = syn::Error;
 
@@ -283,7 +618,7 @@ impl TryFrom<Field> for Column {
 
         let Some(attribute) = attribute else {
             return Ok(Self::Data(DataColumn {
-                modifiers: ColumnModifiers { unique: false },
+                modifiers: ColumnModifiers::default(),
                 name: NameSet::new(name, None),
                 ty,
             }));
@@ -294,14 +629,194 @@ impl TryFrom<Field> for Column {
         let modifiers = attribute.modifiers;
         let name = NameSet::new(name, attribute.renamed);
 
+        let is_fk = matches!(attribute.kind, attribute::ColumnKind::ForeignKey { .. });
+
+        if let Some(ident) = attribute.relation.as_ref().or(attribute.inverse.as_ref()) {
+            if !is_fk {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`relation` and `inverse` are only supported on foreign keys (`#[sql(fk -> Other, relation = \"..\")]`)",
+                ));
+            }
+        }
+
+        if !is_fk && (attribute.on_delete.is_some() || attribute.on_update.is_some()) {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "`on_delete` and `on_update` are only supported on foreign keys",
+            ));
+        }
+
+        if let (None, Some(inverse)) = (&attribute.relation, &attribute.inverse) {
+            return Err(syn::Error::new(
+                inverse.span(),
+                "`inverse` requires the relation to be named using `relation = \"..\"`",
+            ));
+        }
+
+        if modifiers.auto && attribute.kind != attribute::ColumnKind::PrimaryKey {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `auto` modifier is only supported on primary keys (`#[sql(pk, auto)]`)",
+            ));
+        }
+
+        if modifiers.uuid.is_some() && attribute.kind != attribute::ColumnKind::PrimaryKey {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `uuid` modifier is only supported on primary keys (`#[sql(pk, uuid)]`)",
+            ));
+        }
+
+        if modifiers.uuid.is_some() && modifiers.auto {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "primary keys are either generated by the database (`auto`) or as `uuid`, not both",
+            ));
+        }
+
+        if modifiers.uuid.is_some() && !cfg!(feature = "uuid") {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `uuid` modifier requires the `uuid` feature of atmosphere",
+            ));
+        }
+
+        if modifiers.tenant && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `tenant` modifier is only supported on data columns (`#[sql(tenant)]`)",
+            ));
+        }
+
+        if modifiers.check.is_some() && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `check` modifier is only supported on data columns, use `#[table(check = \"..\")]` for other columns",
+            ));
+        }
+
+        if modifiers.default && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `default` modifier is only supported on data columns (`#[sql(default)]`)",
+            ));
+        }
+
+        if modifiers.readonly && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `readonly` modifier is only supported on data columns (`#[sql(readonly)]`)",
+            ));
+        }
+
+        if modifiers.readonly && (modifiers.default || modifiers.tenant || modifiers.immutable) {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "`readonly` columns are never written, they can not be combined with `default`, `tenant` or `immutable`",
+            ));
+        }
+
+        if modifiers.immutable && !is_fk && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `immutable` modifier is only supported on data columns and foreign keys",
+            ));
+        }
+
+        if modifiers.index && attribute.kind == attribute::ColumnKind::PrimaryKey {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "primary keys are always indexed, the `index` modifier is not supported on them",
+            ));
+        }
+
+        if modifiers.encrypted && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `encrypted` modifier is only supported on data columns (`#[sql(encrypted)]`)",
+            ));
+        }
+
+        if modifiers.sensitive && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `sensitive` modifier is only supported on data columns (`#[sql(sensitive)]`)",
+            ));
+        }
+
+        if modifiers.fulltext.is_some() && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `fulltext` modifier is only supported on data columns (`#[sql(fulltext)]`)",
+            ));
+        }
+
+        if modifiers.fulltext.is_some() && !cfg!(feature = "postgres") {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "full-text searched columns (`#[sql(fulltext)]`) are only supported on postgres",
+            ));
+        }
+
+        if modifiers.fulltext.is_some() && modifiers.encrypted {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "encrypted values can not be searched by the database, `fulltext` columns can not be `encrypted`",
+            ));
+        }
+
+        if modifiers.json && attribute.kind != attribute::ColumnKind::Data {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `json` modifier is only supported on data columns (`#[sql(json)]`)",
+            ));
+        }
+
+        if modifiers.json && !cfg!(feature = "postgres") {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "`json` columns (`#[sql(json)]`) are only supported on postgres",
+            ));
+        }
+
+        if modifiers.json && modifiers.encrypted {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "encrypted documents can not be manipulated by the database, `json` columns can not be `encrypted`",
+            ));
+        }
+
+        if modifiers.encrypted && (modifiers.unique || modifiers.index || modifiers.check.is_some())
+        {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "equal values are encrypted into different ciphertexts, `encrypted` columns can not be `unique`, indexed or checked",
+            ));
+        }
+
+        if modifiers.encrypted && !cfg!(feature = "encryption") {
+            return Err(syn::Error::new(
+                name.field().span(),
+                "the `encrypted` modifier requires the `encryption` feature of atmosphere",
+            ));
+        }
+
         match attribute.kind {
             attribute::ColumnKind::PrimaryKey => Ok(Self::PrimaryKey(PrimaryKey {
-                modifiers: ColumnModifiers { unique: true },
+                modifiers: ColumnModifiers {
+                    unique: true,
+                    ..modifiers
+                },
                 name,
                 ty,
             })),
             attribute::ColumnKind::ForeignKey { on } => Ok(Self::ForeignKey(ForeignKey {
                 on,
+                relation: attribute.relation,
+                inverse: attribute.inverse,
+                on_delete: attribute.on_delete,
+                on_update: attribute.on_update,
                 modifiers,
                 name,
                 ty,
@@ -317,11 +832,24 @@ impl TryFrom<Field> for Column {
                 name,
                 ty,
             })),
+            attribute::ColumnKind::Skip => Err(syn::Error::new(
+                name.field().span(),
+                "skipped fields are not columns",
+            )),
         }
     }
 }
 
 impl Column {
+    pub fn modifiers(&self) -> &ColumnModifiers {
+        match self {
+            Self::PrimaryKey(pk) => &pk.modifiers,
+            Self::ForeignKey(fk) => &fk.modifiers,
+            Self::Data(data) => &data.modifiers,
+            Self::Timestamp(ts) => &ts.modifiers,
+        }
+    }
+
     pub fn name(&self) -> &NameSet {
         match self {
             Self::PrimaryKey(pk) => &pk.name,
@@ -330,6 +858,15 @@ impl Column {
             Self::Timestamp(ts) => &ts.name,
         }
     }
+
+    pub fn name_mut(&mut self) -> &mut NameSet {
+        match self {
+            Self::PrimaryKey(pk) => &mut pk.name,
+            Self::ForeignKey(fk) => &mut fk.name,
+            Self::Data(data) => &mut data.name,
+            Self::Timestamp(ts) => &mut ts.name,
+        }
+    }
 }
 
 /// Utility implementations for determining the enum type
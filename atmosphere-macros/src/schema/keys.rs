@@ -1,8 +1,8 @@
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{Ident, Type};
 
-use super::column::{ColumnModifiers, NameSet};
+use super::column::{column_type, is_option, ColumnModifiers, NameSet};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PrimaryKey {
@@ -16,29 +16,116 @@ impl PrimaryKey {
         let field = self.name.field();
         let sql = self.name.sql();
 
-        quote!(::atmosphere::PrimaryKey::new(
+        let mut pk = quote!(::atmosphere::PrimaryKey::new(
             stringify!(#field),
             stringify!(#sql)
-        ))
+        ));
+
+        if self.modifiers.auto {
+            pk.extend(quote!(.with_auto()));
+        }
+
+        if let Some(comment) = &self.modifiers.comment {
+            pk.extend(quote!(.with_comment(#comment)));
+        }
+
+        pk
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReferentialAction {
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+impl ReferentialAction {
+    pub fn parse(value: &syn::LitStr) -> syn::Result<Self> {
+        match value.value().to_lowercase().replace(' ', "_").as_str() {
+            "no_action" => Ok(Self::NoAction),
+            "restrict" => Ok(Self::Restrict),
+            "cascade" => Ok(Self::Cascade),
+            "set_null" => Ok(Self::SetNull),
+            "set_default" => Ok(Self::SetDefault),
+            _ => Err(syn::Error::new_spanned(
+                value,
+                "only `no_action`, `restrict`, `cascade`, `set_null` and `set_default` are supported",
+            )),
+        }
+    }
+}
+
+impl ToTokens for ReferentialAction {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let path = match self {
+            Self::NoAction => quote!(::atmosphere::column::ReferentialAction::NoAction),
+            Self::Restrict => quote!(::atmosphere::column::ReferentialAction::Restrict),
+            Self::Cascade => quote!(::atmosphere::column::ReferentialAction::Cascade),
+            Self::SetNull => quote!(::atmosphere::column::ReferentialAction::SetNull),
+            Self::SetDefault => quote!(::atmosphere::column::ReferentialAction::SetDefault),
+        };
+
+        tokens.extend(path);
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ForeignKey {
     pub on: Ident,
+    /// The name of the relation, required to disambiguate multiple keys on the same table
+    pub relation: Option<Ident>,
+    /// The name of the inverse relation, defaults to `<table>s_by_<relation>`
+    pub inverse: Option<Ident>,
+    pub on_delete: Option<ReferentialAction>,
+    pub on_update: Option<ReferentialAction>,
     pub modifiers: ColumnModifiers,
     pub name: NameSet,
     pub ty: Type,
 }
 
 impl ForeignKey {
+    /// Whether the foreign key column is nullable (`Option<T>`)
+    pub fn nullable(&self) -> bool {
+        is_option(&self.ty)
+    }
+
     pub fn quote(&self) -> TokenStream {
         let field = self.name.field();
         let sql = self.name.sql();
 
-        quote!(::atmosphere::ForeignKey::new(
+        let mut fk = quote!(::atmosphere::ForeignKey::new(
             stringify!(#field),
             stringify!(#sql)
-        ))
+        ));
+
+        if let Some(action) = self.on_delete {
+            fk.extend(quote!(.with_on_delete(#action)));
+        }
+
+        if let Some(action) = self.on_update {
+            fk.extend(quote!(.with_on_update(#action)));
+        }
+
+        let on = &self.on;
+        let ty = column_type(&self.ty, self.nullable());
+
+        fk.extend(quote!(.with_type(#ty).with_references::<#on>()));
+
+        if self.modifiers.unique {
+            fk.extend(quote!(.with_unique()));
+        }
+
+        if self.modifiers.immutable {
+            fk.extend(quote!(.with_immutable()));
+        }
+
+        if let Some(comment) = &self.modifiers.comment {
+            fk.extend(quote!(.with_comment(#comment)));
+        }
+
+        fk
     }
 }
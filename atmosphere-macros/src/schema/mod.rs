@@ -1,4 +1,5 @@
 pub mod column;
 pub mod keys;
 pub mod relation;
+pub mod rename;
 pub mod table;
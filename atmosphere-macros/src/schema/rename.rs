@@ -0,0 +1,95 @@
+use syn::{Error, LitStr};
+
+/// Renames all columns of a table (`#[table(.., rename_all = "..")]`) or all values of an enum
+/// (`#[sql(rename_all = "..")]`), following serde
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+}
+
+impl RenameRule {
+    pub fn parse(value: &LitStr) -> syn::Result<Self> {
+        match value.value().as_str() {
+            "lowercase" => Ok(Self::Lower),
+            "UPPERCASE" => Ok(Self::Upper),
+            "PascalCase" => Ok(Self::Pascal),
+            "camelCase" => Ok(Self::Camel),
+            "snake_case" => Ok(Self::Snake),
+            "SCREAMING_SNAKE_CASE" => Ok(Self::ScreamingSnake),
+            _ => Err(Error::new(
+                value.span(),
+                "`rename_all` supports only `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`, `snake_case` and `SCREAMING_SNAKE_CASE`",
+            )),
+        }
+    }
+
+    /// Applies this rule to the (snake case) name of a field
+    pub fn apply(&self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_owned(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => {
+                let mut pascal = String::with_capacity(field.len());
+                let mut capitalize = true;
+
+                for c in field.chars() {
+                    match c {
+                        '_' => capitalize = true,
+                        c if capitalize => {
+                            pascal.push(c.to_ascii_uppercase());
+                            capitalize = false;
+                        }
+                        c => pascal.push(c),
+                    }
+                }
+
+                pascal
+            }
+            Self::Camel => {
+                let pascal = Self::Pascal.apply(field);
+                let mut chars = pascal.chars();
+
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => pascal,
+                }
+            }
+        }
+    }
+
+    /// Applies this rule to the (pascal case) name of an enum variant
+    pub fn apply_to_variant(&self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_owned(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => {
+                let mut chars = variant.chars();
+
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => variant.to_owned(),
+                }
+            }
+            Self::Snake => {
+                let mut snake = String::with_capacity(variant.len());
+
+                for (i, c) in variant.char_indices() {
+                    if c.is_uppercase() && i != 0 {
+                        snake.push('_');
+                    }
+
+                    snake.push(c.to_ascii_lowercase());
+                }
+
+                snake
+            }
+            Self::ScreamingSnake => Self::Snake.apply_to_variant(variant).to_ascii_uppercase(),
+        }
+    }
+}
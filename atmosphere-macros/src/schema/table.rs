@@ -1,37 +1,359 @@
-use std::collections::HashSet;
-
+use proc_macro2::TokenStream;
 use syn::parse::{Parse, ParseStream};
-use syn::{Error, Fields, Generics, Ident, LitStr, Token, Visibility};
+use syn::punctuated::Punctuated;
+use syn::{Error, Fields, Generics, Ident, LitInt, LitStr, Token, Visibility};
 
 use crate::hooks::Hooks;
-use crate::schema::column::{Column, DataColumn, TimestampColumn};
+use crate::schema::column::{self, Column, DataColumn, TimestampColumn};
 use crate::schema::keys::{ForeignKey, PrimaryKey};
+use crate::schema::rename::RenameRule;
 
 #[derive(Clone, Debug)]
 pub struct TableId {
     pub schema: String,
     pub table: String,
+    /// Whether the table name is decided at runtime by a `TableName` provider
+    pub dynamic: bool,
+    /// Whether the generated statements are verified at compile time using `sqlx::query!`
+    pub checked: bool,
+    /// Whether the table is a view, which is only read
+    pub view: bool,
+    /// Whether the view is a postgres materialized view, which is refreshed
+    pub materialized: bool,
+    /// Whether the changes of rows can be tracked using `Tracked`
+    pub tracked: bool,
+    /// Whether a `<Table>Patch` type is generated for partial updates
+    pub patch: bool,
+    /// Whether rows can be generated for tests using `<Table>::factory()`
+    pub factory: bool,
+    /// Whether previous versions of rows are kept in a `<table>_history` companion table
+    pub history: bool,
+    /// Whether rows are never updated (`deny(update)`)
+    pub deny_update: bool,
+    /// Whether rows are never deleted (`deny(delete)`)
+    pub deny_delete: bool,
+    /// The named database holding the table
+    pub database: Option<String>,
+    /// Renames all columns which are not renamed explicitly
+    pub rename_all: Option<RenameRule>,
+    /// The `IdGenerator` assigning unset primary keys
+    pub ids: Option<TokenStream>,
+    /// The fields of uniqueness constraints spanning multiple columns
+    pub unique: Vec<Vec<Ident>>,
+    /// The conditions of check constraints of the table
+    pub checks: Vec<String>,
+    /// The comment describing the table
+    pub comment: Option<String>,
+    /// The fields of indexes declared on the table and whether they are sorted descending
+    pub indexes: Vec<Vec<(Ident, bool)>>,
+    /// The in-process cache of rows read by primary key
+    pub cache: Option<Cache>,
+}
+
+/// The configuration of the cache of a table (`cache(ttl = "30s", capacity = 1000)`)
+#[derive(Clone, Debug)]
+pub struct Cache {
+    /// The time rows are cached for, in milliseconds
+    pub ttl: u64,
+    /// The maximum number of rows cached in process, if not the default
+    pub capacity: Option<usize>,
+    /// Whether the rows are cached in the `CacheBackend` shared by all processes
+    pub shared: bool,
+}
+
+impl Parse for Cache {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut ttl = None;
+        let mut capacity = None;
+        let mut shared = false;
+
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+
+            if ident == "shared" {
+                shared = true;
+
+                if !input.peek(Token![,]) {
+                    break;
+                }
+
+                input.parse::<Token![,]>()?;
+
+                continue;
+            }
+
+            input.parse::<Token![=]>()?;
+
+            match ident.to_string().as_str() {
+                "ttl" => ttl = Some(millis(&input.parse()?)?),
+                "capacity" => {
+                    let value: LitInt = input.parse()?;
+                    let value = value.base10_parse()?;
+
+                    if value == 0 {
+                        return Err(syn::Error::new_spanned(
+                            ident,
+                            "`capacity` has to be at least 1",
+                        ));
+                    }
+
+                    capacity = Some(value);
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "`cache(..)` supports only the values `ttl`, `capacity` and `shared`",
+                    ))
+                }
+            }
+
+            if !input.peek(Token![,]) {
+                break;
+            }
+
+            input.parse::<Token![,]>()?;
+        }
+
+        let ttl = ttl.ok_or_else(|| {
+            syn::Error::new(
+                input.span(),
+                "`cache(..)` requires a `ttl`, e.g. `cache(ttl = \"30s\")`",
+            )
+        })?;
+
+        if shared && capacity.is_some() {
+            return Err(syn::Error::new(
+                input.span(),
+                "`capacity` limits the rows cached in process, `shared` rows are cached by the backend",
+            ));
+        }
+
+        Ok(Self {
+            ttl,
+            capacity,
+            shared,
+        })
+    }
+}
+
+/// Parses a duration like `500ms`, `30s`, `5m` or `1h` into milliseconds
+fn millis(value: &LitStr) -> syn::Result<u64> {
+    let duration = value.value();
+
+    let split = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(duration.len());
+
+    let (amount, unit) = duration.split_at(split);
+
+    let factor = match unit {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => 0,
+    };
+
+    match amount.parse::<u64>() {
+        Ok(amount) if factor > 0 && amount > 0 => Ok(amount * factor),
+        _ => Err(syn::Error::new_spanned(
+            value,
+            "durations are a positive number of `ms`, `s`, `m` or `h`, e.g. `30s`",
+        )),
+    }
+}
+
+impl TableId {
+    /// Whether rows of the table are updated
+    pub fn updatable(&self) -> bool {
+        !self.view && !self.deny_update
+    }
+
+    /// Whether rows of the table are deleted
+    pub fn deletable(&self) -> bool {
+        !self.view && !self.deny_delete
+    }
 }
 
 impl Parse for TableId {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut schema = None;
         let mut table = None;
+        let mut dynamic = false;
+        let mut checked = false;
+        let mut view = false;
+        let mut materialized = false;
+        let mut tracked = false;
+        let mut patch = false;
+        let mut factory = false;
+        let mut history = false;
+        let mut deny_update = false;
+        let mut deny_delete = false;
+        let mut database = None;
+        let mut rename_all = None;
+        let mut ids = None;
+        let mut unique = vec![];
+        let mut checks = vec![];
+        let mut comment = None;
+        let mut indexes = vec![];
+        let mut cache = None;
 
         while !input.is_empty() {
             let ident: syn::Ident = input.parse()?;
+
+            if [
+                "dynamic",
+                "checked",
+                "view",
+                "materialized",
+                "tracked",
+                "patch",
+                "factory",
+                "history",
+            ]
+            .iter()
+            .any(|flag| ident == flag)
+            {
+                match ident.to_string().as_str() {
+                    "dynamic" => dynamic = true,
+                    "checked" => checked = true,
+                    "view" => view = true,
+                    "tracked" => tracked = true,
+                    "patch" => patch = true,
+                    "factory" => factory = true,
+                    "history" => history = true,
+                    // materialized views are views as well
+                    _ => (view, materialized) = (true, true),
+                }
+
+                if !input.peek(Token![,]) {
+                    break;
+                }
+
+                input.parse::<Token![,]>()?;
+
+                continue;
+            }
+
+            if ident == "unique" {
+                let content;
+                syn::parenthesized!(content in input);
+
+                let fields = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+
+                if fields.len() < 2 {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "`unique(..)` declares constraints spanning multiple columns, use `#[sql(unique)]` for single columns",
+                    ));
+                }
+
+                unique.push(fields.into_iter().collect());
+
+                if !input.peek(Token![,]) {
+                    break;
+                }
+
+                input.parse::<Token![,]>()?;
+
+                continue;
+            }
+
+            if ident == "deny" {
+                let content;
+                syn::parenthesized!(content in input);
+
+                for op in Punctuated::<Ident, Token![,]>::parse_terminated(&content)? {
+                    match op.to_string().as_str() {
+                        "update" => deny_update = true,
+                        "delete" => deny_delete = true,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                op,
+                                "`deny(..)` supports only the operations `update` and `delete`",
+                            ))
+                        }
+                    }
+                }
+
+                if !input.peek(Token![,]) {
+                    break;
+                }
+
+                input.parse::<Token![,]>()?;
+
+                continue;
+            }
+
+            if ident == "cache" {
+                let content;
+                syn::parenthesized!(content in input);
+
+                cache = Some(content.parse::<Cache>()?);
+
+                if !input.peek(Token![,]) {
+                    break;
+                }
+
+                input.parse::<Token![,]>()?;
+
+                continue;
+            }
+
+            if ident == "index" {
+                let content;
+                syn::parenthesized!(content in input);
+
+                let mut fields: Vec<(Ident, bool)> = vec![];
+
+                // `asc` and `desc` set the order of the preceding field
+                for ident in Punctuated::<Ident, Token![,]>::parse_terminated(&content)? {
+                    match (ident.to_string().as_str(), fields.last_mut()) {
+                        ("asc", Some((_, descending))) => *descending = false,
+                        ("desc", Some((_, descending))) => *descending = true,
+                        ("asc" | "desc", None) => {
+                            return Err(syn::Error::new_spanned(
+                                ident,
+                                "`asc` and `desc` follow the column they order",
+                            ))
+                        }
+                        _ => fields.push((ident, false)),
+                    }
+                }
+
+                if fields.is_empty() {
+                    return Err(syn::Error::new(
+                        content.span(),
+                        "`index(..)` requires at least one column",
+                    ));
+                }
+
+                indexes.push(fields);
+
+                if !input.peek(Token![,]) {
+                    break;
+                }
+
+                input.parse::<Token![,]>()?;
+
+                continue;
+            }
+
             input.parse::<Token![=]>()?;
             let value: LitStr = input.parse()?;
 
             match ident.to_string().as_str() {
                 "schema" => schema = Some(value.value()),
                 "name" => table = Some(value.value()),
-                _ => {
-                    return Err(syn::Error::new_spanned(
-                        ident,
-                        "`#[table]` supports only the values `schema` and `name`",
-                    ))
-                }
+                "database" => database = Some(value.value()),
+                "rename_all" => rename_all = Some(RenameRule::parse(&value)?),
+                "ids" => ids = Some(value.parse::<TokenStream>()?),
+                "check" => checks.push(value.value()),
+                "comment" => comment = Some(value.value()),
+                _ => return Err(syn::Error::new_spanned(
+                    ident,
+                    "`#[table]` supports only the values `schema`, `name`, `database`, `rename_all`, `ids`, `unique(..)`, `index(..)`, `deny(..)`, `cache(..)`, `check`, `comment`, `dynamic`, `checked`, `view`, `materialized`, `tracked`, `patch`, `factory` and `history`",
+                )),
             }
 
             if !input.peek(Token![,]) {
@@ -49,16 +371,85 @@ impl Parse for TableId {
             syn::Error::new(input.span(), "`#[table]` requires a value for `name`")
         })?;
 
-        Ok(Self { schema, table })
+        if materialized && !cfg!(feature = "postgres") {
+            return Err(syn::Error::new(
+                input.span(),
+                "materialized views (`#[table(materialized)]`) are only supported on postgres",
+            ));
+        }
+
+        if patch && (view || deny_update) {
+            return Err(syn::Error::new(
+                input.span(),
+                "`patch` generates partial updates, views and tables using `deny(update)` are not updated",
+            ));
+        }
+
+        if factory && view {
+            return Err(syn::Error::new(
+                input.span(),
+                "`factory` creates rows for tests, views are not written to",
+            ));
+        }
+
+        if history && view {
+            return Err(syn::Error::new(
+                input.span(),
+                "`history` keeps the versions of changed rows, views are not written to",
+            ));
+        }
+
+        if history && !cfg!(feature = "postgres") {
+            return Err(syn::Error::new(
+                input.span(),
+                "history tables (`#[table(history)]`) are only supported on postgres",
+            ));
+        }
+
+        if cache.is_some() && (view || dynamic) {
+            return Err(syn::Error::new(
+                input.span(),
+                "`cache` is invalidated by the writes to the table, views and dynamic tables can not be cached",
+            ));
+        }
+
+        if dynamic && checked {
+            return Err(syn::Error::new(
+                input.span(),
+                "`#[table]` can not be both `dynamic` and `checked`, dynamic table names are unknown at compile time",
+            ));
+        }
+
+        Ok(Self {
+            schema,
+            table,
+            dynamic,
+            checked,
+            view,
+            materialized,
+            tracked,
+            patch,
+            factory,
+            history,
+            deny_update,
+            deny_delete,
+            database,
+            rename_all,
+            ids,
+            unique,
+            checks,
+            comment,
+            indexes,
+            cache,
+        })
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct Table {
     // TODO(flrn):
-    //  confirm what the fields `vis` and `generics` were
-    //  intended for; remove them if they are not needed
-    #[allow(dead_code)]
+    //  confirm what the field `generics` was
+    //  intended for; remove it if it is not needed
     pub vis: Visibility,
     #[allow(dead_code)]
     pub generics: Generics,
@@ -67,11 +458,29 @@ pub struct Table {
     pub id: TableId,
 
     pub primary_key: PrimaryKey,
-    pub foreign_keys: HashSet<ForeignKey>,
-    pub data_columns: HashSet<DataColumn>,
-    pub timestamp_columns: HashSet<TimestampColumn>,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub data_columns: Vec<DataColumn>,
+    pub timestamp_columns: Vec<TimestampColumn>,
+
+    /// The fields which are not columns (`#[sql(skip)]`)
+    pub skipped: Vec<Ident>,
+
+    /// All fields, in order of declaration
+    pub fields: Vec<Ident>,
+
+    /// The columns of uniqueness constraints spanning multiple columns (`#[table(unique(..))]`)
+    pub unique: Vec<Vec<Column>>,
+
+    /// The indexes of the table (`#[sql(index)]` and `#[table(index(..))]`)
+    pub indexes: Vec<Index>,
 
     pub hooks: Hooks,
+
+    /// Whether changes are recorded in an audit table (`#[audit]`)
+    pub audit: bool,
+
+    /// Whether the struct uses `#[validate(..)]` attributes of the `validator` crate
+    pub validator: bool,
 }
 
 impl Parse for Table {
@@ -98,6 +507,8 @@ impl Parse for Table {
             }
         };
 
+        let audit = item.attrs.iter().any(|attr| attr.path().is_ident("audit"));
+
         let ident = item.ident;
 
         let fields = match item.fields {
@@ -113,14 +524,41 @@ impl Parse for Table {
             }
         };
 
-        let columns = fields
+        let validator = item
+            .attrs
+            .iter()
+            .chain(fields.named.iter().flat_map(|f| f.attrs.iter()))
+            .any(|attr| attr.path().is_ident("validate"));
+
+        let all = fields
             .named
+            .iter()
+            .filter_map(|f| f.ident.clone())
+            .collect();
+
+        let (skipped, fields): (Vec<_>, Vec<_>) =
+            fields.named.into_iter().partition(column::is_skipped);
+
+        let skipped = skipped.into_iter().filter_map(|f| f.ident).collect();
+
+        let columns = fields
             .into_iter()
             .map(Column::try_from)
-            .collect::<syn::Result<HashSet<Column>>>()?;
+            .collect::<syn::Result<Vec<Column>>>()?;
+
+        let columns = match id.rename_all {
+            Some(rule) => columns
+                .into_iter()
+                .map(|mut column| {
+                    column.name_mut().rename_all(rule);
+                    column
+                })
+                .collect(),
+            None => columns,
+        };
 
         let primary_key = {
-            let primary_keys: HashSet<PrimaryKey> = columns
+            let primary_keys: Vec<PrimaryKey> = columns
                 .iter()
                 .filter_map(|c| c.as_primary_key())
                 .cloned()
@@ -145,18 +583,170 @@ impl Parse for Table {
             ))?
         };
 
+        if id.ids.is_some() && (primary_key.modifiers.auto || primary_key.modifiers.uuid.is_some())
+        {
+            return Err(Error::new(
+                primary_key.name.field().span(),
+                "primary keys generated by an `IdGenerator` (`#[table(ids = ..)]`) can not be `auto` or `uuid`",
+            ));
+        }
+
+        if id.view && audit {
+            return Err(Error::new(
+                ident.span(),
+                "views (`#[table(view)]`) are only read, there are no changes to `#[audit]`",
+            ));
+        }
+
+        if id.view && (id.ids.is_some() || !id.unique.is_empty() || !id.checks.is_empty()) {
+            return Err(Error::new(
+                ident.span(),
+                "views (`#[table(view)]`) are only read, they can not declare `ids`, `unique(..)` or `check`",
+            ));
+        }
+
+        let encrypted = id
+            .unique
+            .iter()
+            .flatten()
+            .chain(id.indexes.iter().flatten().map(|(field, _)| field))
+            .find(|field| {
+                columns
+                    .iter()
+                    .any(|c| c.name().field() == *field && c.modifiers().encrypted)
+            });
+
+        if let Some(field) = encrypted {
+            return Err(Error::new(
+                field.span(),
+                "equal values are encrypted into different ciphertexts, `encrypted` columns can not be unique or indexed",
+            ));
+        }
+
+        if let Some(column) = columns
+            .iter()
+            .find(|c| id.checked && c.modifiers().encrypted)
+        {
+            return Err(Error::new(
+                column.name().field().span(),
+                "`encrypted` columns are not supported by compile time checked tables (`#[table(checked)]`)",
+            ));
+        }
+
+        let unique = id
+            .unique
+            .iter()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|field| {
+                        columns
+                            .iter()
+                            .find(|c| c.name().field() == field)
+                            .filter(|c| matches!(c, Column::ForeignKey(_) | Column::Data(_)))
+                            .cloned()
+                            .ok_or_else(|| {
+                                Error::new(
+                                    field.span(),
+                                    format!("{ident} has no foreign key or data column `{field}`"),
+                                )
+                            })
+                    })
+                    .collect::<syn::Result<Vec<Column>>>()
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let declared = id
+            .indexes
+            .iter()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(field, descending)| {
+                        columns
+                            .iter()
+                            .find(|c| c.name().field() == field)
+                            .map(|c| (c.name().sql().to_string(), *descending))
+                            .ok_or_else(|| {
+                                Error::new(field.span(), format!("{ident} has no column `{field}`"))
+                            })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let mut indexes: Vec<Index> = columns
+            .iter()
+            .filter(|c| c.modifiers().index)
+            .map(|c| vec![(c.name().sql().to_string(), false)])
+            .chain(declared)
+            .map(|columns| Index::new(&id.table, columns))
+            .collect();
+
+        let fulltext: Vec<(String, &str)> = columns
+            .iter()
+            .filter_map(|c| {
+                Some((
+                    c.name().sql().to_string(),
+                    c.modifiers().fulltext.as_deref()?,
+                ))
+            })
+            .collect();
+
+        if fulltext.iter().any(|(_, config)| *config != fulltext[0].1) {
+            return Err(Error::new(
+                ident.span(),
+                "all `fulltext` columns of a table are searched together and have to use the same text search configuration",
+            ));
+        }
+
+        // views are not indexed by atmosphere
+        if !fulltext.is_empty() && !id.view {
+            indexes.push(Index::fulltext(&id.table, &fulltext));
+        }
+
         let foreign_keys = columns
             .iter()
             .filter_map(|c| c.as_foreign_key())
             .cloned()
             .collect();
 
-        let data_columns = columns
+        let data_columns: Vec<DataColumn> = columns
             .iter()
             .filter_map(|c| c.as_data_column())
             .cloned()
             .collect();
 
+        if id.cache.is_some() && data_columns.iter().any(|c| c.modifiers.tenant) {
+            return Err(Error::new(
+                ident.span(),
+                "rows are cached by primary key, tables with a tenant column can not be cached",
+            ));
+        }
+
+        if data_columns.iter().filter(|c| c.modifiers.tenant).count() > 1 {
+            return Err(Error::new(
+                input.span(),
+                format!(
+                    "{} declares more than one column as its tenant – only one is allowed",
+                    ident
+                ),
+            ));
+        }
+
+        if id.patch
+            && !columns.iter().any(|c| match c {
+                Column::ForeignKey(fk) => !fk.modifiers.immutable,
+                Column::Data(data) => !data.modifiers.readonly && !data.modifiers.immutable,
+                _ => false,
+            })
+        {
+            return Err(Error::new(
+                ident.span(),
+                format!("{ident} has no updated columns to `patch`"),
+            ));
+        }
+
         let timestamp_columns = columns
             .iter()
             .filter_map(|c| c.as_timestamp_column())
@@ -172,7 +762,54 @@ impl Parse for Table {
             foreign_keys,
             data_columns,
             timestamp_columns,
+            skipped,
+            fields: all,
+            unique,
+            indexes,
             hooks,
+            audit,
+            validator,
         })
     }
 }
+
+/// An index of a table, by the sql names of its columns
+#[derive(Clone, Debug)]
+pub struct Index {
+    pub name: String,
+    /// The columns and whether they are sorted descending
+    pub columns: Vec<(String, bool)>,
+    /// The index method, if not the default of the database
+    pub using: Option<String>,
+}
+
+impl Index {
+    /// The index of `columns` of `table`, named like the indexes postgres names
+    fn new(table: &str, columns: Vec<(String, bool)>) -> Self {
+        let names: Vec<&str> = columns.iter().map(|(c, _)| c.as_str()).collect();
+
+        Self {
+            name: format!("{table}_{}_idx", names.join("_")),
+            columns,
+            using: None,
+        }
+    }
+
+    /// The `GIN` index of the `tsvector` document of the full-text searched `columns` of `table`,
+    /// by their sql names and text search configurations.
+    ///
+    /// The document has to be the very expression `atmosphere::runtime::sql::document` searches.
+    fn fulltext(table: &str, columns: &[(String, &str)]) -> Self {
+        let document = columns
+            .iter()
+            .map(|(sql, config)| format!("to_tsvector('{config}', coalesce({sql}, ''))"))
+            .collect::<Vec<_>>()
+            .join(" || ");
+
+        Self {
+            name: format!("{table}_fulltext_idx"),
+            columns: vec![(format!("({document})"), false)],
+            using: Some("GIN".to_owned()),
+        }
+    }
+}
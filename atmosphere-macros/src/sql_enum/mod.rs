@@ -0,0 +1,237 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Fields, Ident, ItemEnum, LitStr, Token};
+
+use crate::schema::{column::attribute::PATH, rename::RenameRule};
+
+/// The representation of an enum in the database
+enum Repr {
+    /// Text, or a postgres enum type if named
+    Text { type_name: Option<LitStr> },
+    /// An integer type, holding the discriminants of the variants
+    Integer(Ident),
+}
+
+/// The arguments of `#[sql(..)]` on an enum
+#[derive(Default)]
+struct EnumAttribute {
+    type_name: Option<LitStr>,
+    rename_all: Option<RenameRule>,
+    repr: Option<Ident>,
+}
+
+impl syn::parse::Parse for EnumAttribute {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut attribute = Self::default();
+
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+
+            input.parse::<Token![=]>()?;
+
+            match ident.to_string().as_str() {
+                "type_name" => attribute.type_name = Some(input.parse()?),
+                "rename_all" => attribute.rename_all = Some(RenameRule::parse(&input.parse()?)?),
+                "repr" => attribute.repr = Some(input.parse()?),
+                _ => {
+                    return Err(Error::new_spanned(
+                        ident,
+                        "`#[sql]` on enums supports only the values `type_name`, `rename_all` and `repr`",
+                    ))
+                }
+            }
+
+            if !input.peek(Token![,]) {
+                break;
+            }
+
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(attribute)
+    }
+}
+
+/// The arguments of `#[sql(..)]` on a variant
+struct VariantAttribute {
+    rename: LitStr,
+}
+
+impl syn::parse::Parse for VariantAttribute {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+
+        if ident != "rename" {
+            return Err(Error::new_spanned(
+                ident,
+                "`#[sql]` on variants supports only `rename`",
+            ));
+        }
+
+        input.parse::<Token![=]>()?;
+
+        Ok(Self {
+            rename: input.parse()?,
+        })
+    }
+}
+
+pub fn sql_enum(item: &ItemEnum) -> syn::Result<TokenStream> {
+    let ident = &item.ident;
+
+    let attribute = item
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident(PATH))
+        .map(|a| a.parse_args::<EnumAttribute>())
+        .transpose()?
+        .unwrap_or_default();
+
+    let repr = match (attribute.repr, attribute.type_name) {
+        (Some(repr), None) => Repr::Integer(repr),
+        (None, type_name) => Repr::Text { type_name },
+        (Some(_), Some(type_name)) => {
+            return Err(Error::new(
+                type_name.span(),
+                "`type_name` names a postgres enum type, it can not be combined with `repr`",
+            ))
+        }
+    };
+
+    if let Repr::Text {
+        type_name: Some(type_name),
+    } = &repr
+    {
+        if !cfg!(feature = "postgres") {
+            return Err(Error::new(
+                type_name.span(),
+                "`type_name` is only supported on postgres",
+            ));
+        }
+    }
+
+    let mut variants = vec![];
+    let mut values = vec![];
+
+    for (i, variant) in item.variants.iter().enumerate() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new(
+                variant.ident.span(),
+                "`SqlEnum` is only supported on enums with unit variants",
+            ));
+        }
+
+        let rename = variant
+            .attrs
+            .iter()
+            .find(|a| a.path().is_ident(PATH))
+            .map(|a| a.parse_args::<VariantAttribute>())
+            .transpose()?
+            .map(|a| a.rename);
+
+        let value = match &repr {
+            Repr::Text { .. } => {
+                let value = match (rename, attribute.rename_all) {
+                    (Some(rename), _) => rename.value(),
+                    (None, Some(rule)) => rule.apply_to_variant(&variant.ident.to_string()),
+                    (None, None) => variant.ident.to_string(),
+                };
+
+                quote!(#value)
+            }
+            Repr::Integer(_) => {
+                if let Some(rename) = rename {
+                    return Err(Error::new(
+                        rename.span(),
+                        "integer enums are stored as their discriminants, `rename` is only supported on text enums",
+                    ));
+                }
+
+                match &variant.discriminant {
+                    Some((_, discriminant)) => quote!(#discriminant),
+                    None => {
+                        let index = proc_macro2::Literal::usize_unsuffixed(i);
+                        quote!(#index)
+                    }
+                }
+            }
+        };
+
+        variants.push(&variant.ident);
+        values.push(value);
+    }
+
+    let type_info = match &repr {
+        Repr::Text {
+            type_name: Some(type_name),
+        } => Some(quote!(::atmosphere::sqlx::postgres::PgTypeInfo::with_name(#type_name))),
+        _ => None,
+    };
+
+    let decoded = match &repr {
+        Repr::Text { .. } => quote!(&'r str),
+        Repr::Integer(repr) => quote!(#repr),
+    };
+
+    let encoded = match &repr {
+        Repr::Text { .. } => quote!(&str),
+        Repr::Integer(repr) => quote!(#repr),
+    };
+
+    let ty_impl = match type_info {
+        Some(type_info) => quote!(
+            fn type_info() -> <::atmosphere::Driver as ::atmosphere::sqlx::Database>::TypeInfo {
+                #type_info
+            }
+        ),
+        None => quote!(
+            fn type_info() -> <::atmosphere::Driver as ::atmosphere::sqlx::Database>::TypeInfo {
+                <#encoded as ::atmosphere::sqlx::Type<::atmosphere::Driver>>::type_info()
+            }
+
+            fn compatible(
+                ty: &<::atmosphere::Driver as ::atmosphere::sqlx::Database>::TypeInfo,
+            ) -> bool {
+                <#encoded as ::atmosphere::sqlx::Type<::atmosphere::Driver>>::compatible(ty)
+            }
+        ),
+    };
+
+    Ok(quote!(
+        #[automatically_derived]
+        impl ::atmosphere::sqlx::Type<::atmosphere::Driver> for #ident {
+            #ty_impl
+        }
+
+        #[automatically_derived]
+        impl<'q> ::atmosphere::sqlx::Encode<'q, ::atmosphere::Driver> for #ident {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <::atmosphere::Driver as ::atmosphere::sqlx::database::HasArguments<'q>>::ArgumentBuffer,
+            ) -> ::atmosphere::sqlx::encode::IsNull {
+                let value: #encoded = match self {
+                    #(Self::#variants => #values,)*
+                };
+
+                <#encoded as ::atmosphere::sqlx::Encode<'q, ::atmosphere::Driver>>::encode(value, buf)
+            }
+        }
+
+        #[automatically_derived]
+        impl<'r> ::atmosphere::sqlx::Decode<'r, ::atmosphere::Driver> for #ident {
+            fn decode(
+                value: <::atmosphere::Driver as ::atmosphere::sqlx::database::HasValueRef<'r>>::ValueRef,
+            ) -> ::std::result::Result<Self, ::atmosphere::sqlx::error::BoxDynError> {
+                let value = <#decoded as ::atmosphere::sqlx::Decode<'r, ::atmosphere::Driver>>::decode(value)?;
+
+                #(
+                    if value == #values {
+                        return Ok(Self::#variants);
+                    }
+                )*
+
+                Err(format!("invalid value {value:?} for `{}`", stringify!(#ident)).into())
+            }
+        }
+    ))
+}
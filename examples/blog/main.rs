@@ -27,7 +27,7 @@ struct Post {
     #[sql(timestamp = updated)]
     updated_at: chrono::DateTime<chrono::Utc>,
     #[sql(timestamp = deleted)]
-    deleted_at: chrono::DateTime<chrono::Utc>,
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[tokio::main]
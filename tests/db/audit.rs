@@ -0,0 +1,76 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "lodge", schema = "public")]
+#[audit]
+struct Lodge {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+}
+
+type Entry = (String, Option<String>, Option<String>, Option<String>);
+
+async fn entries(pool: &sqlx::PgPool) -> Vec<Entry> {
+    sqlx::query_as(
+        "SELECT operation, actor, before ->> 'name', after ->> 'name' FROM public.lodge_audit ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+fn entry(op: &str, actor: Option<&str>, before: Option<&str>, after: Option<&str>) -> Entry {
+    (
+        op.to_owned(),
+        actor.map(str::to_owned),
+        before.map(str::to_owned),
+        after.map(str::to_owned),
+    )
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn audit(pool: sqlx::PgPool) {
+    sqlx::query(&atmosphere::audit::table_sql::<Lodge>())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut lodge = Lodge {
+        id: 0,
+        name: "lodge".to_owned(),
+    };
+
+    lodge.create(&pool).await.unwrap();
+
+    atmosphere::audit::actor("ranger", async {
+        lodge.name = "hut".to_owned();
+        assert_eq!(lodge.update(&pool).await.unwrap().rows_affected, 1);
+
+        lodge.name = "cabin".to_owned();
+        lodge.upsert(&pool).await.unwrap();
+    })
+    .await;
+
+    // rolled back changes are not recorded
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            Lodge::delete_by(&mut *conn, &0).await?;
+            Err::<(), _>(atmosphere::Error::Other)
+        })
+    })
+    .await
+    .unwrap_err();
+
+    assert_eq!(lodge.delete(&pool).await.unwrap().rows_affected, 1);
+
+    assert_eq!(
+        entries(&pool).await,
+        vec![
+            entry("insert", None, None, Some("lodge")),
+            entry("update", Some("ranger"), Some("lodge"), Some("hut")),
+            entry("upsert", Some("ranger"), Some("hut"), Some("cabin")),
+            entry("delete", None, Some("cabin"), None),
+        ]
+    );
+}
@@ -0,0 +1,42 @@
+use atmosphere::prelude::*;
+use atmosphere::query::WriteOutcome;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "ranger", schema = "public")]
+struct Ranger {
+    #[sql(pk, auto)]
+    id: i32,
+    name: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn create(pool: sqlx::PgPool) {
+    let mut first = Ranger {
+        id: 0,
+        name: "first".to_owned(),
+    };
+
+    let mut second = Ranger {
+        id: 0,
+        name: "second".to_owned(),
+    };
+
+    let res = first.create(&pool).await.unwrap();
+
+    // postgres assigns the generated key using `RETURNING` instead of reporting it
+    assert_eq!(
+        res,
+        WriteOutcome {
+            rows_affected: 1,
+            last_insert_id: None,
+        }
+    );
+
+    second.create(&pool).await.unwrap();
+
+    assert_ne!(first.id, 0);
+    assert_ne!(first.id, second.id);
+
+    assert_eq!(Ranger::read(&pool, &first.id).await.unwrap(), first);
+    assert_eq!(Ranger::read(&pool, &second.id).await.unwrap(), second);
+}
@@ -0,0 +1,396 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use atmosphere::{
+    cache::{CacheBackend, CacheError},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "bookmark", schema = "public", cache(ttl = "1h", capacity = 2))]
+struct Bookmark {
+    #[sql(pk)]
+    id: i32,
+    url: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "bookmark", schema = "public", cache(ttl = "100ms"))]
+struct Expiring {
+    #[sql(pk)]
+    id: i32,
+    url: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[table(name = "bookmark", schema = "public", cache(ttl = "1h", shared))]
+struct Shared {
+    #[sql(pk)]
+    id: i32,
+    url: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "bookmark", schema = "public", cache(ttl = "1h"))]
+struct Scoped {
+    #[sql(pk)]
+    id: i32,
+    url: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "folder", schema = "public", cache(ttl = "1h"))]
+struct Folder {
+    #[sql(pk)]
+    id: i32,
+    #[sql(unique)]
+    name: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "pin", schema = "public", cache(ttl = "1h"))]
+struct Pin {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Folder, rename = "folder_id")]
+    folder: i32,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "label", schema = "public", cache(ttl = "1h"))]
+struct Label {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Folder, rename = "folder_id", relation = "folder", inverse = "labels")]
+    folder: i32,
+}
+
+/// A backend shared by all "processes" of a test
+#[derive(Clone, Default)]
+struct Memory {
+    values: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+impl Memory {
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.values.lock().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[async_trait]
+impl CacheBackend for Memory {
+    async fn get(&self, key: &str) -> std::result::Result<Option<Vec<u8>>, CacheError> {
+        Ok(self.values.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        _: Duration,
+    ) -> std::result::Result<(), CacheError> {
+        self.values.lock().unwrap().insert(key.to_owned(), value);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> std::result::Result<(), CacheError> {
+        self.values.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn clear(&self, prefix: &str) -> std::result::Result<(), CacheError> {
+        self.values
+            .lock()
+            .unwrap()
+            .retain(|key, _| !key.starts_with(prefix));
+        Ok(())
+    }
+}
+
+/// Changes the url of a bookmark without going through atmosphere
+async fn rename(pool: &sqlx::PgPool, id: i32, url: &str) {
+    sqlx::query("UPDATE public.bookmark SET url = $1 WHERE id = $2")
+        .bind(url)
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+fn url(bookmark: Option<Bookmark>) -> Option<String> {
+    bookmark.map(|bookmark| bookmark.url)
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn cache(pool: sqlx::PgPool) {
+    Bookmark::create_table(&pool).await.unwrap();
+
+    let cache = Bookmark::cache().unwrap();
+
+    let mut bookmark = Bookmark {
+        id: 0,
+        url: "a".to_owned(),
+    };
+
+    bookmark.create(&pool).await.unwrap();
+
+    assert!(cache.is_empty());
+    assert_eq!(Bookmark::read(&pool, &0).await.unwrap().url, "a");
+    assert_eq!(cache.len(), 1);
+
+    // served from the cache
+    rename(&pool, 0, "b").await;
+
+    assert_eq!(Bookmark::read(&pool, &0).await.unwrap().url, "a");
+    assert_eq!(
+        url(Bookmark::find(&pool, &0).await.unwrap()),
+        Some("a".into())
+    );
+
+    // writes through atmosphere evict the row
+    bookmark.url = "c".to_owned();
+    bookmark.update(&pool).await.unwrap();
+
+    assert!(cache.is_empty());
+    assert_eq!(
+        url(Bookmark::find(&pool, &0).await.unwrap()),
+        Some("c".into())
+    );
+
+    // reads inside of transactions bypass the cache, changes are evicted on commit
+    rename(&pool, 0, "d").await;
+
+    let read = atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            let mut bookmark = Bookmark::read(&mut *conn, &0).await?;
+            let read = bookmark.url.clone();
+
+            bookmark.url = "e".to_owned();
+            bookmark.update(&mut *conn).await?;
+
+            Ok::<_, atmosphere::Error>(read)
+        })
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(read, "d");
+    assert!(cache.is_empty());
+    assert_eq!(
+        url(Bookmark::find(&pool, &0).await.unwrap()),
+        Some("e".into())
+    );
+
+    // the least recently used rows are evicted first
+    for id in 1..=2 {
+        Bookmark {
+            id,
+            url: id.to_string(),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    for id in [0, 1, 0, 2] {
+        Bookmark::read(&pool, &id).await.unwrap();
+    }
+
+    assert_eq!(cache.len(), 2);
+
+    rename(&pool, 0, "f").await;
+    rename(&pool, 1, "g").await;
+
+    assert_eq!(
+        url(Bookmark::find(&pool, &0).await.unwrap()),
+        Some("e".into())
+    );
+    assert_eq!(
+        url(Bookmark::find(&pool, &1).await.unwrap()),
+        Some("g".into())
+    );
+
+    // writes of many rows clear the cache
+    Bookmark::delete_many(&pool, &[0, 1]).await.unwrap();
+
+    assert!(cache.is_empty());
+    assert_eq!(Bookmark::find(&pool, &0).await.unwrap(), None);
+    assert!(cache.is_empty());
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn expiry(pool: sqlx::PgPool) {
+    Expiring::create_table(&pool).await.unwrap();
+
+    Expiring {
+        id: 0,
+        url: "a".to_owned(),
+    }
+    .create(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(Expiring::read(&pool, &0).await.unwrap().url, "a");
+
+    rename(&pool, 0, "b").await;
+
+    assert_eq!(Expiring::read(&pool, &0).await.unwrap().url, "a");
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    assert_eq!(Expiring::read(&pool, &0).await.unwrap().url, "b");
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn schemas(pool: sqlx::PgPool) {
+    Scoped::create_table(&pool).await.unwrap();
+
+    sqlx::query("CREATE SCHEMA tenant_7")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    sqlx::query("CREATE TABLE tenant_7.bookmark (LIKE public.bookmark INCLUDING ALL)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let public = Scoped {
+        id: 0,
+        url: "a".to_owned(),
+    };
+
+    let mut tenant = Scoped {
+        id: 0,
+        url: "b".to_owned(),
+    };
+
+    public.clone().create(&pool).await.unwrap();
+    SchemaContext::scope("tenant_7", tenant.create(&pool))
+        .await
+        .unwrap();
+
+    // rows with the same primary key are cached per schema
+    assert_eq!(Scoped::read(&pool, &0).await.unwrap(), public);
+
+    let read = SchemaContext::scope("tenant_7", Scoped::read(&pool, &0)).await;
+    assert_eq!(read.unwrap(), tenant);
+
+    assert_eq!(Scoped::cache().unwrap().len(), 2);
+
+    // writes evict the row of their schema only
+    tenant.url = "c".to_owned();
+    SchemaContext::scope("tenant_7", tenant.update(&pool))
+        .await
+        .unwrap();
+
+    assert_eq!(Scoped::cache().unwrap().len(), 1);
+    assert_eq!(Scoped::read(&pool, &0).await.unwrap(), public);
+
+    let read = SchemaContext::scope("tenant_7", Scoped::read(&pool, &0)).await;
+    assert_eq!(read.unwrap(), tenant);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn hookless(pool: sqlx::PgPool) {
+    let mut folder = Folder {
+        id: 0,
+        name: "reading".to_owned(),
+    };
+
+    folder.create(&pool).await.unwrap();
+    Pin { id: 0, folder: 0 }.create(&pool).await.unwrap();
+    Label { id: 0, folder: 0 }.create(&pool).await.unwrap();
+
+    Pin::read(&pool, &0).await.unwrap();
+    Label::read(&pool, &0).await.unwrap();
+
+    assert_eq!(Pin::cache().unwrap().len(), 1);
+    assert_eq!(Label::cache().unwrap().len(), 1);
+
+    // deletes of the rows referring to another row evict them
+    folder.delete_pins(&pool).await.unwrap();
+
+    assert!(Pin::cache().unwrap().is_empty());
+    assert_eq!(Pin::find(&pool, &0).await.unwrap(), None);
+
+    folder.delete_labels(&pool).await.unwrap();
+
+    assert!(Label::cache().unwrap().is_empty());
+    assert_eq!(Label::find(&pool, &0).await.unwrap(), None);
+
+    // as do deletes by unique columns
+    Folder::read(&pool, &0).await.unwrap();
+
+    assert_eq!(Folder::cache().unwrap().len(), 1);
+
+    Folder::delete_by_name(&pool, &folder.name).await.unwrap();
+
+    assert!(Folder::cache().unwrap().is_empty());
+    assert_eq!(Folder::find(&pool, &0).await.unwrap(), None);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn shared(pool: sqlx::PgPool) {
+    Shared::create_table(&pool).await.unwrap();
+
+    let backend = Memory::default();
+    atmosphere::cache::set_backend(backend.clone());
+
+    let mut shared = Shared {
+        id: 0,
+        url: "a".to_owned(),
+    };
+
+    shared.create(&pool).await.unwrap();
+
+    assert!(backend.keys().is_empty());
+    assert_eq!(Shared::read(&pool, &0).await.unwrap().url, "a");
+    assert_eq!(backend.keys(), vec!["atmosphere:public.bookmark:0"]);
+
+    // served from the backend, not from the process
+    rename(&pool, 0, "b").await;
+
+    assert!(Shared::cache().unwrap().is_empty());
+    assert_eq!(Shared::read(&pool, &0).await.unwrap().url, "a");
+
+    // writes through atmosphere evict the row from the backend
+    shared.url = "c".to_owned();
+    shared.update(&pool).await.unwrap();
+
+    assert!(backend.keys().is_empty());
+    assert_eq!(Shared::read(&pool, &0).await.unwrap().url, "c");
+
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            shared.url = "d".to_owned();
+            shared.update(&mut *conn).await?;
+
+            Ok::<_, atmosphere::Error>(())
+        })
+    })
+    .await
+    .unwrap();
+
+    assert!(backend.keys().is_empty());
+    assert_eq!(Shared::read(&pool, &0).await.unwrap().url, "d");
+
+    // rows cached by other versions of a service are ignored
+    backend
+        .values
+        .lock()
+        .unwrap()
+        .insert("atmosphere:public.bookmark:0".to_owned(), b"{}".to_vec());
+
+    assert_eq!(Shared::read(&pool, &0).await.unwrap().url, "d");
+
+    Shared::delete_many(&pool, &[0]).await.unwrap();
+
+    assert!(backend.keys().is_empty());
+}
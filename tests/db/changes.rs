@@ -0,0 +1,59 @@
+use atmosphere::prelude::*;
+use atmosphere::query::Operation;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn changes(pool: sqlx::PgPool) {
+    let mut changes = atmosphere::changes::<Forest>();
+
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    forest.create(&pool).await.unwrap();
+
+    forest.name = "spreewald".to_owned();
+    forest.update(&pool).await.unwrap();
+
+    // rolled back changes are not published
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            Forest::delete_by(&mut *conn, &0).await?;
+            Err::<(), _>(atmosphere::Error::Other)
+        })
+    })
+    .await
+    .unwrap_err();
+
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move { Forest::delete_by(&mut *conn, &0).await })
+    })
+    .await
+    .unwrap();
+
+    let event = changes.recv().await.unwrap();
+    assert_eq!(event.op, Operation::Insert);
+    assert_eq!(event.pk, 0);
+    assert_eq!(event.row.unwrap().name, "grunewald");
+
+    let event = changes.recv().await.unwrap();
+    assert_eq!(event.op, Operation::Update);
+    assert_eq!(event.row, Some(forest));
+
+    let event = changes.recv().await.unwrap();
+    assert_eq!(event.op, Operation::Delete);
+    assert_eq!(event.pk, 0);
+    assert_eq!(event.row, None);
+
+    assert!(changes.try_recv().is_err());
+}
@@ -0,0 +1,173 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "torch", schema = "public")]
+struct Torch {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(skip)]
+    lit: bool,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn skip(pool: sqlx::PgPool) {
+    Torch::create_table(&pool).await.unwrap();
+
+    assert!(Torch::describe().columns.iter().all(|c| c.field != "lit"));
+
+    let mut torch = Torch {
+        id: 0,
+        name: "pine".to_owned(),
+        lit: true,
+    };
+
+    torch.create(&pool).await.unwrap();
+
+    torch.name = "birch".to_owned();
+    torch.update(&pool).await.unwrap();
+
+    let read = Torch::read(&pool, &0).await.unwrap();
+
+    assert_eq!(read.name, "birch");
+    assert!(!read.lit);
+
+    assert_eq!(Torch::read_all(&pool).await.unwrap(), [read]);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "cairn", schema = "public")]
+struct Cairn {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(default)]
+    status: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn default(pool: sqlx::PgPool) {
+    sqlx::query(
+        "CREATE TABLE cairn (id INT PRIMARY KEY, name TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'pending')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let mut cairn = Cairn {
+        id: 0,
+        name: "summit".to_owned(),
+        status: String::new(),
+    };
+
+    cairn.create(&pool).await.unwrap();
+
+    assert_eq!(cairn.status, "pending");
+    assert_eq!(Cairn::read(&pool, &0).await.unwrap(), cairn);
+
+    cairn.status = "built".to_owned();
+    cairn.update(&pool).await.unwrap();
+
+    assert_eq!(Cairn::read(&pool, &0).await.unwrap().status, "built");
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "hive", schema = "public")]
+struct Hive {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(readonly)]
+    visits: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn readonly(pool: sqlx::PgPool) {
+    sqlx::query(
+        "CREATE TABLE hive (id INT PRIMARY KEY, name TEXT NOT NULL, visits INT NOT NULL DEFAULT 0)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let mut hive = Hive {
+        id: 0,
+        name: "linden".to_owned(),
+        visits: 42,
+    };
+
+    hive.create(&pool).await.unwrap();
+
+    assert_eq!(hive.visits, 0);
+
+    sqlx::query("UPDATE hive SET visits = visits + 1")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    hive.name = "acacia".to_owned();
+    hive.update(&pool).await.unwrap();
+    hive.upsert(&pool).await.unwrap();
+
+    let read = Hive::read(&pool, &0).await.unwrap();
+
+    assert_eq!(read.name, "acacia");
+    assert_eq!(read.visits, 1);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "marker", schema = "public")]
+struct Marker {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(immutable)]
+    placed_by: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn immutable(pool: sqlx::PgPool) {
+    Marker::create_table(&pool).await.unwrap();
+
+    let mut marker = Marker {
+        id: 0,
+        name: "ridge".to_owned(),
+        placed_by: "ranger".to_owned(),
+    };
+
+    marker.create(&pool).await.unwrap();
+
+    marker.name = "summit".to_owned();
+    marker.placed_by = "hiker".to_owned();
+
+    marker.update(&pool).await.unwrap();
+    marker.upsert(&pool).await.unwrap();
+
+    let read = Marker::read(&pool, &0).await.unwrap();
+
+    assert_eq!(read.name, "summit");
+    assert_eq!(read.placed_by, "ranger");
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "legacy", schema = "public", rename_all = "camelCase")]
+struct Legacy {
+    #[sql(pk)]
+    legacy_id: i32,
+    display_name: String,
+    #[sql(rename = "notes_txt")]
+    notes: String,
+}
+
+#[test]
+fn rename_all() {
+    assert_eq!(Legacy::PRIMARY_KEY.sql, "legacyId");
+    assert_eq!(Legacy::DATA_COLUMNS[0].sql, "displayName");
+    assert_eq!(Legacy::DATA_COLUMNS[0].field, "display_name");
+    assert_eq!(Legacy::DATA_COLUMNS[1].sql, "notes_txt");
+
+    assert_eq!(
+        Legacy::preview_read().unwrap().sql,
+        "SELECT\n  legacyId,\n  displayName,\n  notes_txt\nFROM\n  \"public\".\"legacy\"\nWHERE legacyId = $1"
+    );
+}
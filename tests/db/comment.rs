@@ -0,0 +1,79 @@
+use std::sync::Mutex;
+
+use atmosphere::hooks::{Hook, HookInput, HookStage};
+use atmosphere::prelude::*;
+use atmosphere::query::{Operation, Query};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "signal", schema = "public")]
+#[hooks(Narrow, Executed)]
+struct Signal {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+}
+
+static EXECUTED: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+/// Appends to selects after the comment has been added
+struct Narrow;
+
+impl Hook<Signal> for Narrow {
+    fn stage(&self) -> HookStage {
+        HookStage::PreBind
+    }
+
+    fn modify(&self, query: &mut Query<Signal>) -> Result<()> {
+        if query.op == Operation::Select {
+            query.and_where("name = ").push_bind("flag".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+struct Executed;
+
+#[async_trait]
+impl Hook<Signal> for Executed {
+    fn stage(&self) -> HookStage {
+        HookStage::PostExec
+    }
+
+    async fn apply(&self, ctx: &Query<Signal>, _: &mut HookInput<'_, Signal>) -> Result<()> {
+        EXECUTED.lock().unwrap().push(ctx.sql().to_owned());
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn comment(pool: sqlx::PgPool) {
+    Signal::create_table(&pool).await.unwrap();
+
+    let mut signal = Signal {
+        id: 0,
+        name: "flag".to_owned(),
+    };
+
+    atmosphere::comment::tagged([("app", "beacon")], async {
+        signal.create(&pool).await.unwrap();
+
+        atmosphere::comment::tagged([("route", "GET /signal")], async {
+            assert_eq!(Signal::read(&pool, &0).await.unwrap(), signal);
+        })
+        .await;
+    })
+    .await;
+
+    signal.update(&pool).await.unwrap();
+
+    let executed = EXECUTED.lock().unwrap();
+
+    assert!(executed[0].starts_with("INSERT INTO"));
+    assert!(executed[0].ends_with(" /*app='beacon'*/"));
+
+    assert!(executed[1].contains("AND name = $2"));
+    assert!(executed[1].ends_with(" /*app='beacon',route='GET%20%2Fsignal'*/"));
+
+    assert!(!executed[2].contains("/*"));
+}
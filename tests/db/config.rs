@@ -0,0 +1,35 @@
+use atmosphere::prelude::*;
+
+#[sqlx::test]
+async fn config(pool: sqlx::PgPool) {
+    let url = std::env::var("DATABASE_URL").unwrap();
+
+    let configured = Config::new(url)
+        .with_max_connections(2)
+        .with_after_connect_sql("SET application_name = 'configured'")
+        .connect()
+        .await
+        .unwrap();
+
+    assert_eq!(configured.options().get_max_connections(), 2);
+
+    let name: String = sqlx::query_scalar("SELECT current_setting('application_name')")
+        .fetch_one(&configured)
+        .await
+        .unwrap();
+
+    assert_eq!(name, "configured");
+
+    // the test pool is not affected
+    let name: String = sqlx::query_scalar("SELECT current_setting('application_name')")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    assert_ne!(name, "configured");
+
+    assert!(matches!(
+        Config::new("not a url").connect().await,
+        Err(Error::Query(_))
+    ));
+}
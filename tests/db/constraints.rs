@@ -0,0 +1,116 @@
+use atmosphere::prelude::*;
+use atmosphere::query::{QueryError, ViolationError};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "station", schema = "public", unique(name, location))]
+struct Station {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+    capacity: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn unique(pool: sqlx::PgPool) {
+    assert_eq!(
+        Station::UNIQUE,
+        [UniqueConstraint::new(&["name", "location"])]
+    );
+
+    assert!(Station::create_table_sql().contains("UNIQUE (name, location)"));
+
+    Station::create_table(&pool).await.unwrap();
+
+    let mut station = Station {
+        id: 1,
+        name: "central".to_owned(),
+        location: "north".to_owned(),
+        capacity: 4,
+    };
+
+    station.create(&pool).await.unwrap();
+
+    assert_eq!(
+        Station::find_by_name_and_location(&pool, &station.name, &station.location)
+            .await
+            .unwrap(),
+        Some(station.clone())
+    );
+
+    assert_eq!(
+        Station::find_by_name_and_location(&pool, &station.name, &"south".to_owned())
+            .await
+            .unwrap(),
+        None
+    );
+
+    let mut duplicate = Station {
+        id: 2,
+        capacity: 8,
+        ..station.clone()
+    };
+
+    assert!(duplicate.create(&pool).await.is_err());
+
+    // the row of the constraint is updated, its primary key is kept
+    duplicate
+        .upsert_on(&pool, &Station::UNIQUE[0])
+        .await
+        .unwrap();
+
+    assert_eq!(Station::read(&pool, &1).await.unwrap().capacity, 8);
+    assert!(Station::read(&pool, &2).await.is_err());
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "booking", schema = "public", check = "starts_at < ends_at")]
+struct Booking {
+    #[sql(pk)]
+    id: i32,
+    #[sql(check = "price > 0")]
+    price: i64,
+    starts_at: i32,
+    ends_at: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn check(pool: sqlx::PgPool) {
+    assert_eq!(Booking::CHECKS, ["starts_at < ends_at"]);
+    assert_eq!(Booking::DATA_COLUMNS[0].check, Some("price > 0"));
+
+    Booking::create_table(&pool).await.unwrap();
+
+    let mut booking = Booking {
+        id: 1,
+        price: 0,
+        starts_at: 8,
+        ends_at: 10,
+    };
+
+    let err = booking.create(&pool).await.unwrap_err();
+
+    assert!(matches!(
+        err.query(),
+        Some(QueryError::Violation(ViolationError::Check {
+            column: Some("price"),
+            ..
+        }))
+    ));
+
+    booking.price = 100;
+    booking.ends_at = 6;
+
+    let err = booking.create(&pool).await.unwrap_err();
+
+    assert!(matches!(
+        err.query(),
+        Some(QueryError::Violation(ViolationError::Check {
+            column: None,
+            ..
+        }))
+    ));
+
+    booking.ends_at = 12;
+    booking.create(&pool).await.unwrap();
+}
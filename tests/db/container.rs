@@ -0,0 +1,26 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[tokio::test]
+#[ignore = "requires a docker daemon"]
+async fn container() {
+    let db = atmosphere::testing::container(&sqlx::migrate!("tests/db/migrations")).await;
+
+    atmosphere::testing::create(
+        db.pool(),
+        Forest {
+            id: 0,
+            name: "grunewald".to_owned(),
+            location: "berlin".to_owned(),
+        },
+    )
+    .await;
+}
@@ -1,4 +1,5 @@
 use atmosphere::prelude::*;
+use atmosphere::query::Operation;
 use atmosphere_core::Table;
 
 #[derive(Schema, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
@@ -145,3 +146,268 @@ async fn delete(pool: sqlx::PgPool) {
 
     atmosphere::testing::delete(&pool, Tree { id: 0, forest: 99 }).await;
 }
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn roundtrip(pool: sqlx::PgPool) {
+    // a deterministic stand-in for a property based generator, repeating primary keys
+    let forests = (0..32).map(|n| Forest {
+        id: n % 8,
+        name: "🌲".repeat(n as usize),
+        location: format!("{:x}", n * 7919),
+    });
+
+    atmosphere::testing::roundtrip(&pool, forests).await;
+
+    assert!(Forest::read_all(&pool).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn batch(pool: sqlx::PgPool) {
+    for id in 0..5 {
+        Forest {
+            id,
+            name: format!("forest {id}"),
+            location: "berlin".to_owned(),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    // split the batches into several statements
+    let remaining = atmosphere::query::with_batch_size(2, async {
+        let mut found = Forest::find_many(&pool, &[4, 0, 2, 42, 1, 3])
+            .await
+            .unwrap();
+        found.sort();
+
+        assert_eq!(
+            found.iter().map(|forest| forest.id).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+
+        let deleted = Forest::delete_many(&pool, &[0, 1, 2, 42]).await.unwrap();
+
+        assert_eq!(deleted.rows_affected, 3);
+
+        Forest::find_many(&pool, &[0, 1, 2, 3, 4]).await.unwrap()
+    })
+    .await;
+
+    assert_eq!(remaining.len(), 2);
+    assert!(Forest::find_many(&pool, &[]).await.unwrap().is_empty());
+}
+
+/// Subscribed to by a single test, as change events are published per type
+#[derive(Schema, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Grove {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn batch_changes(pool: sqlx::PgPool) {
+    for id in 0..5 {
+        Grove {
+            id,
+            name: format!("grove {id}"),
+            location: "berlin".to_owned(),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    let mut changes = atmosphere::changes::<Grove>();
+
+    atmosphere::query::with_batch_size(2, async {
+        let deleted = Grove::delete_many(&pool, &[0, 1, 2, 42]).await.unwrap();
+        assert_eq!(deleted.rows_affected, 3);
+    })
+    .await;
+
+    let mut pks = vec![];
+
+    for _ in 0..3 {
+        let event = changes.recv().await.unwrap();
+        assert_eq!(event.op, Operation::Delete);
+        assert_eq!(event.row, None);
+        pks.push(event.pk);
+    }
+
+    pks.sort();
+    assert_eq!(pks, vec![0, 1, 2]);
+
+    assert!(changes.try_recv().is_err());
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[table(name = "account", schema = "public")]
+struct Account {
+    #[sql(pk)]
+    id: i32,
+    org: i32,
+    handle: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[table(name = "posting", schema = "public")]
+struct Posting {
+    #[sql(pk)]
+    id: i32,
+    org: i32,
+    #[sql(fk -> Account, rename = "account_id")]
+    account: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn batch_atomic(pool: sqlx::PgPool) {
+    for id in 0..4 {
+        Account {
+            id,
+            org: 0,
+            handle: format!("account {id}"),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    // the posting keeps the last account from being deleted
+    Posting {
+        id: 0,
+        org: 0,
+        account: 3,
+    }
+    .create(&pool)
+    .await
+    .unwrap();
+
+    atmosphere::query::with_batch_size(2, async {
+        Account::delete_many(&pool, &[0, 1, 2, 3])
+            .await
+            .unwrap_err();
+
+        // the first chunk is rolled back along with the failing one
+        assert_eq!(Account::read_all(&pool).await.unwrap().len(), 4);
+
+        // within a transaction, only the savepoint of the batch is rolled back
+        atmosphere::transaction(&pool, |conn| {
+            Box::pin(async move {
+                Account::delete_many(&mut *conn, &[0, 1, 2, 3])
+                    .await
+                    .unwrap_err();
+
+                let deleted = Account::delete_many(&mut *conn, &[0, 1, 2]).await?;
+                assert_eq!(deleted.rows_affected, 3);
+
+                Ok::<_, atmosphere::Error>(())
+            })
+        })
+        .await
+        .unwrap();
+    })
+    .await;
+
+    assert_eq!(Account::read_all(&pool).await.unwrap().len(), 1);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn error_context(pool: sqlx::PgPool) {
+    use std::error::Error as _;
+
+    use atmosphere::query::{Cardinality, Operation, QueryContext, QueryError, ViolationError};
+
+    let err = Forest::read(&pool, &404).await.unwrap_err();
+
+    assert_eq!(
+        err.context(),
+        Some(&QueryContext {
+            table: "forest",
+            op: Operation::Select,
+            cardinality: Cardinality::One,
+            statement: None,
+        })
+    );
+    assert!(matches!(err.query(), Some(QueryError::NotFound(_))));
+    assert_eq!(err.to_string(), "select (one) on `forest` failed");
+
+    let mut tree = Tree { id: 0, forest: 404 };
+
+    let err = tree.create(&pool).await.unwrap_err();
+
+    assert_eq!(err.context().map(|c| c.op), Some(Operation::Insert));
+    assert_eq!(err.context().map(|c| c.table), Some("tree"));
+    assert!(matches!(
+        err.query(),
+        Some(QueryError::Violation(ViolationError::ForeignKey {
+            column: Some("forest_id"),
+            ..
+        }))
+    ));
+    assert_eq!(
+        err.source().unwrap().source().unwrap().to_string(),
+        "foreign key violation of `forest_id`"
+    );
+
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    forest.create(&pool).await.unwrap();
+
+    let err = forest.create(&pool).await.unwrap_err();
+
+    assert!(matches!(
+        err.query(),
+        Some(QueryError::Violation(ViolationError::Unique {
+            column: Some("id"),
+            ..
+        }))
+    ));
+
+    // statements are only included on request
+    let err = atmosphere::query::with_statements(true, Forest::read(&pool, &404))
+        .await
+        .unwrap_err();
+
+    let statement = err.context().unwrap().statement.as_ref().unwrap();
+
+    assert!(statement.sql.starts_with("SELECT"));
+    assert_eq!(statement.bindings, ["id"]);
+    assert_eq!(
+        err.to_string(),
+        format!("select (one) on `forest` failed:\n{statement}")
+    );
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn result_ext(pool: sqlx::PgPool) {
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    assert_eq!(Forest::read(&pool, &0).await.optional().unwrap(), None);
+
+    forest.create(&pool).await.created().unwrap();
+
+    assert_eq!(
+        Forest::read(&pool, &0).await.optional().unwrap(),
+        Some(forest.clone())
+    );
+
+    // other errors are kept
+    assert!(forest.create(&pool).await.optional().is_err());
+
+    assert!(matches!(
+        Forest::delete_by(&pool, &404).await.created(),
+        Err(Error::Affected(0))
+    ));
+}
@@ -0,0 +1,63 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public", database = "analytics")]
+struct Survey {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn databases(pool: sqlx::PgPool) {
+    let databases = Databases::new(pool.clone());
+
+    // an unregistered database is an error instead of falling back to the default one
+    assert!(matches!(
+        databases.of::<Survey>(),
+        Err(Error::Database("analytics"))
+    ));
+
+    let analytics = sqlx::PgPool::connect_with((*pool.connect_options()).clone())
+        .await
+        .unwrap();
+
+    let databases = databases.with("analytics", analytics);
+
+    assert!(std::ptr::eq(
+        databases.of::<Forest>().unwrap(),
+        databases.default_pool()
+    ));
+    assert!(std::ptr::eq(
+        databases.of::<Survey>().unwrap(),
+        databases.get("analytics").unwrap()
+    ));
+
+    let mut survey = Survey {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    survey
+        .create(databases.of::<Survey>().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Survey::read(databases.of::<Survey>().unwrap(), &0)
+            .await
+            .unwrap(),
+        survey
+    );
+}
@@ -0,0 +1,187 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "grove", schema = "public")]
+struct Grove {
+    #[sql(pk, auto)]
+    id: i32,
+    #[sql(unique)]
+    name: String,
+    note: Option<String>,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "oak", schema = "public")]
+struct Oak {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Grove, rename = "grove_id", on_delete = "cascade")]
+    grove: i32,
+    age: i16,
+}
+
+#[test]
+fn create_table_sql() {
+    assert_eq!(
+        Oak::create_table_sql(),
+        "CREATE TABLE IF NOT EXISTS \"public\".\"oak\" (\n  id INT4 NOT NULL,\n  grove_id INT4 NOT NULL,\n  age INT2 NOT NULL,\n  PRIMARY KEY (id),\n  FOREIGN KEY (grove_id) REFERENCES \"public\".\"grove\" (id) ON DELETE CASCADE ON UPDATE NO ACTION\n)"
+    );
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn create_table(pool: sqlx::PgPool) {
+    Grove::create_table(&pool).await.unwrap();
+    Oak::create_table(&pool).await.unwrap();
+
+    // existing tables are left as they are
+    Grove::create_table(&pool).await.unwrap();
+
+    let mut grove = Grove {
+        id: 0,
+        name: "grunewald".to_owned(),
+        note: None,
+    };
+
+    grove.create(&pool).await.unwrap();
+
+    assert_eq!(Grove::read(&pool, &grove.id).await.unwrap(), grove);
+
+    let mut oak = Oak {
+        id: 0,
+        grove: grove.id,
+        age: 120,
+    };
+
+    oak.create(&pool).await.unwrap();
+
+    // the unique constraint is enforced
+    let mut duplicate = Grove {
+        id: 0,
+        name: "grunewald".to_owned(),
+        note: Some("duplicate".to_owned()),
+    };
+
+    assert!(duplicate.create(&pool).await.is_err());
+
+    // as well as the foreign key
+    let mut orphan = Oak {
+        id: 1,
+        grove: grove.id + 1,
+        age: 80,
+    };
+
+    assert!(orphan.create(&pool).await.is_err());
+
+    grove.delete(&pool).await.unwrap();
+
+    assert_eq!(Oak::find(&pool, &0).await.unwrap(), None);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "birch", schema = "public", index(grove, height, desc))]
+struct Birch {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Grove, rename = "grove_id")]
+    grove: i32,
+    #[sql(index)]
+    height: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn indexes(pool: sqlx::PgPool) {
+    const INDEXES: &[Index] = &[
+        Index::new("birch_height_idx", &[IndexColumn::asc("height")]),
+        Index::new(
+            "birch_grove_id_height_idx",
+            &[IndexColumn::asc("grove_id"), IndexColumn::desc("height")],
+        ),
+    ];
+
+    assert_eq!(Birch::INDEXES, INDEXES);
+
+    assert!(Birch::create_table_sql().ends_with(
+        ";\nCREATE INDEX IF NOT EXISTS birch_height_idx ON \"public\".\"birch\" (height);\nCREATE INDEX IF NOT EXISTS birch_grove_id_height_idx ON \"public\".\"birch\" (grove_id, height DESC)"
+    ));
+
+    Grove::create_table(&pool).await.unwrap();
+    Birch::create_table(&pool).await.unwrap();
+
+    // existing indexes are left as they are
+    Birch::create_table(&pool).await.unwrap();
+
+    let report = atmosphere::validate_schema::<(Grove, Birch)>(&pool)
+        .await
+        .unwrap();
+
+    assert!(report.is_valid(), "{report}");
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "maple", schema = "public", comment = "Maples of a grove")]
+struct Maple {
+    #[sql(pk, comment = "The tag number")]
+    id: i32,
+    #[sql(comment = "Tapped for syrup since ('yyyy')")]
+    tapped: Option<i32>,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn comments(pool: sqlx::PgPool) {
+    assert_eq!(Maple::COMMENT, Some("Maples of a grove"));
+
+    assert!(Maple::create_table_sql().ends_with(
+        ";\nCOMMENT ON TABLE \"public\".\"maple\" IS 'Maples of a grove';\nCOMMENT ON COLUMN \"public\".\"maple\".id IS 'The tag number';\nCOMMENT ON COLUMN \"public\".\"maple\".tapped IS 'Tapped for syrup since (''yyyy'')'"
+    ));
+
+    Maple::create_table(&pool).await.unwrap();
+
+    let (table, id, tapped): (Option<String>, Option<String>, Option<String>) = sqlx::query_as(
+        "SELECT obj_description('public.maple'::regclass, 'pg_class'), col_description('public.maple'::regclass, 1), col_description('public.maple'::regclass, 2)",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(table.as_deref(), Some("Maples of a grove"));
+    assert_eq!(id.as_deref(), Some("The tag number"));
+    assert_eq!(tapped.as_deref(), Some("Tapped for syrup since ('yyyy')"));
+}
+
+#[test]
+fn sql_snapshot() {
+    let snapshot = atmosphere::testing::sql_snapshot::<Grove>();
+
+    let operations: Vec<&str> = snapshot
+        .lines()
+        .filter_map(|line| line.strip_prefix("-- "))
+        .collect();
+
+    assert_eq!(
+        operations,
+        vec![
+            "select",
+            "select_all",
+            "insert",
+            "update",
+            "upsert",
+            "delete",
+            "find_by_name",
+            "create_table"
+        ]
+    );
+
+    assert!(snapshot.contains(
+        "-- find_by_name\nSELECT\n  id,\n  name,\n  note\nFROM\n  \"public\".\"grove\"\nWHERE name = $1\n"
+    ));
+    assert!(snapshot.ends_with(&format!("-- create_table\n{}\n", Grove::create_table_sql())));
+
+    // deterministic
+    assert_eq!(snapshot, atmosphere::testing::sql_snapshot::<Grove>());
+
+    let oak = atmosphere::testing::sql_snapshot::<Oak>();
+
+    assert!(
+        oak.contains("-- delete_by_grove\nDELETE FROM \"public\".\"oak\" WHERE grove_id = $1\n")
+    );
+}
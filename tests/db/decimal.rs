@@ -0,0 +1,111 @@
+use atmosphere::prelude::*;
+use sqlx::types::Json;
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal {
+    use std::str::FromStr;
+
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use sqlx::types::Decimal;
+
+    #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    struct Line {
+        item: String,
+        amount: Decimal,
+    }
+
+    #[derive(Schema, Debug, PartialEq, Eq, Clone)]
+    #[table(name = "invoice", schema = "public")]
+    struct Invoice {
+        #[sql(pk)]
+        id: i32,
+        #[sql(unique)]
+        total: Decimal,
+        rate: Option<Decimal>,
+        #[sql(json)]
+        lines: Json<Vec<Line>>,
+    }
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[sqlx::test(migrations = "tests/db/migrations")]
+    async fn decimals(pool: sqlx::PgPool) {
+        let mut invoice = Invoice {
+            id: 0,
+            total: decimal("1234567890.12"),
+            rate: Some(decimal("0.000000000000000001")),
+            lines: Json(vec![Line {
+                item: "timber".to_owned(),
+                amount: decimal("0.10"),
+            }]),
+        };
+
+        atmosphere::testing::create(&pool, invoice.clone()).await;
+
+        assert_eq!(
+            Invoice::find_by_total(&pool, &decimal("1234567890.12"))
+                .await
+                .unwrap(),
+            Some(invoice.clone())
+        );
+
+        // values are rounded to the scale of the column
+        invoice.total = decimal("0.125");
+        invoice.rate = None;
+        invoice.update(&pool).await.unwrap();
+
+        let read = Invoice::read(&pool, &0).await.unwrap();
+
+        assert_eq!(read.total, decimal("0.13"));
+        assert_eq!(read.rate, None);
+        assert_eq!(read.lines, invoice.lines);
+
+        assert_eq!(Invoice::DATA_COLUMNS[0].ty.unwrap().name(), "NUMERIC");
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal {
+    use std::str::FromStr;
+
+    use super::*;
+    use sqlx::types::{BigDecimal, JsonValue};
+
+    #[derive(Schema, Debug, PartialEq, Eq, Clone)]
+    #[table(name = "invoice", schema = "public")]
+    struct Invoice {
+        #[sql(pk)]
+        id: i32,
+        #[sql(unique)]
+        total: BigDecimal,
+        rate: Option<BigDecimal>,
+        #[sql(json)]
+        lines: Json<JsonValue>,
+    }
+
+    fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[sqlx::test(migrations = "tests/db/migrations")]
+    async fn decimals(pool: sqlx::PgPool) {
+        let invoice = Invoice {
+            id: 0,
+            total: decimal("1234567890.12"),
+            rate: Some(decimal("3.141592653589793238462643383279502884197")),
+            lines: Json(JsonValue::Array(vec![])),
+        };
+
+        atmosphere::testing::create(&pool, invoice.clone()).await;
+
+        assert_eq!(
+            Invoice::find_by_total(&pool, &decimal("1234567890.12"))
+                .await
+                .unwrap(),
+            Some(invoice)
+        );
+    }
+}
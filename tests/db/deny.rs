@@ -0,0 +1,24 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "ledger", schema = "public", deny(update, delete))]
+struct Ledger {
+    #[sql(pk)]
+    id: i32,
+    org: i32,
+    amount: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn append_only(pool: sqlx::PgPool) {
+    let mut entry = Ledger {
+        id: 0,
+        org: 1,
+        amount: 100,
+    };
+
+    entry.create(&pool).await.unwrap();
+
+    assert_eq!(Ledger::read(&pool, &0).await.unwrap(), entry);
+    assert_eq!(Ledger::read_all(&pool).await.unwrap(), vec![entry]);
+}
@@ -0,0 +1,129 @@
+use atmosphere::{
+    column::ReferentialAction,
+    describe::{ColumnDescription, ColumnKind, Reference},
+    prelude::*,
+};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk, auto)]
+    id: i32,
+    #[sql(unique)]
+    name: String,
+    location: Option<String>,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "tree", schema = "public", comment = "Trees of a forest")]
+struct Tree {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id", on_delete = "cascade")]
+    forest: i32,
+    #[sql(comment = "Species' common names")]
+    tags: Vec<String>,
+}
+
+#[test]
+fn mermaid() {
+    assert_eq!(
+        atmosphere::describe::mermaid::<(Forest, Tree)>(),
+        r#"erDiagram
+    forest {
+        INT4 id PK
+        TEXT name UK
+        TEXT location "nullable"
+    }
+    tree {
+        INT4 id PK
+        INT4 forest_id FK
+        TEXT[] tags
+    }
+    forest ||--o{ tree : "forest_id"
+"#
+    );
+}
+
+#[test]
+fn dbml() {
+    assert_eq!(
+        atmosphere::describe::dbml::<(Forest, Tree)>(),
+        r#"Table public.forest {
+  id INT4 [pk, increment]
+  name TEXT [not null, unique]
+  location TEXT
+}
+
+Table public.tree {
+  id INT4 [pk]
+  forest_id INT4 [not null]
+  tags "TEXT[]" [not null, note: 'Species\' common names']
+
+  Note: 'Trees of a forest'
+}
+
+Ref: public.tree.forest_id > public.forest.id [delete: cascade, update: no action]
+"#
+    );
+}
+
+#[test]
+fn describe() {
+    let description = Tree::describe();
+
+    assert_eq!(description.schema, "public");
+    assert_eq!(description.table, "tree");
+    assert_eq!(description.comment, Some("Trees of a forest"));
+
+    assert_eq!(
+        description.columns,
+        vec![
+            ColumnDescription {
+                field: "id",
+                name: "id",
+                kind: ColumnKind::PrimaryKey,
+                ty: Some("INT4".to_owned()),
+                nullable: false,
+                unique: false,
+                auto: false,
+                references: None,
+                comment: None,
+            },
+            ColumnDescription {
+                field: "forest",
+                name: "forest_id",
+                kind: ColumnKind::ForeignKey,
+                ty: Some("INT4".to_owned()),
+                nullable: false,
+                unique: false,
+                auto: false,
+                references: Some(Reference {
+                    schema: "public".to_owned(),
+                    table: "forest",
+                    column: "id",
+                    on_delete: ReferentialAction::Cascade,
+                    on_update: ReferentialAction::NoAction,
+                }),
+                comment: None,
+            },
+            ColumnDescription {
+                field: "tags",
+                name: "tags",
+                kind: ColumnKind::Data,
+                ty: Some("TEXT[]".to_owned()),
+                nullable: false,
+                unique: false,
+                auto: false,
+                references: None,
+                comment: Some("Species' common names"),
+            },
+        ]
+    );
+
+    // all derived tables are registered
+    let tables = atmosphere::describe::tables();
+
+    assert!(tables.contains(&Forest::describe()));
+    assert!(tables.contains(&description));
+}
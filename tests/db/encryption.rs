@@ -0,0 +1,66 @@
+use atmosphere::crypto::StaticKey;
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "patient", schema = "public")]
+struct Patient {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(encrypted)]
+    diagnosis: String,
+    #[sql(encrypted)]
+    notes: Option<String>,
+    #[sql(encrypted)]
+    scan: Vec<u8>,
+}
+
+// a single test, as the key provider is global
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn encrypted(pool: sqlx::PgPool) {
+    atmosphere::crypto::set_key_provider(StaticKey::new(7, [42; 32]));
+
+    Patient::create_table(&pool).await.unwrap();
+
+    let mut patient = Patient {
+        id: 0,
+        name: "ada".to_owned(),
+        diagnosis: "hypochondria".to_owned(),
+        notes: None,
+        scan: vec![1, 2, 3],
+    };
+
+    patient.create(&pool).await.unwrap();
+
+    assert_eq!(Patient::read(&pool, &0).await.unwrap(), patient);
+
+    patient.notes = Some("fine".to_owned());
+    patient.update(&pool).await.unwrap();
+
+    assert_eq!(
+        Patient::read_all(&pool).await.unwrap(),
+        vec![patient.clone()]
+    );
+
+    // the database only holds the ciphertext
+    let (diagnosis, notes): (Vec<u8>, Option<Vec<u8>>) =
+        sqlx::query_as("SELECT diagnosis, notes FROM public.patient")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    assert_eq!(&diagnosis[..4], &7u32.to_be_bytes());
+    assert!(!diagnosis.windows(12).any(|w| w == b"hypochondria"));
+    assert!(notes.is_some());
+
+    // values encrypted with an unknown key fail to decode
+    atmosphere::crypto::set_key_provider(StaticKey::new(8, [0; 32]));
+
+    assert!(Patient::read(&pool, &0).await.is_err());
+
+    // new values are encrypted using the current key
+    patient.id = 1;
+    patient.create(&pool).await.unwrap();
+
+    assert_eq!(Patient::read(&pool, &1).await.unwrap(), patient);
+}
@@ -0,0 +1,89 @@
+use atmosphere::prelude::*;
+
+#[derive(SqlEnum, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[sql(type_name = "season", rename_all = "snake_case")]
+enum Season {
+    Spring,
+    LateSummer,
+    #[sql(rename = "fall")]
+    Autumn,
+}
+
+#[derive(SqlEnum, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[sql(rename_all = "SCREAMING_SNAKE_CASE")]
+enum Canopy {
+    Open,
+    Dense,
+}
+
+#[derive(SqlEnum, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[sql(repr = i32)]
+enum Stage {
+    Seedling,
+    Sapling,
+    Mature = 10,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "meadow", schema = "public")]
+struct Meadow {
+    #[sql(pk)]
+    id: i32,
+    season: Season,
+    canopy: Canopy,
+    stage: Stage,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn enums(pool: sqlx::PgPool) {
+    sqlx::query("CREATE TYPE season AS ENUM ('spring', 'late_summer', 'fall')")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    sqlx::query(
+        "CREATE TABLE meadow (id INT PRIMARY KEY, season season NOT NULL, canopy TEXT NOT NULL, stage INT NOT NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let mut meadow = Meadow {
+        id: 0,
+        season: Season::LateSummer,
+        canopy: Canopy::Dense,
+        stage: Stage::Mature,
+    };
+
+    meadow.create(&pool).await.unwrap();
+
+    assert_eq!(Meadow::read(&pool, &0).await.unwrap(), meadow);
+
+    let (season, canopy, stage): (String, String, i32) =
+        sqlx::query_as("SELECT season::TEXT, canopy, stage FROM meadow")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    assert_eq!(
+        (season.as_str(), canopy.as_str(), stage),
+        ("late_summer", "DENSE", 10)
+    );
+
+    meadow.season = Season::Autumn;
+    meadow.stage = Stage::Sapling;
+    meadow.update(&pool).await.unwrap();
+
+    assert_eq!(Meadow::read(&pool, &0).await.unwrap(), meadow);
+
+    sqlx::query("UPDATE meadow SET stage = 7")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let err = Meadow::read(&pool, &0).await.unwrap_err();
+    assert!(err.to_string().contains("failed"));
+}
@@ -0,0 +1,55 @@
+use atmosphere::{extract::ByPk, prelude::*};
+use axum::{body::Body, http::Request, http::StatusCode, routing::get, Router};
+use tower::ServiceExt;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+async fn show(ByPk(forest): ByPk<Forest>) -> String {
+    forest.name
+}
+
+async fn get_status(app: &Router, uri: &str) -> StatusCode {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn by_pk(pool: sqlx::PgPool) {
+    Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    }
+    .create(&pool)
+    .await
+    .unwrap();
+
+    let app = Router::new()
+        .route("/forests/{id}", get(show))
+        .with_state(pool);
+
+    let request = Request::get("/forests/0").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    assert_eq!(body, "grunewald");
+
+    assert_eq!(get_status(&app, "/forests/1").await, StatusCode::NOT_FOUND);
+    assert_eq!(
+        get_status(&app, "/forests/ada").await,
+        StatusCode::BAD_REQUEST
+    );
+}
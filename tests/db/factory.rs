@@ -0,0 +1,64 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public", factory)]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "tree", schema = "public", factory)]
+struct Tree {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id")]
+    forest: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn factory(pool: sqlx::PgPool) {
+    let forest = Forest::factory()
+        .with(|f| f.location = "berlin".to_owned())
+        .create(&pool)
+        .await
+        .unwrap();
+
+    #[cfg(not(feature = "fake"))]
+    assert_eq!(forest.name, format!("name-{}", forest.id));
+    #[cfg(feature = "fake")]
+    assert!(forest.name.ends_with(&format!("-{}", forest.id)));
+    assert_eq!(Forest::read(&pool, &forest.id).await.unwrap(), forest);
+
+    // parents are created for foreign keys
+    let trees = Tree::factory().create_many(&pool, 3).await.unwrap();
+
+    for tree in &trees {
+        assert_eq!(
+            tree.forest(&pool).await.unwrap().id,
+            tree.forest,
+            "{tree:?} references a missing forest"
+        );
+    }
+
+    assert_eq!(Forest::read_all(&pool).await.unwrap().len(), 4);
+
+    let id = forest.id;
+
+    let tree = Tree::factory()
+        .without_parents()
+        .with(move |t| t.forest = id)
+        .create(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(tree.forest, forest.id);
+    assert_eq!(Forest::read_all(&pool).await.unwrap().len(), 4);
+
+    // built rows are not created
+    let built = Forest::factory().build();
+
+    assert!(Forest::find(&pool, &built.id).await.unwrap().is_none());
+}
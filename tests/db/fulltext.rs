@@ -0,0 +1,63 @@
+use atmosphere::{fulltext::Syntax, prelude::*};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "article", schema = "public")]
+struct Article {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fulltext = "english")]
+    title: String,
+    #[sql(fulltext = "english")]
+    body: Option<String>,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn fulltext(pool: sqlx::PgPool) {
+    Article::create_table(&pool).await.unwrap();
+
+    let (using,): (String,) =
+        sqlx::query_as("SELECT indexdef FROM pg_indexes WHERE indexname = 'article_fulltext_idx'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    assert!(using.contains("USING gin"));
+
+    let articles = [
+        (0, "Oaks of the grunewald", Some("The oak trees are old.")),
+        (1, "Birches", Some("Birches and a single oak.")),
+        (2, "Pines", None),
+    ];
+
+    for (id, title, body) in articles {
+        let mut article = Article {
+            id,
+            title: title.to_owned(),
+            body: body.map(str::to_owned),
+        };
+
+        article.create(&pool).await.unwrap();
+    }
+
+    let ids = |articles: Vec<Article>| articles.iter().map(|a| a.id).collect::<Vec<_>>();
+
+    // stemmed, ranked by the number of matches
+    assert_eq!(
+        ids(Article::search(&pool, "oak").await.unwrap()),
+        vec![0, 1]
+    );
+    assert_eq!(ids(Article::search(&pool, "pine").await.unwrap()), vec![2]);
+    assert!(Article::search(&pool, "maple").await.unwrap().is_empty());
+
+    let raw = Article::search_with(&pool, "oak & !birch", Syntax::Raw)
+        .await
+        .unwrap();
+
+    assert_eq!(ids(raw), vec![0]);
+
+    let web = Article::search_with(&pool, "oak -grunewald", Syntax::WebSearch)
+        .await
+        .unwrap();
+
+    assert_eq!(ids(web), vec![1]);
+}
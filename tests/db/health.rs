@@ -0,0 +1,30 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Drifted {
+    #[sql(pk)]
+    id: i32,
+    area: i64,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn health(pool: sqlx::PgPool) {
+    let health = atmosphere::health(&pool).await.unwrap();
+
+    assert!(health.pool_in_use + health.pool_idle >= 1);
+
+    Forest::ping(&pool).await.unwrap();
+
+    // a missing column fails the ping
+    assert!(Drifted::ping(&pool).await.is_err());
+}
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use atmosphere::prelude::*;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "price", schema = "public", history)]
+struct Price {
+    #[sql(pk)]
+    id: i32,
+    amount: i64,
+    #[sql(timestamp = created)]
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "lodge", schema = "public", history)]
+#[audit]
+struct Lodge {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+}
+
+/// The current time, after waiting for the clock to pass the previous statement
+async fn now() -> DateTime<Utc> {
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let now = Utc::now();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    now
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn history(pool: sqlx::PgPool) {
+    Price::create_table(&pool).await.unwrap();
+
+    sqlx::query(&atmosphere::history::table_sql::<Price>())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let before = now().await;
+
+    let mut price = Price {
+        id: 0,
+        amount: 100,
+        created_at: DateTime::<Utc>::default(),
+    };
+
+    price.create(&pool).await.unwrap();
+
+    let created = price.created_at;
+
+    assert!(Price::history(&pool, &0).await.unwrap().is_empty());
+    assert_eq!(Price::as_of(&pool, &0, before).await.unwrap(), None);
+
+    let first = now().await;
+
+    price.amount = 200;
+    price.update(&pool).await.unwrap();
+
+    let second = now().await;
+
+    price.amount = 300;
+    price.upsert(&pool).await.unwrap();
+
+    let amount = |price: Option<Price>| price.map(|price| price.amount);
+
+    assert_eq!(
+        amount(Price::as_of(&pool, &0, first).await.unwrap()),
+        Some(100)
+    );
+    assert_eq!(
+        amount(Price::as_of(&pool, &0, second).await.unwrap()),
+        Some(200)
+    );
+    assert_eq!(
+        amount(Price::as_of(&pool, &0, now().await).await.unwrap()),
+        Some(300)
+    );
+
+    let history = Price::history(&pool, &0).await.unwrap();
+
+    assert_eq!(
+        history.iter().map(|v| v.row.amount).collect::<Vec<_>>(),
+        vec![100, 200]
+    );
+
+    // versions are contiguous, the first one starts with the creation of the row
+    assert_eq!(
+        history[0].valid_from.map(|t| t.timestamp_micros()),
+        Some(created.timestamp_micros())
+    );
+    assert_eq!(history[1].valid_from, Some(history[0].valid_to));
+    assert!(history[0].valid_to > first && history[0].valid_to < second);
+
+    price.delete(&pool).await.unwrap();
+
+    assert_eq!(Price::as_of(&pool, &0, now().await).await.unwrap(), None);
+    assert_eq!(
+        amount(Price::as_of(&pool, &0, second).await.unwrap()),
+        Some(200)
+    );
+    assert_eq!(Price::history(&pool, &0).await.unwrap().len(), 3);
+
+    // rolled back changes leave no versions behind
+    let mut other = Price {
+        id: 1,
+        amount: 1,
+        created_at: DateTime::<Utc>::default(),
+    };
+
+    other.create(&pool).await.unwrap();
+
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            other.amount = 2;
+            other.update(&mut *conn).await?;
+            Err::<(), _>(atmosphere::Error::Other)
+        })
+    })
+    .await
+    .unwrap_err();
+
+    assert!(Price::history(&pool, &1).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn audited(pool: sqlx::PgPool) {
+    for sql in [
+        atmosphere::audit::table_sql::<Lodge>(),
+        atmosphere::history::table_sql::<Lodge>(),
+    ] {
+        sqlx::query(&sql).execute(&pool).await.unwrap();
+    }
+
+    let mut lodge = Lodge {
+        id: 0,
+        name: "lodge".to_owned(),
+    };
+
+    lodge.create(&pool).await.unwrap();
+
+    lodge.name = "hut".to_owned();
+    lodge.update(&pool).await.unwrap();
+
+    let (entries,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM public.lodge_audit")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(entries, 2);
+
+    let history = Lodge::history(&pool, &0).await.unwrap();
+
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].row.name, "lodge");
+    assert_eq!(history[0].valid_from, None);
+}
@@ -0,0 +1,482 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use atmosphere::hooks::{Hook, HookInput, HookStage, Hooks};
+use atmosphere::prelude::*;
+use atmosphere::query::{Cardinality, Lock, Operation, Query};
+use atmosphere::queue::Queue;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+#[hooks(Committed { stage: HookStage::PreCommit }, Committed { stage: HookStage::PostCommit })]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+static COMMITTED: Mutex<Vec<(HookStage, String)>> = Mutex::new(vec![]);
+
+struct Committed {
+    stage: HookStage,
+}
+
+#[async_trait]
+impl Hook<Forest> for Committed {
+    fn stage(&self) -> HookStage {
+        self.stage
+    }
+
+    async fn apply(&self, ctx: &Query<Forest>, _: &mut HookInput<'_, Forest>) -> Result<()> {
+        COMMITTED
+            .lock()
+            .unwrap()
+            .push((self.stage, ctx.sql().to_owned()));
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn commit(pool: sqlx::PgPool) {
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    // outside of a transaction no commit hooks run
+    forest.create(&pool).await.unwrap();
+    assert!(COMMITTED.lock().unwrap().is_empty());
+
+    // rolled back transactions don't run commit hooks
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            Forest::delete_by(&mut *conn, &0).await?;
+            Err::<(), _>(atmosphere::Error::Other)
+        })
+    })
+    .await
+    .unwrap_err();
+
+    assert!(COMMITTED.lock().unwrap().is_empty());
+
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            forest.name = "tegeler forst".to_owned();
+            forest.update(&mut *conn).await?;
+
+            assert!(COMMITTED.lock().unwrap().is_empty());
+
+            Forest::delete_by(&mut *conn, &0).await?;
+
+            Ok::<_, atmosphere::Error>(())
+        })
+    })
+    .await
+    .unwrap();
+
+    let committed = COMMITTED.lock().unwrap();
+    let stages: Vec<HookStage> = committed.iter().map(|(stage, _)| *stage).collect();
+
+    assert_eq!(
+        stages,
+        vec![
+            HookStage::PreCommit,
+            HookStage::PreCommit,
+            HookStage::PostCommit,
+            HookStage::PostCommit
+        ]
+    );
+
+    assert!(committed[0].1.starts_with("UPDATE"));
+    assert!(committed[1].1.starts_with("DELETE"));
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "ranger", schema = "public")]
+#[hooks(Failed)]
+struct Ranger {
+    #[sql(pk, auto)]
+    id: i32,
+    name: String,
+}
+
+static FAILED: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+struct Failed;
+
+#[async_trait]
+impl Hook<Ranger> for Failed {
+    fn stage(&self) -> HookStage {
+        HookStage::OnError
+    }
+
+    async fn apply(&self, _: &Query<Ranger>, input: &mut HookInput<'_, Ranger>) -> Result<()> {
+        let HookInput::Error(err) = input else {
+            panic!("on error hooks must receive the error");
+        };
+
+        FAILED.lock().unwrap().push(err.to_string());
+
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn error(pool: sqlx::PgPool) {
+    let mut ranger = Ranger {
+        id: 0,
+        name: "ranger".to_owned(),
+    };
+
+    ranger.create(&pool).await.unwrap();
+    Ranger::read(&pool, &ranger.id).await.unwrap();
+
+    assert!(FAILED.lock().unwrap().is_empty());
+
+    Ranger::read(&pool, &(ranger.id + 1)).await.unwrap_err();
+
+    assert_eq!(*FAILED.lock().unwrap(), vec!["not found".to_owned()]);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+#[hooks(Scoped)]
+struct Woodland {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+/// Restricts reads and deletes to woodlands in berlin
+struct Scoped;
+
+#[async_trait]
+impl Hook<Woodland> for Scoped {
+    fn stage(&self) -> HookStage {
+        HookStage::PreBind
+    }
+
+    fn modify(&self, query: &mut Query<Woodland>) -> Result<()> {
+        if matches!(query.op, Operation::Select | Operation::Delete)
+            && query.cardinality == Cardinality::One
+        {
+            query.and_where("location = ").push_bind("berlin");
+        }
+
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn modify(pool: sqlx::PgPool) {
+    let mut grunewald = Woodland {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    let mut sachsenwald = Woodland {
+        id: 1,
+        name: "sachsenwald".to_owned(),
+        location: "hamburg".to_owned(),
+    };
+
+    grunewald.create(&pool).await.unwrap();
+    sachsenwald.create(&pool).await.unwrap();
+
+    assert_eq!(
+        Woodland::find(&pool, &grunewald.id).await.unwrap(),
+        Some(grunewald.clone())
+    );
+    assert_eq!(Woodland::find(&pool, &sachsenwald.id).await.unwrap(), None);
+
+    let res = sachsenwald.delete(&pool).await.unwrap();
+    assert_eq!(res.rows_affected, 0);
+
+    let res = grunewald.delete(&pool).await.unwrap();
+    assert_eq!(res.rows_affected, 1);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+#[hooks(Berlin)]
+struct Grove {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+/// Restricts all reads to groves in berlin
+struct Berlin;
+
+impl Hook<Grove> for Berlin {
+    fn stage(&self) -> HookStage {
+        HookStage::PreBind
+    }
+
+    fn modify(&self, query: &mut Query<Grove>) -> Result<()> {
+        if query.op == Operation::Select {
+            query.and_where("location = ").push_bind("berlin");
+        }
+
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn and_where(pool: sqlx::PgPool) {
+    let mut grunewald = Grove {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    let mut sachsenwald = Grove {
+        id: 1,
+        name: "sachsenwald".to_owned(),
+        location: "hamburg".to_owned(),
+    };
+
+    grunewald.create(&pool).await.unwrap();
+    sachsenwald.create(&pool).await.unwrap();
+
+    // the query has no `WHERE` clause of its own
+    assert_eq!(
+        Grove::read_all(&pool).await.unwrap(),
+        vec![grunewald.clone()]
+    );
+
+    // the predicate precedes `FOR UPDATE`
+    let mut tx = pool.begin().await.unwrap();
+
+    let locked = Grove::read_for_update(&mut *tx, &grunewald.id, Lock::Wait)
+        .await
+        .unwrap();
+
+    assert_eq!(locked, grunewald);
+
+    Grove::read_for_update(&mut *tx, &sachsenwald.id, Lock::Wait)
+        .await
+        .unwrap_err();
+
+    tx.rollback().await.unwrap();
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "chore", schema = "public")]
+#[hooks(Outdoors)]
+struct Errand {
+    #[sql(pk)]
+    id: i32,
+    state: String,
+    name: String,
+}
+
+impl Queue for Errand {
+    type State = &'static str;
+
+    const STATE: &'static str = "state";
+
+    fn pending() -> &'static str {
+        "pending"
+    }
+
+    fn in_progress() -> &'static str {
+        "running"
+    }
+}
+
+/// Only lets errands outside of the house be claimed
+struct Outdoors;
+
+impl Hook<Errand> for Outdoors {
+    fn stage(&self) -> HookStage {
+        HookStage::PreBind
+    }
+
+    fn modify(&self, query: &mut Query<Errand>) -> Result<()> {
+        if query.op == Operation::Update {
+            query.and_where("name <> ").push_bind("water plants");
+        }
+
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn and_where_claim(pool: sqlx::PgPool) {
+    for (id, name) in ["water plants", "feed birds"].into_iter().enumerate() {
+        Errand {
+            id: id as i32,
+            state: "pending".to_owned(),
+            name: name.to_owned(),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    // the predicate narrows down the subquery selecting the claimed row
+    let claimed = Errand::claim_next(&pool).await.unwrap().unwrap();
+    assert_eq!(claimed.name, "feed birds");
+
+    assert!(Errand::claim_next(&pool).await.unwrap().is_none());
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "clearing", schema = "public")]
+#[hooks(Recorded on(create, delete), Recorded on(update))]
+struct Clearing {
+    #[sql(pk)]
+    id: i32,
+    forest_id: Option<i32>,
+}
+
+static RECORDED: Mutex<Vec<Operation>> = Mutex::new(vec![]);
+
+struct Recorded;
+
+#[async_trait]
+impl Hook<Clearing> for Recorded {
+    fn stage(&self) -> HookStage {
+        HookStage::PreExec
+    }
+
+    async fn apply(&self, ctx: &Query<Clearing>, _: &mut HookInput<'_, Clearing>) -> Result<()> {
+        RECORDED.lock().unwrap().push(ctx.op);
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn on(pool: sqlx::PgPool) {
+    let mut clearing = Clearing {
+        id: 0,
+        forest_id: None,
+    };
+
+    clearing.create(&pool).await.unwrap();
+    clearing.reload(&pool).await.unwrap();
+    clearing.update(&pool).await.unwrap();
+    clearing.upsert(&pool).await.unwrap();
+    clearing.delete(&pool).await.unwrap();
+
+    assert_eq!(
+        *RECORDED.lock().unwrap(),
+        vec![Operation::Insert, Operation::Update, Operation::Delete]
+    );
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "ranger", schema = "public")]
+struct Warden {
+    #[sql(pk, auto)]
+    id: i32,
+    name: String,
+}
+
+/// Counts the executed queries, holding its state at runtime
+struct Counter(Arc<AtomicUsize>);
+
+#[async_trait]
+impl Hook<Warden> for Counter {
+    fn stage(&self) -> HookStage {
+        HookStage::PostExec
+    }
+
+    async fn apply(&self, _: &Query<Warden>, _: &mut HookInput<'_, Warden>) -> Result<()> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn register(pool: sqlx::PgPool) {
+    let count = Arc::new(AtomicUsize::new(0));
+
+    Warden::register_hook(Arc::new(Counter(count.clone())));
+
+    let mut warden = Warden {
+        id: 0,
+        name: "warden".to_owned(),
+    };
+
+    warden.create(&pool).await.unwrap();
+    Warden::read(&pool, &warden.id).await.unwrap();
+
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "ranger", schema = "public")]
+struct Keeper {
+    #[sql(pk, auto)]
+    id: i32,
+    name: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn record(pool: sqlx::PgPool) {
+    use atmosphere::testing::hooks::RecordedInput;
+
+    let recording = atmosphere::testing::hooks::record::<Keeper>();
+
+    let mut keeper = Keeper {
+        id: 0,
+        name: "keeper".to_owned(),
+    };
+
+    keeper.create(&pool).await.unwrap();
+
+    let calls = recording.take();
+
+    assert_eq!(
+        calls.iter().map(|call| call.stage).collect::<Vec<_>>(),
+        vec![HookStage::PreBind, HookStage::PreExec, HookStage::PostExec]
+    );
+    assert!(calls.iter().all(|call| call.op == Operation::Insert));
+    assert_eq!(
+        calls[0].input,
+        RecordedInput::Row("Keeper { id: 0, name: \"keeper\" }".to_owned())
+    );
+    assert_eq!(calls[2].input, RecordedInput::QueryResult { ok: true });
+
+    Keeper::read(&pool, &(keeper.id + 1)).await.unwrap_err();
+
+    assert_eq!(
+        recording.stages(Operation::Select),
+        vec![
+            HookStage::PreBind,
+            HookStage::PreExec,
+            HookStage::PostExec,
+            HookStage::OnError
+        ]
+    );
+    assert_eq!(
+        recording.take().last().unwrap().input,
+        RecordedInput::Error("not found".to_owned())
+    );
+
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move { Keeper::delete_by(&mut *conn, &keeper.id).await })
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        recording.stages(Operation::Delete),
+        vec![
+            HookStage::PreBind,
+            HookStage::PreExec,
+            HookStage::PostExec,
+            HookStage::PreCommit,
+            HookStage::PostCommit
+        ]
+    );
+}
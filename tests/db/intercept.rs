@@ -0,0 +1,141 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use atmosphere::intercept::{Interceptor, Outcome, Statement};
+use atmosphere::prelude::*;
+use atmosphere::query::Operation;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "gauge", schema = "public")]
+struct Gauge {
+    #[sql(pk)]
+    id: i32,
+    #[sql(unique)]
+    name: String,
+}
+
+static CALLS: Mutex<Vec<String>> = Mutex::new(vec![]);
+static OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Hints selects, records outcomes and rejects statements while open
+struct Breaker;
+
+#[async_trait]
+impl Interceptor for Breaker {
+    fn rewrite(&self, statement: &Statement<'_>) -> Option<String> {
+        (statement.table == "gauge" && statement.op == Operation::Select)
+            .then(|| format!("/* hinted */ {}", statement.sql))
+    }
+
+    async fn before(&self, statement: &Statement<'_>) -> Result<()> {
+        if statement.table != "gauge" {
+            return Ok(());
+        }
+
+        if OPEN.load(Ordering::Relaxed) {
+            return Err(Error::Rejected("circuit open".to_owned()));
+        }
+
+        CALLS.lock().unwrap().push("before breaker".to_owned());
+
+        Ok(())
+    }
+
+    async fn after(&self, statement: &Statement<'_>, outcome: &Outcome<'_>) -> Result<()> {
+        if statement.table != "gauge" {
+            return Ok(());
+        }
+
+        CALLS.lock().unwrap().push(format!(
+            "after breaker {} {:?} {} {}",
+            statement.op.name(),
+            outcome.rows,
+            outcome.error.is_some(),
+            statement.sql.starts_with("/* hinted */"),
+        ));
+
+        Ok(())
+    }
+}
+
+/// Records the order interceptors run in
+struct Inner;
+
+#[async_trait]
+impl Interceptor for Inner {
+    async fn before(&self, statement: &Statement<'_>) -> Result<()> {
+        if statement.table == "gauge" {
+            CALLS.lock().unwrap().push("before inner".to_owned());
+        }
+
+        Ok(())
+    }
+
+    async fn after(&self, statement: &Statement<'_>, _: &Outcome<'_>) -> Result<()> {
+        if statement.table == "gauge" {
+            CALLS.lock().unwrap().push("after inner".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn intercept(pool: sqlx::PgPool) {
+    Gauge::create_table(&pool).await.unwrap();
+
+    atmosphere::intercept::install(Breaker);
+    atmosphere::intercept::install(Inner);
+
+    let gauge = Gauge {
+        id: 0,
+        name: "pressure".to_owned(),
+    }
+    .create(&pool)
+    .await;
+
+    assert!(gauge.is_ok());
+
+    // statements executed with and without hooks are intercepted
+    let gauge = Gauge::read(&pool, &0).await.unwrap();
+
+    assert_eq!(
+        Gauge::find_by_name(&pool, &gauge.name).await.unwrap(),
+        Some(gauge)
+    );
+
+    assert!(Gauge::read(&pool, &1).await.is_err());
+
+    OPEN.store(true, Ordering::Relaxed);
+
+    assert!(matches!(
+        Gauge::delete_by(&pool, &0).await,
+        Err(Error::Rejected(reason)) if reason == "circuit open"
+    ));
+
+    OPEN.store(false, Ordering::Relaxed);
+
+    assert!(Gauge::find(&pool, &0).await.unwrap().is_some());
+
+    let calls = CALLS.lock().unwrap();
+
+    assert_eq!(
+        calls[..8],
+        [
+            "before breaker",
+            "before inner",
+            "after inner",
+            "after breaker insert Some(1) false false",
+            "before breaker",
+            "before inner",
+            "after inner",
+            "after breaker select Some(1) false true",
+        ]
+    );
+
+    assert_eq!(calls[11], "after breaker select Some(1) false true");
+    assert_eq!(calls[15], "after breaker select None true true");
+    assert_eq!(calls.len(), 20);
+}
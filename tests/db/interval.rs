@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use atmosphere::{interval::Interval, prelude::*};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "retention", schema = "public")]
+struct Retention {
+    #[sql(pk)]
+    id: i32,
+    ttl: Interval,
+    grace: Option<Interval>,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn interval(pool: sqlx::PgPool) {
+    Retention::create_table(&pool).await.unwrap();
+
+    let ttls = [
+        (0, Interval::from_secs(7 * 24 * 60 * 60), None),
+        (
+            1,
+            Interval::from_millis(1_500),
+            Some(Interval::from_secs(60)),
+        ),
+    ];
+
+    for (id, ttl, grace) in ttls {
+        let mut retention = Retention { id, ttl, grace };
+
+        retention.create(&pool).await.unwrap();
+    }
+
+    let retention = Retention::read(&pool, &1).await.unwrap();
+
+    assert_eq!(retention.ttl.duration(), Duration::from_millis(1_500));
+    assert_eq!(retention.grace, Some(Interval::from_secs(60)));
+
+    // compared by their duration within the database
+    let (longer,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM public.retention WHERE ttl > $1")
+        .bind(Interval::from_secs(60 * 60))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(longer, 1);
+
+    // days are read as 24 hours
+    sqlx::query("UPDATE public.retention SET ttl = '1 day 1 second' WHERE id = 0")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Retention::read(&pool, &0).await.unwrap().ttl,
+        Interval::from_secs(86_401)
+    );
+
+    // months have no fixed duration
+    sqlx::query("UPDATE public.retention SET ttl = '1 month' WHERE id = 0")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert!(Retention::read(&pool, &0).await.is_err());
+}
@@ -0,0 +1,55 @@
+use atmosphere::prelude::*;
+use sqlx::types::{Json, JsonValue};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "survey", schema = "public")]
+struct Survey {
+    #[sql(pk)]
+    id: i32,
+    #[sql(json)]
+    answers: Json<JsonValue>,
+}
+
+fn json(value: &str) -> JsonValue {
+    value.parse().unwrap()
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn json_columns(pool: sqlx::PgPool) {
+    for (id, answers) in [(0, r#"{"color": "green"}"#), (1, r#"{"color": "blue"}"#)] {
+        let mut survey = Survey {
+            id,
+            answers: Json(json(answers)),
+        };
+
+        survey.create(&pool).await.unwrap();
+    }
+
+    let merged = Survey::merge_answers(&pool, &0, &json(r#"{"trees": {"oak": 3}}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(merged.rows_affected, 1);
+
+    Survey::set_answers_path(&pool, &0, &["trees", "birch"], &json("5"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Survey::read(&pool, &0).await.unwrap().answers.0,
+        json(r#"{"color": "green", "trees": {"oak": 3, "birch": 5}}"#)
+    );
+
+    let found = Survey::find_by_answers_contains(&pool, &json(r#"{"trees": {"oak": 3}}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, 0);
+
+    let missing = Survey::merge_answers(&pool, &2, &json(r#"{"color": "red"}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(missing.rows_affected, 0);
+}
@@ -0,0 +1,133 @@
+use atmosphere::id::{Sequence, Snowflake, Ulid};
+use atmosphere::prelude::*;
+
+#[derive(PrimaryKey, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct PlotId(i64);
+
+#[derive(PrimaryKey, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct SproutId(i64);
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "plot", schema = "public")]
+struct Plot {
+    #[sql(pk)]
+    id: PlotId,
+    name: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "sprout", schema = "public")]
+struct Sprout {
+    #[sql(pk)]
+    id: SproutId,
+    #[sql(fk -> Plot, rename = "plot_id")]
+    plot: PlotId,
+}
+
+static SEED_IDS: Snowflake = Snowflake::new(1);
+static BULB_IDS: Sequence = Sequence::starting_at(100);
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "seed", schema = "public", ids = "SEED_IDS")]
+struct Seed {
+    #[sql(pk)]
+    id: i64,
+    name: String,
+}
+
+#[derive(PrimaryKey, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+struct BulbId(i64);
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "bulb", schema = "public", ids = "BULB_IDS")]
+struct Bulb {
+    #[sql(pk)]
+    id: BulbId,
+    name: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "spore", schema = "public", ids = "Ulid")]
+struct Spore {
+    #[sql(pk)]
+    id: String,
+    name: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn newtype(pool: sqlx::PgPool) {
+    Plot::create_table(&pool).await.unwrap();
+    Sprout::create_table(&pool).await.unwrap();
+
+    let mut plot = Plot {
+        id: PlotId(1),
+        name: "north".to_owned(),
+    };
+
+    let mut sprout = Sprout {
+        id: SproutId(1),
+        plot: plot.id,
+    };
+
+    plot.create(&pool).await.unwrap();
+    sprout.create(&pool).await.unwrap();
+
+    assert_eq!(Plot::read(&pool, &PlotId(1)).await.unwrap(), plot);
+    assert_eq!(sprout.plot(&pool).await.unwrap(), plot);
+    assert_eq!(plot.sprouts(&pool).await.unwrap(), [sprout.clone()]);
+    assert_eq!(
+        Sprout::find_by_plot(&pool, &PlotId(1)).await.unwrap(),
+        [sprout]
+    );
+
+    assert_eq!(i64::from(plot.id), 1);
+    assert_eq!(PlotId::from(1), plot.id);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn generated(pool: sqlx::PgPool) {
+    Seed::create_table(&pool).await.unwrap();
+    Bulb::create_table(&pool).await.unwrap();
+    Spore::create_table(&pool).await.unwrap();
+
+    let mut seeds = [1, 2].map(|i| Seed {
+        id: 0,
+        name: format!("seed {i}"),
+    });
+
+    for seed in &mut seeds {
+        seed.create(&pool).await.unwrap();
+    }
+
+    assert!(0 < seeds[0].id && seeds[0].id < seeds[1].id);
+    assert_eq!(Seed::read(&pool, &seeds[1].id).await.unwrap(), seeds[1]);
+
+    let mut bulb = Bulb {
+        id: BulbId::default(),
+        name: "tulip".to_owned(),
+    };
+
+    bulb.upsert(&pool).await.unwrap();
+
+    assert_eq!(bulb.id, BulbId(100));
+
+    // keys which are already set are kept
+    let mut given = Bulb {
+        id: BulbId(7),
+        name: "lily".to_owned(),
+    };
+
+    given.create(&pool).await.unwrap();
+
+    assert_eq!(given.id, BulbId(7));
+
+    let mut spore = Spore {
+        id: String::new(),
+        name: "morel".to_owned(),
+    };
+
+    spore.create(&pool).await.unwrap();
+
+    assert_eq!(spore.id.len(), 26);
+    assert_eq!(Spore::read(&pool, &spore.id).await.unwrap(), spore);
+}
@@ -0,0 +1,69 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn search_by(pool: sqlx::PgPool) {
+    let names = [
+        "Grunewald",
+        "Spandauer Forst",
+        "100% forest",
+        "100 forests",
+        "a_b",
+    ];
+
+    for (id, name) in names.into_iter().enumerate() {
+        Forest {
+            id: id as i32,
+            name: name.to_owned(),
+            location: "berlin".to_owned(),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    let names = |forests: Vec<Forest>| {
+        let mut names: Vec<String> = forests.into_iter().map(|forest| forest.name).collect();
+        names.sort();
+        names
+    };
+
+    assert_eq!(
+        names(Forest::search_by_name(&pool, "GRUNE").await.unwrap()),
+        vec!["Grunewald"]
+    );
+
+    assert_eq!(
+        names(Forest::search_by_name(&pool, "forest").await.unwrap()),
+        vec!["100 forests", "100% forest"]
+    );
+
+    // wildcards match literally
+    assert_eq!(
+        names(Forest::search_by_name(&pool, "100%").await.unwrap()),
+        vec!["100% forest"]
+    );
+
+    assert_eq!(
+        names(Forest::search_by_name(&pool, "a_").await.unwrap()),
+        vec!["a_b"]
+    );
+
+    assert_eq!(
+        names(Forest::search_by_name(&pool, "_").await.unwrap()),
+        vec!["a_b"]
+    );
+
+    assert_eq!(
+        Forest::search_by_location(&pool, "").await.unwrap().len(),
+        5
+    );
+}
@@ -0,0 +1,58 @@
+use atmosphere::prelude::*;
+use atmosphere::query::Lock;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn read_for_update(pool: sqlx::PgPool) {
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    forest.create(&pool).await.unwrap();
+
+    let mut holder = pool.begin().await.unwrap();
+
+    let locked = Forest::read_for_update(&mut *holder, &forest.id, Lock::Wait)
+        .await
+        .unwrap();
+
+    assert_eq!(locked, forest);
+
+    let mut other = pool.begin().await.unwrap();
+
+    Forest::read_for_update(&mut *other, &forest.id, Lock::NoWait)
+        .await
+        .expect_err("locked row could be locked again");
+
+    other.rollback().await.unwrap();
+
+    let mut other = pool.begin().await.unwrap();
+
+    Forest::read_for_update(&mut *other, &forest.id, Lock::SkipLocked)
+        .await
+        .expect_err("locked row was not skipped");
+
+    other.rollback().await.unwrap();
+
+    holder.commit().await.unwrap();
+
+    let mut other = pool.begin().await.unwrap();
+
+    let locked = Forest::read_for_update(&mut *other, &forest.id, Lock::NoWait)
+        .await
+        .unwrap();
+
+    assert_eq!(locked, forest);
+
+    other.commit().await.unwrap();
+}
@@ -0,0 +1,101 @@
+use atmosphere::prelude::*;
+use metrics_util::{
+    debugging::{DebugValue, DebuggingRecorder},
+    MetricKind,
+};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "beacon", schema = "public")]
+struct Beacon {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn metrics(pool: sqlx::PgPool) {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    recorder.install().unwrap();
+
+    Beacon::create_table(&pool).await.unwrap();
+
+    let mut beacon = Beacon {
+        id: 0,
+        name: "lighthouse".to_owned(),
+    };
+
+    beacon.create(&pool).await.unwrap();
+    Beacon::read(&pool, &0).await.unwrap();
+    Beacon::read(&pool, &1).await.unwrap_err();
+
+    let metrics: Vec<_> = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .filter(|(key, ..)| {
+            key.key()
+                .labels()
+                .any(|l| l.key() == "table" && l.value() == "beacon")
+        })
+        .collect();
+
+    let value = |kind: MetricKind, name: &str, labels: &[(&str, &str)]| {
+        metrics
+            .iter()
+            .find(|(key, ..)| {
+                key.kind() == kind
+                    && key.key().name() == name
+                    && labels
+                        .iter()
+                        .all(|(k, v)| key.key().labels().any(|l| l.key() == *k && l.value() == *v))
+            })
+            .map(|(.., value)| value)
+    };
+
+    assert_eq!(
+        value(
+            MetricKind::Counter,
+            "atmosphere_queries_total",
+            &[("operation", "insert")]
+        ),
+        Some(&DebugValue::Counter(1))
+    );
+    assert_eq!(
+        value(
+            MetricKind::Counter,
+            "atmosphere_queries_total",
+            &[("operation", "select")]
+        ),
+        Some(&DebugValue::Counter(2))
+    );
+    assert_eq!(
+        value(
+            MetricKind::Counter,
+            "atmosphere_query_errors_total",
+            &[("operation", "select"), ("error", "not_found")]
+        ),
+        Some(&DebugValue::Counter(1))
+    );
+
+    let Some(DebugValue::Histogram(durations)) = value(
+        MetricKind::Histogram,
+        "atmosphere_query_duration_seconds",
+        &[("operation", "select")],
+    ) else {
+        panic!("query durations are not recorded");
+    };
+
+    assert_eq!(durations.len(), 2);
+
+    let Some(DebugValue::Histogram(rows)) = value(
+        MetricKind::Histogram,
+        "atmosphere_query_rows",
+        &[("operation", "insert")],
+    ) else {
+        panic!("affected rows are not recorded");
+    };
+
+    assert_eq!(rows.iter().map(|r| r.0).collect::<Vec<_>>(), [1.0]);
+}
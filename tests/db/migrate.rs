@@ -0,0 +1,191 @@
+use atmosphere::{
+    migrate::{Migrator, Mismatch},
+    prelude::*,
+};
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    area: Option<i32>,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "ranger", schema = "public")]
+struct Ranger {
+    #[sql(pk, auto)]
+    id: i64,
+    name: Option<String>,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "camp", schema = "public")]
+struct Camp {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id")]
+    forest: Option<i32>,
+    #[sql(index)]
+    name: String,
+    #[sql(timestamp = deleted)]
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "glade", schema = "public", index(forest, desc))]
+struct Glade {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id")]
+    forest: i32,
+}
+
+fn migrator() -> Migrator {
+    Migrator::new()
+        .table::<Forest>()
+        .table::<Ranger>()
+        .table::<Camp>()
+        .table::<Glade>()
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn migrate(pool: sqlx::PgPool) {
+    let migration = migrator().diff(&pool).await.unwrap();
+
+    assert_eq!(
+        migration.statements,
+        vec![
+            "ALTER TABLE \"public\".\"forest\" ADD COLUMN area INT4".to_owned(),
+            "ALTER TABLE \"public\".\"forest\" DROP COLUMN location".to_owned(),
+            "ALTER TABLE \"public\".\"ranger\" ALTER COLUMN id TYPE INT8 USING id::INT8".to_owned(),
+            "ALTER TABLE \"public\".\"ranger\" ALTER COLUMN name DROP NOT NULL".to_owned(),
+            "ALTER TABLE \"public\".\"camp\" ADD COLUMN forest_id INT4".to_owned(),
+            "CREATE INDEX IF NOT EXISTS camp_name_idx ON \"public\".\"camp\" (name)".to_owned(),
+            Glade::create_table_sql(),
+            "ALTER TABLE \"public\".\"camp\" ADD FOREIGN KEY (forest_id) REFERENCES \"public\".\"forest\" (id) ON DELETE NO ACTION ON UPDATE NO ACTION".to_owned(),
+        ]
+    );
+
+    sqlx::raw_sql(&migration.sql())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // the database now matches the definitions
+    assert!(migrator().diff(&pool).await.unwrap().is_empty());
+
+    let mut camp = Camp {
+        id: 0,
+        forest: None,
+        name: "base".to_owned(),
+        deleted_at: None,
+    };
+
+    camp.create(&pool).await.unwrap();
+    assert_eq!(Camp::read(&pool, &0).await.unwrap(), camp);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn write(pool: sqlx::PgPool) {
+    let migration = migrator().diff(&pool).await.unwrap();
+
+    let dir = std::env::temp_dir().join(format!("atmosphere-migrate-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let path = migration.write(&dir, "sync schema").unwrap();
+
+    assert!(path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .ends_with("_sync_schema.sql"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), migration.sql());
+
+    // the written migration is picked up by sqlx
+    let migrator = sqlx::migrate::Migrator::new(dir.as_path()).await.unwrap();
+    assert_eq!(migrator.iter().count(), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+mod valid {
+    use atmosphere::prelude::*;
+
+    #[derive(Schema, Debug, PartialEq, Eq, Clone)]
+    #[table(name = "forest", schema = "public")]
+    pub struct Forest {
+        #[sql(pk)]
+        id: i32,
+        name: String,
+        location: String,
+    }
+
+    #[derive(Schema, Debug, PartialEq, Eq, Clone)]
+    #[table(name = "tree", schema = "public")]
+    pub struct Tree {
+        #[sql(pk)]
+        id: i32,
+        #[sql(fk -> Forest, rename = "forest_id")]
+        forest: i32,
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn validate_schema(pool: sqlx::PgPool) {
+    let report = atmosphere::validate_schema::<(valid::Forest, valid::Tree)>(&pool)
+        .await
+        .unwrap();
+
+    assert!(report.is_valid(), "{report}");
+
+    let report = atmosphere::validate_schema::<(Forest, Ranger, Camp, Glade)>(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.mismatches,
+        vec![
+            Mismatch::MissingColumn {
+                table: "public.forest".to_owned(),
+                column: "area",
+            },
+            Mismatch::Type {
+                table: "public.ranger".to_owned(),
+                column: "id",
+                declared: "int8".to_owned(),
+                actual: "int4".to_owned(),
+            },
+            Mismatch::Nullability {
+                table: "public.ranger".to_owned(),
+                column: "name",
+                nullable: true,
+            },
+            Mismatch::MissingColumn {
+                table: "public.camp".to_owned(),
+                column: "forest_id",
+            },
+            Mismatch::MissingIndex {
+                table: "public.camp".to_owned(),
+                index: "camp_name_idx",
+            },
+            Mismatch::MissingTable {
+                table: "public.glade".to_owned(),
+            },
+        ]
+    );
+
+    assert_eq!(
+        report.to_string(),
+        "the schema does not match the database:
+  - column public.forest.area does not exist
+  - column public.ranger.id is declared as int8 but is int4
+  - column public.ranger.name is declared nullable but is NOT NULL
+  - column public.camp.forest_id does not exist
+  - index camp_name_idx of table public.camp does not exist
+  - table public.glade does not exist"
+    );
+}
@@ -0,0 +1,4 @@
+CREATE TABLE ranger (
+    id   SERIAL PRIMARY KEY,
+    name TEXT NOT NULL
+);
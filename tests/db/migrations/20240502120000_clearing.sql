@@ -0,0 +1,4 @@
+CREATE TABLE clearing (
+    id        INT PRIMARY KEY,
+    forest_id INT NULL REFERENCES forest(id) ON DELETE SET NULL
+);
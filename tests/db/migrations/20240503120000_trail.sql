@@ -0,0 +1,5 @@
+CREATE TABLE trail (
+    id          INT PRIMARY KEY,
+    origin      INT NOT NULL REFERENCES forest(id) ON DELETE CASCADE,
+    destination INT NOT NULL REFERENCES forest(id) ON DELETE CASCADE
+);
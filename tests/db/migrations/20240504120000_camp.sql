@@ -0,0 +1,5 @@
+CREATE TABLE camp (
+    id         INT PRIMARY KEY,
+    name       TEXT NOT NULL,
+    deleted_at TIMESTAMPTZ
+);
@@ -0,0 +1,5 @@
+CREATE TABLE chore (
+    id    INT PRIMARY KEY,
+    state TEXT NOT NULL,
+    name  TEXT NOT NULL
+);
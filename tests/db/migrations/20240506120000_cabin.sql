@@ -0,0 +1,6 @@
+CREATE TABLE cabin (
+    id         INT PRIMARY KEY,
+    name       TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ
+);
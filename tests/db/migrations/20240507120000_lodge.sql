@@ -0,0 +1,4 @@
+CREATE TABLE lodge (
+    id   INT PRIMARY KEY,
+    name TEXT NOT NULL
+);
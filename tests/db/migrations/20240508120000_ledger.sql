@@ -0,0 +1,5 @@
+CREATE TABLE ledger (
+    id     INT PRIMARY KEY,
+    org    INT NOT NULL,
+    amount INT NOT NULL
+);
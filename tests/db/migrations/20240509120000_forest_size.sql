@@ -0,0 +1,8 @@
+CREATE VIEW forest_size AS
+SELECT
+    forest.id,
+    forest.id AS forest_id,
+    COUNT(tree.id) AS trees
+FROM forest
+LEFT JOIN tree ON tree.forest_id = forest.id
+GROUP BY forest.id;
@@ -0,0 +1,10 @@
+CREATE MATERIALIZED VIEW forest_census AS
+SELECT
+    forest.id,
+    COUNT(tree.id) AS trees
+FROM forest
+LEFT JOIN tree ON tree.forest_id = forest.id
+GROUP BY forest.id;
+
+-- required by concurrent refreshes
+CREATE UNIQUE INDEX forest_census_id_idx ON forest_census (id);
@@ -0,0 +1,4 @@
+CREATE TABLE survey (
+    id      INT PRIMARY KEY,
+    answers JSONB NOT NULL
+);
@@ -0,0 +1,11 @@
+CREATE TABLE account (
+    id     INT PRIMARY KEY,
+    org    INT NOT NULL,
+    handle TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE posting (
+    id         INT PRIMARY KEY,
+    org        INT NOT NULL,
+    account_id INT NOT NULL REFERENCES account (id)
+);
@@ -0,0 +1,14 @@
+CREATE TABLE folder (
+    id   INT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE pin (
+    id        INT PRIMARY KEY,
+    folder_id INT NOT NULL REFERENCES folder (id)
+);
+
+CREATE TABLE label (
+    id        INT PRIMARY KEY,
+    folder_id INT NOT NULL REFERENCES folder (id)
+);
@@ -0,0 +1,6 @@
+CREATE TABLE invoice (
+    id    INT PRIMARY KEY,
+    total NUMERIC(12, 2) NOT NULL UNIQUE,
+    rate  NUMERIC,
+    lines JSONB NOT NULL
+);
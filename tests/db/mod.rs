@@ -1 +1,63 @@
+mod audit;
+mod auto;
+mod cache;
+mod changes;
+mod columns;
+mod comment;
+mod config;
+mod constraints;
+#[cfg(feature = "testcontainers")]
+mod container;
 mod crud;
+mod databases;
+mod ddl;
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+mod decimal;
+mod deny;
+mod describe;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod enums;
+#[cfg(feature = "axum")]
+mod extract;
+mod factory;
+mod fulltext;
+mod health;
+mod history;
+mod hooks;
+mod intercept;
+mod interval;
+mod json;
+mod keys;
+mod like;
+mod locking;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod migrate;
+#[cfg(feature = "utoipa")]
+mod openapi;
+mod partition;
+mod patch;
+mod pools;
+mod preview;
+#[cfg(feature = "proptest")]
+mod property;
+mod queue;
+mod redact;
+mod relationships;
+mod retry;
+mod schema;
+mod seed;
+mod session;
+mod shard;
+mod soft_delete;
+mod tenant;
+mod timestamps;
+#[cfg(feature = "tracing")]
+mod tracing;
+mod tracked;
+mod transaction;
+#[cfg(feature = "uuid")]
+mod uuids;
+mod validation;
+mod views;
@@ -0,0 +1,73 @@
+use atmosphere::{openapi::Documented, prelude::*};
+use sqlx::types::{Json, JsonValue};
+use utoipa::openapi::{schema::Components, RefOr, Schema};
+
+#[derive(PrimaryKey, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct KettleId(i64);
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "kettle", schema = "public", patch)]
+struct Kettle {
+    #[sql(pk)]
+    id: KettleId,
+    name: String,
+    #[sql(json)]
+    settings: Json<JsonValue>,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "brew", schema = "public", deny(update, delete))]
+struct Brew {
+    #[sql(pk)]
+    id: i32,
+    tea: String,
+}
+
+#[test]
+fn openapi() {
+    let paths = Kettle::paths("/kettles/");
+
+    let list = &paths.paths["/kettles"];
+
+    assert_eq!(
+        list.get.as_ref().unwrap().operation_id.as_deref(),
+        Some("list_kettle")
+    );
+    assert!(list.post.is_some());
+
+    let single = &paths.paths["/kettles/{id}"];
+
+    for operation in [&single.get, &single.put, &single.patch, &single.delete] {
+        let operation = operation.as_ref().unwrap();
+
+        assert_eq!(operation.tags.as_deref(), Some(&["kettle".to_owned()][..]));
+        assert!(operation.responses.responses.contains_key("404"));
+
+        let pk = &operation.parameters.as_ref().unwrap()[0];
+
+        assert_eq!(pk.name, "id");
+        assert!(matches!(
+            &pk.schema,
+            Some(RefOr::Ref(pk)) if pk.ref_location.ends_with("/KettleId")
+        ));
+    }
+
+    let mut components = Components::new();
+    Kettle::components(&mut components);
+
+    assert!(components.schemas.contains_key("Kettle"));
+    assert!(components.schemas.contains_key("KettleId"));
+    assert!(components.schemas.contains_key("KettlePatch"));
+
+    // tables opting out of writes only document reading them
+    let paths = Brew::paths("/brews");
+
+    assert!(paths.paths["/brews"].post.is_some());
+
+    let read = paths.paths["/brews/{id}"].get.as_ref().unwrap();
+    let pk = &read.parameters.as_ref().unwrap()[0];
+
+    assert!(matches!(pk.schema, Some(RefOr::T(Schema::Object(_)))));
+    assert!(paths.paths["/brews/{id}"].put.is_none());
+    assert!(paths.paths["/brews/{id}"].delete.is_none());
+}
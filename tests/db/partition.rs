@@ -0,0 +1,55 @@
+use std::{borrow::Cow, sync::Mutex};
+
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "event", schema = "public", dynamic)]
+struct Event {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+}
+
+static PARTITION: Mutex<&str> = Mutex::new("2024_05");
+
+impl TableName for Event {
+    fn table_name() -> Cow<'static, str> {
+        format!("event_{}", PARTITION.lock().unwrap()).into()
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn partition(pool: sqlx::PgPool) {
+    for partition in ["2024_05", "2024_06"] {
+        sqlx::query(&format!(
+            "CREATE TABLE event_{partition} (id INT PRIMARY KEY, name TEXT NOT NULL)"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let mut may = Event {
+        id: 0,
+        name: "may".to_owned(),
+    };
+
+    may.create(&pool).await.unwrap();
+
+    *PARTITION.lock().unwrap() = "2024_06";
+
+    assert_eq!(Event::read_all(&pool).await.unwrap(), vec![]);
+
+    let mut june = Event {
+        id: 0,
+        name: "june".to_owned(),
+    };
+
+    june.create(&pool).await.unwrap();
+
+    assert_eq!(Event::read_all(&pool).await.unwrap(), vec![june]);
+
+    *PARTITION.lock().unwrap() = "2024_05";
+
+    assert_eq!(Event::read_all(&pool).await.unwrap(), vec![may]);
+}
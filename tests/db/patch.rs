@@ -0,0 +1,120 @@
+use atmosphere::prelude::*;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public", patch)]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    #[sql(validate = "named")]
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "cabin", schema = "public", patch)]
+struct Cabin {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(timestamp = created)]
+    created_at: DateTime<Utc>,
+    #[sql(timestamp = updated)]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+fn named(value: &str) -> std::result::Result<(), String> {
+    if value.is_empty() {
+        return Err("a forest requires a name".to_owned());
+    }
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn patch(pool: sqlx::PgPool) {
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    forest.create(&pool).await.unwrap();
+
+    let empty = Forest::patch(&pool, &0, ForestPatch::default()).await;
+    assert_eq!(empty.unwrap().rows_affected, 0);
+
+    let patch = ForestPatch {
+        location: Some("brandenburg".to_owned()),
+        ..Default::default()
+    };
+
+    assert_eq!(patch.fields(), vec!["location"]);
+    assert_eq!(
+        Forest::patch(&pool, &0, patch).await.unwrap().rows_affected,
+        1
+    );
+
+    // only the provided column has been written
+    assert_eq!(
+        Forest::read(&pool, &0).await.unwrap(),
+        Forest {
+            id: 0,
+            name: "grunewald".to_owned(),
+            location: "brandenburg".to_owned(),
+        }
+    );
+
+    let missing = ForestPatch {
+        name: Some("spreewald".to_owned()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        Forest::patch(&pool, &1, missing)
+            .await
+            .unwrap()
+            .rows_affected,
+        0
+    );
+
+    let invalid = ForestPatch {
+        name: Some(String::new()),
+        ..Default::default()
+    };
+
+    let Err(Error::Validation(err)) = Forest::patch(&pool, &0, invalid).await else {
+        panic!("invalid patch was applied");
+    };
+
+    assert_eq!(
+        err.fields,
+        vec![FieldError::new("name", "a forest requires a name")]
+    );
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn patch_timestamps(pool: sqlx::PgPool) {
+    let mut cabin = Cabin {
+        id: 0,
+        name: "cabin".to_owned(),
+        created_at: DateTime::<Utc>::default(),
+        updated_at: None,
+    };
+
+    cabin.create(&pool).await.unwrap();
+
+    let cabin = Cabin::read(&pool, &0).await.unwrap();
+
+    let patch = CabinPatch {
+        name: Some("hut".to_owned()),
+    };
+
+    Cabin::patch(&pool, &0, patch).await.unwrap();
+
+    let patched = Cabin::read(&pool, &0).await.unwrap();
+
+    assert_eq!(patched.name, "hut");
+    assert_eq!(patched.created_at, cabin.created_at);
+    assert!(patched.updated_at > cabin.updated_at);
+}
@@ -0,0 +1,62 @@
+use atmosphere::prelude::*;
+use sqlx::{postgres::PgPoolOptions, Executor};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+async fn application_name(pools: &Pools) -> String {
+    sqlx::query_scalar("SELECT current_setting('application_name')")
+        .fetch_one(pools)
+        .await
+        .unwrap()
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn pools(pool: sqlx::PgPool) {
+    // a read only replica, told apart by its application name
+    let reader = PgPoolOptions::new()
+        .after_connect(|conn, _| {
+            Box::pin(async move {
+                conn.execute("SET application_name = 'reader'").await?;
+                conn.execute("SET default_transaction_read_only = on")
+                    .await?;
+                Ok(())
+            })
+        })
+        .connect_with((*pool.connect_options()).clone())
+        .await
+        .unwrap();
+
+    let pools = Pools::new(pool, vec![reader]);
+
+    assert_eq!(application_name(&pools).await, "reader");
+    assert_ne!(Pools::consistent(application_name(&pools)).await, "reader");
+
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    // writes go to the primary
+    forest.create(&pools).await.unwrap();
+
+    forest.name = "spreewald".to_owned();
+    forest.update(&pools).await.unwrap();
+
+    assert_eq!(Forest::read(&pools, &0).await.unwrap(), forest);
+    assert_eq!(
+        Forest::read_all(&pools).await.unwrap(),
+        vec![forest.clone()]
+    );
+
+    forest.delete(&pools).await.unwrap();
+
+    assert_eq!(Forest::find(&pools, &0).await.unwrap(), None);
+}
@@ -0,0 +1,44 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "lamp", schema = "public")]
+struct Lamp {
+    #[sql(pk)]
+    id: i32,
+    #[sql(rename = "lamp_color")]
+    color: String,
+}
+
+#[test]
+fn preview() {
+    let lamp = Lamp {
+        id: 0,
+        color: "amber".to_owned(),
+    };
+
+    let create = lamp.preview_create().unwrap();
+
+    assert_eq!(
+        create.sql,
+        "INSERT INTO \"public\".\"lamp\"\n  (id, lamp_color)\nVALUES\n  ($1, $2)"
+    );
+    assert_eq!(create.bindings, ["id", "color (column lamp_color)"]);
+
+    assert_eq!(
+        create.to_string(),
+        format!(
+            "{}\n-- $1: id\n-- $2: color (column lamp_color)",
+            create.sql
+        )
+    );
+
+    let read = Lamp::preview_read().unwrap();
+
+    assert!(read.sql.starts_with("SELECT"));
+    assert_eq!(read.bindings, ["id"]);
+
+    let delete = lamp.preview_delete().unwrap();
+
+    assert!(delete.sql.starts_with("DELETE FROM \"public\".\"lamp\""));
+    assert_eq!(delete.bindings, ["id"]);
+}
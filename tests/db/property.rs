@@ -0,0 +1,80 @@
+use atmosphere::{prelude::*, testing};
+use proptest::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "tree", schema = "public")]
+struct Tree {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id")]
+    forest: i32,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[table(name = "ledger", schema = "public")]
+struct Entry {
+    #[sql(pk)]
+    id: i32,
+    org: i32,
+    amount: i32,
+}
+
+fn forests(name: &'static str) -> impl Strategy<Value = Forest> {
+    (any::<i32>(), name, "[a-z]{0,8}").prop_map(|(id, name, location)| Forest {
+        id,
+        name,
+        location,
+    })
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn roundtrip(pool: sqlx::PgPool) {
+    testing::roundtrip_prop(&pool, forests("[a-z🌲 ]{0,16}"), 64).await;
+
+    assert!(Forest::read_all(&pool).await.unwrap().is_empty());
+
+    let mut parents = vec![];
+
+    for id in 0..3 {
+        let mut forest = Forest {
+            id,
+            name: "grunewald".to_owned(),
+            location: "berlin".to_owned(),
+        };
+
+        forest.create(&pool).await.unwrap();
+        parents.push(forest);
+    }
+
+    let trees =
+        (any::<i32>(), testing::references(&parents)).prop_map(|(id, forest)| Tree { id, forest });
+
+    testing::roundtrip_prop(&pool, trees, 64).await;
+
+    assert!(Tree::read_all(&pool).await.unwrap().is_empty());
+}
+
+// postgres rejects NUL bytes in text, the failing name is shrunk to the NUL byte alone
+#[sqlx::test(migrations = "tests/db/migrations")]
+#[should_panic(expected = "name: \"\\0\"")]
+async fn shrink(pool: sqlx::PgPool) {
+    testing::roundtrip_prop(&pool, forests("[a-z]{0,8}\0[a-z]{0,8}"), 8).await;
+}
+
+#[cfg(feature = "arbitrary")]
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn arbitrary(pool: sqlx::PgPool) {
+    testing::roundtrip_prop(&pool, testing::arbitrary::<Entry>(), 64).await;
+
+    assert!(Entry::read_all(&pool).await.unwrap().is_empty());
+}
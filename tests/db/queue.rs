@@ -0,0 +1,67 @@
+use atmosphere::prelude::*;
+use atmosphere::queue::Queue;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "chore", schema = "public")]
+struct Chore {
+    #[sql(pk)]
+    id: i32,
+    state: String,
+    name: String,
+}
+
+impl Queue for Chore {
+    type State = &'static str;
+
+    const STATE: &'static str = "state";
+
+    fn pending() -> &'static str {
+        "pending"
+    }
+
+    fn in_progress() -> &'static str {
+        "running"
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn claim_next(pool: sqlx::PgPool) {
+    for (id, name) in ["water plants", "feed birds", "sweep path"]
+        .into_iter()
+        .enumerate()
+    {
+        Chore {
+            id: id as i32,
+            state: "pending".to_owned(),
+            name: name.to_owned(),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    let mut first = pool.begin().await.unwrap();
+    let mut second = pool.begin().await.unwrap();
+
+    let a = Chore::claim_next(&mut *first).await.unwrap().unwrap();
+    let b = Chore::claim_next(&mut *second).await.unwrap().unwrap();
+
+    assert_eq!(a.id, 0);
+    assert_eq!(a.state, "running");
+
+    // the first row is locked by the first worker and skipped
+    assert_eq!(b.id, 1);
+
+    first.commit().await.unwrap();
+    second.rollback().await.unwrap();
+
+    let c = Chore::claim_next(&pool).await.unwrap().unwrap();
+    assert_eq!(c.id, 1);
+
+    let d = Chore::claim_next(&pool).await.unwrap().unwrap();
+    assert_eq!(d.id, 2);
+
+    assert!(Chore::claim_next(&pool).await.unwrap().is_none());
+
+    assert_eq!(Chore::read(&pool, &0).await.unwrap().state, "running");
+}
@@ -0,0 +1,69 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, PartialEq, Eq, Clone)]
+#[table(name = "member", schema = "public")]
+#[audit]
+struct Member {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(sensitive, validate = "email")]
+    email: String,
+    #[sql(skip)]
+    visits: u32,
+}
+
+fn email(value: &str) -> std::result::Result<(), String> {
+    if !value.contains('@') {
+        return Err(format!("`{value}` is not an email address"));
+    }
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn redact(pool: sqlx::PgPool) {
+    Member::create_table(&pool).await.unwrap();
+
+    sqlx::query(&atmosphere::audit::table_sql::<Member>())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut member = Member {
+        id: 0,
+        name: "ada".to_owned(),
+        email: "ada.example.com".to_owned(),
+        visits: 3,
+    };
+
+    assert_eq!(
+        format!("{member:?}"),
+        "Member { id: 0, name: \"ada\", email: «redacted», visits: 3 }"
+    );
+
+    let Err(Error::Validation(err)) = member.create(&pool).await else {
+        panic!("invalid member was created");
+    };
+
+    assert_eq!(err.fields, vec![FieldError::new("email", "«redacted»")]);
+
+    member.email = "ada@example.com".to_owned();
+    member.create(&pool).await.unwrap();
+
+    let (name, email): (String, String) = sqlx::query_as(
+        "SELECT after ->> 'name', after ->> 'email' FROM public.member_audit ORDER BY id",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(name, "ada");
+    assert_eq!(email, "«redacted»");
+
+    // only the audit records are redacted
+    assert_eq!(
+        Member::read(&pool, &0).await.unwrap().email,
+        "ada@example.com"
+    );
+}
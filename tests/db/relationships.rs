@@ -0,0 +1,261 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "clearing", schema = "public")]
+struct Clearing {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id", on_delete = "set null")]
+    forest: Option<i32>,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "tree", schema = "public")]
+struct Tree {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id", on_delete = "cascade")]
+    forest: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn nullable(pool: sqlx::PgPool) {
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    forest.create(&pool).await.unwrap();
+
+    let mut wild = Clearing {
+        id: 0,
+        forest: None,
+    };
+
+    let mut owned = Clearing {
+        id: 1,
+        forest: Some(0),
+    };
+
+    wild.create(&pool).await.unwrap();
+    owned.create(&pool).await.unwrap();
+
+    assert_eq!(wild.forest(&pool).await.unwrap(), None);
+    assert_eq!(owned.forest(&pool).await.unwrap(), Some(forest.clone()));
+
+    assert_eq!(forest.clearings(&pool).await.unwrap(), vec![owned.clone()]);
+    assert_eq!(
+        Clearing::find_by_forest(&pool, &0).await.unwrap(),
+        vec![owned.clone()]
+    );
+
+    assert_eq!(Clearing::read(&pool, &0).await.unwrap(), wild);
+
+    assert_eq!(
+        Clearing::read_all_with::<Forest>(&pool).await.unwrap(),
+        vec![(owned, forest.clone())]
+    );
+
+    assert_eq!(
+        Clearing::FOREIGN_KEYS[0].on_delete,
+        atmosphere::column::ReferentialAction::SetNull
+    );
+    assert!(Clearing::FOREIGN_KEYS[0].ty.unwrap().nullable);
+
+    // the generated schema carries the nullability and action of the key, not only the migration
+    let ddl = Clearing::create_table_sql();
+
+    assert!(ddl.contains("\n  forest_id INT4,\n"));
+    assert!(ddl.contains(
+        "FOREIGN KEY (forest_id) REFERENCES \"public\".\"forest\" (id) ON DELETE SET NULL"
+    ));
+
+    forest.delete(&pool).await.unwrap();
+
+    assert_eq!(Clearing::read(&pool, &1).await.unwrap().forest, None);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "trail", schema = "public")]
+struct Trail {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, relation = "origin", inverse = "departures")]
+    origin: i32,
+    #[sql(fk -> Forest, relation = "destination")]
+    destination: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn named(pool: sqlx::PgPool) {
+    let mut a = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    let mut b = Forest {
+        id: 1,
+        name: "spreewald".to_owned(),
+        location: "brandenburg".to_owned(),
+    };
+
+    a.create(&pool).await.unwrap();
+    b.create(&pool).await.unwrap();
+
+    let mut trail = Trail {
+        id: 0,
+        origin: 0,
+        destination: 1,
+    };
+
+    trail.create(&pool).await.unwrap();
+
+    assert_eq!(trail.origin(&pool).await.unwrap(), a);
+    assert_eq!(trail.destination(&pool).await.unwrap(), b);
+
+    assert_eq!(a.departures(&pool).await.unwrap(), vec![trail.clone()]);
+    assert!(a.trails_by_destination(&pool).await.unwrap().is_empty());
+    assert_eq!(
+        b.trails_by_destination(&pool).await.unwrap(),
+        vec![trail.clone()]
+    );
+
+    assert_eq!(
+        Trail::find_by_origin(&pool, &0).await.unwrap(),
+        vec![trail.clone()]
+    );
+
+    b.delete_trails_by_destination(&pool).await.unwrap();
+
+    assert!(Trail::find(&pool, &0).await.unwrap().is_none());
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn batch(pool: sqlx::PgPool) {
+    use atmosphere::rel::ReferredBy;
+
+    let forests: Vec<Forest> = (0..3)
+        .map(|id| Forest {
+            id,
+            name: format!("forest {id}"),
+            location: "berlin".to_owned(),
+        })
+        .collect();
+
+    for forest in forests.clone().iter_mut() {
+        forest.create(&pool).await.unwrap();
+    }
+
+    for (id, forest) in [(0, Some(0)), (1, Some(0)), (2, Some(1)), (3, None)] {
+        Clearing { id, forest }.create(&pool).await.unwrap();
+    }
+
+    let resolved = <Forest as ReferredBy<Clearing>>::resolve_for(&forests, &pool)
+        .await
+        .unwrap();
+
+    assert_eq!(resolved.len(), 3);
+    assert_eq!(resolved[&0].len(), 2);
+    assert_eq!(
+        resolved[&1],
+        vec![Clearing {
+            id: 2,
+            forest: Some(1)
+        }]
+    );
+    assert!(resolved[&2].is_empty());
+
+    assert!(<Forest as ReferredBy<Clearing>>::resolve_for(&[], &pool)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn relationship(pool: sqlx::PgPool) {
+    let forest = |id| Forest {
+        id,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    atmosphere::testing::relationship(
+        &pool,
+        forest(0),
+        vec![Tree { id: 0, forest: 0 }, Tree { id: 1, forest: 0 }],
+    )
+    .await;
+
+    atmosphere::testing::relationship(
+        &pool,
+        forest(1),
+        vec![Clearing {
+            id: 0,
+            forest: Some(1),
+        }],
+    )
+    .await;
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn group_by(pool: sqlx::PgPool) {
+    for id in 0..3 {
+        Forest {
+            id,
+            name: format!("forest {id}"),
+            location: "berlin".to_owned(),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    for (id, forest) in [(0, 0), (1, 0), (2, 1), (3, 0)] {
+        Tree { id, forest }.create(&pool).await.unwrap();
+    }
+
+    for (id, forest) in [(0, None), (1, Some(2)), (2, None)] {
+        Clearing { id, forest }.create(&pool).await.unwrap();
+    }
+
+    assert_eq!(
+        Tree::group_by(Tree::FOREST).count(&pool).await.unwrap(),
+        vec![(0, 3), (1, 1)]
+    );
+
+    assert_eq!(
+        Tree::group_by(Tree::FOREST)
+            .having_at_least(2)
+            .count(&pool)
+            .await
+            .unwrap(),
+        vec![(0, 3)]
+    );
+
+    assert_eq!(
+        Clearing::group_by(Clearing::FOREST)
+            .count(&pool)
+            .await
+            .unwrap(),
+        vec![(Some(2), 1), (None, 2)]
+    );
+
+    assert_eq!(
+        Forest::group_by(Forest::LOCATION)
+            .count(&pool)
+            .await
+            .unwrap(),
+        vec![("berlin".to_owned(), 3)]
+    );
+}
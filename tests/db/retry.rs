@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use atmosphere::prelude::*;
+use atmosphere::query::QueryError;
+use atmosphere::retry::{retry, RetryPolicy};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "beacon", schema = "public")]
+struct Beacon {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn retries(pool: sqlx::PgPool) {
+    Beacon::create_table(&pool).await.unwrap();
+
+    let beacon = Beacon {
+        id: 0,
+        name: "north".to_owned(),
+    };
+
+    beacon.clone().create(&pool).await.unwrap();
+
+    let timeout = || Error::Query(QueryError::Io(sqlx::Error::PoolTimedOut));
+
+    assert!(timeout().is_transient());
+    assert!(!Error::Query(QueryError::Io(sqlx::Error::PoolClosed)).is_transient());
+
+    atmosphere::retry::set_policy(
+        RetryPolicy::new(3).backoff(Duration::from_millis(1), Duration::from_millis(10)),
+    );
+
+    // transient failures are retried
+    let mut attempts = 0;
+
+    let read = retry(|| {
+        attempts += 1;
+        let pool = &pool;
+        let failed = attempts < 3;
+
+        async move {
+            if failed {
+                return Err(timeout());
+            }
+
+            Beacon::read(pool, &0).await
+        }
+    })
+    .await;
+
+    assert_eq!(read.unwrap(), beacon);
+    assert_eq!(attempts, 3);
+
+    // .. up to the maximum number of attempts
+    let mut attempts = 0;
+
+    let failed = retry(|| {
+        attempts += 1;
+        async { Err::<(), _>(timeout()) }
+    })
+    .await;
+
+    assert!(failed.unwrap_err().is_transient());
+    assert_eq!(attempts, 3);
+
+    // other errors are not retried
+    let mut attempts = 0;
+
+    let missing = retry(|| {
+        attempts += 1;
+        Beacon::read(&pool, &1)
+    })
+    .await;
+
+    assert!(matches!(
+        missing.unwrap_err().query(),
+        Some(QueryError::NotFound(_))
+    ));
+    assert_eq!(attempts, 1);
+}
@@ -0,0 +1,41 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn scope(pool: sqlx::PgPool) {
+    sqlx::query("CREATE SCHEMA tenant_42")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    sqlx::query("CREATE TABLE tenant_42.forest (LIKE public.forest INCLUDING ALL)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    SchemaContext::scope("tenant_42", forest.create(&pool))
+        .await
+        .unwrap();
+
+    assert_eq!(Forest::find(&pool, &0).await.unwrap(), None);
+
+    let read = SchemaContext::scope("tenant_42", Forest::read(&pool, &0))
+        .await
+        .unwrap();
+
+    assert_eq!(read, forest);
+}
@@ -0,0 +1,91 @@
+use atmosphere::{prelude::*, seed::SeedError};
+use serde::Deserialize;
+
+#[derive(Schema, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[table(name = "tree", schema = "public")]
+struct Tree {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id")]
+    forest: i32,
+}
+
+const SEED: &str = r#"{
+    "tree": [{ "id": 0, "forest": 1 }, { "id": 1, "forest": 1 }],
+    "forest": [
+        { "id": 0, "name": "grunewald", "location": "berlin" },
+        { "id": 1, "name": "spreewald", "location": "brandenburg" }
+    ]
+}"#;
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn seed(pool: sqlx::PgPool) {
+    // forests are inserted before the trees referring to them
+    let inserted = atmosphere::seed::from_json(SEED)
+        .unwrap()
+        .table::<Tree>()
+        .table::<Forest>()
+        .load(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(inserted, 4);
+    assert_eq!(Forest::read_all(&pool).await.unwrap().len(), 2);
+    assert_eq!(Tree::find_by_forest(&pool, &1).await.unwrap().len(), 2);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn seed_errors(pool: sqlx::PgPool) {
+    let unknown = atmosphere::seed::from_json(SEED)
+        .unwrap()
+        .table::<Tree>()
+        .load(&pool)
+        .await;
+
+    assert!(matches!(
+        unknown,
+        Err(Error::Seed(SeedError::UnknownTable(table))) if table == "forest"
+    ));
+
+    let missing = r#"{
+        "forest": [{ "id": 0, "name": "grunewald", "location": "berlin" }],
+        "tree": [{ "id": 0, "forest": 9 }]
+    }"#;
+
+    let res = atmosphere::seed::from_json(missing)
+        .unwrap()
+        .table::<Forest>()
+        .table::<Tree>()
+        .load(&pool)
+        .await;
+
+    assert!(res.is_err());
+
+    // loaded within a single transaction
+    assert!(Forest::read_all(&pool).await.unwrap().is_empty());
+
+    let invalid = atmosphere::seed::from_json(r#"{ "tree": [{ "id": "oak" }] }"#)
+        .unwrap()
+        .table::<Tree>()
+        .load(&pool)
+        .await;
+
+    assert!(matches!(
+        invalid,
+        Err(Error::Seed(SeedError::Rows { table: "tree", .. }))
+    ));
+
+    assert!(matches!(
+        atmosphere::seed::from_json("[]"),
+        Err(Error::Seed(SeedError::Malformed(_)))
+    ));
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use atmosphere::{prelude::*, session::Session};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "visitor", schema = "public")]
+struct Visitor {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "tree", schema = "public")]
+struct Tree {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id")]
+    forest: i32,
+}
+
+fn forest(id: i32) -> Forest {
+    Forest {
+        id,
+        name: format!("forest {id}"),
+        location: "berlin".to_owned(),
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn session(pool: sqlx::PgPool) {
+    Visitor::create_table(&pool).await.unwrap();
+
+    for (id, name) in [(0, "ada"), (1, "grace")] {
+        Visitor {
+            id,
+            name: name.to_owned(),
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+
+    atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            let mut session = Session::new(&mut *conn);
+
+            let ada = session.read::<Visitor>(&0).await?;
+
+            // read once, the identity map holds the loaded instance
+            sqlx::query("UPDATE public.visitor SET name = 'renamed'")
+                .execute(&mut *session.conn())
+                .await
+                .unwrap();
+
+            let again = session.read::<Visitor>(&0).await?;
+            let found = session.find::<Visitor>(&0).await?.unwrap();
+
+            assert!(Arc::ptr_eq(&ada, &again));
+            assert!(Arc::ptr_eq(&ada, &found));
+            assert_eq!(again.name, "ada");
+
+            assert_eq!(session.read::<Visitor>(&1).await?.name, "renamed");
+            assert_eq!(session.find::<Visitor>(&2).await?, None);
+            assert!(session.read::<Visitor>(&2).await.is_err());
+
+            // recorded writes replace the instance right away
+            let mut renamed = (*ada).clone();
+            renamed.name = "lovelace".to_owned();
+
+            let renamed = session.update(renamed);
+
+            assert!(Arc::ptr_eq(&renamed, &session.read::<Visitor>(&0).await?));
+
+            session.delete::<Visitor>(&1);
+
+            assert_eq!(session.find::<Visitor>(&1).await?, None);
+            assert!(session.read::<Visitor>(&1).await.is_err());
+
+            // .. but are executed on commit only
+            session.evict::<Visitor>(&0);
+
+            assert_eq!(session.read::<Visitor>(&0).await?.name, "renamed");
+
+            session.commit().await?;
+
+            assert_eq!(Visitor::read(&mut *conn, &0).await?.name, "lovelace");
+            assert_eq!(Visitor::find(&mut *conn, &1).await?, None);
+
+            Ok::<_, atmosphere::Error>(())
+        })
+    })
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn session_order(pool: sqlx::PgPool) {
+    forest(0).create(&pool).await.unwrap();
+    Tree { id: 0, forest: 0 }.create(&pool).await.unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let mut session = Session::new(&mut conn);
+
+    // trees are recorded first, but written after and deleted before their forests
+    session.create(Tree { id: 1, forest: 1 });
+    session.delete::<Tree>(&0);
+    session.create(forest(1));
+    session.delete::<Forest>(&0);
+
+    session.commit().await.unwrap();
+
+    assert_eq!(Forest::read_all(&pool).await.unwrap(), vec![forest(1)]);
+    assert_eq!(
+        Tree::read_all(&pool).await.unwrap(),
+        vec![Tree { id: 1, forest: 1 }]
+    );
+
+    // a failing change rolls back the ones flushed before it
+    let mut session = Session::new(&mut conn);
+
+    session.create(forest(2));
+    session.create(Tree { id: 2, forest: 3 });
+
+    assert!(session.commit().await.is_err());
+    assert_eq!(Forest::find(&pool, &2).await.unwrap(), None);
+
+    // dropping a session discards its changes
+    let mut session = Session::new(&mut conn);
+
+    session.delete::<Tree>(&1);
+    drop(session);
+
+    assert_eq!(Tree::read_all(&pool).await.unwrap().len(), 1);
+}
@@ -0,0 +1,102 @@
+use atmosphere::prelude::*;
+use sqlx::{postgres::PgPoolOptions, Executor, PgPool};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+/// Even ids live on the first shard, odd ids on the second
+struct ByParity(Vec<PgPool>);
+
+impl ShardRouter<Forest> for ByParity {
+    fn shard(&self, pk: &i32) -> &PgPool {
+        &self.0[*pk as usize % 2]
+    }
+
+    fn shards(&self) -> &[PgPool] {
+        &self.0
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn shard(pool: PgPool) {
+    // a second, migrated database next to the one of the test
+    let name: String = sqlx::query_scalar("SELECT current_database()")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let second = format!("{name}_shard");
+
+    pool.execute(format!("DROP DATABASE IF EXISTS \"{second}\"").as_str())
+        .await
+        .unwrap();
+    pool.execute(format!("CREATE DATABASE \"{second}\"").as_str())
+        .await
+        .unwrap();
+
+    let shard = PgPoolOptions::new()
+        .connect_with((*pool.connect_options()).clone().database(&second))
+        .await
+        .unwrap();
+
+    sqlx::migrate!("tests/db/migrations")
+        .run(&shard)
+        .await
+        .unwrap();
+
+    let router = ByParity(vec![pool.clone(), shard.clone()]);
+
+    let mut forests: Vec<Forest> = (0..4)
+        .map(|id| Forest {
+            id,
+            name: format!("forest {id}"),
+            location: "berlin".to_owned(),
+        })
+        .collect();
+
+    for forest in &mut forests {
+        forest.create_sharded(&router).await.unwrap();
+    }
+
+    // each shard only holds its own rows
+    assert_eq!(
+        Forest::read_all(&pool).await.unwrap(),
+        vec![forests[0].clone(), forests[2].clone()]
+    );
+    assert_eq!(
+        Forest::read_all(&shard).await.unwrap(),
+        vec![forests[1].clone(), forests[3].clone()]
+    );
+
+    assert_eq!(
+        Forest::read_all_sharded(&router).await.unwrap().len(),
+        forests.len()
+    );
+
+    forests[3].name = "spreewald".to_owned();
+    forests[3].update_sharded(&router).await.unwrap();
+
+    assert_eq!(Forest::read_sharded(&router, &3).await.unwrap(), forests[3]);
+
+    forests[1].delete_sharded(&router).await.unwrap();
+    Forest::delete_by_sharded(&router, &2).await.unwrap();
+
+    assert_eq!(Forest::find_sharded(&router, &1).await.unwrap(), None);
+    assert_eq!(Forest::find_sharded(&router, &2).await.unwrap(), None);
+    assert_eq!(
+        Forest::read_all_sharded(&router).await.unwrap(),
+        vec![forests[0].clone(), forests[3].clone()]
+    );
+
+    shard.close().await;
+
+    // closed connections may linger on the server for a moment
+    pool.execute(format!("DROP DATABASE \"{second}\" WITH (FORCE)").as_str())
+        .await
+        .unwrap();
+}
@@ -0,0 +1,71 @@
+use atmosphere::prelude::*;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "camp", schema = "public")]
+struct Camp {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(timestamp = deleted)]
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn soft_delete(pool: sqlx::PgPool) {
+    let mut base = Camp {
+        id: 0,
+        name: "base".to_owned(),
+        deleted_at: None,
+    };
+
+    let mut outpost = Camp {
+        id: 1,
+        name: "outpost".to_owned(),
+        deleted_at: None,
+    };
+
+    base.create(&pool).await.unwrap();
+    outpost.create(&pool).await.unwrap();
+
+    let res = base.delete(&pool).await.unwrap();
+    assert_eq!(res.rows_affected, 1);
+
+    // deleting again does not touch the row
+    let res = Camp::delete_by(&pool, &base.id).await.unwrap();
+    assert_eq!(res.rows_affected, 0);
+
+    assert!(Camp::find(&pool, &base.id).await.unwrap().is_none());
+    assert_eq!(Camp::read_all(&pool).await.unwrap(), vec![outpost.clone()]);
+
+    let (deleted_at,): (Option<DateTime<Utc>>,) =
+        sqlx::query_as("SELECT deleted_at FROM camp WHERE id = $1")
+            .bind(base.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    assert!(deleted_at.is_some());
+
+    // soft deleted rows are only found by their deletion timestamp
+    let deleted = Camp::find_deleted_between(&pool, &DateTime::<Utc>::default(), &Utc::now())
+        .await
+        .unwrap();
+
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].id, base.id);
+    assert_eq!(deleted[0].deleted_at, deleted_at);
+
+    let res = base.hard_delete(&pool).await.unwrap();
+    assert_eq!(res.rows_affected, 1);
+
+    let res = Camp::hard_delete_by(&pool, &outpost.id).await.unwrap();
+    assert_eq!(res.rows_affected, 1);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM camp")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,188 @@
+use atmosphere::prelude::*;
+use atmosphere::tenant;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "ledger", schema = "public")]
+struct Ledger {
+    #[sql(pk)]
+    id: i32,
+    #[sql(tenant)]
+    org: i32,
+    amount: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn tenant(pool: sqlx::PgPool) {
+    let mut a = Ledger {
+        id: 0,
+        org: 1,
+        amount: 10,
+    };
+
+    let mut b = Ledger {
+        id: 1,
+        org: 2,
+        amount: 20,
+    };
+
+    a.create(&pool).await.unwrap();
+    b.create(&pool).await.unwrap();
+
+    // without a tenant in scope, tenant tables can't be queried
+    assert!(matches!(Ledger::read_all(&pool).await, Err(Error::Tenant)));
+
+    tenant::scope(1, async {
+        assert_eq!(Ledger::read_all(&pool).await.unwrap(), vec![a.clone()]);
+        assert_eq!(Ledger::find(&pool, &1).await.unwrap(), None);
+
+        // rows of other tenants are neither updated nor deleted
+        b.amount = 0;
+        assert_eq!(b.update(&pool).await.unwrap().rows_affected, 0);
+        b.upsert(&pool).await.unwrap();
+        assert_eq!(Ledger::delete_by(&pool, &1).await.unwrap().rows_affected, 0);
+
+        a.amount = 15;
+        assert_eq!(a.update(&pool).await.unwrap().rows_affected, 1);
+    })
+    .await;
+
+    tenant::scope(2, async {
+        let b = Ledger::read(&pool, &1).await.unwrap();
+        assert_eq!(b.amount, 20);
+
+        assert_eq!(Ledger::read_all(&pool).await.unwrap(), vec![b]);
+    })
+    .await;
+
+    tenant::scope(1, async {
+        assert_eq!(Ledger::read(&pool, &0).await.unwrap().amount, 15);
+    })
+    .await;
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn writes(pool: sqlx::PgPool) {
+    tenant::scope(1, async {
+        // rows are created within the tenant in scope, whatever tenant they hold
+        let mut ledger = Ledger {
+            id: 0,
+            org: 2,
+            amount: 10,
+        };
+
+        ledger.create(&pool).await.unwrap();
+
+        assert_eq!(ledger.org, 1);
+        assert_eq!(Ledger::read(&pool, &0).await.unwrap(), ledger);
+
+        // updates never move rows to another tenant
+        ledger.org = 2;
+        ledger.amount = 20;
+
+        assert_eq!(ledger.update(&pool).await.unwrap().rows_affected, 1);
+
+        let stored = Ledger::read(&pool, &0).await.unwrap();
+
+        assert_eq!(stored.org, 1);
+        assert_eq!(stored.amount, 20);
+    })
+    .await;
+
+    tenant::scope(2, async {
+        assert!(Ledger::read_all(&pool).await.unwrap().is_empty());
+    })
+    .await;
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "account", schema = "public")]
+struct Account {
+    #[sql(pk)]
+    id: i32,
+    #[sql(tenant)]
+    org: i32,
+    #[sql(unique)]
+    handle: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "posting", schema = "public")]
+struct Posting {
+    #[sql(pk)]
+    id: i32,
+    #[sql(tenant)]
+    org: i32,
+    #[sql(fk -> Account, rename = "account_id", relation = "account", inverse = "postings")]
+    account: i32,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn generated(pool: sqlx::PgPool) {
+    let mut account = Account {
+        id: 0,
+        org: 1,
+        handle: "treasury".to_owned(),
+    };
+
+    let mut posting = Posting {
+        id: 0,
+        org: 1,
+        account: 0,
+    };
+
+    account.create(&pool).await.unwrap();
+    posting.create(&pool).await.unwrap();
+
+    // the generated unique and relation queries are scoped like the crud ones
+    assert!(matches!(
+        Account::find_by_handle(&pool, &account.handle).await,
+        Err(Error::Tenant)
+    ));
+    assert!(matches!(posting.account(&pool).await, Err(Error::Tenant)));
+
+    tenant::scope(2, async {
+        assert_eq!(
+            Account::find_by_handle(&pool, &account.handle)
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(Posting::find_by_account(&pool, &0)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(account.postings(&pool).await.unwrap().is_empty());
+
+        let deleted = account.delete_postings(&pool).await.unwrap();
+        assert_eq!(deleted.rows_affected, 0);
+
+        let deleted = Account::delete_by_handle(&pool, &account.handle).await;
+        assert_eq!(deleted.unwrap().rows_affected, 0);
+    })
+    .await;
+
+    tenant::scope(1, async {
+        assert_eq!(
+            Account::find_by_handle(&pool, &account.handle)
+                .await
+                .unwrap(),
+            Some(account.clone())
+        );
+        assert_eq!(posting.account(&pool).await.unwrap(), account);
+        assert_eq!(
+            Posting::find_by_account(&pool, &0).await.unwrap(),
+            vec![posting.clone()]
+        );
+        assert_eq!(
+            account.postings(&pool).await.unwrap(),
+            vec![posting.clone()]
+        );
+
+        let deleted = account.delete_postings(&pool).await.unwrap();
+        assert_eq!(deleted.rows_affected, 1);
+
+        let deleted = Account::delete_by_handle(&pool, &account.handle).await;
+        assert_eq!(deleted.unwrap().rows_affected, 1);
+    })
+    .await;
+}
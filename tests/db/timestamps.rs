@@ -0,0 +1,95 @@
+use atmosphere::prelude::*;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "cabin", schema = "public")]
+struct Cabin {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    #[sql(timestamp = created)]
+    created_at: DateTime<Utc>,
+    #[sql(timestamp = updated)]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn timestamps(pool: sqlx::PgPool) {
+    let epoch = DateTime::<Utc>::default();
+
+    let mut cabin = Cabin {
+        id: 0,
+        name: "cabin".to_owned(),
+        created_at: epoch,
+        updated_at: None,
+    };
+
+    cabin.create(&pool).await.unwrap();
+
+    let created = cabin.created_at;
+
+    assert!(created > epoch);
+    assert_eq!(cabin.updated_at, Some(created));
+
+    cabin.name = "hut".to_owned();
+    cabin.update(&pool).await.unwrap();
+
+    let updated = cabin.updated_at.unwrap();
+
+    assert_eq!(cabin.created_at, created);
+    assert!(updated > created);
+
+    // upserting an existing row keeps its creation timestamp
+    cabin.upsert(&pool).await.unwrap();
+
+    let stored = Cabin::read(&pool, &0).await.unwrap();
+
+    assert_eq!(
+        stored.created_at.timestamp_micros(),
+        created.timestamp_micros()
+    );
+    assert!(stored.updated_at.unwrap() > updated);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn windows(pool: sqlx::PgPool) {
+    let mut cabins = vec![];
+
+    for id in 0..3 {
+        let mut cabin = Cabin {
+            id,
+            name: format!("cabin {id}"),
+            created_at: DateTime::<Utc>::default(),
+            updated_at: None,
+        };
+
+        cabin.create(&pool).await.unwrap();
+        cabins.push(cabin);
+    }
+
+    let times: Vec<_> = cabins.iter().map(|cabin| cabin.created_at).collect();
+
+    let ids = |cabins: Vec<Cabin>| cabins.into_iter().map(|c| c.id).collect::<Vec<_>>();
+
+    assert_eq!(
+        ids(Cabin::find_created_before(&pool, &times[1]).await.unwrap()),
+        vec![0]
+    );
+    assert_eq!(
+        ids(Cabin::find_created_after(&pool, &times[1]).await.unwrap()),
+        vec![2]
+    );
+
+    // windows include their start but not their end
+    assert_eq!(
+        ids(Cabin::find_created_between(&pool, &times[0], &times[2])
+            .await
+            .unwrap()),
+        vec![0, 1]
+    );
+
+    assert_eq!(
+        ids(Cabin::find_updated_after(&pool, &times[0]).await.unwrap()),
+        vec![1, 2]
+    );
+}
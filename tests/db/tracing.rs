@@ -0,0 +1,247 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ::tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Subscriber,
+};
+use atmosphere::{
+    hooks::Hooks,
+    prelude::*,
+    slow::SlowQueryLog,
+    statement_log::{self, Values},
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "lantern", schema = "public")]
+struct Lantern {
+    #[sql(pk)]
+    id: i32,
+    #[sql(unique)]
+    name: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "flare", schema = "public")]
+struct Flare {
+    #[sql(pk)]
+    id: i32,
+}
+
+#[derive(Schema, PartialEq, Eq, Clone)]
+#[table(name = "beacon", schema = "public")]
+struct Beacon {
+    #[sql(pk)]
+    id: i32,
+    #[sql(unique)]
+    name: String,
+    #[sql(sensitive)]
+    code: String,
+}
+
+type Fields = HashMap<&'static str, String>;
+
+/// Captures the fields of all spans named `atmosphere.query`
+#[derive(Clone, Default)]
+struct Spans(Arc<Mutex<Vec<(Id, Fields)>>>);
+
+impl Spans {
+    fn fields(&self) -> Vec<Fields> {
+        let spans = self.0.lock().unwrap();
+        spans.iter().map(|(_, fields)| fields.clone()).collect()
+    }
+}
+
+struct Visitor<'a>(&'a mut Fields);
+
+/// Captures the fields of all warnings
+#[derive(Clone, Default)]
+struct Warnings(Arc<Mutex<Vec<Fields>>>);
+
+impl<S: Subscriber> Layer<S> for Warnings {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        if *event.metadata().level() != Level::WARN {
+            return;
+        }
+
+        let mut fields = Fields::new();
+        event.record(&mut Visitor(&mut fields));
+
+        self.0.lock().unwrap().push(fields);
+    }
+}
+
+/// Captures the fields of all logged statements
+#[derive(Clone, Default)]
+struct Statements(Arc<Mutex<Vec<Fields>>>);
+
+impl<S: Subscriber> Layer<S> for Statements {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        if event.metadata().target() != "atmosphere::statements" {
+            return;
+        }
+
+        let mut fields = Fields::new();
+        event.record(&mut Visitor(&mut fields));
+
+        self.0.lock().unwrap().push(fields);
+    }
+}
+
+impl Visit for Visitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(
+            field.name(),
+            format!("{value:?}").trim_matches('"').to_owned(),
+        );
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Spans {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _: Context<'_, S>) {
+        if attrs.metadata().name() != "atmosphere.query" {
+            return;
+        }
+
+        let mut fields = Fields::new();
+        attrs.record(&mut Visitor(&mut fields));
+
+        self.0.lock().unwrap().push((id.clone(), fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _: Context<'_, S>) {
+        let mut spans = self.0.lock().unwrap();
+
+        if let Some((_, fields)) = spans.iter_mut().find(|(span, _)| span == id) {
+            values.record(&mut Visitor(fields));
+        }
+    }
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn tracing(pool: sqlx::PgPool) {
+    Lantern::create_table(&pool).await.unwrap();
+
+    let spans = Spans::default();
+    let _guard =
+        ::tracing::subscriber::set_default(tracing_subscriber::registry().with(spans.clone()));
+
+    let mut lantern = Lantern {
+        id: 0,
+        name: "storm".to_owned(),
+    };
+
+    lantern.create(&pool).await.unwrap();
+    Lantern::read(&pool, &1).await.unwrap_err();
+    Lantern::find_by_name(&pool, &"storm".to_owned())
+        .await
+        .unwrap();
+
+    let spans = spans.fields();
+
+    assert_eq!(spans.len(), 3);
+
+    for span in &spans {
+        assert_eq!(span["db.system"], "postgresql");
+        assert_eq!(span["db.namespace"], "public");
+        assert_eq!(span["db.collection.name"], "lantern");
+    }
+
+    assert_eq!(spans[0]["db.operation.name"], "insert");
+    assert_eq!(spans[0]["db.rows_affected"], "1");
+    assert!(spans[0]["db.query.text"].starts_with("INSERT INTO"));
+
+    assert_eq!(spans[1]["db.operation.name"], "select");
+    assert_eq!(spans[1]["error.type"], "not_found");
+    assert_eq!(spans[1]["otel.status_code"], "ERROR");
+    assert!(!spans[1].contains_key("db.rows_affected"));
+
+    // unique finders are executed without hooks, but traced all the same
+    assert_eq!(spans[2]["db.operation.name"], "select");
+    assert_eq!(spans[2]["db.rows_affected"], "1");
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn slow_queries(pool: sqlx::PgPool) {
+    Flare::create_table(&pool).await.unwrap();
+
+    let warnings = Warnings::default();
+    let _guard =
+        ::tracing::subscriber::set_default(tracing_subscriber::registry().with(warnings.clone()));
+
+    Flare::register_hook(Arc::new(SlowQueryLog::new(Duration::ZERO)));
+
+    Flare { id: 0 }.create(&pool).await.unwrap();
+
+    let warnings = warnings.0.lock().unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0]["message"], "slow query");
+    assert_eq!(warnings[0]["table"], "flare");
+    assert_eq!(warnings[0]["operation"], "insert");
+    assert!(warnings[0]["sql"].starts_with("INSERT INTO"));
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn statement_log(pool: sqlx::PgPool) {
+    Beacon::create_table(&pool).await.unwrap();
+
+    let statements = Statements::default();
+    let _guard =
+        ::tracing::subscriber::set_default(tracing_subscriber::registry().with(statements.clone()));
+
+    statement_log::enable(Values::Hashed);
+
+    let mut beacon = Beacon {
+        id: 0,
+        name: "storm".to_owned(),
+        code: "1234".to_owned(),
+    };
+
+    beacon.create(&pool).await.unwrap();
+    Beacon::read(&pool, &0).await.unwrap();
+    Beacon::find_by_name(&pool, &"storm".to_owned())
+        .await
+        .unwrap();
+
+    statement_log::enable(Values::Redacted);
+
+    beacon.update(&pool).await.unwrap();
+
+    statement_log::disable();
+
+    Beacon::read(&pool, &0).await.unwrap();
+
+    let statements = statements.0.lock().unwrap();
+
+    assert_eq!(statements.len(), 4);
+
+    assert_eq!(statements[0]["table"], "beacon");
+    assert_eq!(statements[0]["operation"], "insert");
+    assert_eq!(statements[0]["rows"], "1");
+    assert!(statements[0]["sql"].starts_with("INSERT INTO"));
+
+    let bindings: Vec<&str> = statements[0]["bindings"].split(", ").collect();
+
+    assert_eq!(bindings.len(), 3);
+    assert!(bindings[0].starts_with("$1 id = #"));
+    assert!(bindings[1].starts_with("$2 name = #"));
+    assert_eq!(bindings[2], "$3 code = «redacted»");
+    assert!(!statements[0]["bindings"].contains("storm"));
+
+    // the same value has the same fingerprint
+    assert_eq!(statements[1]["operation"], "select");
+    assert_eq!(statements[1]["bindings"], bindings[0]);
+
+    // values bound without hooks are unknown
+    assert_eq!(statements[2]["bindings"], "$1 name = ?");
+
+    assert_eq!(statements[3]["operation"], "update");
+    assert!(statements[3]["bindings"].contains("name = «redacted»"));
+    assert!(!statements[3]["bindings"].contains('#'));
+}
@@ -0,0 +1,46 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public", tracked)]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn tracked(pool: sqlx::PgPool) {
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    forest.create(&pool).await.unwrap();
+
+    let mut tracked = Tracked::new(Forest::read(&pool, &0).await.unwrap());
+
+    assert!(!tracked.is_dirty());
+    assert_eq!(tracked.update(&pool).await.unwrap().rows_affected, 0);
+
+    // updated concurrently
+    forest.location = "brandenburg".to_owned();
+    forest.update(&pool).await.unwrap();
+
+    tracked.name = "spreewald".to_owned();
+
+    assert_eq!(tracked.changes(), vec!["name"]);
+    assert_eq!(tracked.update(&pool).await.unwrap().rows_affected, 1);
+    assert!(!tracked.is_dirty());
+
+    // only the changed column has been written
+    assert_eq!(
+        Forest::read(&pool, &0).await.unwrap(),
+        Forest {
+            id: 0,
+            name: "spreewald".to_owned(),
+            location: "brandenburg".to_owned(),
+        }
+    );
+}
@@ -0,0 +1,248 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use atmosphere::hooks::HookStage;
+use atmosphere::prelude::*;
+use atmosphere::query::Operation;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn commit(pool: sqlx::PgPool) {
+    let forest = atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            let mut forest = Forest {
+                id: 0,
+                name: "grunewald".to_owned(),
+                location: "berlin".to_owned(),
+            };
+
+            forest.create(&mut *conn).await?;
+
+            forest.name = "tegeler forst".to_owned();
+            forest.update(&mut *conn).await?;
+
+            Ok::<_, atmosphere::Error>(forest)
+        })
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(Forest::read(&pool, &0).await.unwrap(), forest);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn rollback(pool: sqlx::PgPool) {
+    let res = atmosphere::transaction(&pool, |conn| {
+        Box::pin(async move {
+            Forest {
+                id: 0,
+                name: "grunewald".to_owned(),
+                location: "berlin".to_owned(),
+            }
+            .create(&mut *conn)
+            .await?;
+
+            // violates the primary key constraint
+            Forest {
+                id: 0,
+                name: "spandauer forst".to_owned(),
+                location: "berlin".to_owned(),
+            }
+            .create(&mut *conn)
+            .await?;
+
+            Ok::<_, atmosphere::Error>(())
+        })
+    })
+    .await;
+
+    assert!(res.is_err());
+    assert!(Forest::find(&pool, &0).await.unwrap().is_none());
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn isolation(pool: sqlx::PgPool) {
+    let level = atmosphere::transaction_with(&pool, IsolationLevel::Serializable, |conn| {
+        Box::pin(async move {
+            let (level,): (String,) = sqlx::query_as("SHOW transaction_isolation")
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(atmosphere::query::QueryError::from)?;
+
+            Ok::<_, atmosphere::Error>(level)
+        })
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(level, "serializable");
+
+    let (level, forest) =
+        atmosphere::testing::isolated(&pool, IsolationLevel::RepeatableRead, |conn| {
+            Box::pin(async move {
+                Forest {
+                    id: 0,
+                    name: "grunewald".to_owned(),
+                    location: "berlin".to_owned(),
+                }
+                .create(&mut *conn)
+                .await
+                .unwrap();
+
+                let (level,): (String,) = sqlx::query_as("SHOW transaction_isolation")
+                    .fetch_one(&mut *conn)
+                    .await
+                    .unwrap();
+
+                (level, Forest::find(&mut *conn, &0).await.unwrap())
+            })
+        })
+        .await;
+
+    assert_eq!(level, "repeatable read");
+    assert!(forest.is_some());
+
+    // isolated transactions are always rolled back
+    assert!(Forest::find(&pool, &0).await.unwrap().is_none());
+}
+
+/// Renames the forest, running into a conflicting update for the first `conflicts` attempts
+async fn rename(
+    conn: &mut sqlx::PgConnection,
+    pool: sqlx::PgPool,
+    attempts: Arc<AtomicU32>,
+    conflicts: u32,
+) -> Result<u32> {
+    let attempt = attempts.fetch_add(1, Ordering::Relaxed) + 1;
+
+    let mut forest = Forest::read(&mut *conn, &0).await?;
+
+    if attempt <= conflicts {
+        sqlx::query("UPDATE forest SET location = 'potsdam' WHERE id = 0")
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    forest.name = format!("attempt {attempt}");
+    forest.update(&mut *conn).await?;
+
+    Ok(attempt)
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn serialization_failure(pool: sqlx::PgPool) {
+    Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    }
+    .create(&pool)
+    .await
+    .unwrap();
+
+    let attempts = Arc::new(AtomicU32::new(0));
+
+    let attempt = atmosphere::transaction_with(&pool, IsolationLevel::RepeatableRead, |conn| {
+        Box::pin(rename(conn, pool.clone(), attempts.clone(), 1))
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(attempt, 2);
+    assert_eq!(Forest::read(&pool, &0).await.unwrap().name, "attempt 2");
+
+    // the failure is noticed although it is not returned
+    attempts.store(0, Ordering::Relaxed);
+
+    let attempt = atmosphere::transaction_with(&pool, IsolationLevel::Serializable, |conn| {
+        let renamed = rename(conn, pool.clone(), attempts.clone(), 1);
+        Box::pin(async move { Ok::<_, atmosphere::Error>(renamed.await.unwrap_or(0)) })
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(attempt, 2);
+
+    // the transaction is attempted three times at most
+    attempts.store(0, Ordering::Relaxed);
+
+    let res = atmosphere::transaction_with(&pool, IsolationLevel::RepeatableRead, |conn| {
+        Box::pin(rename(conn, pool.clone(), attempts.clone(), u32::MAX))
+    })
+    .await;
+
+    assert!(res.unwrap_err().query().unwrap().is_serialization_failure());
+    assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+    // other isolation levels are not retried
+    attempts.store(0, Ordering::Relaxed);
+
+    let attempt = atmosphere::transaction_with(&pool, IsolationLevel::ReadCommitted, |conn| {
+        Box::pin(rename(conn, pool.clone(), attempts.clone(), 1))
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(attempt, 1);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Thicket {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn independent(pool: sqlx::PgPool) {
+    let recording = atmosphere::testing::hooks::record::<Thicket>();
+
+    // a transaction on another connection commits on its own, although the outer one rolls back
+    let inner = pool.clone();
+
+    atmosphere::transaction(&pool, |_| {
+        Box::pin(async move {
+            atmosphere::transaction(&inner, |conn| {
+                Box::pin(async move {
+                    Thicket {
+                        id: 0,
+                        name: "grunewald".to_owned(),
+                        location: "berlin".to_owned(),
+                    }
+                    .create(&mut *conn)
+                    .await
+                })
+            })
+            .await?;
+
+            Err::<(), _>(atmosphere::Error::Other)
+        })
+    })
+    .await
+    .unwrap_err();
+
+    assert!(Thicket::find(&pool, &0).await.unwrap().is_some());
+    assert_eq!(
+        recording.stages(Operation::Insert),
+        vec![
+            HookStage::PreBind,
+            HookStage::PreExec,
+            HookStage::PostExec,
+            HookStage::PreCommit,
+            HookStage::PostCommit
+        ]
+    );
+}
@@ -0,0 +1,72 @@
+use atmosphere::prelude::*;
+use atmosphere::uuid::Uuid;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "parcel", schema = "public")]
+struct Parcel {
+    #[sql(pk, uuid)]
+    id: Uuid,
+    label: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "crate", schema = "public")]
+struct Crate {
+    #[sql(pk, uuid = "v7")]
+    id: Option<Uuid>,
+    label: String,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn generated(pool: sqlx::PgPool) {
+    Parcel::create_table(&pool).await.unwrap();
+
+    let mut parcel = Parcel {
+        id: Uuid::nil(),
+        label: "fragile".to_owned(),
+    };
+
+    parcel.create(&pool).await.unwrap();
+
+    assert!(!parcel.id.is_nil());
+    assert_eq!(parcel.id.get_version_num(), 4);
+    assert_eq!(Parcel::read(&pool, &parcel.id).await.unwrap(), parcel);
+
+    // keys which are already set are kept
+    let id = Uuid::new_v4();
+
+    let mut given = Parcel {
+        id,
+        label: "heavy".to_owned(),
+    };
+
+    given.create(&pool).await.unwrap();
+
+    assert_eq!(given.id, id);
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn generated_v7(pool: sqlx::PgPool) {
+    Crate::create_table(&pool).await.unwrap();
+
+    let mut first = Crate {
+        id: None,
+        label: "first".to_owned(),
+    };
+
+    let mut second = Crate {
+        id: None,
+        label: "second".to_owned(),
+    };
+
+    first.upsert(&pool).await.unwrap();
+    second.create(&pool).await.unwrap();
+
+    let (Some(a), Some(b)) = (first.id, second.id) else {
+        panic!("keys were not generated");
+    };
+
+    assert_eq!(a.get_version_num(), 7);
+    assert!(a < b);
+    assert_eq!(Crate::read(&pool, &first.id).await.unwrap(), first);
+}
@@ -0,0 +1,105 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk, validate = "positive")]
+    id: i32,
+    #[sql(validate = "not_empty")]
+    name: String,
+    #[sql(validate = "self::not_empty")]
+    location: String,
+}
+
+fn positive(value: &i32) -> std::result::Result<(), &'static str> {
+    if *value < 0 {
+        return Err("must not be negative");
+    }
+
+    Ok(())
+}
+
+fn not_empty(value: &str) -> std::result::Result<(), String> {
+    if value.is_empty() {
+        return Err("must not be empty".to_owned());
+    }
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn validate(pool: sqlx::PgPool) {
+    let mut forest = Forest {
+        id: -1,
+        name: String::new(),
+        location: "berlin".to_owned(),
+    };
+
+    let Err(Error::Validation(err)) = forest.create(&pool).await else {
+        panic!("invalid forest was created");
+    };
+
+    assert_eq!(
+        err.fields,
+        vec![
+            FieldError::new("id", "must not be negative"),
+            FieldError::new("name", "must not be empty"),
+        ]
+    );
+
+    assert!(Forest::read_all(&pool).await.unwrap().is_empty());
+
+    forest.id = 0;
+    forest.name = "grunewald".to_owned();
+    forest.create(&pool).await.unwrap();
+
+    forest.location = String::new();
+
+    let Err(Error::Validation(err)) = forest.update(&pool).await else {
+        panic!("invalid forest was updated");
+    };
+
+    assert_eq!(err.to_string(), "invalid `location` (must not be empty)");
+}
+
+#[cfg(feature = "validator")]
+mod derived {
+    use atmosphere::prelude::*;
+    use validator::Validate;
+
+    #[derive(Schema, Validate, Debug, PartialEq, Eq, Clone)]
+    #[table(name = "forest", schema = "public")]
+    struct Forest {
+        #[sql(pk)]
+        id: i32,
+        #[validate(length(min = 1, message = "must not be empty"))]
+        name: String,
+        #[validate(length(max = 16))]
+        location: String,
+    }
+
+    #[sqlx::test(migrations = "tests/db/migrations")]
+    async fn validate(pool: sqlx::PgPool) {
+        let mut forest = Forest {
+            id: 0,
+            name: String::new(),
+            location: "somewhere far far away".to_owned(),
+        };
+
+        let Err(Error::Validation(err)) = forest.create(&pool).await else {
+            panic!("invalid forest was created");
+        };
+
+        assert_eq!(
+            err.fields,
+            vec![
+                FieldError::new("location", "length"),
+                FieldError::new("name", "must not be empty"),
+            ]
+        );
+
+        forest.name = "grunewald".to_owned();
+        forest.location = "berlin".to_owned();
+        forest.create(&pool).await.unwrap();
+    }
+}
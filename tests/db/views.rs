@@ -0,0 +1,98 @@
+use atmosphere::prelude::*;
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest", schema = "public")]
+struct Forest {
+    #[sql(pk)]
+    id: i32,
+    name: String,
+    location: String,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "tree", schema = "public")]
+struct Tree {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id")]
+    forest: i32,
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest_size", schema = "public", view)]
+struct ForestSize {
+    #[sql(pk)]
+    id: i32,
+    #[sql(fk -> Forest, rename = "forest_id")]
+    forest: i32,
+    trees: i64,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn view(pool: sqlx::PgPool) {
+    let mut forest = Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    };
+
+    forest.create(&pool).await.unwrap();
+
+    for id in 0..3 {
+        Tree { id, forest: 0 }.create(&pool).await.unwrap();
+    }
+
+    let size = ForestSize {
+        id: 0,
+        forest: 0,
+        trees: 3,
+    };
+
+    assert_eq!(ForestSize::read(&pool, &0).await.unwrap(), size);
+    assert_eq!(
+        ForestSize::read_all(&pool).await.unwrap(),
+        vec![size.clone()]
+    );
+
+    assert_eq!(size.forest(&pool).await.unwrap(), forest);
+    assert_eq!(forest.forestsizes(&pool).await.unwrap(), vec![size]);
+}
+
+#[derive(Schema, Debug, PartialEq, Eq, Clone)]
+#[table(name = "forest_census", schema = "public", materialized)]
+struct ForestCensus {
+    #[sql(pk)]
+    id: i32,
+    trees: i64,
+}
+
+#[sqlx::test(migrations = "tests/db/migrations")]
+async fn materialized(pool: sqlx::PgPool) {
+    Forest {
+        id: 0,
+        name: "grunewald".to_owned(),
+        location: "berlin".to_owned(),
+    }
+    .create(&pool)
+    .await
+    .unwrap();
+
+    // the view holds the result as of the last refresh
+    assert!(ForestCensus::read_all(&pool).await.unwrap().is_empty());
+
+    ForestCensus::refresh(&pool, false).await.unwrap();
+
+    assert_eq!(
+        ForestCensus::read(&pool, &0).await.unwrap(),
+        ForestCensus { id: 0, trees: 0 }
+    );
+
+    Tree { id: 0, forest: 0 }.create(&pool).await.unwrap();
+
+    ForestCensus::refresh(&pool, true).await.unwrap();
+
+    assert_eq!(
+        ForestCensus::read(&pool, &0).await.unwrap(),
+        ForestCensus { id: 0, trees: 1 }
+    );
+}